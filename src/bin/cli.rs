@@ -25,10 +25,12 @@ enum Command {
         msg: Option<Bytes>,
     },
     Get {
-        key: String,
+        #[clap(value_parser = bytes_from_str)]
+        key: Bytes,
     },
     Set {
-        key: String,
+        #[clap(value_parser = bytes_from_str)]
+        key: Bytes,
         #[clap(value_parser = bytes_from_str)]
         value: Bytes,
     },
@@ -50,7 +52,8 @@ async fn main() {
             Err(e) => eprintln!("{}", e),
         },
         Command::Get { key } => match client.get(key).await {
-            Ok(msg) => println!("{:?}", std::str::from_utf8(&msg[..]).unwrap()),
+            Ok(Some(msg)) => println!("{:?}", std::str::from_utf8(&msg[..]).unwrap()),
+            Ok(None) => println!("(nil)"),
             Err(e) => eprintln!("{}", e),
         },
         Command::Set { key, value } => match client.set(key, value).await {
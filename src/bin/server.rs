@@ -1,48 +1,256 @@
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use clap::Parser;
+use my_redis::cmd::{check_memory, Command};
+use my_redis::db::EvictionPolicy;
+use my_redis::resp::ProtocolLimits;
+use std::future::Future;
+use std::time::Duration;
 
-use bytes::Bytes;
-use my_redis::cmd::Command;
-use my_redis::{self, Connection, RESPType};
+use my_redis::{self, Connection, Db, Error, RESPType};
 use tokio::net::{TcpListener, TcpStream};
 
-type ShardedDb = Arc<Vec<Mutex<HashMap<String, Bytes>>>>;
+#[derive(Parser, Debug)]
+struct Args {
+    #[clap(long = "bind", default_value = "127.0.0.1")]
+    host: String,
+    #[clap(short = 'p', long = "port", default_value_t = 6379)]
+    port: u16,
+    #[clap(long = "proto-max-bulk-len", default_value_t = ProtocolLimits::default().max_bulk_len)]
+    max_bulk_len: usize,
+    #[clap(long = "proto-max-multibulk-len", default_value_t = ProtocolLimits::default().max_array_len)]
+    max_array_len: usize,
+    #[clap(long = "proto-max-nesting", default_value_t = ProtocolLimits::default().max_depth)]
+    max_depth: usize,
+    #[clap(long = "proto-inline-max-size", default_value_t = ProtocolLimits::default().max_inline_len)]
+    max_inline_len: usize,
+    #[clap(long = "client-query-buffer-limit", default_value_t = ProtocolLimits::default().max_query_buffer)]
+    max_query_buffer: usize,
+    #[clap(long = "databases", default_value_t = 16)]
+    databases: usize,
+    /// Memory limit in bytes, 0 for none.
+    #[clap(long = "maxmemory", default_value_t = 0)]
+    maxmemory: usize,
+    #[clap(long = "maxmemory-policy", default_value = "noeviction", value_parser = eviction_policy)]
+    policy: EvictionPolicy,
+}
+
+fn eviction_policy(name: &str) -> Result<EvictionPolicy, String> {
+    EvictionPolicy::from_name(name).ok_or_else(|| format!("unknown eviction policy '{name}'"))
+}
 
 #[tokio::main]
 async fn main() {
-    let listener = TcpListener::bind("127.0.0.1:6379").await.unwrap();
-    let db = new_sharded_db(25);
+    let args = Args::parse();
+    let limits = ProtocolLimits {
+        max_bulk_len: args.max_bulk_len,
+        max_array_len: args.max_array_len,
+        max_depth: args.max_depth,
+        max_inline_len: args.max_inline_len,
+        max_query_buffer: args.max_query_buffer,
+    };
+
+    let listener = TcpListener::bind((args.host, args.port)).await.unwrap();
+    let db = Db::with_databases(args.databases, 25);
+    db.set_maxmemory(args.maxmemory);
+    db.set_eviction_policy(args.policy);
+    db.start_active_expiry(Duration::from_millis(100));
 
     loop {
         let (socket, _) = listener.accept().await.unwrap();
         let db = db.clone();
 
-        tokio::spawn(async move { process(socket, db).await });
+        tokio::spawn(async move { process(socket, db, limits).await });
     }
 }
 
-async fn process(socket: TcpStream, db: ShardedDb) {
-    let mut connection = Connection::new(socket);
+async fn process(socket: TcpStream, mut db: Db, limits: ProtocolLimits) {
+    let mut connection = Connection::with_limits(socket, limits);
+
+    loop {
+        let frame = match connection.read_frame().await {
+            Ok(Some(frame)) => frame,
+            Ok(None) => return,
+            Err(e @ Error::Protocol(_)) => {
+                // the stream can't be resynchronized after a malformed frame, so report and close
+                let _ = connection.write_frame(&e.into()).await;
+                return;
+            }
+            Err(_) => return,
+        };
+
+        // redis silently skips empty requests
+        if matches!(&frame, RESPType::Array(arr) if arr.is_empty()) {
+            continue;
+        }
+
+        if let Err(e) = check_memory(&frame, &db) {
+            if connection.write_frame(&e.into()).await.is_err() {
+                return;
+            }
+            continue;
+        }
 
-    while let Some(frame) = connection.read_frame().await.unwrap() {
         let response = match frame.try_into() {
             Ok(cmd) => match cmd {
                 Command::Ping(ping) => ping.response(),
                 Command::Echo(echo) => echo.response(),
                 Command::Get(get) => get.response(&db),
                 Command::Set(set) => set.response(&db),
+                Command::Mget(mget) => mget.response(&db),
+                Command::Mset(mset) => mset.response(&db),
+                Command::MsetNx(msetnx) => msetnx.response(&db),
+                Command::SetNx(setnx) => setnx.response(&db),
+                Command::GetDel(getdel) => getdel.response(&db),
+                Command::GetEx(getex) => getex.response(&db),
+                Command::Incr(incr) => incr.response(&db),
+                Command::IncrByFloat(incr) => incr.response(&db),
+                Command::Append(append) => append.response(&db),
+                Command::Strlen(strlen) => strlen.response(&db),
+                Command::GetRange(getrange) => getrange.response(&db),
+                Command::SetRange(setrange) => setrange.response(&db),
+                Command::Push(push) => push.response(&db),
+                Command::Pop(pop) => pop.response(&db),
+                Command::Llen(llen) => llen.response(&db),
+                Command::Lrange(lrange) => lrange.response(&db),
+                Command::Lindex(lindex) => lindex.response(&db),
+                Command::Lset(lset) => lset.response(&db),
+                Command::Lrem(lrem) => lrem.response(&db),
+                Command::Ltrim(ltrim) => ltrim.response(&db),
+                Command::Linsert(linsert) => linsert.response(&db),
+                Command::Lpos(lpos) => lpos.response(&db),
+                Command::Lmove(lmove) => lmove.response(&db),
+                Command::Lmpop(lmpop) => lmpop.response(&db),
+                Command::Hset(hset) => hset.response(&db),
+                Command::HsetNx(hsetnx) => hsetnx.response(&db),
+                Command::Hget(hget) => hget.response(&db),
+                Command::Hmget(hmget) => hmget.response(&db),
+                Command::Hdel(hdel) => hdel.response(&db),
+                Command::Hexists(hexists) => hexists.response(&db),
+                Command::Hlen(hlen) => hlen.response(&db),
+                Command::Hstrlen(hstrlen) => hstrlen.response(&db),
+                Command::HgetAll(hgetall) => hgetall.response(&db),
+                Command::Hincrby(hincrby) => hincrby.response(&db),
+                Command::HincrbyFloat(hincrby) => hincrby.response(&db),
+                Command::HrandField(hrandfield) => hrandfield.response(&db),
+                Command::Hscan(hscan) => hscan.response(&db),
+                Command::Hexpire(hexpire) => hexpire.response(&db),
+                Command::Httl(httl) => httl.response(&db),
+                Command::Hpersist(hpersist) => hpersist.response(&db),
+                Command::Sadd(sadd) => sadd.response(&db),
+                Command::Srem(srem) => srem.response(&db),
+                Command::Scard(scard) => scard.response(&db),
+                Command::Smembers(smembers) => smembers.response(&db),
+                Command::Sismember(sismember) => sismember.response(&db),
+                Command::Smismember(smismember) => smismember.response(&db),
+                Command::Spop(spop) => spop.response(&db),
+                Command::SrandMember(srandmember) => srandmember.response(&db),
+                Command::Smove(smove) => smove.response(&db),
+                Command::Sscan(sscan) => sscan.response(&db),
+                Command::SetOp(setop) => setop.response(&db),
+                Command::SinterCard(sintercard) => sintercard.response(&db),
+                Command::Zadd(zadd) => zadd.response(&db),
+                Command::Zrem(zrem) => zrem.response(&db),
+                Command::Zscore(zscore) => zscore.response(&db),
+                Command::Zmscore(zmscore) => zmscore.response(&db),
+                Command::Zincrby(zincrby) => zincrby.response(&db),
+                Command::Zcard(zcard) => zcard.response(&db),
+                Command::Zrank(zrank) => zrank.response(&db),
+                Command::Zcount(zcount) => zcount.response(&db),
+                Command::ZlexCount(zlexcount) => zlexcount.response(&db),
+                Command::Zrange(zrange) => zrange.response(&db),
+                Command::Zpop(zpop) => zpop.response(&db),
+                Command::ZsetOp(zsetop) => zsetop.response(&db),
+                Command::Xadd(xadd) => xadd.response(&db),
+                Command::Xtrim(xtrim) => xtrim.response(&db),
+                Command::Xrange(xrange) => xrange.response(&db),
+                Command::Xlen(xlen) => xlen.response(&db),
+                Command::Xdel(xdel) => xdel.response(&db),
+                Command::Xgroup(xgroup) => xgroup.response(&db),
+                Command::Xack(xack) => xack.response(&db),
+                Command::Xpending(xpending) => xpending.response(&db),
+                Command::Xclaim(xclaim) => xclaim.response(&db),
+                Command::XautoClaim(xautoclaim) => xautoclaim.response(&db),
+                Command::Xinfo(xinfo) => xinfo.response(&db),
+                Command::Setbit(setbit) => setbit.response(&db),
+                Command::Getbit(getbit) => getbit.response(&db),
+                Command::Bitcount(bitcount) => bitcount.response(&db),
+                Command::Bitpos(bitpos) => bitpos.response(&db),
+                Command::Bitop(bitop) => bitop.response(&db),
+                Command::Bitfield(bitfield) => bitfield.response(&db),
+                Command::Pfadd(pfadd) => pfadd.response(&db),
+                Command::Pfcount(pfcount) => pfcount.response(&db),
+                Command::Pfmerge(pfmerge) => pfmerge.response(&db),
+                Command::Geoadd(geoadd) => geoadd.response(&db),
+                Command::Geodist(geodist) => geodist.response(&db),
+                Command::Geopos(geopos) => geopos.response(&db),
+                Command::Geohash(geohash) => geohash.response(&db),
+                Command::Geosearch(geosearch) => geosearch.response(&db),
+                Command::Del(del) => del.response(&db),
+                Command::Exists(exists) => exists.response(&db),
+                Command::Type(kind) => kind.response(&db),
+                Command::Rename(rename) => rename.response(&db),
+                Command::Copy(copy) => copy.response(&db),
+                Command::Dbsize(dbsize) => dbsize.response(&db),
+                Command::Flush(flush) => flush.response(&db),
+                Command::RandomKey(randomkey) => randomkey.response(&db),
+                Command::Keys(keys) => keys.response(&db),
+                Command::Scan(scan) => scan.response(&db),
+                Command::Select(select) => select.response(&mut db),
+                Command::Move(mv) => mv.response(&db),
+                Command::Swapdb(swapdb) => swapdb.response(&db),
+                Command::Info(info) => info.response(&db),
+                Command::Object(object) => object.response(&db),
+                // blocking commands park this task until served, timed out or the client hangs up
+                Command::Bpop(bpop) => match block(&mut connection, bpop.response(&db)).await {
+                    Some(response) => response,
+                    None => return,
+                },
+                Command::Blmove(blmove) => match block(&mut connection, blmove.response(&db)).await
+                {
+                    Some(response) => response,
+                    None => return,
+                },
+                Command::Blmpop(blmpop) => match block(&mut connection, blmpop.response(&db)).await
+                {
+                    Some(response) => response,
+                    None => return,
+                },
+                Command::Bzpop(bzpop) => match block(&mut connection, bzpop.response(&db)).await {
+                    Some(response) => response,
+                    None => return,
+                },
+                Command::Xread(xread) => match block(&mut connection, xread.response(&db)).await {
+                    Some(response) => response,
+                    None => return,
+                },
+                Command::XreadGroup(xreadgroup) => {
+                    match block(&mut connection, xreadgroup.response(&db)).await {
+                        Some(response) => response,
+                        None => return,
+                    }
+                }
+                Command::Expire(expire) => expire.response(&db),
+                Command::Ttl(ttl) => ttl.response(&db),
+                Command::Persist(persist) => persist.response(&db),
+                Command::Hello(hello) => hello.response(&mut connection),
+                Command::CommandInfo(info) => info.response(),
             },
-            Err(e) => RESPType::Error(e.to_string()),
+            Err(e) => e.into(),
         };
 
-        connection.write_frame(&response).await.unwrap();
+        if connection.write_frame(&response).await.is_err() {
+            return;
+        }
     }
 }
 
-fn new_sharded_db(num_shards: usize) -> ShardedDb {
-    let mut db = Vec::with_capacity(num_shards);
-    for _ in 0..num_shards {
-        db.push(Mutex::new(HashMap::new()));
+/// Runs a blocking command until it is served, `None` if the client goes away first. Dropping
+/// the command gives up the client's place in line, so a later push isn't popped for nobody.
+async fn block(
+    connection: &mut Connection,
+    response: impl Future<Output = RESPType>,
+) -> Option<RESPType> {
+    tokio::select! {
+        response = response => Some(response),
+        _ = connection.closed() => None,
     }
-    Arc::new(db)
 }
@@ -1,10 +1,25 @@
-use std::io::{Error, ErrorKind};
+use std::io;
+use std::time::Duration;
 
-use crate::cmd::{Echo, Ping};
-use crate::{resp::*, Connection};
+use crate::cmd::{
+    BitUnit, Bitcount, Bitop, BitopKind, Blmove, Blmpop, Bpop, Bzpop, Dbsize, Del, Echo, Exists,
+    Expire, ExpireUnit, GeoUnit, Geoadd, Geodist, Get, Getbit, Hdel, Hello, Hexists, Hexpire, Hget,
+    HgetAll, HgetAllKind, Hincrby, HincrbyFloat, Hlen, Hmget, Hpersist, HrandField, Hscan, Hset,
+    HsetNx, Hstrlen, Httl, Incr, Keys, Lindex, Linsert, ListEnd, Llen, Lmove, Lmpop, Lpos, Lrange,
+    Lrem, Lset, Ltrim, Mget, Mset, Persist, Pfadd, Pfcount, Pfmerge, Ping, Pop, Push, Rename, Sadd,
+    Scan, Scard, ScoreBound, Select, Set, SetOp, SetOpKind, Setbit, SinterCard, Sismember,
+    Smembers, Smismember, Smove, Spop, SrandMember, Srem, Sscan, StartId, StreamId, StreamTrim,
+    Ttl, TtlKind, Xack, Xadd, XaddId, Xdel, Xgroup, XgroupOp, Xlen, Xrange, Xread, XreadGroup,
+    Xtrim, Zadd, Zcard, Zcount, Zincrby, Zpop, Zrange, ZrangeBy, Zrank, Zrem, Zscore, ZsetOp,
+    ZsetOpKind,
+};
+use crate::{resp::*, Connection, Error};
 use bytes::Bytes;
 use tokio::net::{TcpStream, ToSocketAddrs};
 
+/// A stream entry's ID and its field value pairs.
+pub type StreamEntry = (StreamId, Vec<(Bytes, Bytes)>);
+
 pub struct Client {
     connection: Connection,
 }
@@ -12,7 +27,10 @@ pub struct Client {
 impl Client {
     pub async fn connect<T: ToSocketAddrs>(addr: T) -> crate::Result<Self> {
         Ok(Client {
-            connection: Connection::new(TcpStream::connect(addr).await?),
+            connection: Connection::with_limits(
+                TcpStream::connect(addr).await?,
+                ProtocolLimits::unlimited(),
+            ),
         })
     }
 
@@ -25,7 +43,7 @@ impl Client {
         match self.read_response().await? {
             RESPType::String(msg) => Ok(msg.into()),
             RESPType::Bulk(msg) => Ok(msg),
-            err => Err(format!("unexpected resp data type: {:?}", err).into()),
+            resp => Err(Error::UnexpectedReply(resp)),
         }
     }
 
@@ -38,55 +56,1598 @@ impl Client {
         match self.read_response().await? {
             RESPType::String(msg) => Ok(msg.into()),
             RESPType::Bulk(msg) => Ok(msg),
-            err => Err(format!("unexpected resp data type: {:?}", err).into()),
+            resp => Err(Error::UnexpectedReply(resp)),
         }
     }
 
-    pub async fn get(&mut self, key: String) -> crate::Result<Bytes> {
-        let mut arr = vec![];
+    pub async fn get(&mut self, key: Bytes) -> crate::Result<Option<Bytes>> {
+        let get = Get::new(key);
+        let frame = get.into();
+
+        self.connection.write_frame(&frame).await?;
 
-        arr.push(RESPType::Bulk(Bytes::from("get")));
-        arr.push(RESPType::Bulk(Bytes::from(key)));
+        match self.read_response().await? {
+            RESPType::String(msg) => Ok(Some(msg.into())),
+            RESPType::Bulk(msg) => Ok(Some(msg)),
+            RESPType::Null => Ok(None),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
 
-        let frame = RESPType::Array(arr);
+    pub async fn set(&mut self, key: Bytes, value: Bytes) -> crate::Result<Bytes> {
+        let set = Set::new(key, value);
+        let frame = set.into();
 
         self.connection.write_frame(&frame).await?;
 
         match self.read_response().await? {
             RESPType::String(msg) => Ok(msg.into()),
             RESPType::Bulk(msg) => Ok(msg),
-            err => Err(format!("unexpected resp data type: {:?}", err).into()),
+            resp => Err(Error::UnexpectedReply(resp)),
         }
     }
 
-    pub async fn set(&mut self, key: String, value: Bytes) -> crate::Result<Bytes> {
-        let mut arr = vec![];
+    /// Fetches several keys in one round trip, missing keys come back as `None`.
+    pub async fn mget(&mut self, keys: Vec<Bytes>) -> crate::Result<Vec<Option<Bytes>>> {
+        let mget = Mget::new(keys);
+        let frame = mget.into();
+
+        self.connection.write_frame(&frame).await?;
 
-        arr.push(RESPType::Bulk(Bytes::from("set")));
-        arr.push(RESPType::Bulk(Bytes::from(key)));
-        arr.push(RESPType::Bulk(value));
+        match self.read_response().await? {
+            RESPType::Array(values) => values
+                .into_iter()
+                .map(|value| match value {
+                    RESPType::Bulk(value) => Ok(Some(value)),
+                    RESPType::Null => Ok(None),
+                    resp => Err(Error::UnexpectedReply(resp)),
+                })
+                .collect(),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
 
-        let frame = RESPType::Array(arr);
+    /// Atomically stores every key value pair.
+    pub async fn mset(&mut self, pairs: Vec<(Bytes, Bytes)>) -> crate::Result<()> {
+        let mset = Mset::new(pairs);
+        let frame = mset.into();
 
         self.connection.write_frame(&frame).await?;
 
         match self.read_response().await? {
-            RESPType::String(msg) => Ok(msg.into()),
-            RESPType::Bulk(msg) => Ok(msg),
-            err => Err(format!("unexpected resp data type: {:?}", err).into()),
+            RESPType::String(_) => Ok(()),
+            resp => Err(Error::UnexpectedReply(resp)),
         }
     }
 
-    async fn read_response(&mut self) -> crate::Result<RESPType> {
-        let frame = self.connection.read_frame().await?;
+    pub async fn incr(&mut self, key: Bytes) -> crate::Result<i64> {
+        self.incr_by(key, 1).await
+    }
 
-        match frame {
-            Some(RESPType::Error(err)) => Err(err.into()),
-            Some(frame) => Ok(frame),
-            None => {
-                let err = Error::new(ErrorKind::ConnectionReset, "connection reset by server");
-                Err(err.into())
-            }
+    /// Atomically adds `delta` to the integer stored at `key`, returning the new value.
+    pub async fn incr_by(&mut self, key: Bytes, delta: i64) -> crate::Result<i64> {
+        let incr = Incr::new(key, delta);
+        let frame = incr.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Sets a timeout of `seconds` on `key`, returns false if the key does not exist.
+    pub async fn expire(&mut self, key: Bytes, seconds: i64) -> crate::Result<bool> {
+        let expire = Expire::new(key, seconds, ExpireUnit::Seconds, false);
+        let frame = expire.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n == 1),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Remaining time to live of `key` in seconds, -1 without a timeout and -2 if missing.
+    pub async fn ttl(&mut self, key: Bytes) -> crate::Result<i64> {
+        let ttl = Ttl::new(key, TtlKind::Ttl);
+        let frame = ttl.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn persist(&mut self, key: Bytes) -> crate::Result<bool> {
+        let persist = Persist::new(key);
+        let frame = persist.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n == 1),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Prepends `values` to the list at `key` one after the other, returning its new length.
+    pub async fn lpush(&mut self, key: Bytes, values: Vec<Bytes>) -> crate::Result<i64> {
+        self.push(Push::new(key, values, ListEnd::Left)).await
+    }
+
+    /// Appends `values` to the list at `key`, returning its new length.
+    pub async fn rpush(&mut self, key: Bytes, values: Vec<Bytes>) -> crate::Result<i64> {
+        self.push(Push::new(key, values, ListEnd::Right)).await
+    }
+
+    /// Same as [`Client::lpush`] but does nothing unless the list exists.
+    pub async fn lpushx(&mut self, key: Bytes, values: Vec<Bytes>) -> crate::Result<i64> {
+        self.push(Push::new(key, values, ListEnd::Left).existing(true))
+            .await
+    }
+
+    /// Same as [`Client::rpush`] but does nothing unless the list exists.
+    pub async fn rpushx(&mut self, key: Bytes, values: Vec<Bytes>) -> crate::Result<i64> {
+        self.push(Push::new(key, values, ListEnd::Right).existing(true))
+            .await
+    }
+
+    pub async fn lpop(&mut self, key: Bytes) -> crate::Result<Option<Bytes>> {
+        self.pop(Pop::new(key, ListEnd::Left)).await
+    }
+
+    pub async fn rpop(&mut self, key: Bytes) -> crate::Result<Option<Bytes>> {
+        self.pop(Pop::new(key, ListEnd::Right)).await
+    }
+
+    pub async fn llen(&mut self, key: Bytes) -> crate::Result<i64> {
+        let llen = Llen::new(key);
+        let frame = llen.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Elements between `start` and `stop` inclusive, negative indexes count from the tail.
+    pub async fn lrange(&mut self, key: Bytes, start: i64, stop: i64) -> crate::Result<Vec<Bytes>> {
+        let lrange = Lrange::new(key, start, stop);
+        let frame = lrange.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        bulk_array(resp)
+    }
+
+    pub async fn lindex(&mut self, key: Bytes, index: i64) -> crate::Result<Option<Bytes>> {
+        let lindex = Lindex::new(key, index);
+        let frame = lindex.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Bulk(value) => Ok(Some(value)),
+            RESPType::Null => Ok(None),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn lset(&mut self, key: Bytes, index: i64, value: Bytes) -> crate::Result<()> {
+        let lset = Lset::new(key, index, value);
+        let frame = lset.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::String(_) => Ok(()),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Removes up to `count` occurrences of `value`, see [`Lrem::new`]. Returns how many
+    /// were removed.
+    pub async fn lrem(&mut self, key: Bytes, count: i64, value: Bytes) -> crate::Result<i64> {
+        let lrem = Lrem::new(key, count, value);
+        let frame = lrem.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn ltrim(&mut self, key: Bytes, start: i64, stop: i64) -> crate::Result<()> {
+        let ltrim = Ltrim::new(key, start, stop);
+        let frame = ltrim.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::String(_) => Ok(()),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Inserts `value` next to `pivot`, returning the new length or -1 if there is no pivot.
+    pub async fn linsert(
+        &mut self,
+        key: Bytes,
+        before: bool,
+        pivot: Bytes,
+        value: Bytes,
+    ) -> crate::Result<i64> {
+        let linsert = Linsert::new(key, before, pivot, value);
+        let frame = linsert.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Index of the first element equal to `value`.
+    pub async fn lpos(&mut self, key: Bytes, value: Bytes) -> crate::Result<Option<i64>> {
+        let lpos = Lpos::new(key, value);
+        let frame = lpos.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(Some(n)),
+            RESPType::Null => Ok(None),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Atomically pops from one end of `source` and pushes onto one end of `destination`.
+    pub async fn lmove(
+        &mut self,
+        source: Bytes,
+        destination: Bytes,
+        from: ListEnd,
+        to: ListEnd,
+    ) -> crate::Result<Option<Bytes>> {
+        let lmove = Lmove::new(source, destination, from, to);
+        let frame = lmove.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Bulk(value) => Ok(Some(value)),
+            RESPType::Null => Ok(None),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Pops up to `count` elements from the first non-empty list, returning its key along
+    /// with them.
+    pub async fn lmpop(
+        &mut self,
+        keys: Vec<Bytes>,
+        end: ListEnd,
+        count: usize,
+    ) -> crate::Result<Option<(Bytes, Vec<Bytes>)>> {
+        let lmpop = Lmpop::new(keys, end).count(count);
+        let frame = lmpop.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        popped(resp)
+    }
+
+    /// Pops the head of the first non-empty list among `keys`, waiting up to `timeout` for
+    /// one to be pushed to. A zero timeout waits forever.
+    pub async fn blpop(
+        &mut self,
+        keys: Vec<Bytes>,
+        timeout: Duration,
+    ) -> crate::Result<Option<(Bytes, Bytes)>> {
+        self.bpop(Bpop::new(keys, ListEnd::Left, timeout)).await
+    }
+
+    /// Same as [`Client::blpop`] but pops the tail.
+    pub async fn brpop(
+        &mut self,
+        keys: Vec<Bytes>,
+        timeout: Duration,
+    ) -> crate::Result<Option<(Bytes, Bytes)>> {
+        self.bpop(Bpop::new(keys, ListEnd::Right, timeout)).await
+    }
+
+    /// Blocking [`Client::lmove`], waiting up to `timeout` for `source` to receive an element.
+    pub async fn blmove(
+        &mut self,
+        source: Bytes,
+        destination: Bytes,
+        from: ListEnd,
+        to: ListEnd,
+        timeout: Duration,
+    ) -> crate::Result<Option<Bytes>> {
+        let blmove = Blmove::new(source, destination, from, to, timeout);
+        let frame = blmove.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Bulk(value) => Ok(Some(value)),
+            RESPType::Null => Ok(None),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Blocking [`Client::lmpop`], waiting up to `timeout` for one of `keys` to receive
+    /// elements.
+    pub async fn blmpop(
+        &mut self,
+        keys: Vec<Bytes>,
+        end: ListEnd,
+        count: usize,
+        timeout: Duration,
+    ) -> crate::Result<Option<(Bytes, Vec<Bytes>)>> {
+        let blmpop = Blmpop::new(keys, end, timeout).count(count);
+        let frame = blmpop.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        popped(resp)
+    }
+
+    /// Sets each field value pair of the hash at `key`, returning how many fields were added.
+    pub async fn hset(&mut self, key: Bytes, pairs: Vec<(Bytes, Bytes)>) -> crate::Result<i64> {
+        let hset = Hset::new(key, pairs);
+        let frame = hset.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn hsetnx(&mut self, key: Bytes, field: Bytes, value: Bytes) -> crate::Result<bool> {
+        let hsetnx = HsetNx::new(key, field, value);
+        let frame = hsetnx.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n == 1),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn hget(&mut self, key: Bytes, field: Bytes) -> crate::Result<Option<Bytes>> {
+        let hget = Hget::new(key, field);
+        let frame = hget.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Bulk(value) => Ok(Some(value)),
+            RESPType::Null => Ok(None),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn hmget(
+        &mut self,
+        key: Bytes,
+        fields: Vec<Bytes>,
+    ) -> crate::Result<Vec<Option<Bytes>>> {
+        let hmget = Hmget::new(key, fields);
+        let frame = hmget.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Array(values) => values
+                .into_iter()
+                .map(|value| match value {
+                    RESPType::Bulk(value) => Ok(Some(value)),
+                    RESPType::Null => Ok(None),
+                    resp => Err(Error::UnexpectedReply(resp)),
+                })
+                .collect(),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn hdel(&mut self, key: Bytes, fields: Vec<Bytes>) -> crate::Result<i64> {
+        let hdel = Hdel::new(key, fields);
+        let frame = hdel.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn hexists(&mut self, key: Bytes, field: Bytes) -> crate::Result<bool> {
+        let hexists = Hexists::new(key, field);
+        let frame = hexists.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n == 1),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn hlen(&mut self, key: Bytes) -> crate::Result<i64> {
+        let hlen = Hlen::new(key);
+        let frame = hlen.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn hstrlen(&mut self, key: Bytes, field: Bytes) -> crate::Result<i64> {
+        let hstrlen = Hstrlen::new(key, field);
+        let frame = hstrlen.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn hkeys(&mut self, key: Bytes) -> crate::Result<Vec<Bytes>> {
+        let hkeys = HgetAll::new(key, HgetAllKind::Keys);
+        let frame = hkeys.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        bulk_array(resp)
+    }
+
+    pub async fn hvals(&mut self, key: Bytes) -> crate::Result<Vec<Bytes>> {
+        let hvals = HgetAll::new(key, HgetAllKind::Values);
+        let frame = hvals.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        bulk_array(resp)
+    }
+
+    pub async fn hgetall(&mut self, key: Bytes) -> crate::Result<Vec<(Bytes, Bytes)>> {
+        let hgetall = HgetAll::new(key, HgetAllKind::All);
+        let frame = hgetall.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        // RESP2 connections get the pairs flattened into an array
+        match self.read_response().await? {
+            RESPType::Map(map) => map
+                .into_iter()
+                .map(|pair| match pair {
+                    (RESPType::Bulk(field), RESPType::Bulk(value)) => Ok((field, value)),
+                    (_, resp) => Err(Error::UnexpectedReply(resp)),
+                })
+                .collect(),
+            resp => bulk_pairs(bulk_array(resp)?),
+        }
+    }
+
+    pub async fn hincrby(&mut self, key: Bytes, field: Bytes, delta: i64) -> crate::Result<i64> {
+        let hincrby = Hincrby::new(key, field, delta);
+        let frame = hincrby.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn hincrbyfloat(
+        &mut self,
+        key: Bytes,
+        field: Bytes,
+        delta: f64,
+    ) -> crate::Result<Bytes> {
+        let hincrbyfloat = HincrbyFloat::new(key, field, delta);
+        let frame = hincrbyfloat.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Bulk(value) => Ok(value),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Up to `count` random fields with their values, repeating fields if `count` is negative.
+    pub async fn hrandfield(
+        &mut self,
+        key: Bytes,
+        count: i64,
+    ) -> crate::Result<Vec<(Bytes, Bytes)>> {
+        let hrandfield = HrandField::new(key).count(count).with_values(true);
+        let frame = hrandfield.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        bulk_pairs(bulk_array(resp)?)
+    }
+
+    /// One page of fields and values from `cursor`, with the cursor to continue from.
+    pub async fn hscan(
+        &mut self,
+        key: Bytes,
+        cursor: u64,
+        pattern: Option<Bytes>,
+    ) -> crate::Result<(u64, Vec<(Bytes, Bytes)>)> {
+        let mut hscan = Hscan::new(key, cursor);
+        if let Some(pattern) = pattern {
+            hscan = hscan.pattern(pattern);
         }
+        let frame = hscan.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        let (next, page) = scan_page(resp)?;
+        Ok((next, bulk_pairs(bulk_array(page)?)?))
+    }
+
+    /// Sets a timeout in seconds on each field, returning one status code per field.
+    pub async fn hexpire(
+        &mut self,
+        key: Bytes,
+        seconds: i64,
+        fields: Vec<Bytes>,
+    ) -> crate::Result<Vec<i64>> {
+        let hexpire = Hexpire::new(key, seconds, ExpireUnit::Seconds, false, fields);
+        let frame = hexpire.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        integer_array(resp)
+    }
+
+    /// Remaining time to live of each field in seconds, -1 without a timeout and -2 if missing.
+    pub async fn httl(&mut self, key: Bytes, fields: Vec<Bytes>) -> crate::Result<Vec<i64>> {
+        let httl = Httl::new(key, TtlKind::Ttl, fields);
+        let frame = httl.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        integer_array(resp)
+    }
+
+    pub async fn hpersist(&mut self, key: Bytes, fields: Vec<Bytes>) -> crate::Result<Vec<i64>> {
+        let hpersist = Hpersist::new(key, fields);
+        let frame = hpersist.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        integer_array(resp)
     }
+
+    /// Adds `members` to the set at `key`, returning how many weren't there yet.
+    pub async fn sadd(&mut self, key: Bytes, members: Vec<Bytes>) -> crate::Result<i64> {
+        let sadd = Sadd::new(key, members);
+        let frame = sadd.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn srem(&mut self, key: Bytes, members: Vec<Bytes>) -> crate::Result<i64> {
+        let srem = Srem::new(key, members);
+        let frame = srem.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn scard(&mut self, key: Bytes) -> crate::Result<i64> {
+        let scard = Scard::new(key);
+        let frame = scard.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn smembers(&mut self, key: Bytes) -> crate::Result<Vec<Bytes>> {
+        let smembers = Smembers::new(key);
+        let frame = smembers.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        bulk_array(resp)
+    }
+
+    pub async fn sismember(&mut self, key: Bytes, member: Bytes) -> crate::Result<bool> {
+        let sismember = Sismember::new(key, member);
+        let frame = sismember.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n == 1),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn smismember(
+        &mut self,
+        key: Bytes,
+        members: Vec<Bytes>,
+    ) -> crate::Result<Vec<bool>> {
+        let smismember = Smismember::new(key, members);
+        let frame = smismember.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        Ok(integer_array(resp)?.into_iter().map(|n| n == 1).collect())
+    }
+
+    /// Removes and returns up to `count` random members.
+    pub async fn spop(&mut self, key: Bytes, count: usize) -> crate::Result<Vec<Bytes>> {
+        let spop = Spop::new(key).count(count);
+        let frame = spop.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        bulk_array(resp)
+    }
+
+    /// Up to `count` random members, repeating members if `count` is negative.
+    pub async fn srandmember(&mut self, key: Bytes, count: i64) -> crate::Result<Vec<Bytes>> {
+        let srandmember = SrandMember::new(key).count(count);
+        let frame = srandmember.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        bulk_array(resp)
+    }
+
+    pub async fn smove(
+        &mut self,
+        source: Bytes,
+        destination: Bytes,
+        member: Bytes,
+    ) -> crate::Result<bool> {
+        let smove = Smove::new(source, destination, member);
+        let frame = smove.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n == 1),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// One page of members from `cursor`, with the cursor to continue from.
+    pub async fn sscan(
+        &mut self,
+        key: Bytes,
+        cursor: u64,
+        pattern: Option<Bytes>,
+    ) -> crate::Result<(u64, Vec<Bytes>)> {
+        let mut sscan = Sscan::new(key, cursor);
+        if let Some(pattern) = pattern {
+            sscan = sscan.pattern(pattern);
+        }
+        let frame = sscan.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        let (next, page) = scan_page(resp)?;
+        Ok((next, bulk_array(page)?))
+    }
+
+    pub async fn sinter(&mut self, keys: Vec<Bytes>) -> crate::Result<Vec<Bytes>> {
+        self.setop(SetOp::new(SetOpKind::Inter, keys)).await
+    }
+
+    pub async fn sunion(&mut self, keys: Vec<Bytes>) -> crate::Result<Vec<Bytes>> {
+        self.setop(SetOp::new(SetOpKind::Union, keys)).await
+    }
+
+    pub async fn sdiff(&mut self, keys: Vec<Bytes>) -> crate::Result<Vec<Bytes>> {
+        self.setop(SetOp::new(SetOpKind::Diff, keys)).await
+    }
+
+    /// Stores the intersection of the sets at `keys` in `destination`, returning its size.
+    pub async fn sinterstore(
+        &mut self,
+        destination: Bytes,
+        keys: Vec<Bytes>,
+    ) -> crate::Result<i64> {
+        self.setop_store(SetOp::new(SetOpKind::Inter, keys).store(destination))
+            .await
+    }
+
+    pub async fn sunionstore(
+        &mut self,
+        destination: Bytes,
+        keys: Vec<Bytes>,
+    ) -> crate::Result<i64> {
+        self.setop_store(SetOp::new(SetOpKind::Union, keys).store(destination))
+            .await
+    }
+
+    pub async fn sdiffstore(&mut self, destination: Bytes, keys: Vec<Bytes>) -> crate::Result<i64> {
+        self.setop_store(SetOp::new(SetOpKind::Diff, keys).store(destination))
+            .await
+    }
+
+    /// Size of the intersection of the sets at `keys`, counting stops at `limit` unless 0.
+    pub async fn sintercard(&mut self, keys: Vec<Bytes>, limit: usize) -> crate::Result<i64> {
+        let sintercard = SinterCard::new(keys).limit(limit);
+        let frame = sintercard.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn zadd(&mut self, key: Bytes, members: Vec<(f64, Bytes)>) -> crate::Result<i64> {
+        let zadd = Zadd::new(key, members);
+        let frame = zadd.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn zrem(&mut self, key: Bytes, members: Vec<Bytes>) -> crate::Result<i64> {
+        let zrem = Zrem::new(key, members);
+        let frame = zrem.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn zscore(&mut self, key: Bytes, member: Bytes) -> crate::Result<Option<f64>> {
+        let zscore = Zscore::new(key, member);
+        let frame = zscore.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Null => Ok(None),
+            resp => score(resp).map(Some),
+        }
+    }
+
+    pub async fn zincrby(&mut self, key: Bytes, delta: f64, member: Bytes) -> crate::Result<f64> {
+        let zincrby = Zincrby::new(key, delta, member);
+        let frame = zincrby.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        score(resp)
+    }
+
+    pub async fn zcard(&mut self, key: Bytes) -> crate::Result<i64> {
+        let zcard = Zcard::new(key);
+        let frame = zcard.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Position of `member` ordered by ascending score, or descending with `rev`.
+    pub async fn zrank(
+        &mut self,
+        key: Bytes,
+        member: Bytes,
+        rev: bool,
+    ) -> crate::Result<Option<i64>> {
+        let zrank = Zrank::new(key, member, rev);
+        let frame = zrank.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(Some(n)),
+            RESPType::Null => Ok(None),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn zcount(
+        &mut self,
+        key: Bytes,
+        min: ScoreBound,
+        max: ScoreBound,
+    ) -> crate::Result<i64> {
+        let zcount = Zcount::new(key, min, max);
+        let frame = zcount.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Members selected by `by` with their scores, in descending order with `rev`.
+    pub async fn zrange(
+        &mut self,
+        key: Bytes,
+        by: ZrangeBy,
+        rev: bool,
+    ) -> crate::Result<Vec<(Bytes, f64)>> {
+        let zrange = Zrange::new(key, by).rev(rev).with_scores(true);
+        let frame = zrange.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        scored_members(resp)
+    }
+
+    /// Removes and returns up to `count` members with the lowest scores, or the highest
+    /// with `max`.
+    pub async fn zpop(
+        &mut self,
+        key: Bytes,
+        max: bool,
+        count: usize,
+    ) -> crate::Result<Vec<(Bytes, f64)>> {
+        let zpop = Zpop::new(key, max).count(count);
+        let frame = zpop.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        scored_members(resp)
+    }
+
+    /// Pops the lowest or highest scoring member of the first non-empty sorted set, waiting
+    /// up to `timeout` for one, forever if zero.
+    pub async fn bzpop(
+        &mut self,
+        keys: Vec<Bytes>,
+        max: bool,
+        timeout: Duration,
+    ) -> crate::Result<Option<(Bytes, Bytes, f64)>> {
+        let bzpop = Bzpop::new(keys, max, timeout);
+        let frame = bzpop.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Array(arr) if arr.len() == 3 => {
+                let mut arr = arr.into_iter();
+                match (arr.next(), arr.next(), arr.next()) {
+                    (Some(RESPType::Bulk(key)), Some(RESPType::Bulk(member)), Some(resp)) => {
+                        Ok(Some((key, member, score(resp)?)))
+                    }
+                    (key, _, _) => Err(Error::UnexpectedReply(key.unwrap_or(RESPType::Null))),
+                }
+            }
+            RESPType::NullArray | RESPType::Null => Ok(None),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Stores the union, intersection or difference of the sorted sets at `keys` in
+    /// `destination`, returning its size.
+    pub async fn zsetop_store(
+        &mut self,
+        kind: ZsetOpKind,
+        destination: Bytes,
+        keys: Vec<Bytes>,
+    ) -> crate::Result<i64> {
+        let zsetop = ZsetOp::new(kind, keys).store(destination);
+        let frame = zsetop.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Appends an entry to the stream at `key`, returning its ID.
+    pub async fn xadd(
+        &mut self,
+        key: Bytes,
+        id: XaddId,
+        fields: Vec<(Bytes, Bytes)>,
+    ) -> crate::Result<StreamId> {
+        let xadd = Xadd::new(key, id, fields);
+        let frame = xadd.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        stream_id(resp)
+    }
+
+    pub async fn xlen(&mut self, key: Bytes) -> crate::Result<i64> {
+        let xlen = Xlen::new(key);
+        let frame = xlen.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Entries from `start` to `end` inclusive, walking from the end if `rev` is set.
+    pub async fn xrange(
+        &mut self,
+        key: Bytes,
+        start: StreamId,
+        end: StreamId,
+        rev: bool,
+    ) -> crate::Result<Vec<StreamEntry>> {
+        let xrange = Xrange::new(key, start, end, rev);
+        let frame = xrange.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        stream_entries(resp)
+    }
+
+    pub async fn xdel(&mut self, key: Bytes, ids: Vec<StreamId>) -> crate::Result<i64> {
+        let xdel = Xdel::new(key, ids);
+        let frame = xdel.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Trims the stream at `key`, returning the number of entries removed.
+    pub async fn xtrim(&mut self, key: Bytes, trim: StreamTrim) -> crate::Result<i64> {
+        let xtrim = Xtrim::new(key, trim);
+        let frame = xtrim.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Entries after `ids` of the streams at `keys`, grouped by stream. With a `block`
+    /// timeout waits for new entries if there are none, zero waiting forever.
+    pub async fn xread(
+        &mut self,
+        keys: Vec<Bytes>,
+        ids: Vec<StartId>,
+        block: Option<Duration>,
+    ) -> crate::Result<Vec<(Bytes, Vec<StreamEntry>)>> {
+        let mut xread = Xread::new(keys, ids);
+        if let Some(block) = block {
+            xread = xread.block(block);
+        }
+        let frame = xread.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        stream_reads(resp)
+    }
+
+    /// Creates `group` on the stream at `key`, delivering the entries after `id`.
+    pub async fn xgroup_create(
+        &mut self,
+        key: Bytes,
+        group: Bytes,
+        id: StartId,
+        mkstream: bool,
+    ) -> crate::Result<()> {
+        let xgroup = Xgroup::new(key, group, XgroupOp::Create { id, mkstream });
+        let frame = xgroup.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::String(s) if s == "OK" => Ok(()),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Delivers entries of the streams at `keys` to `consumer` of `group`, a `None` ID
+    /// reading entries new to the group.
+    pub async fn xreadgroup(
+        &mut self,
+        group: Bytes,
+        consumer: Bytes,
+        keys: Vec<Bytes>,
+        ids: Vec<Option<StreamId>>,
+    ) -> crate::Result<Vec<(Bytes, Vec<StreamEntry>)>> {
+        let xreadgroup = XreadGroup::new(group, consumer, keys, ids);
+        let frame = xreadgroup.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        stream_reads(resp)
+    }
+
+    pub async fn xack(
+        &mut self,
+        key: Bytes,
+        group: Bytes,
+        ids: Vec<StreamId>,
+    ) -> crate::Result<i64> {
+        let xack = Xack::new(key, group, ids);
+        let frame = xack.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Sets or clears the bit at `offset`, returning its previous value.
+    pub async fn setbit(&mut self, key: Bytes, offset: u64, value: bool) -> crate::Result<bool> {
+        let setbit = Setbit::new(key, offset, value);
+        let frame = setbit.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(bit) => Ok(bit == 1),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn getbit(&mut self, key: Bytes, offset: u64) -> crate::Result<bool> {
+        let getbit = Getbit::new(key, offset);
+        let frame = getbit.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(bit) => Ok(bit == 1),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Number of set bits in the string at `key`, or in its bytes from `start` to `end`.
+    pub async fn bitcount(&mut self, key: Bytes, range: Option<(i64, i64)>) -> crate::Result<i64> {
+        let mut bitcount = Bitcount::new(key);
+        if let Some((start, end)) = range {
+            bitcount = bitcount.range(start, end, BitUnit::Byte);
+        }
+        let frame = bitcount.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Stores the bitwise combination of the strings at `keys` in `destination`, returning
+    /// its length.
+    pub async fn bitop(
+        &mut self,
+        kind: BitopKind,
+        destination: Bytes,
+        keys: Vec<Bytes>,
+    ) -> crate::Result<i64> {
+        let bitop = Bitop::new(kind, destination, keys);
+        let frame = bitop.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Adds elements to a HyperLogLog, returns true if its estimate may have changed.
+    pub async fn pfadd(&mut self, key: Bytes, elements: Vec<Bytes>) -> crate::Result<bool> {
+        let pfadd = Pfadd::new(key, elements);
+        let frame = pfadd.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n == 1),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Estimates the cardinality of the union of the HyperLogLogs at `keys`.
+    pub async fn pfcount(&mut self, keys: Vec<Bytes>) -> crate::Result<i64> {
+        let pfcount = Pfcount::new(keys);
+        let frame = pfcount.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn pfmerge(&mut self, destination: Bytes, sources: Vec<Bytes>) -> crate::Result<()> {
+        let pfmerge = Pfmerge::new(destination, sources);
+        let frame = pfmerge.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::String(s) if s == "OK" => Ok(()),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Adds members at longitude and latitude pairs, returns how many were new.
+    pub async fn geoadd(
+        &mut self,
+        key: Bytes,
+        members: Vec<(f64, f64, Bytes)>,
+    ) -> crate::Result<i64> {
+        let geoadd = Geoadd::new(key, members);
+        let frame = geoadd.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Distance between two members in `unit`, `None` if either is missing.
+    pub async fn geodist(
+        &mut self,
+        key: Bytes,
+        member1: Bytes,
+        member2: Bytes,
+        unit: GeoUnit,
+    ) -> crate::Result<Option<f64>> {
+        let geodist = Geodist::new(key, member1, member2).unit(unit);
+        let frame = geodist.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Null => Ok(None),
+            resp => score(resp).map(Some),
+        }
+    }
+
+    /// Deletes keys, returns how many existed.
+    pub async fn del(&mut self, keys: Vec<Bytes>) -> crate::Result<i64> {
+        let del = Del::new(keys);
+        let frame = del.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Counts how many of `keys` exist, a key given twice counts twice.
+    pub async fn exists(&mut self, keys: Vec<Bytes>) -> crate::Result<i64> {
+        let exists = Exists::new(keys);
+        let frame = exists.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn rename(&mut self, key: Bytes, new_key: Bytes) -> crate::Result<()> {
+        let rename = Rename::new(key, new_key);
+        let frame = rename.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::String(s) if s == "OK" => Ok(()),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    pub async fn dbsize(&mut self) -> crate::Result<i64> {
+        let frame = Dbsize::new().into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Every key matching the glob style `pattern`.
+    pub async fn keys(&mut self, pattern: Bytes) -> crate::Result<Vec<Bytes>> {
+        let frame = Keys::new(pattern).into();
+
+        self.connection.write_frame(&frame).await?;
+
+        bulk_array(self.read_response().await?)
+    }
+
+    /// One page of the keyspace from `cursor`, with the cursor to continue from, 0 once done.
+    pub async fn scan(
+        &mut self,
+        cursor: u64,
+        pattern: Option<Bytes>,
+    ) -> crate::Result<(u64, Vec<Bytes>)> {
+        let mut scan = Scan::new(cursor);
+        if let Some(pattern) = pattern {
+            scan = scan.pattern(pattern);
+        }
+        let frame = scan.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        let (next, page) = scan_page(resp)?;
+        Ok((next, bulk_array(page)?))
+    }
+
+    /// Switches this connection to database `index`.
+    pub async fn select(&mut self, index: i64) -> crate::Result<()> {
+        let frame = Select::new(index).into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::String(_) => Ok(()),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    /// Negotiates the protocol version, returning the server's description of itself.
+    pub async fn hello(
+        &mut self,
+        protover: Option<i64>,
+    ) -> crate::Result<Vec<(RESPType, RESPType)>> {
+        let hello = Hello::new(protover);
+        let frame = hello.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Map(map) => Ok(map),
+            RESPType::Array(arr) if arr.len() % 2 == 0 => {
+                let mut map = vec![];
+                let mut arr = arr.into_iter();
+                while let (Some(key), Some(val)) = (arr.next(), arr.next()) {
+                    map.push((key, val));
+                }
+                Ok(map)
+            }
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    async fn push(&mut self, push: Push) -> crate::Result<i64> {
+        let frame = push.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    async fn pop(&mut self, pop: Pop) -> crate::Result<Option<Bytes>> {
+        let frame = pop.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Bulk(value) => Ok(Some(value)),
+            RESPType::Null => Ok(None),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    async fn bpop(&mut self, bpop: Bpop) -> crate::Result<Option<(Bytes, Bytes)>> {
+        let frame = bpop.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Array(arr) if arr.len() == 2 => {
+                let mut arr = arr.into_iter();
+                match (arr.next(), arr.next()) {
+                    (Some(RESPType::Bulk(key)), Some(RESPType::Bulk(value))) => {
+                        Ok(Some((key, value)))
+                    }
+                    (_, value) => Err(Error::UnexpectedReply(value.unwrap_or(RESPType::Null))),
+                }
+            }
+            RESPType::NullArray | RESPType::Null => Ok(None),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    async fn setop(&mut self, setop: SetOp) -> crate::Result<Vec<Bytes>> {
+        let frame = setop.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        let resp = self.read_response().await?;
+        bulk_array(resp)
+    }
+
+    async fn setop_store(&mut self, setop: SetOp) -> crate::Result<i64> {
+        let frame = setop.into();
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            RESPType::Integer(n) => Ok(n),
+            resp => Err(Error::UnexpectedReply(resp)),
+        }
+    }
+
+    async fn read_response(&mut self) -> crate::Result<RESPType> {
+        let mut frame = self.connection.read_frame().await?;
+
+        // attributes only annotate the reply that follows them
+        while let Some(RESPType::Attribute(_)) = frame {
+            frame = self.connection.read_frame().await?;
+        }
+
+        match frame {
+            Some(RESPType::Error(err)) => Err(Error::from_reply(&err)),
+            Some(RESPType::BlobError(err)) => {
+                Err(Error::from_reply(&String::from_utf8_lossy(&err)))
+            }
+            Some(frame) => Ok(frame),
+            None => {
+                let err =
+                    io::Error::new(io::ErrorKind::ConnectionReset, "connection reset by server");
+                Err(err.into())
+            }
+        }
+    }
+}
+
+/// Elements of an array reply made of bulk strings.
+fn bulk_array(resp: RESPType) -> crate::Result<Vec<Bytes>> {
+    match resp {
+        RESPType::Array(values) | RESPType::Set(values) => values
+            .into_iter()
+            .map(|value| match value {
+                RESPType::Bulk(value) => Ok(value),
+                resp => Err(Error::UnexpectedReply(resp)),
+            })
+            .collect(),
+        resp => Err(Error::UnexpectedReply(resp)),
+    }
+}
+
+fn integer_array(resp: RESPType) -> crate::Result<Vec<i64>> {
+    match resp {
+        RESPType::Array(values) => values
+            .into_iter()
+            .map(|value| match value {
+                RESPType::Integer(n) => Ok(n),
+                resp => Err(Error::UnexpectedReply(resp)),
+            })
+            .collect(),
+        resp => Err(Error::UnexpectedReply(resp)),
+    }
+}
+
+/// Pairs up a flat field value array.
+fn bulk_pairs(values: Vec<Bytes>) -> crate::Result<Vec<(Bytes, Bytes)>> {
+    if !values.len().is_multiple_of(2) {
+        return Err(Error::UnexpectedReply(RESPType::Array(
+            values.into_iter().map(RESPType::Bulk).collect(),
+        )));
+    }
+    let mut values = values.into_iter();
+    let mut pairs = vec![];
+    while let (Some(field), Some(value)) = (values.next(), values.next()) {
+        pairs.push((field, value));
+    }
+    Ok(pairs)
+}
+
+/// A score, sent as a double in RESP3 and as a bulk string in RESP2.
+fn score(resp: RESPType) -> crate::Result<f64> {
+    match resp {
+        RESPType::Double(score) => Ok(score),
+        RESPType::Bulk(score) => std::str::from_utf8(&score)
+            .ok()
+            .and_then(|score| score.parse().ok())
+            .ok_or(Error::UnexpectedReply(RESPType::Bulk(score))),
+        resp => Err(Error::UnexpectedReply(resp)),
+    }
+}
+
+/// Pairs up a flat member score array.
+fn scored_members(resp: RESPType) -> crate::Result<Vec<(Bytes, f64)>> {
+    let values = match resp {
+        RESPType::Array(values) if values.len().is_multiple_of(2) => values,
+        resp => return Err(Error::UnexpectedReply(resp)),
+    };
+    let mut values = values.into_iter();
+    let mut members = vec![];
+    while let (Some(member), Some(resp)) = (values.next(), values.next()) {
+        match member {
+            RESPType::Bulk(member) => members.push((member, score(resp)?)),
+            resp => return Err(Error::UnexpectedReply(resp)),
+        }
+    }
+    Ok(members)
+}
+
+/// Next cursor and the page of a scan reply.
+fn scan_page(resp: RESPType) -> crate::Result<(u64, RESPType)> {
+    match resp {
+        RESPType::Array(arr) if arr.len() == 2 => {
+            let mut arr = arr.into_iter();
+            match (arr.next(), arr.next()) {
+                (Some(RESPType::Bulk(next)), Some(page)) => {
+                    let cursor = std::str::from_utf8(&next)
+                        .ok()
+                        .and_then(|next| next.parse().ok())
+                        .ok_or(Error::UnexpectedReply(RESPType::Bulk(next)))?;
+                    Ok((cursor, page))
+                }
+                (next, _) => Err(Error::UnexpectedReply(next.unwrap_or(RESPType::Null))),
+            }
+        }
+        resp => Err(Error::UnexpectedReply(resp)),
+    }
+}
+
+/// Key and elements of an LMPOP or BLMPOP reply, `None` if nothing was popped.
+fn popped(resp: RESPType) -> crate::Result<Option<(Bytes, Vec<Bytes>)>> {
+    match resp {
+        RESPType::Array(arr) if arr.len() == 2 => {
+            let mut arr = arr.into_iter();
+            match (arr.next(), arr.next()) {
+                (Some(RESPType::Bulk(key)), Some(values)) => Ok(Some((key, bulk_array(values)?))),
+                (key, _) => Err(Error::UnexpectedReply(key.unwrap_or(RESPType::Null))),
+            }
+        }
+        RESPType::NullArray | RESPType::Null => Ok(None),
+        resp => Err(Error::UnexpectedReply(resp)),
+    }
+}
+
+fn stream_id(resp: RESPType) -> crate::Result<StreamId> {
+    match resp {
+        RESPType::Bulk(id) => {
+            StreamId::parse(&id, 0).ok_or(Error::UnexpectedReply(RESPType::Bulk(id)))
+        }
+        resp => Err(Error::UnexpectedReply(resp)),
+    }
+}
+
+/// Stream entries replied as `[id, [field, value, ...]]` arrays. Entries deleted since
+/// they were delivered have no fields.
+fn stream_entries(resp: RESPType) -> crate::Result<Vec<StreamEntry>> {
+    let entries = match resp {
+        RESPType::Array(entries) => entries,
+        resp => return Err(Error::UnexpectedReply(resp)),
+    };
+    entries
+        .into_iter()
+        .map(|entry| match entry {
+            RESPType::Array(entry) if entry.len() == 2 => {
+                let mut entry = entry.into_iter();
+                match (entry.next(), entry.next()) {
+                    (Some(id), Some(RESPType::Null)) => Ok((stream_id(id)?, vec![])),
+                    (Some(id), Some(fields)) => {
+                        Ok((stream_id(id)?, bulk_pairs(bulk_array(fields)?)?))
+                    }
+                    (id, _) => Err(Error::UnexpectedReply(id.unwrap_or(RESPType::Null))),
+                }
+            }
+            resp => Err(Error::UnexpectedReply(resp)),
+        })
+        .collect()
+}
+
+/// Entries of an XREAD or XREADGROUP reply by stream, empty if the read timed out.
+fn stream_reads(resp: RESPType) -> crate::Result<Vec<(Bytes, Vec<StreamEntry>)>> {
+    let streams = match resp {
+        RESPType::NullArray | RESPType::Null => return Ok(vec![]),
+        RESPType::Array(streams) => streams,
+        resp => return Err(Error::UnexpectedReply(resp)),
+    };
+    streams
+        .into_iter()
+        .map(|stream| match stream {
+            RESPType::Array(stream) if stream.len() == 2 => {
+                let mut stream = stream.into_iter();
+                match (stream.next(), stream.next()) {
+                    (Some(RESPType::Bulk(key)), Some(entries)) => {
+                        Ok((key, stream_entries(entries)?))
+                    }
+                    (key, _) => Err(Error::UnexpectedReply(key.unwrap_or(RESPType::Null))),
+                }
+            }
+            resp => Err(Error::UnexpectedReply(resp)),
+        })
+        .collect()
 }
@@ -0,0 +1,49 @@
+use bytes::{Bytes, BytesMut};
+
+use crate::db::{set_keep_ttl, Entry, Value, MAX_STRING_LEN};
+use crate::{Db, Error, RESPType};
+
+pub struct Append {
+    key: Bytes,
+    value: Bytes,
+}
+
+impl Append {
+    pub fn new(key: Bytes, value: Bytes) -> Self {
+        Append { key, value }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let current = match slot.as_ref().map(Entry::string) {
+                None => Bytes::new(),
+                Some(Err(e)) => return e.into(),
+                Some(Ok(value)) => value.clone(),
+            };
+            let len = current.len() + self.value.len();
+            if len > MAX_STRING_LEN {
+                return too_long();
+            }
+
+            let mut value = BytesMut::with_capacity(len);
+            value.extend_from_slice(&current);
+            value.extend_from_slice(&self.value);
+            set_keep_ttl(slot, Value::String(value.freeze()));
+            RESPType::Integer(len as i64)
+        })
+    }
+}
+
+pub(crate) fn too_long() -> RESPType {
+    Error::command("string exceeds maximum allowed size (proto-max-bulk-len)").into()
+}
+
+impl From<Append> for RESPType {
+    fn from(append: Append) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("append")),
+            RESPType::Bulk(append.key),
+            RESPType::Bulk(append.value),
+        ])
+    }
+}
@@ -0,0 +1,104 @@
+use bytes::Bytes;
+
+use crate::{Db, RESPType};
+
+/// Unit of the ranges of BITCOUNT and BITPOS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitUnit {
+    Byte,
+    Bit,
+}
+
+/// BITCOUNT, counting the set bits of the whole string or of a range of it.
+pub struct Bitcount {
+    key: Bytes,
+    range: Option<(i64, i64, BitUnit)>,
+}
+
+impl Bitcount {
+    pub fn new(key: Bytes) -> Self {
+        Bitcount { key, range: None }
+    }
+
+    /// Only count from `start` to `end` inclusive, negative offsets counting back from the
+    /// end of the string.
+    pub fn range(mut self, start: i64, end: i64, unit: BitUnit) -> Self {
+        self.range = Some((start, end, unit));
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let value = match db.get(&self.key) {
+            Err(e) => return e.into(),
+            Ok(None) => return RESPType::Integer(0),
+            Ok(Some(value)) => value,
+        };
+        let (start, end, unit) = self.range.unwrap_or((0, -1, BitUnit::Byte));
+
+        let count = match bit_range(value.len(), start, end, unit) {
+            None => 0,
+            Some((first, last)) if first / 8 == last / 8 => {
+                (value[first / 8] & mask(first, last)).count_ones()
+            }
+            Some((first, last)) => {
+                let (head, tail) = (first / 8, last / 8);
+                let middle: u32 = value[head + 1..tail].iter().map(|b| b.count_ones()).sum();
+                (value[head] & mask(first, 8 * head + 7)).count_ones()
+                    + middle
+                    + (value[tail] & mask(8 * tail, last)).count_ones()
+            }
+        };
+        RESPType::Integer(count as i64)
+    }
+}
+
+/// Resolves a BITCOUNT or BITPOS range over a string of `len` bytes into the first and last
+/// bit it covers, `None` if it is empty.
+pub(crate) fn bit_range(len: usize, start: i64, end: i64, unit: BitUnit) -> Option<(usize, usize)> {
+    // checked before clamping, which would turn it into a range at the start
+    if start < 0 && end < 0 && start > end {
+        return None;
+    }
+    let total = match unit {
+        BitUnit::Byte => len as i64,
+        BitUnit::Bit => len as i64 * 8,
+    };
+    let resolve = |offset: i64| match offset {
+        offset if offset < 0 => (total + offset).max(0),
+        offset => offset,
+    };
+    let (start, end) = (resolve(start), resolve(end).min(total - 1));
+    if start > end {
+        return None;
+    }
+
+    let (start, end) = (start as usize, end as usize);
+    match unit {
+        BitUnit::Byte => Some((start * 8, end * 8 + 7)),
+        BitUnit::Bit => Some((start, end)),
+    }
+}
+
+/// Mask of the bits from `first` to `last` within their byte.
+fn mask(first: usize, last: usize) -> u8 {
+    (0xff >> (first % 8)) & (0xff << (7 - last % 8))
+}
+
+impl From<Bitcount> for RESPType {
+    fn from(bitcount: Bitcount) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("bitcount")),
+            RESPType::Bulk(bitcount.key),
+        ];
+        if let Some((start, end, unit)) = bitcount.range {
+            arr.push(RESPType::Bulk(Bytes::from(start.to_string())));
+            arr.push(RESPType::Bulk(Bytes::from(end.to_string())));
+            arr.push(RESPType::Bulk(Bytes::from(match unit {
+                BitUnit::Byte => "BYTE",
+                BitUnit::Bit => "BIT",
+            })));
+        }
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,232 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use bytes::Bytes;
+
+use crate::db::{set_keep_ttl, Entry, Value};
+use crate::{Db, RESPType};
+
+/// Integer type of a BITFIELD operation, signed up to 64 bits or unsigned up to 63.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitfieldType {
+    pub signed: bool,
+    pub bits: u8,
+}
+
+impl BitfieldType {
+    fn min(&self) -> i128 {
+        match self.signed {
+            true => -(1 << (self.bits - 1)),
+            false => 0,
+        }
+    }
+
+    fn max(&self) -> i128 {
+        match self.signed {
+            true => (1 << (self.bits - 1)) - 1,
+            false => (1 << self.bits) - 1,
+        }
+    }
+
+    /// Reinterprets the low bits of `value` as this type, as a wrapping overflow does.
+    fn wrap(&self, value: i128) -> i128 {
+        let value = value & ((1 << self.bits) - 1);
+        match self.signed && value > self.max() {
+            true => value - (1 << self.bits),
+            false => value,
+        }
+    }
+}
+
+impl fmt::Display for BitfieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", if self.signed { "i" } else { "u" }, self.bits)
+    }
+}
+
+/// What a write does with a value outside of the range of its type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    Wrap,
+    Sat,
+    Fail,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitfieldOp {
+    Get(BitfieldType, u64),
+    Set(BitfieldType, u64, i64),
+    IncrBy(BitfieldType, u64, i64),
+    /// Overflow handling of the writes that follow.
+    Overflow(Overflow),
+}
+
+/// BITFIELD, or BITFIELD_RO when read only, reading and writing integers at arbitrary bit
+/// offsets of a string. Replies with a value for every GET, SET and INCRBY.
+pub struct Bitfield {
+    key: Bytes,
+    ops: Vec<BitfieldOp>,
+    readonly: bool,
+}
+
+impl Bitfield {
+    pub fn new(key: Bytes, ops: Vec<BitfieldOp>) -> Self {
+        Bitfield {
+            key,
+            ops,
+            readonly: false,
+        }
+    }
+
+    /// Only GET operations are allowed.
+    pub fn readonly(mut self, readonly: bool) -> Self {
+        self.readonly = readonly;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let current = match slot.as_mut().map(Entry::string_mut) {
+                None => None,
+                Some(Err(e)) => return e.into(),
+                Some(Ok(value)) => Some(value),
+            };
+
+            // writes grow the string to cover every field they touch, even failed ones
+            let end = self
+                .ops
+                .iter()
+                .filter_map(|op| match op {
+                    BitfieldOp::Set(kind, offset, _) | BitfieldOp::IncrBy(kind, offset, _) => {
+                        Some(((offset + kind.bits as u64).div_ceil(8)) as usize)
+                    }
+                    _ => None,
+                })
+                .max();
+            // reads look at the string where it is, writes take it out of the entry and
+            // only copy it if shared
+            let mut value = match end {
+                None => Cow::Borrowed(current.map_or(&[][..], |value| &value[..])),
+                Some(end) => {
+                    let mut value = Vec::from(current.map(std::mem::take).unwrap_or_default());
+                    if value.len() < end {
+                        value.resize(end, 0);
+                    }
+                    Cow::Owned(value)
+                }
+            };
+
+            let mut overflow = Overflow::Wrap;
+            let mut replies = vec![];
+            for op in &self.ops {
+                let (kind, offset, new) = match *op {
+                    BitfieldOp::Overflow(mode) => {
+                        overflow = mode;
+                        continue;
+                    }
+                    BitfieldOp::Get(kind, offset) => {
+                        let field = read(&value, kind, offset);
+                        replies.push(RESPType::Integer(field as i64));
+                        continue;
+                    }
+                    // unsigned fields take the bits of a negative value as is
+                    BitfieldOp::Set(kind, offset, new) if !kind.signed => {
+                        (kind, offset, new as u64 as i128)
+                    }
+                    BitfieldOp::Set(kind, offset, new) => (kind, offset, new as i128),
+                    BitfieldOp::IncrBy(kind, offset, incr) => {
+                        (kind, offset, read(&value, kind, offset) + incr as i128)
+                    }
+                };
+
+                let old = read(&value, kind, offset);
+                let new = match overflow {
+                    _ if (kind.min()..=kind.max()).contains(&new) => new,
+                    Overflow::Wrap => kind.wrap(new),
+                    Overflow::Sat => new.clamp(kind.min(), kind.max()),
+                    Overflow::Fail => {
+                        replies.push(RESPType::Null);
+                        continue;
+                    }
+                };
+                write(value.to_mut(), kind, offset, new);
+                replies.push(RESPType::Integer(match op {
+                    BitfieldOp::Set(..) => old as i64,
+                    _ => new as i64,
+                }));
+            }
+
+            if let Cow::Owned(value) = value {
+                set_keep_ttl(slot, Value::String(value.into()));
+            }
+            RESPType::Array(replies)
+        })
+    }
+}
+
+/// The field of type `kind` at bit `offset`, bits past the end of the string being 0.
+fn read(value: &[u8], kind: BitfieldType, offset: u64) -> i128 {
+    let mut field: i128 = 0;
+    for bit in offset..offset + kind.bits as u64 {
+        let byte = value.get((bit / 8) as usize).copied().unwrap_or(0);
+        field = (field << 1) | ((byte >> (7 - bit % 8)) & 1) as i128;
+    }
+    kind.wrap(field)
+}
+
+/// Writes the field of type `kind` at bit `offset`, which must be within `value`.
+fn write(value: &mut [u8], kind: BitfieldType, offset: u64, field: i128) {
+    for (idx, bit) in (offset..offset + kind.bits as u64).enumerate() {
+        let set = (field >> (kind.bits as usize - 1 - idx)) & 1 == 1;
+        let mask = 0x80 >> (bit % 8);
+        match set {
+            true => value[(bit / 8) as usize] |= mask,
+            false => value[(bit / 8) as usize] &= !mask,
+        }
+    }
+}
+
+impl From<Bitfield> for RESPType {
+    fn from(bitfield: Bitfield) -> RESPType {
+        let name = if bitfield.readonly {
+            "bitfield_ro"
+        } else {
+            "bitfield"
+        };
+        let mut args: Vec<String> = vec![];
+        for op in bitfield.ops {
+            match op {
+                BitfieldOp::Get(kind, offset) => {
+                    args.extend(["GET".into(), kind.to_string(), offset.to_string()])
+                }
+                BitfieldOp::Set(kind, offset, value) => args.extend([
+                    "SET".into(),
+                    kind.to_string(),
+                    offset.to_string(),
+                    value.to_string(),
+                ]),
+                BitfieldOp::IncrBy(kind, offset, incr) => args.extend([
+                    "INCRBY".into(),
+                    kind.to_string(),
+                    offset.to_string(),
+                    incr.to_string(),
+                ]),
+                BitfieldOp::Overflow(overflow) => {
+                    let overflow = match overflow {
+                        Overflow::Wrap => "WRAP",
+                        Overflow::Sat => "SAT",
+                        Overflow::Fail => "FAIL",
+                    };
+                    args.extend(["OVERFLOW".into(), overflow.into()])
+                }
+            }
+        }
+
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from(name)),
+            RESPType::Bulk(bitfield.key),
+        ];
+        arr.extend(args.into_iter().map(|arg| RESPType::Bulk(Bytes::from(arg))));
+        RESPType::Array(arr)
+    }
+}
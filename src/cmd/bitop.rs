@@ -0,0 +1,96 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, Value};
+use crate::{Db, RESPType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitopKind {
+    And,
+    Or,
+    Xor,
+    Not,
+}
+
+impl BitopKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BitopKind::And => "AND",
+            BitopKind::Or => "OR",
+            BitopKind::Xor => "XOR",
+            BitopKind::Not => "NOT",
+        }
+    }
+}
+
+/// BITOP, storing the bitwise combination of strings at the destination. Shorter strings
+/// and missing keys count as zero bytes up to the longest one.
+pub struct Bitop {
+    kind: BitopKind,
+    destination: Bytes,
+    keys: Vec<Bytes>,
+}
+
+impl Bitop {
+    /// NOT takes a single key.
+    pub fn new(kind: BitopKind, destination: Bytes, keys: Vec<Bytes>) -> Self {
+        Bitop {
+            kind,
+            destination,
+            keys,
+        }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let mut keys = self.keys.clone();
+        keys.push(self.destination.clone());
+        let mut locked = db.lock(&keys);
+
+        let mut values = vec![];
+        for key in &self.keys {
+            let value = locked.update(key, |slot| match slot.as_ref().map(Entry::string) {
+                None => Ok(Bytes::new()),
+                Some(value) => value.cloned(),
+            });
+            match value {
+                Err(e) => return e.into(),
+                Ok(value) => values.push(value),
+            }
+        }
+
+        let len = values.iter().map(Bytes::len).max().unwrap_or(0);
+        let byte = |value: &Bytes, idx: usize| value.get(idx).copied().unwrap_or(0);
+        let result: Vec<u8> = (0..len)
+            .map(|idx| {
+                let mut bytes = values.iter().map(|value| byte(value, idx));
+                match self.kind {
+                    BitopKind::Not => !bytes.next().unwrap_or(0),
+                    BitopKind::And => bytes.fold(0xff, |acc, b| acc & b),
+                    BitopKind::Or => bytes.fold(0, |acc, b| acc | b),
+                    BitopKind::Xor => bytes.fold(0, |acc, b| acc ^ b),
+                }
+            })
+            .collect();
+
+        // an empty result deletes the destination rather than storing an empty string
+        locked.update(&self.destination, |slot| {
+            *slot = match result.is_empty() {
+                true => None,
+                false => Some(Entry::new(Value::String(Bytes::from(result)))),
+            };
+        });
+        RESPType::Integer(len as i64)
+    }
+}
+
+impl From<Bitop> for RESPType {
+    fn from(bitop: Bitop) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("bitop")),
+            RESPType::Bulk(Bytes::from(bitop.kind.name())),
+            RESPType::Bulk(bitop.destination),
+        ];
+        arr.extend(bitop.keys.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,100 @@
+use bytes::Bytes;
+
+use crate::{Db, RESPType};
+
+use super::bitcount::bit_range;
+use super::getbit::get_bit;
+use super::BitUnit;
+
+/// BITPOS, finding the first bit set to 1 or 0.
+pub struct Bitpos {
+    key: Bytes,
+    bit: bool,
+    start: Option<i64>,
+    end: Option<i64>,
+    unit: BitUnit,
+}
+
+impl Bitpos {
+    pub fn new(key: Bytes, bit: bool) -> Self {
+        Bitpos {
+            key,
+            bit,
+            start: None,
+            end: None,
+            unit: BitUnit::Byte,
+        }
+    }
+
+    pub fn start(mut self, start: i64) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Stop searching after `end`. Without it a search for a 0 bit in a string of ones
+    /// finds the first bit past its end.
+    pub fn end(mut self, end: i64, unit: BitUnit) -> Self {
+        self.end = Some(end);
+        self.unit = unit;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let value = match db.get(&self.key) {
+            Err(e) => return e.into(),
+            // a missing string is all zeros
+            Ok(None) => return RESPType::Integer(if self.bit { -1 } else { 0 }),
+            Ok(Some(value)) => value,
+        };
+
+        let start = self.start.unwrap_or(0);
+        let end = self.end.unwrap_or(-1);
+        let (first, last) = match bit_range(value.len(), start, end, self.unit) {
+            None => return RESPType::Integer(-1),
+            Some(range) => range,
+        };
+
+        // whole bytes without the bit are skipped at once
+        let skip = if self.bit { 0x00 } else { 0xff };
+        let mut offset = first;
+        while offset <= last {
+            if offset % 8 == 0 && offset + 7 <= last && value[offset / 8] == skip {
+                offset += 8;
+                continue;
+            }
+            if get_bit(&value, offset as u64) == self.bit {
+                return RESPType::Integer(offset as i64);
+            }
+            offset += 1;
+        }
+
+        match (self.bit, self.end) {
+            (false, None) => RESPType::Integer(last as i64 + 1),
+            _ => RESPType::Integer(-1),
+        }
+    }
+}
+
+impl From<Bitpos> for RESPType {
+    fn from(bitpos: Bitpos) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("bitpos")),
+            RESPType::Bulk(bitpos.key),
+            RESPType::Bulk(Bytes::from(if bitpos.bit { "1" } else { "0" })),
+        ];
+        if bitpos.start.is_some() || bitpos.end.is_some() {
+            arr.push(RESPType::Bulk(Bytes::from(
+                bitpos.start.unwrap_or(0).to_string(),
+            )));
+        }
+        if let Some(end) = bitpos.end {
+            arr.push(RESPType::Bulk(Bytes::from(end.to_string())));
+            arr.push(RESPType::Bulk(Bytes::from(match bitpos.unit {
+                BitUnit::Byte => "BYTE",
+                BitUnit::Bit => "BIT",
+            })));
+        }
+
+        RESPType::Array(arr)
+    }
+}
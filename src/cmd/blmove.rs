@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+
+use crate::db::Locked;
+use crate::{Db, RESPType};
+
+use super::lmove::move_element;
+use super::ListEnd;
+
+/// BLMOVE, and BRPOPLPUSH as its right to left special case.
+pub struct Blmove {
+    source: Bytes,
+    destination: Bytes,
+    from: ListEnd,
+    to: ListEnd,
+    timeout: Duration,
+}
+
+impl Blmove {
+    /// A zero `timeout` blocks until an element arrives.
+    pub fn new(
+        source: Bytes,
+        destination: Bytes,
+        from: ListEnd,
+        to: ListEnd,
+        timeout: Duration,
+    ) -> Self {
+        Blmove {
+            source,
+            destination,
+            from,
+            to,
+            timeout,
+        }
+    }
+
+    pub async fn response(&self, db: &Db) -> RESPType {
+        let timeout = (!self.timeout.is_zero()).then_some(self.timeout);
+        let keys = [self.source.clone()];
+        let moved = db
+            .block(&keys, timeout, || {
+                let mut locked = db.lock(&[&self.source, &self.destination]);
+                match move_element(
+                    &mut locked,
+                    &self.source,
+                    &self.destination,
+                    self.from,
+                    self.to,
+                ) {
+                    // with an element to move the client is served, even if only with the
+                    // destination's WRONGTYPE, rather than left waiting
+                    Err(e) if has_list(&mut locked, &self.source) => Ok(Some(Err(e))),
+                    moved => moved.map(|value| value.map(Ok)),
+                }
+            })
+            .await;
+
+        match moved.and_then(Option::transpose) {
+            Err(e) => e.into(),
+            Ok(None) => RESPType::Null,
+            Ok(Some(value)) => RESPType::Bulk(value),
+        }
+    }
+}
+
+fn has_list(locked: &mut Locked<'_>, key: &[u8]) -> bool {
+    locked.update(key, |slot| {
+        slot.as_ref()
+            .is_some_and(|entry| entry.list().is_ok_and(|list| !list.is_empty()))
+    })
+}
+
+impl From<Blmove> for RESPType {
+    fn from(blmove: Blmove) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("blmove")),
+            RESPType::Bulk(blmove.source),
+            RESPType::Bulk(blmove.destination),
+            RESPType::Bulk(Bytes::from(blmove.from.name())),
+            RESPType::Bulk(Bytes::from(blmove.to.name())),
+            RESPType::Bulk(Bytes::from(blmove.timeout.as_secs_f64().to_string())),
+        ])
+    }
+}
@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+
+use crate::{Db, RESPType};
+
+use super::lmpop::pop_first;
+use super::ListEnd;
+
+/// Blocking LMPOP, waiting for one of `keys` to receive elements when all of them are empty.
+pub struct Blmpop {
+    keys: Vec<Bytes>,
+    end: ListEnd,
+    count: usize,
+    timeout: Duration,
+}
+
+impl Blmpop {
+    /// A zero `timeout` blocks until an element arrives.
+    pub fn new(keys: Vec<Bytes>, end: ListEnd, timeout: Duration) -> Self {
+        Blmpop {
+            keys,
+            end,
+            count: 1,
+            timeout,
+        }
+    }
+
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    pub async fn response(&self, db: &Db) -> RESPType {
+        let timeout = (!self.timeout.is_zero()).then_some(self.timeout);
+        let popped = db
+            .block(&self.keys, timeout, || {
+                pop_first(&mut db.lock(&self.keys), &self.keys, self.end, self.count)
+            })
+            .await;
+
+        match popped {
+            Err(e) => e.into(),
+            Ok(None) => RESPType::NullArray,
+            Ok(Some((key, values))) => RESPType::Array(vec![
+                RESPType::Bulk(key),
+                RESPType::Array(values.into_iter().map(RESPType::Bulk).collect()),
+            ]),
+        }
+    }
+}
+
+impl From<Blmpop> for RESPType {
+    fn from(blmpop: Blmpop) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("blmpop")),
+            RESPType::Bulk(Bytes::from(blmpop.timeout.as_secs_f64().to_string())),
+            RESPType::Bulk(Bytes::from(blmpop.keys.len().to_string())),
+        ];
+        arr.extend(blmpop.keys.into_iter().map(RESPType::Bulk));
+        arr.push(RESPType::Bulk(Bytes::from(blmpop.end.name())));
+        arr.push(RESPType::Bulk(Bytes::from("COUNT")));
+        arr.push(RESPType::Bulk(Bytes::from(blmpop.count.to_string())));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+
+use crate::{Db, RESPType};
+
+use super::lmpop::pop_first;
+use super::ListEnd;
+
+/// BLPOP and BRPOP, popping from the first non-empty list among `keys` or waiting for one.
+pub struct Bpop {
+    keys: Vec<Bytes>,
+    end: ListEnd,
+    timeout: Duration,
+}
+
+impl Bpop {
+    /// A zero `timeout` blocks until an element arrives.
+    pub fn new(keys: Vec<Bytes>, end: ListEnd, timeout: Duration) -> Self {
+        Bpop { keys, end, timeout }
+    }
+
+    pub async fn response(&self, db: &Db) -> RESPType {
+        let timeout = (!self.timeout.is_zero()).then_some(self.timeout);
+        let popped = db
+            .block(&self.keys, timeout, || {
+                pop_first(&mut db.lock(&self.keys), &self.keys, self.end, 1)
+            })
+            .await;
+
+        match popped {
+            Err(e) => e.into(),
+            Ok(None) => RESPType::NullArray,
+            Ok(Some((key, mut values))) => {
+                RESPType::Array(vec![RESPType::Bulk(key), RESPType::Bulk(values.remove(0))])
+            }
+        }
+    }
+}
+
+impl From<Bpop> for RESPType {
+    fn from(bpop: Bpop) -> RESPType {
+        let name = match bpop.end {
+            ListEnd::Left => "blpop",
+            ListEnd::Right => "brpop",
+        };
+        let mut arr = vec![RESPType::Bulk(Bytes::from(name))];
+        arr.extend(bpop.keys.into_iter().map(RESPType::Bulk));
+        arr.push(RESPType::Bulk(Bytes::from(
+            bpop.timeout.as_secs_f64().to_string(),
+        )));
+
+        RESPType::Array(arr)
+    }
+}
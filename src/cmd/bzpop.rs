@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+
+use crate::{Db, RESPType};
+
+use super::zpop::pop_first;
+
+/// BZPOPMIN and BZPOPMAX, popping from the first non-empty sorted set among `keys` or
+/// waiting for one.
+pub struct Bzpop {
+    keys: Vec<Bytes>,
+    max: bool,
+    timeout: Duration,
+}
+
+impl Bzpop {
+    /// A zero `timeout` blocks until a member arrives.
+    pub fn new(keys: Vec<Bytes>, max: bool, timeout: Duration) -> Self {
+        Bzpop { keys, max, timeout }
+    }
+
+    pub async fn response(&self, db: &Db) -> RESPType {
+        let timeout = (!self.timeout.is_zero()).then_some(self.timeout);
+        let popped = db
+            .block(&self.keys, timeout, || {
+                pop_first(&mut db.lock(&self.keys), &self.keys, self.max, 1)
+            })
+            .await;
+
+        match popped {
+            Err(e) => e.into(),
+            Ok(None) => RESPType::NullArray,
+            Ok(Some((key, mut popped))) => {
+                let (member, score) = popped.remove(0);
+                RESPType::Array(vec![
+                    RESPType::Bulk(key),
+                    RESPType::Bulk(member),
+                    RESPType::Double(score),
+                ])
+            }
+        }
+    }
+}
+
+impl From<Bzpop> for RESPType {
+    fn from(bzpop: Bzpop) -> RESPType {
+        let name = if bzpop.max { "bzpopmax" } else { "bzpopmin" };
+        let mut arr = vec![RESPType::Bulk(Bytes::from(name))];
+        arr.extend(bzpop.keys.into_iter().map(RESPType::Bulk));
+        arr.push(RESPType::Bulk(Bytes::from(
+            bzpop.timeout.as_secs_f64().to_string(),
+        )));
+
+        RESPType::Array(arr)
+    }
+}
@@ -1,97 +1,5122 @@
+use std::time::Duration;
+
 use bytes::Bytes;
 
-use crate::RESPType;
+use crate::db::MAX_STRING_LEN;
+use crate::{Db, Error, RESPType};
+
+use super::incr::parse_int;
+use super::incrbyfloat::parse_float;
+use super::*;
 
-use super::{Echo, Get, Ping, Set};
+/// Elements a reply to SRANDMEMBER or HRANDFIELD with a negative count may hold. Picks may
+/// repeat, so unlike other replies its size isn't bounded by the data it is built from.
+const MAX_RANDOM_REPLY: u64 = 1 << 24;
 
 pub enum Command {
     Ping(Ping),
     Echo(Echo),
     Get(Get),
     Set(Set),
+    Mget(Mget),
+    Mset(Mset),
+    MsetNx(MsetNx),
+    SetNx(SetNx),
+    GetDel(GetDel),
+    GetEx(GetEx),
+    Incr(Incr),
+    IncrByFloat(IncrByFloat),
+    Append(Append),
+    Strlen(Strlen),
+    GetRange(GetRange),
+    SetRange(SetRange),
+    Push(Push),
+    Pop(Pop),
+    Llen(Llen),
+    Lrange(Lrange),
+    Lindex(Lindex),
+    Lset(Lset),
+    Lrem(Lrem),
+    Ltrim(Ltrim),
+    Linsert(Linsert),
+    Lpos(Lpos),
+    Lmove(Lmove),
+    Lmpop(Lmpop),
+    Bpop(Bpop),
+    Blmove(Blmove),
+    Blmpop(Blmpop),
+    Hset(Hset),
+    HsetNx(HsetNx),
+    Hget(Hget),
+    Hmget(Hmget),
+    Hdel(Hdel),
+    Hexists(Hexists),
+    Hlen(Hlen),
+    Hstrlen(Hstrlen),
+    HgetAll(HgetAll),
+    Hincrby(Hincrby),
+    HincrbyFloat(HincrbyFloat),
+    HrandField(HrandField),
+    Hscan(Hscan),
+    Hexpire(Hexpire),
+    Httl(Httl),
+    Hpersist(Hpersist),
+    Sadd(Sadd),
+    Srem(Srem),
+    Scard(Scard),
+    Smembers(Smembers),
+    Sismember(Sismember),
+    Smismember(Smismember),
+    Spop(Spop),
+    SrandMember(SrandMember),
+    Smove(Smove),
+    Sscan(Sscan),
+    SetOp(SetOp),
+    SinterCard(SinterCard),
+    Zadd(Zadd),
+    Zrem(Zrem),
+    Zscore(Zscore),
+    Zmscore(Zmscore),
+    Zincrby(Zincrby),
+    Zcard(Zcard),
+    Zrank(Zrank),
+    Zcount(Zcount),
+    ZlexCount(ZlexCount),
+    Zrange(Zrange),
+    Zpop(Zpop),
+    Bzpop(Bzpop),
+    ZsetOp(ZsetOp),
+    Xadd(Xadd),
+    Xtrim(Xtrim),
+    Xrange(Xrange),
+    Xlen(Xlen),
+    Xdel(Xdel),
+    Xread(Xread),
+    XreadGroup(XreadGroup),
+    Xgroup(Xgroup),
+    Xack(Xack),
+    Xpending(Xpending),
+    Xclaim(Xclaim),
+    XautoClaim(XautoClaim),
+    Xinfo(Xinfo),
+    Setbit(Setbit),
+    Getbit(Getbit),
+    Bitcount(Bitcount),
+    Bitpos(Bitpos),
+    Bitop(Bitop),
+    Bitfield(Bitfield),
+    Pfadd(Pfadd),
+    Pfcount(Pfcount),
+    Pfmerge(Pfmerge),
+    Geoadd(Geoadd),
+    Geodist(Geodist),
+    Geopos(Geopos),
+    Geohash(Geohash),
+    Geosearch(Geosearch),
+    Del(Del),
+    Exists(Exists),
+    Type(Type),
+    Rename(Rename),
+    Copy(CopyKey),
+    Dbsize(Dbsize),
+    Flush(Flush),
+    RandomKey(RandomKey),
+    Keys(Keys),
+    Scan(Scan),
+    Select(Select),
+    Move(Move),
+    Swapdb(Swapdb),
+    Info(Info),
+    Object(Object),
+    Expire(Expire),
+    Ttl(Ttl),
+    Persist(Persist),
+    Hello(Hello),
+    CommandInfo(CommandInfo),
 }
 
 impl TryFrom<RESPType> for Command {
     type Error = crate::Error;
 
     fn try_from(value: RESPType) -> Result<Self, Self::Error> {
-        match value {
-            RESPType::Array(arr) => match &arr[0] {
-                RESPType::Bulk(cmd) => match &cmd[..] {
-                    b"ping" => Ok(Command::Ping(try_ping(arr)?)),
-                    b"echo" => Ok(Command::Echo(try_echo(arr)?)),
-                    b"get" => Ok(Command::Get(try_get(arr)?)),
-                    b"set" => Ok(Command::Set(try_set(arr)?)),
-                    _ => todo!(),
-                },
-                RESPType::String(cmd) => match &cmd[..] {
-                    "ping" => Ok(Command::Ping(try_ping(arr)?)),
-                    "echo" => Ok(Command::Echo(try_echo(arr)?)),
-                    "get" => Ok(Command::Get(try_get(arr)?)),
-                    "set" => Ok(Command::Set(try_set(arr)?)),
-                    _ => todo!(),
-                },
-                _ => Err("invalid data type for cmd".into()),
-            },
-            _ => Err("Can only get command from array types".into()),
-        }
+        let arr = match value {
+            RESPType::Array(arr) if !arr.is_empty() => arr,
+            _ => return Err(Error::protocol("expected an array of bulk strings")),
+        };
+
+        let spec = resolve(&bytes_args(&arr)?)?;
+        (spec.parse)(arr)
+    }
+}
+
+/// Evicts keys as maxmemory requires before `frame` runs. If that falls short, commands that
+/// may grow the dataset are refused with an OOM error while the others still run, like redis.
+pub fn check_memory(frame: &RESPType, db: &Db) -> crate::Result<()> {
+    if db.free_memory() {
+        return Ok(());
+    }
+    let args = match frame {
+        RESPType::Array(arr) => bytes_args(arr),
+        _ => return Ok(()),
+    };
+    // requests that don't resolve fail with their own error once parsed
+    match args.and_then(|args| resolve(&args)) {
+        Ok(spec) if spec.has_flag(CommandFlag::Denyoom) => Err(Error::server(
+            "OOM",
+            "command not allowed when used memory > 'maxmemory'.",
+        )),
+        _ => Ok(()),
     }
 }
 
-fn try_ping(arr: Vec<RESPType>) -> crate::Result<Ping> {
+pub(super) fn try_ping(arr: Vec<RESPType>) -> crate::Result<Ping> {
     match arr.len() {
         1 => Ok(Ping::new(None)),
-        2 => match &arr[1] {
-            RESPType::Bulk(msg) => Ok(Ping::new(Some(msg.clone()))),
-            RESPType::String(msg) => Ok(Ping::new(Some(Bytes::from(msg.clone())))),
-            _ => Err("invalid data type for echo message".into()),
-        },
-        _ => Err("Too many arguments for ping request".into()),
+        2 => Ok(Ping::new(Some(bytes_arg(&arr[1])?))),
+        _ => Err(Error::WrongArity("ping".into())),
+    }
+}
+
+pub(super) fn try_echo(arr: Vec<RESPType>) -> crate::Result<Echo> {
+    Ok(Echo::new(Some(bytes_arg(&arr[1])?)))
+}
+
+pub(super) fn try_get(arr: Vec<RESPType>) -> crate::Result<Get> {
+    Ok(Get::new(bytes_arg(&arr[1])?))
+}
+
+pub(super) fn try_set(arr: Vec<RESPType>) -> crate::Result<Set> {
+    let mut set = Set::new(bytes_arg(&arr[1])?, bytes_arg(&arr[2])?);
+    let mut condition = None;
+    let mut expiry = None;
+    let mut get = false;
+
+    let mut args = arr[3..].iter();
+    while let Some(arg) = args.next() {
+        let opt = bytes_arg(arg)?.to_ascii_uppercase();
+        match (&opt[..], condition, expiry) {
+            (b"NX", None, _) => condition = Some(SetCondition::Nx),
+            (b"XX", None, _) => condition = Some(SetCondition::Xx),
+            (b"GET", _, _) if !get => get = true,
+            (b"KEEPTTL", _, None) => expiry = Some(SetExpiry::KeepTtl),
+            (b"EX" | b"PX" | b"EXAT" | b"PXAT", _, None) => {
+                let time = match args.next() {
+                    None => return Err(Error::Syntax),
+                    Some(time) => int_arg(time)?,
+                };
+                if time <= 0 || (opt[..] == *b"EX" || opt[..] == *b"EXAT") && time > i64::MAX / 1000
+                {
+                    return Err(Error::command("invalid expire time in 'set' command"));
+                }
+                let time = time as u64;
+                expiry = Some(match &opt[..] {
+                    b"EX" => SetExpiry::Ex(time),
+                    b"PX" => SetExpiry::Px(time),
+                    b"EXAT" => SetExpiry::ExAt(time),
+                    _ => SetExpiry::PxAt(time),
+                });
+            }
+            _ => return Err(Error::Syntax),
+        }
+    }
+
+    if let Some(condition) = condition {
+        set = set.condition(condition);
+    }
+    if let Some(expiry) = expiry {
+        set = set.expiry(expiry);
+    }
+    Ok(set.get(get))
+}
+
+pub(super) fn try_setnx(arr: Vec<RESPType>) -> crate::Result<SetNx> {
+    Ok(SetNx::new(bytes_arg(&arr[1])?, bytes_arg(&arr[2])?))
+}
+
+pub(super) fn try_getset(arr: Vec<RESPType>) -> crate::Result<Set> {
+    Ok(Set::new(bytes_arg(&arr[1])?, bytes_arg(&arr[2])?).get(true))
+}
+
+pub(super) fn try_getdel(arr: Vec<RESPType>) -> crate::Result<GetDel> {
+    Ok(GetDel::new(bytes_arg(&arr[1])?))
+}
+
+pub(super) fn try_getex(arr: Vec<RESPType>) -> crate::Result<GetEx> {
+    let mut getex = GetEx::new(bytes_arg(&arr[1])?);
+
+    let mut args = arr[2..].iter();
+    let mut expiry = None;
+    while let Some(arg) = args.next() {
+        let opt = bytes_arg(arg)?.to_ascii_uppercase();
+        match (&opt[..], expiry) {
+            (b"PERSIST", None) => expiry = Some(GetExExpiry::Persist),
+            (b"EX" | b"PX" | b"EXAT" | b"PXAT", None) => {
+                let time = match args.next() {
+                    None => return Err(Error::Syntax),
+                    Some(time) => int_arg(time)?,
+                };
+                if time <= 0 || (opt[..] == *b"EX" || opt[..] == *b"EXAT") && time > i64::MAX / 1000
+                {
+                    return Err(Error::command("invalid expire time in 'getex' command"));
+                }
+                let time = time as u64;
+                expiry = Some(match &opt[..] {
+                    b"EX" => GetExExpiry::Ex(time),
+                    b"PX" => GetExExpiry::Px(time),
+                    b"EXAT" => GetExExpiry::ExAt(time),
+                    _ => GetExExpiry::PxAt(time),
+                });
+            }
+            _ => return Err(Error::Syntax),
+        }
+    }
+
+    if let Some(expiry) = expiry {
+        getex = getex.expiry(expiry);
+    }
+    Ok(getex)
+}
+
+/// INCR and DECR step by one, INCRBY and DECRBY take the step as third argument.
+pub(super) fn try_incr(arr: Vec<RESPType>, decrement: bool) -> crate::Result<Incr> {
+    let delta = match arr.get(2) {
+        None => 1,
+        Some(delta) => int_arg(delta)?,
+    };
+    let delta = match decrement {
+        false => delta,
+        true => delta
+            .checked_neg()
+            .ok_or_else(|| Error::command("decrement would overflow"))?,
+    };
+    Ok(Incr::new(bytes_arg(&arr[1])?, delta))
+}
+
+pub(super) fn try_incrbyfloat(arr: Vec<RESPType>) -> crate::Result<IncrByFloat> {
+    let delta = parse_float(&bytes_arg(&arr[2])?)
+        .ok_or_else(|| Error::command("value is not a valid float"))?;
+    Ok(IncrByFloat::new(bytes_arg(&arr[1])?, delta))
+}
+
+pub(super) fn try_append(arr: Vec<RESPType>) -> crate::Result<Append> {
+    Ok(Append::new(bytes_arg(&arr[1])?, bytes_arg(&arr[2])?))
+}
+
+pub(super) fn try_strlen(arr: Vec<RESPType>) -> crate::Result<Strlen> {
+    Ok(Strlen::new(bytes_arg(&arr[1])?))
+}
+
+pub(super) fn try_getrange(arr: Vec<RESPType>) -> crate::Result<GetRange> {
+    Ok(GetRange::new(
+        bytes_arg(&arr[1])?,
+        int_arg(&arr[2])?,
+        int_arg(&arr[3])?,
+    ))
+}
+
+pub(super) fn try_setrange(arr: Vec<RESPType>) -> crate::Result<SetRange> {
+    let offset = int_arg(&arr[2])?;
+    if offset < 0 {
+        return Err(Error::command("offset is out of range"));
+    }
+    Ok(SetRange::new(
+        bytes_arg(&arr[1])?,
+        offset as usize,
+        bytes_arg(&arr[3])?,
+    ))
+}
+
+pub(super) fn try_mget(arr: Vec<RESPType>) -> crate::Result<Mget> {
+    Ok(Mget::new(bytes_args(&arr[1..])?))
+}
+
+/// Key value pairs of MSET and MSETNX, or field value pairs of HSET, starting at `start`.
+pub(super) fn pairs_arg(arr: &[RESPType], start: usize) -> crate::Result<Vec<(Bytes, Bytes)>> {
+    if !(arr.len() - start).is_multiple_of(2) {
+        return Err(arity_error(arr));
+    }
+
+    let mut pairs = Vec::with_capacity((arr.len() - start) / 2);
+    for pair in arr[start..].chunks(2) {
+        pairs.push((bytes_arg(&pair[0])?, bytes_arg(&pair[1])?));
     }
+    Ok(pairs)
+}
+
+/// LPUSH and RPUSH, or LPUSHX and RPUSHX when `existing` is set.
+pub(super) fn try_push(arr: Vec<RESPType>, end: ListEnd, existing: bool) -> crate::Result<Push> {
+    Ok(Push::new(bytes_arg(&arr[1])?, bytes_args(&arr[2..])?, end).existing(existing))
 }
 
-fn try_echo(arr: Vec<RESPType>) -> crate::Result<Echo> {
+pub(super) fn try_pop(arr: Vec<RESPType>, end: ListEnd) -> crate::Result<Pop> {
+    let pop = Pop::new(bytes_arg(&arr[1])?, end);
     match arr.len() {
-        1 => Ok(Echo::new(None)),
-        2 => match &arr[1] {
-            RESPType::Bulk(msg) => Ok(Echo::new(Some(msg.clone()))),
-            RESPType::String(msg) => Ok(Echo::new(Some(Bytes::from(msg.clone())))),
-            _ => Err("invalid data type for echo message".into()),
+        2 => Ok(pop),
+        3 => Ok(pop.count(count_arg(&arr[2])?)),
+        _ => Err(arity_error(&arr)),
+    }
+}
+
+pub(super) fn try_llen(arr: Vec<RESPType>) -> crate::Result<Llen> {
+    Ok(Llen::new(bytes_arg(&arr[1])?))
+}
+
+pub(super) fn try_lrange(arr: Vec<RESPType>) -> crate::Result<Lrange> {
+    Ok(Lrange::new(
+        bytes_arg(&arr[1])?,
+        int_arg(&arr[2])?,
+        int_arg(&arr[3])?,
+    ))
+}
+
+pub(super) fn try_lindex(arr: Vec<RESPType>) -> crate::Result<Lindex> {
+    Ok(Lindex::new(bytes_arg(&arr[1])?, int_arg(&arr[2])?))
+}
+
+pub(super) fn try_lset(arr: Vec<RESPType>) -> crate::Result<Lset> {
+    Ok(Lset::new(
+        bytes_arg(&arr[1])?,
+        int_arg(&arr[2])?,
+        bytes_arg(&arr[3])?,
+    ))
+}
+
+pub(super) fn try_lrem(arr: Vec<RESPType>) -> crate::Result<Lrem> {
+    Ok(Lrem::new(
+        bytes_arg(&arr[1])?,
+        int_arg(&arr[2])?,
+        bytes_arg(&arr[3])?,
+    ))
+}
+
+pub(super) fn try_ltrim(arr: Vec<RESPType>) -> crate::Result<Ltrim> {
+    Ok(Ltrim::new(
+        bytes_arg(&arr[1])?,
+        int_arg(&arr[2])?,
+        int_arg(&arr[3])?,
+    ))
+}
+
+pub(super) fn try_linsert(arr: Vec<RESPType>) -> crate::Result<Linsert> {
+    let before = match &bytes_arg(&arr[2])?.to_ascii_uppercase()[..] {
+        b"BEFORE" => true,
+        b"AFTER" => false,
+        _ => return Err(Error::Syntax),
+    };
+    Ok(Linsert::new(
+        bytes_arg(&arr[1])?,
+        before,
+        bytes_arg(&arr[3])?,
+        bytes_arg(&arr[4])?,
+    ))
+}
+
+pub(super) fn try_lpos(arr: Vec<RESPType>) -> crate::Result<Lpos> {
+    let mut lpos = Lpos::new(bytes_arg(&arr[1])?, bytes_arg(&arr[2])?);
+
+    let mut args = arr[3..].iter();
+    while let Some(arg) = args.next() {
+        let opt = bytes_arg(arg)?.to_ascii_uppercase();
+        let value = match args.next() {
+            None => return Err(Error::Syntax),
+            Some(value) => int_arg(value)?,
+        };
+        lpos = match &opt[..] {
+            b"RANK" if value == 0 => {
+                return Err(Error::command(
+                    "RANK can't be zero: use 1 to start from the first match, 2 from the second ... or use negative to start from the end of the list",
+                ))
+            }
+            b"RANK" if value == i64::MIN => {
+                return Err(Error::command("value is out of range"))
+            }
+            b"RANK" => lpos.rank(value),
+            b"COUNT" if value < 0 => {
+                return Err(Error::command("COUNT can't be negative"))
+            }
+            b"COUNT" => lpos.count(value as usize),
+            b"MAXLEN" if value < 0 => {
+                return Err(Error::command("MAXLEN can't be negative"))
+            }
+            b"MAXLEN" => lpos.maxlen(value as usize),
+            _ => return Err(Error::Syntax),
+        };
+    }
+    Ok(lpos)
+}
+
+pub(super) fn try_lmove(arr: Vec<RESPType>) -> crate::Result<Lmove> {
+    Ok(Lmove::new(
+        bytes_arg(&arr[1])?,
+        bytes_arg(&arr[2])?,
+        end_arg(&arr[3])?,
+        end_arg(&arr[4])?,
+    ))
+}
+
+pub(super) fn try_rpoplpush(arr: Vec<RESPType>) -> crate::Result<Lmove> {
+    Ok(Lmove::new(
+        bytes_arg(&arr[1])?,
+        bytes_arg(&arr[2])?,
+        ListEnd::Right,
+        ListEnd::Left,
+    ))
+}
+
+pub(super) fn try_lmpop(arr: Vec<RESPType>) -> crate::Result<Lmpop> {
+    let (keys, end, count) = mpop_args(&arr[1..])?;
+    Ok(Lmpop::new(keys, end).count(count))
+}
+
+pub(super) fn try_bpop(arr: Vec<RESPType>, end: ListEnd) -> crate::Result<Bpop> {
+    let timeout = timeout_arg(&arr[arr.len() - 1])?;
+    Ok(Bpop::new(bytes_args(&arr[1..arr.len() - 1])?, end, timeout))
+}
+
+pub(super) fn try_blmove(arr: Vec<RESPType>) -> crate::Result<Blmove> {
+    Ok(Blmove::new(
+        bytes_arg(&arr[1])?,
+        bytes_arg(&arr[2])?,
+        end_arg(&arr[3])?,
+        end_arg(&arr[4])?,
+        timeout_arg(&arr[5])?,
+    ))
+}
+
+pub(super) fn try_brpoplpush(arr: Vec<RESPType>) -> crate::Result<Blmove> {
+    Ok(Blmove::new(
+        bytes_arg(&arr[1])?,
+        bytes_arg(&arr[2])?,
+        ListEnd::Right,
+        ListEnd::Left,
+        timeout_arg(&arr[3])?,
+    ))
+}
+
+pub(super) fn try_blmpop(arr: Vec<RESPType>) -> crate::Result<Blmpop> {
+    let timeout = timeout_arg(&arr[1])?;
+    let (keys, end, count) = mpop_args(&arr[2..])?;
+    Ok(Blmpop::new(keys, end, timeout).count(count))
+}
+
+/// HSET, or HMSET when `legacy` is set.
+pub(super) fn try_hset(arr: Vec<RESPType>, legacy: bool) -> crate::Result<Hset> {
+    Ok(Hset::new(bytes_arg(&arr[1])?, pairs_arg(&arr, 2)?).legacy(legacy))
+}
+
+pub(super) fn try_hsetnx(arr: Vec<RESPType>) -> crate::Result<HsetNx> {
+    Ok(HsetNx::new(
+        bytes_arg(&arr[1])?,
+        bytes_arg(&arr[2])?,
+        bytes_arg(&arr[3])?,
+    ))
+}
+
+pub(super) fn try_hget(arr: Vec<RESPType>) -> crate::Result<Hget> {
+    Ok(Hget::new(bytes_arg(&arr[1])?, bytes_arg(&arr[2])?))
+}
+
+pub(super) fn try_hmget(arr: Vec<RESPType>) -> crate::Result<Hmget> {
+    Ok(Hmget::new(bytes_arg(&arr[1])?, bytes_args(&arr[2..])?))
+}
+
+pub(super) fn try_hdel(arr: Vec<RESPType>) -> crate::Result<Hdel> {
+    Ok(Hdel::new(bytes_arg(&arr[1])?, bytes_args(&arr[2..])?))
+}
+
+pub(super) fn try_hexists(arr: Vec<RESPType>) -> crate::Result<Hexists> {
+    Ok(Hexists::new(bytes_arg(&arr[1])?, bytes_arg(&arr[2])?))
+}
+
+pub(super) fn try_hlen(arr: Vec<RESPType>) -> crate::Result<Hlen> {
+    Ok(Hlen::new(bytes_arg(&arr[1])?))
+}
+
+pub(super) fn try_hstrlen(arr: Vec<RESPType>) -> crate::Result<Hstrlen> {
+    Ok(Hstrlen::new(bytes_arg(&arr[1])?, bytes_arg(&arr[2])?))
+}
+
+pub(super) fn try_hgetall(arr: Vec<RESPType>, kind: HgetAllKind) -> crate::Result<HgetAll> {
+    Ok(HgetAll::new(bytes_arg(&arr[1])?, kind))
+}
+
+pub(super) fn try_hincrby(arr: Vec<RESPType>) -> crate::Result<Hincrby> {
+    Ok(Hincrby::new(
+        bytes_arg(&arr[1])?,
+        bytes_arg(&arr[2])?,
+        int_arg(&arr[3])?,
+    ))
+}
+
+pub(super) fn try_hincrbyfloat(arr: Vec<RESPType>) -> crate::Result<HincrbyFloat> {
+    let delta = parse_float(&bytes_arg(&arr[3])?)
+        .ok_or_else(|| Error::command("value is not a valid float"))?;
+    Ok(HincrbyFloat::new(
+        bytes_arg(&arr[1])?,
+        bytes_arg(&arr[2])?,
+        delta,
+    ))
+}
+
+pub(super) fn try_hrandfield(arr: Vec<RESPType>) -> crate::Result<HrandField> {
+    let hrandfield = HrandField::new(bytes_arg(&arr[1])?);
+    let count = match arr.get(2) {
+        None => return Ok(hrandfield),
+        Some(count) => int_arg(count)?,
+    };
+    let with_values = match &arr[3..] {
+        [] => false,
+        [opt] if bytes_arg(opt)?.eq_ignore_ascii_case(b"WITHVALUES") => true,
+        _ => return Err(Error::Syntax),
+    };
+
+    let count = random_count(count, 1 + with_values as u64)?;
+    Ok(hrandfield.count(count).with_values(with_values))
+}
+
+pub(super) fn try_hscan(arr: Vec<RESPType>) -> crate::Result<Hscan> {
+    let mut hscan = Hscan::new(bytes_arg(&arr[1])?, cursor_arg(&arr[2])?);
+
+    let mut args = arr[3..].iter();
+    while let Some(arg) = args.next() {
+        hscan = match &bytes_arg(arg)?.to_ascii_uppercase()[..] {
+            b"MATCH" => hscan.pattern(bytes_arg(args.next().ok_or(Error::Syntax)?)?),
+            b"COUNT" => hscan.count(scan_count_arg(args.next().ok_or(Error::Syntax)?)?),
+            b"NOVALUES" => hscan.no_values(true),
+            _ => return Err(Error::Syntax),
+        };
+    }
+    Ok(hscan)
+}
+
+pub(super) fn try_hexpire(
+    arr: Vec<RESPType>,
+    unit: ExpireUnit,
+    absolute: bool,
+) -> crate::Result<Hexpire> {
+    let key = bytes_arg(&arr[1])?;
+    let time = int_arg(&arr[2])?;
+
+    // the condition is optional, so FIELDS sits either at index 3 or 4
+    let (condition, rest) = match &bytes_arg(&arr[3])?.to_ascii_uppercase()[..] {
+        b"NX" => (Some(ExpireCondition::Nx), &arr[4..]),
+        b"XX" => (Some(ExpireCondition::Xx), &arr[4..]),
+        b"GT" => (Some(ExpireCondition::Gt), &arr[4..]),
+        b"LT" => (Some(ExpireCondition::Lt), &arr[4..]),
+        _ => (None, &arr[3..]),
+    };
+    let fields = fields_arg(rest)?;
+
+    let hexpire = Hexpire::new(key, time, unit, absolute, fields);
+    Ok(match condition {
+        None => hexpire,
+        Some(condition) => hexpire.condition(condition),
+    })
+}
+
+pub(super) fn try_httl(arr: Vec<RESPType>, kind: TtlKind) -> crate::Result<Httl> {
+    Ok(Httl::new(bytes_arg(&arr[1])?, kind, fields_arg(&arr[2..])?))
+}
+
+pub(super) fn try_hpersist(arr: Vec<RESPType>) -> crate::Result<Hpersist> {
+    Ok(Hpersist::new(bytes_arg(&arr[1])?, fields_arg(&arr[2..])?))
+}
+
+pub(super) fn try_sadd(arr: Vec<RESPType>) -> crate::Result<Sadd> {
+    Ok(Sadd::new(bytes_arg(&arr[1])?, bytes_args(&arr[2..])?))
+}
+
+pub(super) fn try_srem(arr: Vec<RESPType>) -> crate::Result<Srem> {
+    Ok(Srem::new(bytes_arg(&arr[1])?, bytes_args(&arr[2..])?))
+}
+
+pub(super) fn try_scard(arr: Vec<RESPType>) -> crate::Result<Scard> {
+    Ok(Scard::new(bytes_arg(&arr[1])?))
+}
+
+pub(super) fn try_smembers(arr: Vec<RESPType>) -> crate::Result<Smembers> {
+    Ok(Smembers::new(bytes_arg(&arr[1])?))
+}
+
+pub(super) fn try_sismember(arr: Vec<RESPType>) -> crate::Result<Sismember> {
+    Ok(Sismember::new(bytes_arg(&arr[1])?, bytes_arg(&arr[2])?))
+}
+
+pub(super) fn try_smismember(arr: Vec<RESPType>) -> crate::Result<Smismember> {
+    Ok(Smismember::new(bytes_arg(&arr[1])?, bytes_args(&arr[2..])?))
+}
+
+pub(super) fn try_spop(arr: Vec<RESPType>) -> crate::Result<Spop> {
+    let spop = Spop::new(bytes_arg(&arr[1])?);
+    match &arr[2..] {
+        [] => Ok(spop),
+        [count] => Ok(spop.count(count_arg(count)?)),
+        _ => Err(Error::Syntax),
+    }
+}
+
+pub(super) fn try_srandmember(arr: Vec<RESPType>) -> crate::Result<SrandMember> {
+    let srandmember = SrandMember::new(bytes_arg(&arr[1])?);
+    match &arr[2..] {
+        [] => Ok(srandmember),
+        [count] => Ok(srandmember.count(random_count(int_arg(count)?, 1)?)),
+        _ => Err(Error::Syntax),
+    }
+}
+
+pub(super) fn try_smove(arr: Vec<RESPType>) -> crate::Result<Smove> {
+    Ok(Smove::new(
+        bytes_arg(&arr[1])?,
+        bytes_arg(&arr[2])?,
+        bytes_arg(&arr[3])?,
+    ))
+}
+
+pub(super) fn try_sscan(arr: Vec<RESPType>) -> crate::Result<Sscan> {
+    let mut sscan = Sscan::new(bytes_arg(&arr[1])?, cursor_arg(&arr[2])?);
+
+    let mut args = arr[3..].iter();
+    while let Some(arg) = args.next() {
+        sscan = match &bytes_arg(arg)?.to_ascii_uppercase()[..] {
+            b"MATCH" => sscan.pattern(bytes_arg(args.next().ok_or(Error::Syntax)?)?),
+            b"COUNT" => sscan.count(scan_count_arg(args.next().ok_or(Error::Syntax)?)?),
+            _ => return Err(Error::Syntax),
+        };
+    }
+    Ok(sscan)
+}
+
+/// SINTER, SUNION and SDIFF, or their STORE variants taking the destination first.
+pub(super) fn try_setop(arr: Vec<RESPType>, kind: SetOpKind, store: bool) -> crate::Result<SetOp> {
+    if !store {
+        return Ok(SetOp::new(kind, bytes_args(&arr[1..])?));
+    }
+    Ok(SetOp::new(kind, bytes_args(&arr[2..])?).store(bytes_arg(&arr[1])?))
+}
+
+pub(super) fn try_sintercard(arr: Vec<RESPType>) -> crate::Result<SinterCard> {
+    let (keys, rest) = numkeys_arg(&arr[1..])?;
+    let sintercard = SinterCard::new(keys);
+    match rest {
+        [] => Ok(sintercard),
+        [opt, limit] if bytes_arg(opt)?.eq_ignore_ascii_case(b"LIMIT") => match int_arg(limit) {
+            Ok(limit) if limit >= 0 => Ok(sintercard.limit(limit as usize)),
+            _ => Err(Error::command("LIMIT can't be negative")),
         },
-        _ => Err("Too many arguments for echo request".into()),
+        _ => Err(Error::Syntax),
     }
 }
 
-fn try_get(arr: Vec<RESPType>) -> crate::Result<Get> {
-    match arr.len() {
-        1 => Err("Array does not hold key for get request".into()),
-        2 => match &arr[1] {
-            RESPType::String(s) => Ok(Get::new(s.to_string())),
-            RESPType::Bulk(b) => Ok(Get::new(std::str::from_utf8(&b).unwrap().to_string())),
-            _ => Err("invalid data type for get key".into()),
+pub(super) fn try_zadd(arr: Vec<RESPType>) -> crate::Result<Zadd> {
+    let key = bytes_arg(&arr[1])?;
+    let (mut nx, mut xx, mut gt, mut lt, mut ch, mut incr) =
+        (false, false, false, false, false, false);
+
+    // options come first, the score and member pairs start at the first other argument
+    let mut idx = 2;
+    while let Some(arg) = arr.get(idx) {
+        match &bytes_arg(arg)?.to_ascii_uppercase()[..] {
+            b"NX" => nx = true,
+            b"XX" => xx = true,
+            b"GT" => gt = true,
+            b"LT" => lt = true,
+            b"CH" => ch = true,
+            b"INCR" => incr = true,
+            _ => break,
+        }
+        idx += 1;
+    }
+    let pairs = &arr[idx..];
+    if pairs.is_empty() || !pairs.len().is_multiple_of(2) {
+        return Err(Error::Syntax);
+    }
+    if nx && xx {
+        return Err(Error::command(
+            "XX and NX options at the same time are not compatible",
+        ));
+    }
+    if (nx && (gt || lt)) || (gt && lt) {
+        return Err(Error::command(
+            "GT, LT, and/or NX options at the same time are not compatible",
+        ));
+    }
+    if incr && pairs.len() > 2 {
+        return Err(Error::command(
+            "INCR option supports a single increment-element pair",
+        ));
+    }
+
+    let members = pairs
+        .chunks(2)
+        .map(|pair| Ok((float_arg(&pair[0])?, bytes_arg(&pair[1])?)))
+        .collect::<crate::Result<_>>()?;
+    let mut zadd = Zadd::new(key, members).changed(ch).incr(incr);
+    if nx {
+        zadd = zadd.condition(SetCondition::Nx);
+    }
+    if xx {
+        zadd = zadd.condition(SetCondition::Xx);
+    }
+    if gt {
+        zadd = zadd.comparison(ScoreComparison::Gt);
+    }
+    if lt {
+        zadd = zadd.comparison(ScoreComparison::Lt);
+    }
+    Ok(zadd)
+}
+
+pub(super) fn try_zrem(arr: Vec<RESPType>) -> crate::Result<Zrem> {
+    Ok(Zrem::new(bytes_arg(&arr[1])?, bytes_args(&arr[2..])?))
+}
+
+pub(super) fn try_zscore(arr: Vec<RESPType>) -> crate::Result<Zscore> {
+    Ok(Zscore::new(bytes_arg(&arr[1])?, bytes_arg(&arr[2])?))
+}
+
+pub(super) fn try_zmscore(arr: Vec<RESPType>) -> crate::Result<Zmscore> {
+    Ok(Zmscore::new(bytes_arg(&arr[1])?, bytes_args(&arr[2..])?))
+}
+
+pub(super) fn try_zincrby(arr: Vec<RESPType>) -> crate::Result<Zincrby> {
+    Ok(Zincrby::new(
+        bytes_arg(&arr[1])?,
+        float_arg(&arr[2])?,
+        bytes_arg(&arr[3])?,
+    ))
+}
+
+pub(super) fn try_zcard(arr: Vec<RESPType>) -> crate::Result<Zcard> {
+    Ok(Zcard::new(bytes_arg(&arr[1])?))
+}
+
+pub(super) fn try_zrank(arr: Vec<RESPType>, rev: bool) -> crate::Result<Zrank> {
+    let zrank = Zrank::new(bytes_arg(&arr[1])?, bytes_arg(&arr[2])?, rev);
+    match &arr[3..] {
+        [] => Ok(zrank),
+        [opt] if bytes_arg(opt)?.eq_ignore_ascii_case(b"WITHSCORE") => Ok(zrank.with_score(true)),
+        _ => Err(Error::Syntax),
+    }
+}
+
+pub(super) fn try_zcount(arr: Vec<RESPType>) -> crate::Result<Zcount> {
+    Ok(Zcount::new(
+        bytes_arg(&arr[1])?,
+        score_bound_arg(&arr[2])?,
+        score_bound_arg(&arr[3])?,
+    ))
+}
+
+pub(super) fn try_zlexcount(arr: Vec<RESPType>) -> crate::Result<ZlexCount> {
+    Ok(ZlexCount::new(
+        bytes_arg(&arr[1])?,
+        lex_bound_arg(&arr[2])?,
+        lex_bound_arg(&arr[3])?,
+    ))
+}
+
+/// ZRANGE, or ZRANGESTORE taking the destination first.
+pub(super) fn try_zrange(arr: Vec<RESPType>, store: bool) -> crate::Result<Zrange> {
+    let (destination, args) = match store {
+        false => (None, &arr[1..]),
+        true => (Some(bytes_arg(&arr[1])?), &arr[2..]),
+    };
+    let (mut by_score, mut by_lex, mut rev, mut limit, mut with_scores) =
+        (false, false, false, None, false);
+
+    let mut opts = args[3..].iter();
+    while let Some(opt) = opts.next() {
+        match &bytes_arg(opt)?.to_ascii_uppercase()[..] {
+            b"BYSCORE" => by_score = true,
+            b"BYLEX" => by_lex = true,
+            b"REV" => rev = true,
+            b"WITHSCORES" if !store => with_scores = true,
+            b"LIMIT" => match (opts.next(), opts.next()) {
+                (Some(offset), Some(count)) => limit = Some((int_arg(offset)?, int_arg(count)?)),
+                _ => return Err(Error::Syntax),
+            },
+            _ => return Err(Error::Syntax),
+        }
+    }
+    if by_score && by_lex {
+        return Err(Error::Syntax);
+    }
+    if limit.is_some() && !by_score && !by_lex {
+        return Err(Error::command(
+            "syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX",
+        ));
+    }
+    if with_scores && by_lex {
+        return Err(Error::command(
+            "syntax error, WITHSCORES not supported in combination with BYLEX",
+        ));
+    }
+
+    // reversed score and lex ranges are given from the upper end
+    let (start, stop) = match rev && (by_score || by_lex) {
+        false => (&args[1], &args[2]),
+        true => (&args[2], &args[1]),
+    };
+    let by = if by_score {
+        ZrangeBy::Score(score_bound_arg(start)?, score_bound_arg(stop)?)
+    } else if by_lex {
+        ZrangeBy::Lex(lex_bound_arg(start)?, lex_bound_arg(stop)?)
+    } else {
+        ZrangeBy::Rank(int_arg(start)?, int_arg(stop)?)
+    };
+
+    let mut zrange = Zrange::new(bytes_arg(&args[0])?, by)
+        .rev(rev)
+        .with_scores(with_scores);
+    zrange = match limit {
+        None => zrange,
+        // a negative offset selects nothing, a negative count everything after the offset
+        Some((offset, _)) if offset < 0 => zrange.limit(0, Some(0)),
+        Some((offset, count)) => zrange.limit(offset as usize, usize::try_from(count).ok()),
+    };
+    Ok(match destination {
+        None => zrange,
+        Some(destination) => zrange.store(destination),
+    })
+}
+
+/// ZREVRANGE, ZRANGEBYSCORE, ZRANGEBYLEX and their reversed forms, which are ZRANGE with
+/// the `by` and REV options implied.
+pub(super) fn try_zrange_legacy(
+    arr: Vec<RESPType>,
+    by: Option<&'static str>,
+    rev: bool,
+) -> crate::Result<Zrange> {
+    let mut args = arr[..4].to_vec();
+    if let Some(by) = by {
+        args.push(RESPType::Bulk(Bytes::from(by)));
+    }
+    if rev {
+        args.push(RESPType::Bulk(Bytes::from("REV")));
+    }
+    args.extend_from_slice(&arr[4..]);
+    try_zrange(args, false)
+}
+
+pub(super) fn try_zpop(arr: Vec<RESPType>, max: bool) -> crate::Result<Zpop> {
+    let zpop = Zpop::new(bytes_arg(&arr[1])?, max);
+    match &arr[2..] {
+        [] => Ok(zpop),
+        [count] => Ok(zpop.count(count_arg(count)?)),
+        _ => Err(Error::Syntax),
+    }
+}
+
+pub(super) fn try_bzpop(arr: Vec<RESPType>, max: bool) -> crate::Result<Bzpop> {
+    let timeout = timeout_arg(&arr[arr.len() - 1])?;
+    Ok(Bzpop::new(
+        bytes_args(&arr[1..arr.len() - 1])?,
+        max,
+        timeout,
+    ))
+}
+
+/// ZUNION, ZINTER and ZDIFF, or their STORE variants taking the destination first.
+pub(super) fn try_zsetop(
+    arr: Vec<RESPType>,
+    kind: ZsetOpKind,
+    store: bool,
+) -> crate::Result<ZsetOp> {
+    let (destination, args) = match store {
+        false => (None, &arr[1..]),
+        true => (Some(bytes_arg(&arr[1])?), &arr[2..]),
+    };
+    if let Ok(0) = int_arg(&args[0]) {
+        let name = bytes_arg(&arr[0])?.to_ascii_lowercase();
+        return Err(Error::command(format!(
+            "at least 1 input key is needed for '{}' command",
+            String::from_utf8_lossy(&name)
+        )));
+    }
+    let (keys, rest) = numkeys_arg(args)?;
+    let numkeys = keys.len();
+    let mut zsetop = ZsetOp::new(kind, keys);
+
+    let mut opts = rest.iter();
+    while let Some(opt) = opts.next() {
+        zsetop = match &bytes_arg(opt)?.to_ascii_uppercase()[..] {
+            b"WEIGHTS" if kind != ZsetOpKind::Diff => {
+                let weights = opts
+                    .by_ref()
+                    .take(numkeys)
+                    .map(|weight| {
+                        parse_float(&bytes_arg(weight)?)
+                            .ok_or_else(|| Error::command("weight value is not a float"))
+                    })
+                    .collect::<crate::Result<Vec<_>>>()?;
+                if weights.len() != numkeys {
+                    return Err(Error::Syntax);
+                }
+                zsetop.weights(weights)
+            }
+            b"AGGREGATE" if kind != ZsetOpKind::Diff => {
+                let aggregate = opts.next().ok_or(Error::Syntax)?;
+                match &bytes_arg(aggregate)?.to_ascii_uppercase()[..] {
+                    b"SUM" => zsetop.aggregate(Aggregate::Sum),
+                    b"MIN" => zsetop.aggregate(Aggregate::Min),
+                    b"MAX" => zsetop.aggregate(Aggregate::Max),
+                    _ => return Err(Error::Syntax),
+                }
+            }
+            b"WITHSCORES" if !store => zsetop.with_scores(true),
+            _ => return Err(Error::Syntax),
+        };
+    }
+    Ok(match destination {
+        None => zsetop,
+        Some(destination) => zsetop.store(destination),
+    })
+}
+
+pub(super) fn try_xadd(arr: Vec<RESPType>) -> crate::Result<Xadd> {
+    let (trim, nomkstream, idx) = trim_args(&arr, 2, true)?;
+    let id = bytes_arg(&arr[idx])?;
+    let id = match id.strip_suffix(b"-*") {
+        _ if &id[..] == b"*" => XaddId::Auto,
+        Some(ms) => match StreamId::parse(ms, 0) {
+            Some(id) if !ms.contains(&b'-') => XaddId::AutoSeq(id.ms),
+            _ => return Err(invalid_stream_id()),
         },
-        _ => Err("Too many arguments for get request".into()),
+        None => XaddId::Explicit(StreamId::parse(&id, 0).ok_or_else(invalid_stream_id)?),
+    };
+    if idx + 1 == arr.len() {
+        return Err(arity_error(&arr));
+    }
+
+    let xadd = Xadd::new(bytes_arg(&arr[1])?, id, pairs_arg(&arr, idx + 1)?).nomkstream(nomkstream);
+    Ok(match trim {
+        None => xadd,
+        Some(trim) => xadd.trim(trim),
+    })
+}
+
+pub(super) fn try_xtrim(arr: Vec<RESPType>) -> crate::Result<Xtrim> {
+    match trim_args(&arr, 2, false)? {
+        (Some(trim), _, idx) if idx == arr.len() => Ok(Xtrim::new(bytes_arg(&arr[1])?, trim)),
+        _ => Err(Error::Syntax),
     }
 }
 
-fn try_set(arr: Vec<RESPType>) -> crate::Result<Set> {
-    match arr.len() {
-        1 => Err("array does not hold key and value for set request".into()),
-        2 => Err("array does not hold value for set request".into()),
-        3 => match (&arr[1], &arr[2]) {
-            (RESPType::Bulk(b1), RESPType::Bulk(b2)) => {
-                Ok(Set::new(std::str::from_utf8(b1)?.to_string(), b2.clone()))
-            }
-            (RESPType::Bulk(b), RESPType::String(s)) => Ok(Set::new(
-                std::str::from_utf8(b)?.to_string(),
-                Bytes::from(s.clone()),
+/// XRANGE, or XREVRANGE taking the end of the range first.
+pub(super) fn try_xrange(arr: Vec<RESPType>, rev: bool) -> crate::Result<Xrange> {
+    let (start, end) = match rev {
+        false => (&arr[2], &arr[3]),
+        true => (&arr[3], &arr[2]),
+    };
+    let xrange = Xrange::new(
+        bytes_arg(&arr[1])?,
+        range_id_arg(start, false)?,
+        range_id_arg(end, true)?,
+        rev,
+    );
+    match &arr[4..] {
+        [] => Ok(xrange),
+        // a negative count reads nothing
+        [opt, count] if bytes_arg(opt)?.eq_ignore_ascii_case(b"COUNT") => {
+            Ok(xrange.count(int_arg(count)?.max(0) as usize))
+        }
+        _ => Err(Error::Syntax),
+    }
+}
+
+pub(super) fn try_xlen(arr: Vec<RESPType>) -> crate::Result<Xlen> {
+    Ok(Xlen::new(bytes_arg(&arr[1])?))
+}
+
+pub(super) fn try_xdel(arr: Vec<RESPType>) -> crate::Result<Xdel> {
+    let ids = arr[2..]
+        .iter()
+        .map(stream_id_arg)
+        .collect::<crate::Result<_>>()?;
+    Ok(Xdel::new(bytes_arg(&arr[1])?, ids))
+}
+
+pub(super) fn try_xread(arr: Vec<RESPType>) -> crate::Result<Xread> {
+    let streams = streams_args(&arr, 1, false)?;
+    let ids = streams
+        .ids
+        .iter()
+        .map(|id| match &bytes_arg(id)?[..] {
+            b"$" => Ok(StartId::Last),
+            b">" => Err(Error::command(
+                "The > ID can be specified only when calling XREADGROUP using the GROUP <group> <consumer> option.",
             )),
-            (RESPType::String(s), RESPType::Bulk(b)) => Ok(Set::new(s.clone(), b.clone())),
-            (RESPType::String(s1), RESPType::String(s2)) => {
-                Ok(Set::new(s1.clone(), Bytes::from(s2.clone())))
-            }
-            _ => Err("invalid data type for set command arguments".into()),
+            _ => Ok(StartId::After(range_id_arg(id, false)?)),
+        })
+        .collect::<crate::Result<_>>()?;
+
+    let mut xread = Xread::new(streams.keys, ids);
+    if let Some(count) = streams.count {
+        xread = xread.count(count);
+    }
+    if let Some(block) = streams.block {
+        xread = xread.block(block);
+    }
+    Ok(xread)
+}
+
+pub(super) fn try_xreadgroup(arr: Vec<RESPType>) -> crate::Result<XreadGroup> {
+    if !bytes_arg(&arr[1])?.eq_ignore_ascii_case(b"GROUP") {
+        return Err(Error::command("Missing GROUP option for XREADGROUP"));
+    }
+    let streams = streams_args(&arr, 4, true)?;
+    let ids = streams
+        .ids
+        .iter()
+        .map(|id| match &bytes_arg(id)?[..] {
+            b">" => Ok(None),
+            b"$" => Err(Error::command(
+                "The $ ID is meaningless in the context of XREADGROUP: you want to read the history of this consumer by specifying a proper ID, or use the > ID to get new messages. The $ ID would just return an empty result set.",
+            )),
+            _ => Ok(Some(range_id_arg(id, false)?)),
+        })
+        .collect::<crate::Result<_>>()?;
+
+    let mut xreadgroup =
+        XreadGroup::new(bytes_arg(&arr[2])?, bytes_arg(&arr[3])?, streams.keys, ids)
+            .noack(streams.noack);
+    if let Some(count) = streams.count {
+        xreadgroup = xreadgroup.count(count);
+    }
+    if let Some(block) = streams.block {
+        xreadgroup = xreadgroup.block(block);
+    }
+    Ok(xreadgroup)
+}
+
+/// The XGROUP subcommands, all taking the key and the group name after the subcommand.
+pub(super) fn try_xgroup(arr: Vec<RESPType>) -> crate::Result<Xgroup> {
+    let start_id = |arg: &RESPType| -> crate::Result<StartId> {
+        match &bytes_arg(arg)?[..] {
+            b"$" => Ok(StartId::Last),
+            _ => Ok(StartId::After(stream_id_arg(arg)?)),
+        }
+    };
+    let op = match &bytes_arg(&arr[1])?.to_ascii_lowercase()[..] {
+        b"create" => match &arr[5..] {
+            [] => XgroupOp::Create {
+                id: start_id(&arr[4])?,
+                mkstream: false,
+            },
+            [opt] if bytes_arg(opt)?.eq_ignore_ascii_case(b"MKSTREAM") => XgroupOp::Create {
+                id: start_id(&arr[4])?,
+                mkstream: true,
+            },
+            _ => return Err(Error::Syntax),
         },
-        _ => Err("Too many arguments for get request".into()),
+        b"setid" => match &arr[5..] {
+            [] => XgroupOp::SetId(start_id(&arr[4])?),
+            _ => return Err(Error::Syntax),
+        },
+        b"destroy" => XgroupOp::Destroy,
+        b"createconsumer" => XgroupOp::CreateConsumer(bytes_arg(&arr[4])?),
+        b"delconsumer" => XgroupOp::DelConsumer(bytes_arg(&arr[4])?),
+        _ => return Err(Error::Syntax),
+    };
+    Ok(Xgroup::new(bytes_arg(&arr[2])?, bytes_arg(&arr[3])?, op))
+}
+
+pub(super) fn try_xack(arr: Vec<RESPType>) -> crate::Result<Xack> {
+    let ids = arr[3..]
+        .iter()
+        .map(stream_id_arg)
+        .collect::<crate::Result<_>>()?;
+    Ok(Xack::new(bytes_arg(&arr[1])?, bytes_arg(&arr[2])?, ids))
+}
+
+pub(super) fn try_xpending(arr: Vec<RESPType>) -> crate::Result<Xpending> {
+    let xpending = Xpending::new(bytes_arg(&arr[1])?, bytes_arg(&arr[2])?);
+    let (min_idle, range) = match &arr[3..] {
+        [] => return Ok(xpending),
+        [opt, min_idle, range @ ..] if bytes_arg(opt)?.eq_ignore_ascii_case(b"IDLE") => {
+            (Some(int_arg(min_idle)?.max(0) as u64), range)
+        }
+        range => (None, range),
+    };
+
+    let (start, end, count, consumer) = match range {
+        [start, end, count] => (start, end, count, None),
+        [start, end, count, consumer] => (start, end, count, Some(bytes_arg(consumer)?)),
+        _ => return Err(Error::Syntax),
+    };
+    let mut xpending = xpending.range(
+        range_id_arg(start, false)?,
+        range_id_arg(end, true)?,
+        int_arg(count)?.max(0) as usize,
+    );
+    if let Some(min_idle) = min_idle {
+        xpending = xpending.min_idle(min_idle);
+    }
+    if let Some(consumer) = consumer {
+        xpending = xpending.consumer(consumer);
+    }
+    Ok(xpending)
+}
+
+pub(super) fn try_xclaim(arr: Vec<RESPType>) -> crate::Result<Xclaim> {
+    let min_idle = int_arg(&arr[4])
+        .map_err(|_| Error::command("Invalid min-idle-time argument for XCLAIM"))?;
+
+    // the IDs run up to the first option
+    let mut ids = vec![];
+    let mut args = arr[5..].iter().peekable();
+    while let Some(id) =
+        args.next_if(|arg| matches!(bytes_arg(arg), Ok(arg) if StreamId::parse(&arg, 0).is_some()))
+    {
+        ids.push(stream_id_arg(id)?);
+    }
+    if ids.is_empty() {
+        return Err(invalid_stream_id());
+    }
+
+    let mut xclaim = Xclaim::new(
+        bytes_arg(&arr[1])?,
+        bytes_arg(&arr[2])?,
+        bytes_arg(&arr[3])?,
+        min_idle.max(0) as u64,
+        ids,
+    );
+    while let Some(opt) = args.next() {
+        let opt = bytes_arg(opt)?.to_ascii_uppercase();
+        xclaim = match &opt[..] {
+            b"FORCE" => xclaim.force(true),
+            b"JUSTID" => xclaim.just_id(true),
+            b"LASTID" => xclaim.last_id(stream_id_arg(args.next().ok_or(Error::Syntax)?)?),
+            b"IDLE" | b"TIME" | b"RETRYCOUNT" => {
+                let value = args
+                    .next()
+                    .ok_or(Error::Syntax)
+                    .and_then(int_arg)
+                    .map_err(|_| {
+                        Error::command(format!(
+                            "Invalid {} option argument for XCLAIM",
+                            String::from_utf8_lossy(&opt)
+                        ))
+                    })?
+                    .max(0) as u64;
+                match &opt[..] {
+                    b"IDLE" => xclaim.idle(value),
+                    b"TIME" => xclaim.time(value),
+                    _ => xclaim.retry_count(value),
+                }
+            }
+            _ => {
+                return Err(Error::command(format!(
+                    "Unrecognized XCLAIM option '{}'",
+                    String::from_utf8_lossy(&opt)
+                )))
+            }
+        };
+    }
+    Ok(xclaim)
+}
+
+pub(super) fn try_xautoclaim(arr: Vec<RESPType>) -> crate::Result<XautoClaim> {
+    let min_idle = int_arg(&arr[4])
+        .map_err(|_| Error::command("Invalid min-idle-time argument for XAUTOCLAIM"))?;
+    let mut xautoclaim = XautoClaim::new(
+        bytes_arg(&arr[1])?,
+        bytes_arg(&arr[2])?,
+        bytes_arg(&arr[3])?,
+        min_idle.max(0) as u64,
+        range_id_arg(&arr[5], false)?,
+    );
+
+    let mut opts = arr[6..].iter();
+    while let Some(opt) = opts.next() {
+        xautoclaim = match &bytes_arg(opt)?.to_ascii_uppercase()[..] {
+            b"COUNT" => {
+                let count = int_arg(opts.next().ok_or(Error::Syntax)?)?;
+                if count < 1 {
+                    return Err(Error::command("COUNT must be > 0"));
+                }
+                xautoclaim.count(count as usize)
+            }
+            b"JUSTID" => xautoclaim.just_id(true),
+            _ => return Err(Error::Syntax),
+        };
+    }
+    Ok(xautoclaim)
+}
+
+pub(super) fn try_xinfo(arr: Vec<RESPType>) -> crate::Result<Xinfo> {
+    let kind = match &bytes_arg(&arr[1])?.to_ascii_lowercase()[..] {
+        b"stream" if arr.len() == 3 => XinfoKind::Stream,
+        b"groups" => XinfoKind::Groups,
+        b"consumers" => XinfoKind::Consumers(bytes_arg(&arr[3])?),
+        _ => return Err(Error::Syntax),
+    };
+    Ok(Xinfo::new(bytes_arg(&arr[2])?, kind))
+}
+
+pub(super) fn try_setbit(arr: Vec<RESPType>) -> crate::Result<Setbit> {
+    let value = match &bytes_arg(&arr[3])?[..] {
+        b"0" => false,
+        b"1" => true,
+        _ => return Err(Error::command("bit is not an integer or out of range")),
+    };
+    Ok(Setbit::new(
+        bytes_arg(&arr[1])?,
+        bit_offset_arg(&arr[2], None)?,
+        value,
+    ))
+}
+
+pub(super) fn try_getbit(arr: Vec<RESPType>) -> crate::Result<Getbit> {
+    Ok(Getbit::new(
+        bytes_arg(&arr[1])?,
+        bit_offset_arg(&arr[2], None)?,
+    ))
+}
+
+pub(super) fn try_bitcount(arr: Vec<RESPType>) -> crate::Result<Bitcount> {
+    let bitcount = Bitcount::new(bytes_arg(&arr[1])?);
+    match &arr[2..] {
+        [] => Ok(bitcount),
+        [start, end] => Ok(bitcount.range(int_arg(start)?, int_arg(end)?, BitUnit::Byte)),
+        [start, end, unit] => {
+            Ok(bitcount.range(int_arg(start)?, int_arg(end)?, bit_unit_arg(unit)?))
+        }
+        _ => Err(Error::Syntax),
+    }
+}
+
+pub(super) fn try_bitpos(arr: Vec<RESPType>) -> crate::Result<Bitpos> {
+    let bit = match int_arg(&arr[2])? {
+        0 => false,
+        1 => true,
+        _ => return Err(Error::command("The bit argument must be 1 or 0.")),
+    };
+    let bitpos = Bitpos::new(bytes_arg(&arr[1])?, bit);
+    match &arr[3..] {
+        [] => Ok(bitpos),
+        [start] => Ok(bitpos.start(int_arg(start)?)),
+        [start, end] => Ok(bitpos
+            .start(int_arg(start)?)
+            .end(int_arg(end)?, BitUnit::Byte)),
+        [start, end, unit] => Ok(bitpos
+            .start(int_arg(start)?)
+            .end(int_arg(end)?, bit_unit_arg(unit)?)),
+        _ => Err(Error::Syntax),
+    }
+}
+
+pub(super) fn try_bitop(arr: Vec<RESPType>) -> crate::Result<Bitop> {
+    let kind = match &bytes_arg(&arr[1])?.to_ascii_uppercase()[..] {
+        b"AND" => BitopKind::And,
+        b"OR" => BitopKind::Or,
+        b"XOR" => BitopKind::Xor,
+        b"NOT" => BitopKind::Not,
+        _ => return Err(Error::Syntax),
+    };
+    if kind == BitopKind::Not && arr.len() != 4 {
+        return Err(Error::command(
+            "BITOP NOT must be called with a single source key.",
+        ));
+    }
+    Ok(Bitop::new(
+        kind,
+        bytes_arg(&arr[2])?,
+        bytes_args(&arr[3..])?,
+    ))
+}
+
+/// BITFIELD, or BITFIELD_RO which only accepts GET.
+pub(super) fn try_bitfield(arr: Vec<RESPType>, readonly: bool) -> crate::Result<Bitfield> {
+    let mut ops = vec![];
+    let mut args = arr[2..].iter();
+    while let Some(op) = args.next() {
+        let mut next = || args.next().ok_or(Error::Syntax);
+        let op = match &bytes_arg(op)?.to_ascii_uppercase()[..] {
+            b"GET" => {
+                let kind = bitfield_type_arg(next()?)?;
+                BitfieldOp::Get(kind, bit_offset_arg(next()?, Some(kind))?)
+            }
+            b"SET" | b"INCRBY" | b"OVERFLOW" if readonly => {
+                return Err(Error::command(
+                    "BITFIELD_RO only supports the GET subcommand",
+                ))
+            }
+            b"SET" => {
+                let kind = bitfield_type_arg(next()?)?;
+                let offset = bit_offset_arg(next()?, Some(kind))?;
+                BitfieldOp::Set(kind, offset, int_arg(next()?)?)
+            }
+            b"INCRBY" => {
+                let kind = bitfield_type_arg(next()?)?;
+                let offset = bit_offset_arg(next()?, Some(kind))?;
+                BitfieldOp::IncrBy(kind, offset, int_arg(next()?)?)
+            }
+            b"OVERFLOW" => match &bytes_arg(next()?)?.to_ascii_uppercase()[..] {
+                b"WRAP" => BitfieldOp::Overflow(Overflow::Wrap),
+                b"SAT" => BitfieldOp::Overflow(Overflow::Sat),
+                b"FAIL" => BitfieldOp::Overflow(Overflow::Fail),
+                _ => return Err(Error::command("Invalid OVERFLOW type specified")),
+            },
+            _ => return Err(Error::Syntax),
+        };
+        ops.push(op);
+    }
+    Ok(Bitfield::new(bytes_arg(&arr[1])?, ops).readonly(readonly))
+}
+
+pub(super) fn try_pfadd(arr: Vec<RESPType>) -> crate::Result<Pfadd> {
+    Ok(Pfadd::new(bytes_arg(&arr[1])?, bytes_args(&arr[2..])?))
+}
+
+pub(super) fn try_pfcount(arr: Vec<RESPType>) -> crate::Result<Pfcount> {
+    Ok(Pfcount::new(bytes_args(&arr[1..])?))
+}
+
+pub(super) fn try_pfmerge(arr: Vec<RESPType>) -> crate::Result<Pfmerge> {
+    Ok(Pfmerge::new(bytes_arg(&arr[1])?, bytes_args(&arr[2..])?))
+}
+
+pub(super) fn try_geoadd(arr: Vec<RESPType>) -> crate::Result<Geoadd> {
+    let (mut nx, mut xx, mut ch) = (false, false, false);
+
+    // options come first, the coordinates and member triples start at the first other argument
+    let mut idx = 2;
+    while let Some(arg) = arr.get(idx) {
+        match &bytes_arg(arg)?.to_ascii_uppercase()[..] {
+            b"NX" => nx = true,
+            b"XX" => xx = true,
+            b"CH" => ch = true,
+            _ => break,
+        }
+        idx += 1;
+    }
+    let triples = &arr[idx..];
+    if triples.is_empty() || !triples.len().is_multiple_of(3) {
+        return Err(Error::Syntax);
+    }
+    if nx && xx {
+        return Err(Error::command(
+            "XX and NX options at the same time are not compatible",
+        ));
+    }
+
+    let members = triples
+        .chunks(3)
+        .map(|triple| {
+            Ok((
+                float_arg(&triple[0])?,
+                float_arg(&triple[1])?,
+                bytes_arg(&triple[2])?,
+            ))
+        })
+        .collect::<crate::Result<_>>()?;
+    let geoadd = Geoadd::new(bytes_arg(&arr[1])?, members).changed(ch);
+    Ok(match (nx, xx) {
+        (true, _) => geoadd.condition(SetCondition::Nx),
+        (_, true) => geoadd.condition(SetCondition::Xx),
+        _ => geoadd,
+    })
+}
+
+pub(super) fn try_geodist(arr: Vec<RESPType>) -> crate::Result<Geodist> {
+    let geodist = Geodist::new(
+        bytes_arg(&arr[1])?,
+        bytes_arg(&arr[2])?,
+        bytes_arg(&arr[3])?,
+    );
+    match &arr[4..] {
+        [] => Ok(geodist),
+        [unit] => Ok(geodist.unit(geo_unit_arg(unit)?)),
+        _ => Err(Error::Syntax),
+    }
+}
+
+pub(super) fn try_geopos(arr: Vec<RESPType>) -> crate::Result<Geopos> {
+    Ok(Geopos::new(bytes_arg(&arr[1])?, bytes_args(&arr[2..])?))
+}
+
+pub(super) fn try_geohash(arr: Vec<RESPType>) -> crate::Result<Geohash> {
+    Ok(Geohash::new(bytes_arg(&arr[1])?, bytes_args(&arr[2..])?))
+}
+
+/// GEOSEARCH, or GEOSEARCHSTORE which takes the destination first and can't return
+/// coordinates, distances or hashes but may store distances instead.
+pub(super) fn try_geosearch(arr: Vec<RESPType>, store: bool) -> crate::Result<Geosearch> {
+    let (destination, args) = match store {
+        false => (None, &arr[1..]),
+        true => (Some(bytes_arg(&arr[1])?), &arr[2..]),
+    };
+    let (mut origin, mut shape, mut sort, mut count, mut any) = (None, None, None, None, false);
+    let (mut with_coord, mut with_dist, mut with_hash, mut store_dist) =
+        (false, false, false, false);
+
+    let mut opts = args[1..].iter();
+    while let Some(opt) = opts.next() {
+        let mut next = || opts.next().ok_or(Error::Syntax);
+        match &bytes_arg(opt)?.to_ascii_uppercase()[..] {
+            b"FROMMEMBER" if origin.is_none() => {
+                origin = Some(GeoOrigin::Member(bytes_arg(next()?)?));
+            }
+            b"FROMLONLAT" if origin.is_none() => {
+                let (longitude, latitude) = (float_arg(next()?)?, float_arg(next()?)?);
+                if !geo::valid(longitude, latitude) {
+                    return Err(Error::command(format!(
+                        "invalid longitude,latitude pair {longitude:.6},{latitude:.6}"
+                    )));
+                }
+                origin = Some(GeoOrigin::Coordinates(longitude, latitude));
+            }
+            b"BYRADIUS" if shape.is_none() => {
+                let radius = float_arg(next()?)?;
+                let unit = geo_unit_arg(next()?)?;
+                if radius < 0.0 {
+                    return Err(Error::command("radius cannot be negative"));
+                }
+                shape = Some(GeoShape::Radius(radius, unit));
+            }
+            b"BYBOX" if shape.is_none() => {
+                let (width, height) = (float_arg(next()?)?, float_arg(next()?)?);
+                let unit = geo_unit_arg(next()?)?;
+                if width < 0.0 || height < 0.0 {
+                    return Err(Error::command("height or width cannot be negative"));
+                }
+                shape = Some(GeoShape::Box(width, height, unit));
+            }
+            b"ASC" => sort = Some(GeoSort::Asc),
+            b"DESC" => sort = Some(GeoSort::Desc),
+            b"COUNT" => match int_arg(next()?)? {
+                count_value if count_value <= 0 => {
+                    return Err(Error::command("COUNT must be > 0"));
+                }
+                count_value => count = Some(count_value as usize),
+            },
+            b"ANY" => any = true,
+            b"WITHCOORD" if !store => with_coord = true,
+            b"WITHDIST" if !store => with_dist = true,
+            b"WITHHASH" if !store => with_hash = true,
+            b"STOREDIST" if store => store_dist = true,
+            _ => return Err(Error::Syntax),
+        }
+    }
+
+    let name = if store { "geosearchstore" } else { "geosearch" };
+    let Some(origin) = origin else {
+        return Err(Error::command(format!(
+            "exactly one of FROMMEMBER or FROMLONLAT can be specified for {name}"
+        )));
+    };
+    let Some(shape) = shape else {
+        return Err(Error::command(format!(
+            "exactly one of BYRADIUS and BYBOX can be specified for {name}"
+        )));
+    };
+    if any && count.is_none() {
+        return Err(Error::command("the ANY argument requires COUNT argument"));
+    }
+
+    let mut geosearch = Geosearch::new(bytes_arg(&args[0])?, origin, shape)
+        .with_coord(with_coord)
+        .with_dist(with_dist)
+        .with_hash(with_hash);
+    if let Some(sort) = sort {
+        geosearch = geosearch.sort(sort);
+    }
+    if let Some(count) = count {
+        geosearch = geosearch.count(count, any);
+    }
+    Ok(match destination {
+        None => geosearch,
+        Some(destination) => geosearch.store(destination, store_dist),
+    })
+}
+
+/// DEL, or UNLINK which frees large values in the background.
+pub(super) fn try_del(arr: Vec<RESPType>, unlink: bool) -> crate::Result<Del> {
+    Ok(Del::new(bytes_args(&arr[1..])?).unlink(unlink))
+}
+
+/// EXISTS, or TOUCH which replies the same way.
+pub(super) fn try_exists(arr: Vec<RESPType>, touch: bool) -> crate::Result<Exists> {
+    Ok(Exists::new(bytes_args(&arr[1..])?).touch(touch))
+}
+
+pub(super) fn try_type(arr: Vec<RESPType>) -> crate::Result<Type> {
+    Ok(Type::new(bytes_arg(&arr[1])?))
+}
+
+/// RENAME, or RENAMENX which doesn't overwrite the destination.
+pub(super) fn try_rename(arr: Vec<RESPType>, nx: bool) -> crate::Result<Rename> {
+    Ok(Rename::new(bytes_arg(&arr[1])?, bytes_arg(&arr[2])?).nx(nx))
+}
+
+pub(super) fn try_copy(arr: Vec<RESPType>) -> crate::Result<CopyKey> {
+    let mut copy = CopyKey::new(bytes_arg(&arr[1])?, bytes_arg(&arr[2])?);
+
+    let mut args = arr[3..].iter();
+    while let Some(arg) = args.next() {
+        copy = match &bytes_arg(arg)?.to_ascii_uppercase()[..] {
+            b"REPLACE" => copy.replace(true),
+            b"DB" => copy.db(int_arg(args.next().ok_or(Error::Syntax)?)?),
+            _ => return Err(Error::Syntax),
+        };
+    }
+    Ok(copy)
+}
+
+pub(super) fn try_dbsize(_arr: Vec<RESPType>) -> crate::Result<Dbsize> {
+    Ok(Dbsize::new())
+}
+
+/// FLUSHDB, or FLUSHALL when `all` is set.
+pub(super) fn try_flush(arr: Vec<RESPType>, all: bool) -> crate::Result<Flush> {
+    let flush = Flush::new(all);
+    match &arr[1..] {
+        [] => Ok(flush),
+        [mode] => match &bytes_arg(mode)?.to_ascii_uppercase()[..] {
+            b"ASYNC" => Ok(flush.lazy(true)),
+            b"SYNC" => Ok(flush),
+            _ => Err(Error::Syntax),
+        },
+        _ => Err(Error::Syntax),
+    }
+}
+
+pub(super) fn try_randomkey(_arr: Vec<RESPType>) -> crate::Result<RandomKey> {
+    Ok(RandomKey::new())
+}
+
+pub(super) fn try_keys(arr: Vec<RESPType>) -> crate::Result<Keys> {
+    Ok(Keys::new(bytes_arg(&arr[1])?))
+}
+
+pub(super) fn try_scan(arr: Vec<RESPType>) -> crate::Result<Scan> {
+    let mut scan = Scan::new(cursor_arg(&arr[1])?);
+
+    let mut args = arr[2..].iter();
+    while let Some(arg) = args.next() {
+        scan = match &bytes_arg(arg)?.to_ascii_uppercase()[..] {
+            b"MATCH" => scan.pattern(bytes_arg(args.next().ok_or(Error::Syntax)?)?),
+            b"COUNT" => scan.count(scan_count_arg(args.next().ok_or(Error::Syntax)?)?),
+            b"TYPE" => scan.kind(bytes_arg(args.next().ok_or(Error::Syntax)?)?),
+            _ => return Err(Error::Syntax),
+        };
+    }
+    Ok(scan)
+}
+
+pub(super) fn try_select(arr: Vec<RESPType>) -> crate::Result<Select> {
+    Ok(Select::new(int_arg(&arr[1])?))
+}
+
+pub(super) fn try_move(arr: Vec<RESPType>) -> crate::Result<Move> {
+    Ok(Move::new(bytes_arg(&arr[1])?, int_arg(&arr[2])?))
+}
+
+pub(super) fn try_swapdb(arr: Vec<RESPType>) -> crate::Result<Swapdb> {
+    let first = int_arg(&arr[1]).map_err(|_| Error::command("invalid first DB index"))?;
+    let second = int_arg(&arr[2]).map_err(|_| Error::command("invalid second DB index"))?;
+    Ok(Swapdb::new(first, second))
+}
+
+pub(super) fn try_info(arr: Vec<RESPType>) -> crate::Result<Info> {
+    Ok(Info::new(bytes_args(&arr[1..])?))
+}
+
+pub(super) fn try_object(arr: Vec<RESPType>) -> crate::Result<Object> {
+    let kind = match &bytes_arg(&arr[1])?.to_ascii_lowercase()[..] {
+        b"freq" => ObjectKind::Freq,
+        b"idletime" => ObjectKind::IdleTime,
+        _ => return Err(Error::Syntax),
+    };
+    Ok(Object::new(bytes_arg(&arr[2])?, kind))
+}
+
+pub(super) fn try_expire(
+    arr: Vec<RESPType>,
+    unit: ExpireUnit,
+    absolute: bool,
+) -> crate::Result<Expire> {
+    let mut expire = Expire::new(bytes_arg(&arr[1])?, int_arg(&arr[2])?, unit, absolute);
+    match arr.len() {
+        3 => Ok(expire),
+        4 => {
+            let condition = match &bytes_arg(&arr[3])?.to_ascii_uppercase()[..] {
+                b"NX" => ExpireCondition::Nx,
+                b"XX" => ExpireCondition::Xx,
+                b"GT" => ExpireCondition::Gt,
+                b"LT" => ExpireCondition::Lt,
+                _ => {
+                    return Err(Error::command(format!(
+                        "Unsupported option {}",
+                        String::from_utf8_lossy(&bytes_arg(&arr[3])?)
+                    )))
+                }
+            };
+            expire = expire.condition(condition);
+            Ok(expire)
+        }
+        _ => Err(arity_error(&arr)),
+    }
+}
+
+pub(super) fn try_ttl(arr: Vec<RESPType>, kind: TtlKind) -> crate::Result<Ttl> {
+    Ok(Ttl::new(bytes_arg(&arr[1])?, kind))
+}
+
+pub(super) fn try_persist(arr: Vec<RESPType>) -> crate::Result<Persist> {
+    Ok(Persist::new(bytes_arg(&arr[1])?))
+}
+
+pub(super) fn try_hello(arr: Vec<RESPType>) -> crate::Result<Hello> {
+    if arr.len() == 1 {
+        return Ok(Hello::new(None));
+    }
+
+    let protover = int_arg(&arr[1])
+        .map_err(|_| Error::command("Protocol version is not an integer or out of range"))?;
+    let mut hello = Hello::new(Some(protover));
+
+    let mut args = arr[2..].iter();
+    while let Some(arg) = args.next() {
+        match &bytes_arg(arg)?.to_ascii_uppercase()[..] {
+            b"AUTH" => match (args.next(), args.next()) {
+                (Some(username), Some(password)) => {
+                    hello = hello.auth(bytes_arg(username)?, bytes_arg(password)?)
+                }
+                _ => return Err(Error::command("Syntax error in HELLO option 'AUTH'")),
+            },
+            b"SETNAME" => match args.next() {
+                Some(name) => hello = hello.setname(bytes_arg(name)?),
+                None => return Err(Error::command("Syntax error in HELLO option 'SETNAME'")),
+            },
+            _ => {
+                return Err(Error::command(format!(
+                    "Syntax error in HELLO option '{}'",
+                    String::from_utf8_lossy(&bytes_arg(arg)?)
+                )))
+            }
+        }
+    }
+
+    Ok(hello)
+}
+
+// argument helpers
+fn arity_error(arr: &[RESPType]) -> Error {
+    let name = bytes_arg(&arr[0]).unwrap_or_default();
+    Error::WrongArity(String::from_utf8_lossy(&name).into_owned())
+}
+
+fn bytes_arg(arg: &RESPType) -> crate::Result<Bytes> {
+    match arg {
+        RESPType::Bulk(b) => Ok(b.clone()),
+        RESPType::String(s) => Ok(Bytes::from(s.clone())),
+        _ => Err(Error::protocol("expected bulk argument")),
+    }
+}
+
+pub(super) fn bytes_args(args: &[RESPType]) -> crate::Result<Vec<Bytes>> {
+    args.iter().map(bytes_arg).collect()
+}
+
+/// Integer argument, parsed as strictly as redis so `+5` or `05` are not integers.
+fn int_arg(arg: &RESPType) -> crate::Result<i64> {
+    parse_int(&bytes_arg(arg)?).ok_or(Error::NotInteger)
+}
+
+/// Count of SRANDMEMBER and HRANDFIELD, rejected if the reply to a negative one would hold
+/// more than [`MAX_RANDOM_REPLY`] elements with `per_pick` of them for every pick.
+fn random_count(count: i64, per_pick: u64) -> crate::Result<i64> {
+    match count.unsigned_abs().checked_mul(per_pick) {
+        Some(len) if count >= 0 || len <= MAX_RANDOM_REPLY => Ok(count),
+        _ => Err(Error::command("value is out of range")),
+    }
+}
+
+/// Optional element count of the pop commands, which must not be negative.
+fn count_arg(arg: &RESPType) -> crate::Result<usize> {
+    let count = int_arg(arg)?;
+    if count < 0 {
+        return Err(Error::command("value is out of range, must be positive"));
+    }
+    Ok(count as usize)
+}
+
+fn end_arg(arg: &RESPType) -> crate::Result<ListEnd> {
+    match &bytes_arg(arg)?.to_ascii_uppercase()[..] {
+        b"LEFT" => Ok(ListEnd::Left),
+        b"RIGHT" => Ok(ListEnd::Right),
+        _ => Err(Error::Syntax),
+    }
+}
+
+/// Arguments of LMPOP and BLMPOP: `numkeys key [key ...] LEFT|RIGHT [COUNT count]`.
+fn mpop_args(args: &[RESPType]) -> crate::Result<(Vec<Bytes>, ListEnd, usize)> {
+    let (keys, rest) = numkeys_arg(args)?;
+    let mut rest = rest.iter();
+    let end = match rest.next() {
+        None => return Err(Error::Syntax),
+        Some(end) => end_arg(end)?,
+    };
+
+    match (rest.next(), rest.next(), rest.next()) {
+        (None, _, _) => Ok((keys, end, 1)),
+        (Some(opt), Some(count), None) if bytes_arg(opt)?.eq_ignore_ascii_case(b"COUNT") => {
+            let count = int_arg(count)
+                .ok()
+                .filter(|count| *count > 0)
+                .ok_or_else(|| Error::command("count should be greater than 0"))?;
+            Ok((keys, end, count as usize))
+        }
+        _ => Err(Error::Syntax),
+    }
+}
+
+fn float_arg(arg: &RESPType) -> crate::Result<f64> {
+    parse_float(&bytes_arg(arg)?).ok_or_else(|| Error::command("value is not a valid float"))
+}
+
+/// Score range bound, a float or `-inf`/`+inf` that is exclusive with a leading `(`.
+fn score_bound_arg(arg: &RESPType) -> crate::Result<ScoreBound> {
+    let arg = bytes_arg(arg)?;
+    let (exclusive, score) = match arg.strip_prefix(b"(") {
+        Some(score) => (true, score),
+        None => (false, &arg[..]),
+    };
+    match parse_float(score) {
+        None => Err(Error::command("min or max is not a float")),
+        Some(score) if exclusive => Ok(ScoreBound::Exclusive(score)),
+        Some(score) => Ok(ScoreBound::Inclusive(score)),
+    }
+}
+
+/// Lexicographical range bound, `-`, `+`, or a member prefixed with `[` or `(`.
+fn lex_bound_arg(arg: &RESPType) -> crate::Result<LexBound> {
+    let arg = bytes_arg(arg)?;
+    match arg.first() {
+        Some(b'-') if arg.len() == 1 => Ok(LexBound::Min),
+        Some(b'+') if arg.len() == 1 => Ok(LexBound::Max),
+        Some(b'[') => Ok(LexBound::Inclusive(arg.slice(1..))),
+        Some(b'(') => Ok(LexBound::Exclusive(arg.slice(1..))),
+        _ => Err(Error::command("min or max not valid string range item")),
+    }
+}
+
+/// Timeout of the blocking commands in seconds, fractions allowed and zero meaning forever.
+fn timeout_arg(arg: &RESPType) -> crate::Result<Duration> {
+    let secs = parse_float(&bytes_arg(arg)?)
+        .filter(|secs| secs.is_finite())
+        .ok_or_else(|| Error::command("timeout is not a float or out of range"))?;
+    if secs < 0.0 {
+        return Err(Error::command("timeout is negative"));
+    }
+    Duration::try_from_secs_f64(secs).map_err(|_| Error::command("timeout is out of range"))
+}
+
+/// The trailing `FIELDS numfields field [field ...]` of the hash field expiry commands.
+fn fields_arg(args: &[RESPType]) -> crate::Result<Vec<Bytes>> {
+    match args.first().map(bytes_arg).transpose()? {
+        Some(arg) if arg.eq_ignore_ascii_case(b"FIELDS") => {}
+        _ => {
+            return Err(Error::command(
+                "Mandatory argument FIELDS is missing or not at the right position",
+            ))
+        }
+    }
+    let numfields = match args.get(1) {
+        None => return Err(Error::Syntax),
+        Some(numfields) => int_arg(numfields)
+            .ok()
+            .filter(|numfields| *numfields > 0)
+            .ok_or_else(|| Error::command("Parameter `numFields` should be greater than 0"))?,
+    };
+    if numfields as usize != args.len() - 2 {
+        return Err(Error::command(
+            "The `numfields` parameter must match the number of arguments",
+        ));
+    }
+    bytes_args(&args[2..])
+}
+
+/// Cursor of the scan commands, 0 starts a new iteration.
+fn cursor_arg(arg: &RESPType) -> crate::Result<u64> {
+    std::str::from_utf8(&bytes_arg(arg)?)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| Error::command("invalid cursor"))
+}
+
+/// COUNT hint of the scan commands, which must be at least 1.
+fn scan_count_arg(arg: &RESPType) -> crate::Result<usize> {
+    match int_arg(arg)? {
+        count if count < 1 => Err(Error::Syntax),
+        count => Ok(count as usize),
+    }
+}
+
+/// Splits `numkeys key [key ...] rest` into the keys and the remaining arguments.
+fn numkeys_arg(args: &[RESPType]) -> crate::Result<(Vec<Bytes>, &[RESPType])> {
+    let numkeys = int_arg(&args[0])
+        .ok()
+        .filter(|numkeys| *numkeys > 0)
+        .ok_or_else(|| Error::command("numkeys should be greater than 0"))?;
+    if numkeys as usize > args.len() - 1 {
+        return Err(Error::command(
+            "Number of keys can't be greater than number of args",
+        ));
+    }
+
+    let (keys, rest) = args[1..].split_at(numkeys as usize);
+    Ok((bytes_args(keys)?, rest))
+}
+
+fn invalid_stream_id() -> Error {
+    Error::command("Invalid stream ID specified as stream command argument")
+}
+
+/// Stream entry ID as `ms-seq`, or `ms` alone with sequence 0.
+fn stream_id_arg(arg: &RESPType) -> crate::Result<StreamId> {
+    StreamId::parse(&bytes_arg(arg)?, 0).ok_or_else(invalid_stream_id)
+}
+
+/// Bound of a stream ID range, `-` and `+` being the smallest and largest IDs. A missing
+/// sequence number includes the whole millisecond and a leading `(` makes the bound
+/// exclusive.
+fn range_id_arg(arg: &RESPType, end: bool) -> crate::Result<StreamId> {
+    let arg = bytes_arg(arg)?;
+    match &arg[..] {
+        b"-" => return Ok(StreamId::MIN),
+        b"+" => return Ok(StreamId::MAX),
+        _ => {}
+    }
+    let (exclusive, id) = match arg.strip_prefix(b"(") {
+        Some(id) => (true, id),
+        None => (false, &arg[..]),
+    };
+    let id = StreamId::parse(id, if end { u64::MAX } else { 0 }).ok_or_else(invalid_stream_id)?;
+    match (exclusive, end) {
+        (false, _) => Ok(id),
+        (true, false) => id
+            .next()
+            .ok_or_else(|| Error::command("invalid start ID for the interval")),
+        (true, true) => id
+            .prev()
+            .ok_or_else(|| Error::command("invalid end ID for the interval")),
+    }
+}
+
+/// Trimming options of XTRIM, or of XADD along with NOMKSTREAM, starting at `start`.
+/// Returns the index of the first argument that isn't one of them.
+fn trim_args(
+    arr: &[RESPType],
+    start: usize,
+    xadd: bool,
+) -> crate::Result<(Option<StreamTrim>, bool, usize)> {
+    let (mut strategy, mut approx, mut limit, mut nomkstream) = (None, false, None, false);
+    let mut idx = start;
+    while let Some(opt) = arr.get(idx) {
+        match &bytes_arg(opt)?.to_ascii_uppercase()[..] {
+            b"NOMKSTREAM" if xadd => nomkstream = true,
+            opt @ (b"MAXLEN" | b"MINID") => {
+                match arr.get(idx + 1).map(bytes_arg).transpose()?.as_deref() {
+                    Some(b"~") => approx = true,
+                    Some(b"=") => approx = false,
+                    _ => idx -= 1,
+                }
+                idx += 2;
+                let threshold = arr.get(idx).ok_or(Error::Syntax)?;
+                strategy = Some(match opt {
+                    b"MAXLEN" => match int_arg(threshold)? {
+                        max if max < 0 => {
+                            return Err(Error::command("The MAXLEN argument must be >= 0."))
+                        }
+                        max => TrimStrategy::MaxLen(max as u64),
+                    },
+                    _ => TrimStrategy::MinId(stream_id_arg(threshold)?),
+                });
+            }
+            b"LIMIT" => {
+                idx += 1;
+                match int_arg(arr.get(idx).ok_or(Error::Syntax)?)? {
+                    count if count < 0 => {
+                        return Err(Error::command("The LIMIT argument must be >= 0."))
+                    }
+                    count => limit = Some(count as usize),
+                }
+            }
+            _ => break,
+        }
+        idx += 1;
+    }
+
+    if limit.is_some() && !approx {
+        return Err(Error::command(
+            "syntax error, LIMIT cannot be used without the special ~ option",
+        ));
+    }
+    let trim = strategy.map(|strategy| {
+        let trim = StreamTrim::new(strategy).approx(approx);
+        match limit {
+            None => trim,
+            Some(limit) => trim.limit(limit),
+        }
+    });
+    Ok((trim, nomkstream, idx))
+}
+
+/// Options of XREAD and XREADGROUP up to STREAMS, and the keys and IDs after it.
+struct StreamsArgs<'a> {
+    count: Option<usize>,
+    block: Option<Duration>,
+    noack: bool,
+    keys: Vec<Bytes>,
+    ids: &'a [RESPType],
+}
+
+fn streams_args(arr: &[RESPType], start: usize, group: bool) -> crate::Result<StreamsArgs<'_>> {
+    let (mut count, mut block, mut noack) = (None, None, false);
+    let mut opts = arr[start..].iter().enumerate();
+    let streams = loop {
+        let (idx, opt) = opts.next().ok_or(Error::Syntax)?;
+        match &bytes_arg(opt)?.to_ascii_uppercase()[..] {
+            // a count that isn't positive reads everything
+            b"COUNT" => {
+                let n = int_arg(opts.next().ok_or(Error::Syntax)?.1)?;
+                count = (n > 0).then_some(n as usize);
+            }
+            b"BLOCK" => {
+                let ms = int_arg(opts.next().ok_or(Error::Syntax)?.1)
+                    .map_err(|_| Error::command("timeout is not an integer or out of range"))?;
+                if ms < 0 {
+                    return Err(Error::command("timeout is negative"));
+                }
+                block = Some(Duration::from_millis(ms as u64));
+            }
+            b"NOACK" if group => noack = true,
+            b"STREAMS" => break &arr[start + idx + 1..],
+            _ => return Err(Error::Syntax),
+        }
+    };
+
+    if streams.is_empty() || !streams.len().is_multiple_of(2) {
+        return Err(Error::command(format!(
+            "Unbalanced '{}' list of streams: for each stream key an ID or '$' must be specified.",
+            if group { "xreadgroup" } else { "xread" }
+        )));
+    }
+    let (keys, ids) = streams.split_at(streams.len() / 2);
+    Ok(StreamsArgs {
+        count,
+        block,
+        noack,
+        keys: bytes_args(keys)?,
+        ids,
+    })
+}
+
+/// Bit offset of the bitmap commands, which must stay within the largest string. BITFIELD
+/// offsets prefixed with `#` count fields of its type instead of bits.
+fn bit_offset_arg(arg: &RESPType, kind: Option<BitfieldType>) -> crate::Result<u64> {
+    let arg = bytes_arg(arg)?;
+    let (multiplier, offset) = match (kind, arg.strip_prefix(b"#")) {
+        (Some(kind), Some(offset)) => (kind.bits as u64, offset),
+        _ => (1, &arg[..]),
+    };
+    std::str::from_utf8(offset)
+        .ok()
+        .and_then(|offset| offset.parse::<u64>().ok())
+        .and_then(|offset| offset.checked_mul(multiplier))
+        .filter(|offset| offset / 8 < MAX_STRING_LEN as u64)
+        .ok_or_else(|| Error::command("bit offset is not an integer or out of range"))
+}
+
+fn bit_unit_arg(arg: &RESPType) -> crate::Result<BitUnit> {
+    match &bytes_arg(arg)?.to_ascii_uppercase()[..] {
+        b"BYTE" => Ok(BitUnit::Byte),
+        b"BIT" => Ok(BitUnit::Bit),
+        _ => Err(Error::Syntax),
+    }
+}
+
+/// BITFIELD integer type such as `i8` or `u16`, signed up to 64 bits or unsigned up to 63.
+fn bitfield_type_arg(arg: &RESPType) -> crate::Result<BitfieldType> {
+    let arg = bytes_arg(arg)?;
+    let (signed, max) = match arg.first().map(u8::to_ascii_lowercase) {
+        Some(b'i') => (true, 64),
+        Some(b'u') => (false, 63),
+        _ => (false, 0),
+    };
+    std::str::from_utf8(&arg[1.min(arg.len())..])
+        .ok()
+        .and_then(|bits| bits.parse::<u8>().ok())
+        .filter(|bits| (1..=max).contains(bits))
+        .map(|bits| BitfieldType { signed, bits })
+        .ok_or_else(|| {
+            Error::command(
+                "Invalid bitfield type. Use something like i16 u8. Note that u64 is not supported but i64 is.",
+            )
+        })
+}
+
+fn geo_unit_arg(arg: &RESPType) -> crate::Result<GeoUnit> {
+    match &bytes_arg(arg)?.to_ascii_lowercase()[..] {
+        b"m" => Ok(GeoUnit::Meters),
+        b"km" => Ok(GeoUnit::Kilometers),
+        b"ft" => Ok(GeoUnit::Feet),
+        b"mi" => Ok(GeoUnit::Miles),
+        _ => Err(Error::command(
+            "unsupported unit provided. please use M, KM, FT, MI",
+        )),
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::EvictionPolicy;
+    use crate::Db;
+
+    fn run(db: &Db, args: &[&str]) -> RESPType {
+        let frame = RESPType::Array(
+            args.iter()
+                .map(|arg| RESPType::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        );
+        match Command::try_from(frame) {
+            Ok(Command::Get(cmd)) => cmd.response(db),
+            Ok(Command::Set(cmd)) => cmd.response(db),
+            Ok(Command::Expire(cmd)) => cmd.response(db),
+            Ok(Command::Ttl(cmd)) => cmd.response(db),
+            Ok(Command::Persist(cmd)) => cmd.response(db),
+            Ok(Command::Mget(cmd)) => cmd.response(db),
+            Ok(Command::Mset(cmd)) => cmd.response(db),
+            Ok(Command::MsetNx(cmd)) => cmd.response(db),
+            Ok(Command::SetNx(cmd)) => cmd.response(db),
+            Ok(Command::GetDel(cmd)) => cmd.response(db),
+            Ok(Command::GetEx(cmd)) => cmd.response(db),
+            Ok(Command::Incr(cmd)) => cmd.response(db),
+            Ok(Command::IncrByFloat(cmd)) => cmd.response(db),
+            Ok(Command::Append(cmd)) => cmd.response(db),
+            Ok(Command::Strlen(cmd)) => cmd.response(db),
+            Ok(Command::GetRange(cmd)) => cmd.response(db),
+            Ok(Command::SetRange(cmd)) => cmd.response(db),
+            Ok(Command::Push(cmd)) => cmd.response(db),
+            Ok(Command::Pop(cmd)) => cmd.response(db),
+            Ok(Command::Llen(cmd)) => cmd.response(db),
+            Ok(Command::Lrange(cmd)) => cmd.response(db),
+            Ok(Command::Lindex(cmd)) => cmd.response(db),
+            Ok(Command::Lset(cmd)) => cmd.response(db),
+            Ok(Command::Lrem(cmd)) => cmd.response(db),
+            Ok(Command::Ltrim(cmd)) => cmd.response(db),
+            Ok(Command::Linsert(cmd)) => cmd.response(db),
+            Ok(Command::Lpos(cmd)) => cmd.response(db),
+            Ok(Command::Lmove(cmd)) => cmd.response(db),
+            Ok(Command::Lmpop(cmd)) => cmd.response(db),
+            Ok(Command::Hset(cmd)) => cmd.response(db),
+            Ok(Command::HsetNx(cmd)) => cmd.response(db),
+            Ok(Command::Hget(cmd)) => cmd.response(db),
+            Ok(Command::Hmget(cmd)) => cmd.response(db),
+            Ok(Command::Hdel(cmd)) => cmd.response(db),
+            Ok(Command::Hexists(cmd)) => cmd.response(db),
+            Ok(Command::Hlen(cmd)) => cmd.response(db),
+            Ok(Command::Hstrlen(cmd)) => cmd.response(db),
+            Ok(Command::HgetAll(cmd)) => cmd.response(db),
+            Ok(Command::Hincrby(cmd)) => cmd.response(db),
+            Ok(Command::HincrbyFloat(cmd)) => cmd.response(db),
+            Ok(Command::HrandField(cmd)) => cmd.response(db),
+            Ok(Command::Hscan(cmd)) => cmd.response(db),
+            Ok(Command::Hexpire(cmd)) => cmd.response(db),
+            Ok(Command::Httl(cmd)) => cmd.response(db),
+            Ok(Command::Hpersist(cmd)) => cmd.response(db),
+            Ok(Command::Sadd(cmd)) => cmd.response(db),
+            Ok(Command::Srem(cmd)) => cmd.response(db),
+            Ok(Command::Scard(cmd)) => cmd.response(db),
+            Ok(Command::Smembers(cmd)) => cmd.response(db),
+            Ok(Command::Sismember(cmd)) => cmd.response(db),
+            Ok(Command::Smismember(cmd)) => cmd.response(db),
+            Ok(Command::Spop(cmd)) => cmd.response(db),
+            Ok(Command::SrandMember(cmd)) => cmd.response(db),
+            Ok(Command::Smove(cmd)) => cmd.response(db),
+            Ok(Command::Sscan(cmd)) => cmd.response(db),
+            Ok(Command::SetOp(cmd)) => cmd.response(db),
+            Ok(Command::SinterCard(cmd)) => cmd.response(db),
+            Ok(Command::Zadd(cmd)) => cmd.response(db),
+            Ok(Command::Zrem(cmd)) => cmd.response(db),
+            Ok(Command::Zscore(cmd)) => cmd.response(db),
+            Ok(Command::Zmscore(cmd)) => cmd.response(db),
+            Ok(Command::Zincrby(cmd)) => cmd.response(db),
+            Ok(Command::Zcard(cmd)) => cmd.response(db),
+            Ok(Command::Zrank(cmd)) => cmd.response(db),
+            Ok(Command::Zcount(cmd)) => cmd.response(db),
+            Ok(Command::ZlexCount(cmd)) => cmd.response(db),
+            Ok(Command::Zrange(cmd)) => cmd.response(db),
+            Ok(Command::Zpop(cmd)) => cmd.response(db),
+            Ok(Command::ZsetOp(cmd)) => cmd.response(db),
+            Ok(Command::Xadd(cmd)) => cmd.response(db),
+            Ok(Command::Xtrim(cmd)) => cmd.response(db),
+            Ok(Command::Xrange(cmd)) => cmd.response(db),
+            Ok(Command::Xlen(cmd)) => cmd.response(db),
+            Ok(Command::Xdel(cmd)) => cmd.response(db),
+            Ok(Command::Xgroup(cmd)) => cmd.response(db),
+            Ok(Command::Xack(cmd)) => cmd.response(db),
+            Ok(Command::Xpending(cmd)) => cmd.response(db),
+            Ok(Command::Xclaim(cmd)) => cmd.response(db),
+            Ok(Command::XautoClaim(cmd)) => cmd.response(db),
+            Ok(Command::Xinfo(cmd)) => cmd.response(db),
+            Ok(Command::Setbit(cmd)) => cmd.response(db),
+            Ok(Command::Getbit(cmd)) => cmd.response(db),
+            Ok(Command::Bitcount(cmd)) => cmd.response(db),
+            Ok(Command::Bitpos(cmd)) => cmd.response(db),
+            Ok(Command::Bitop(cmd)) => cmd.response(db),
+            Ok(Command::Bitfield(cmd)) => cmd.response(db),
+            Ok(Command::Pfadd(cmd)) => cmd.response(db),
+            Ok(Command::Pfcount(cmd)) => cmd.response(db),
+            Ok(Command::Pfmerge(cmd)) => cmd.response(db),
+            Ok(Command::Geoadd(cmd)) => cmd.response(db),
+            Ok(Command::Geodist(cmd)) => cmd.response(db),
+            Ok(Command::Geopos(cmd)) => cmd.response(db),
+            Ok(Command::Geohash(cmd)) => cmd.response(db),
+            Ok(Command::Geosearch(cmd)) => cmd.response(db),
+            Ok(Command::Del(cmd)) => cmd.response(db),
+            Ok(Command::Exists(cmd)) => cmd.response(db),
+            Ok(Command::Type(cmd)) => cmd.response(db),
+            Ok(Command::Rename(cmd)) => cmd.response(db),
+            Ok(Command::Copy(cmd)) => cmd.response(db),
+            Ok(Command::Dbsize(cmd)) => cmd.response(db),
+            Ok(Command::Flush(cmd)) => cmd.response(db),
+            Ok(Command::RandomKey(cmd)) => cmd.response(db),
+            Ok(Command::Keys(cmd)) => cmd.response(db),
+            Ok(Command::Scan(cmd)) => cmd.response(db),
+            Ok(Command::Select(cmd)) => cmd.response(&mut db.clone()),
+            Ok(Command::Move(cmd)) => cmd.response(db),
+            Ok(Command::Swapdb(cmd)) => cmd.response(db),
+            Ok(Command::Info(cmd)) => cmd.response(db),
+            Ok(Command::Object(cmd)) => cmd.response(db),
+            Ok(Command::CommandInfo(cmd)) => cmd.response(),
+            Ok(_) => panic!("unexpected command"),
+            Err(e) => e.into(),
+        }
+    }
+
+    #[test]
+    fn test_set_options() {
+        let db = Db::new(2);
+        assert_eq!(run(&db, &["set", "key", "a", "XX"]), RESPType::Null);
+        assert_eq!(
+            run(&db, &["set", "key", "a", "nx"]),
+            RESPType::String("OK".into())
+        );
+        assert_eq!(run(&db, &["set", "key", "b", "NX"]), RESPType::Null);
+        assert_eq!(
+            run(&db, &["set", "key", "b", "XX", "GET"]),
+            RESPType::Bulk(Bytes::from("a"))
+        );
+        assert_eq!(run(&db, &["get", "key"]), RESPType::Bulk(Bytes::from("b")));
+        assert!(matches!(
+            run(&db, &["set", "key", "b", "NX", "XX"]),
+            RESPType::Error(_)
+        ));
+        assert!(matches!(
+            run(&db, &["set", "key", "b", "EX", "0"]),
+            RESPType::Error(ref e) if e == "ERR invalid expire time in 'set' command"
+        ));
+        assert!(matches!(
+            run(&db, &["set", "key", "b", "EX", "10", "PX", "10"]),
+            RESPType::Error(_)
+        ));
+    }
+
+    #[test]
+    fn test_set_expiry() {
+        let db = Db::new(2);
+        run(&db, &["set", "key", "a", "EX", "100"]);
+        assert!(matches!(run(&db, &["ttl", "key"]), RESPType::Integer(100)));
+
+        run(&db, &["set", "key", "b", "KEEPTTL"]);
+        assert!(matches!(run(&db, &["pttl", "key"]), RESPType::Integer(n) if n > 99_000));
+
+        run(&db, &["set", "key", "c"]);
+        assert_eq!(run(&db, &["ttl", "key"]), RESPType::Integer(-1));
+
+        run(&db, &["set", "key", "d", "PXAT", "1"]);
+        assert_eq!(run(&db, &["get", "key"]), RESPType::Null);
+        assert_eq!(run(&db, &["ttl", "key"]), RESPType::Integer(-2));
+    }
+
+    #[test]
+    fn test_expire_family() {
+        let db = Db::new(2);
+        assert_eq!(run(&db, &["expire", "key", "10"]), RESPType::Integer(0));
+        run(&db, &["set", "key", "a"]);
+        assert_eq!(
+            run(&db, &["expire", "key", "10", "XX"]),
+            RESPType::Integer(0)
+        );
+        assert_eq!(
+            run(&db, &["expire", "key", "10", "NX"]),
+            RESPType::Integer(1)
+        );
+        assert_eq!(
+            run(&db, &["expire", "key", "5", "GT"]),
+            RESPType::Integer(0)
+        );
+        assert_eq!(
+            run(&db, &["expire", "key", "20", "GT"]),
+            RESPType::Integer(1)
+        );
+        assert_eq!(
+            run(&db, &["expireat", "key", "4000000000"]),
+            RESPType::Integer(1)
+        );
+        assert_eq!(
+            run(&db, &["expiretime", "key"]),
+            RESPType::Integer(4000000000)
+        );
+        assert_eq!(run(&db, &["persist", "key"]), RESPType::Integer(1));
+        assert_eq!(run(&db, &["persist", "key"]), RESPType::Integer(0));
+        assert_eq!(run(&db, &["pexpiretime", "key"]), RESPType::Integer(-1));
+
+        assert_eq!(run(&db, &["pexpire", "key", "-1"]), RESPType::Integer(1));
+        assert_eq!(run(&db, &["get", "key"]), RESPType::Null);
+    }
+
+    #[test]
+    fn test_error_replies() {
+        let db = Db::new(2);
+        assert_eq!(
+            run(&db, &["get"]),
+            RESPType::Error("ERR wrong number of arguments for 'get' command".into())
+        );
+        assert_eq!(
+            run(&db, &["pttl", "a", "b"]),
+            RESPType::Error("ERR wrong number of arguments for 'pttl' command".into())
+        );
+        assert_eq!(
+            run(&db, &["expire", "key", "ten"]),
+            RESPType::Error("ERR value is not an integer or out of range".into())
+        );
+        assert_eq!(
+            run(&db, &["set", "key", "a", "EX"]),
+            RESPType::Error("ERR syntax error".into())
+        );
+    }
+
+    #[test]
+    fn test_dispatch_ignores_case() {
+        let db = Db::new(2);
+        assert_eq!(
+            run(&db, &["SET", "key", "a"]),
+            RESPType::String("OK".into())
+        );
+        assert_eq!(run(&db, &["gEt", "key"]), RESPType::Bulk(Bytes::from("a")));
+        assert_eq!(
+            run(&db, &["nope", "a", "b"]),
+            RESPType::Error(
+                "ERR unknown command 'nope', with args beginning with: 'a' 'b' ".into()
+            )
+        );
+        assert_eq!(
+            run(&db, &["GET"]),
+            RESPType::Error("ERR wrong number of arguments for 'get' command".into())
+        );
+    }
+
+    #[test]
+    fn test_counters() {
+        let db = Db::new(2);
+        assert_eq!(run(&db, &["incr", "n"]), RESPType::Integer(1));
+        assert_eq!(run(&db, &["incrby", "n", "41"]), RESPType::Integer(42));
+        assert_eq!(run(&db, &["decrby", "n", "50"]), RESPType::Integer(-8));
+        assert_eq!(run(&db, &["decr", "n"]), RESPType::Integer(-9));
+        assert_eq!(run(&db, &["get", "n"]), RESPType::Bulk(Bytes::from("-9")));
+
+        run(&db, &["set", "n", "9223372036854775807"]);
+        assert_eq!(
+            run(&db, &["incr", "n"]),
+            RESPType::Error("ERR increment or decrement would overflow".into())
+        );
+        assert_eq!(
+            run(&db, &["decrby", "n", "-9223372036854775808"]),
+            RESPType::Error("ERR decrement would overflow".into())
+        );
+        for value in ["abc", " 1", "01", "+1", "-0", ""] {
+            run(&db, &["set", "n", value]);
+            assert_eq!(
+                run(&db, &["incr", "n"]),
+                RESPType::Error("ERR value is not an integer or out of range".into())
+            );
+        }
+        // integer arguments are just as strict
+        run(&db, &["set", "n", "1"]);
+        for delta in ["+5", " 5", "05", "-0"] {
+            assert_eq!(
+                run(&db, &["incrby", "n", delta]),
+                RESPType::Error("ERR value is not an integer or out of range".into())
+            );
+        }
+        assert_eq!(
+            run(&db, &["expire", "n", "+10"]),
+            RESPType::Error("ERR value is not an integer or out of range".into())
+        );
+        assert_eq!(run(&db, &["ttl", "n"]), RESPType::Integer(-1));
+
+        run(&db, &["set", "n", "10.50"]);
+        assert_eq!(
+            run(&db, &["incrbyfloat", "n", "0.1"]),
+            RESPType::Bulk(Bytes::from("10.6"))
+        );
+        assert_eq!(
+            run(&db, &["incrbyfloat", "n", "-5.6"]),
+            RESPType::Bulk(Bytes::from("5"))
+        );
+        assert_eq!(
+            run(&db, &["incrbyfloat", "n", "5e3"]),
+            RESPType::Bulk(Bytes::from("5005"))
+        );
+        assert_eq!(
+            run(&db, &["incrbyfloat", "n", "inf"]),
+            RESPType::Error("ERR increment would produce NaN or Infinity".into())
+        );
+        assert_eq!(
+            run(&db, &["incrbyfloat", "n", "nan"]),
+            RESPType::Error("ERR value is not a valid float".into())
+        );
+
+        // sums are rounded to 17 digits, which hides the binary error of 0.1 + 0.2
+        run(&db, &["set", "f", "0.1"]);
+        for (delta, sum) in [
+            ("0.2", "0.3"),
+            ("-0.3", "0"),
+            ("1e20", "1e+20"),
+            ("-1e20", "0"),
+            ("0.00001", "1e-05"),
+            ("-1.5", "-1.49999"),
+        ] {
+            assert_eq!(
+                run(&db, &["incrbyfloat", "f", delta]),
+                RESPType::Bulk(Bytes::from(sum)),
+                "{delta}"
+            );
+        }
+
+        // counters keep their ttl
+        run(&db, &["expire", "n", "100"]);
+        run(&db, &["incrbyfloat", "n", "1"]);
+        assert_eq!(run(&db, &["ttl", "n"]), RESPType::Integer(100));
+    }
+
+    #[test]
+    fn test_string_ranges() {
+        let db = Db::new(2);
+        assert_eq!(run(&db, &["append", "s", "Hello"]), RESPType::Integer(5));
+        assert_eq!(run(&db, &["append", "s", " World"]), RESPType::Integer(11));
+        assert_eq!(run(&db, &["strlen", "s"]), RESPType::Integer(11));
+        assert_eq!(run(&db, &["strlen", "missing"]), RESPType::Integer(0));
+
+        let range = |start: &str, end: &str| run(&db, &["getrange", "s", start, end]);
+        assert_eq!(range("0", "4"), RESPType::Bulk(Bytes::from("Hello")));
+        assert_eq!(range("-5", "-1"), RESPType::Bulk(Bytes::from("World")));
+        assert_eq!(range("6", "100"), RESPType::Bulk(Bytes::from("World")));
+        assert_eq!(range("-1", "-5"), RESPType::Bulk(Bytes::new()));
+        assert_eq!(range("5", "3"), RESPType::Bulk(Bytes::new()));
+
+        assert_eq!(
+            run(&db, &["setrange", "s", "6", "Redis"]),
+            RESPType::Integer(11)
+        );
+        assert_eq!(
+            run(&db, &["get", "s"]),
+            RESPType::Bulk(Bytes::from("Hello Redis"))
+        );
+        assert_eq!(run(&db, &["setrange", "p", "3", "x"]), RESPType::Integer(4));
+        assert_eq!(
+            run(&db, &["get", "p"]),
+            RESPType::Bulk(Bytes::from_static(b"\0\0\0x"))
+        );
+        assert_eq!(run(&db, &["setrange", "q", "3", ""]), RESPType::Integer(0));
+        assert_eq!(run(&db, &["get", "q"]), RESPType::Null);
+        assert_eq!(
+            run(&db, &["setrange", "q", "-1", "x"]),
+            RESPType::Error("ERR offset is out of range".into())
+        );
+        assert_eq!(
+            run(&db, &["setrange", "q", "536870911", "xy"]),
+            RESPType::Error("ERR string exceeds maximum allowed size (proto-max-bulk-len)".into())
+        );
+    }
+
+    #[test]
+    fn test_get_and_modify() {
+        let db = Db::new(2);
+        assert_eq!(run(&db, &["setnx", "k", "a"]), RESPType::Integer(1));
+        assert_eq!(run(&db, &["setnx", "k", "b"]), RESPType::Integer(0));
+        assert_eq!(
+            run(&db, &["getset", "k", "c"]),
+            RESPType::Bulk(Bytes::from("a"))
+        );
+        assert_eq!(run(&db, &["getset", "new", "c"]), RESPType::Null);
+
+        assert_eq!(
+            run(&db, &["getex", "k", "EX", "100"]),
+            RESPType::Bulk(Bytes::from("c"))
+        );
+        assert_eq!(run(&db, &["ttl", "k"]), RESPType::Integer(100));
+        assert_eq!(
+            run(&db, &["getex", "k", "PERSIST"]),
+            RESPType::Bulk(Bytes::from("c"))
+        );
+        assert_eq!(run(&db, &["ttl", "k"]), RESPType::Integer(-1));
+        assert_eq!(
+            run(&db, &["getex", "k", "EX", "0"]),
+            RESPType::Error("ERR invalid expire time in 'getex' command".into())
+        );
+        assert_eq!(
+            run(&db, &["getex", "k", "PERSIST", "EX", "1"]),
+            RESPType::Error("ERR syntax error".into())
+        );
+        assert_eq!(
+            run(&db, &["getex", "k", "PXAT", "1"]),
+            RESPType::Bulk(Bytes::from("c"))
+        );
+        assert_eq!(run(&db, &["get", "k"]), RESPType::Null);
+
+        assert_eq!(
+            run(&db, &["getdel", "new"]),
+            RESPType::Bulk(Bytes::from("c"))
+        );
+        assert_eq!(run(&db, &["getdel", "new"]), RESPType::Null);
+    }
+
+    #[test]
+    fn test_multi_key_strings() {
+        let db = Db::new(4);
+        assert_eq!(
+            run(&db, &["mset", "a", "1", "b", "2", "c", "3", "a", "4"]),
+            RESPType::String("OK".into())
+        );
+        assert_eq!(
+            run(&db, &["mget", "a", "b", "missing", "c"]),
+            RESPType::Array(vec![
+                RESPType::Bulk(Bytes::from("4")),
+                RESPType::Bulk(Bytes::from("2")),
+                RESPType::Null,
+                RESPType::Bulk(Bytes::from("3")),
+            ])
+        );
+        assert_eq!(
+            run(&db, &["mset", "a", "1", "b"]),
+            RESPType::Error("ERR wrong number of arguments for 'mset' command".into())
+        );
+
+        assert_eq!(
+            run(&db, &["msetnx", "x", "1", "a", "2"]),
+            RESPType::Integer(0)
+        );
+        assert_eq!(run(&db, &["get", "x"]), RESPType::Null);
+        assert_eq!(
+            run(&db, &["msetnx", "x", "1", "y", "2"]),
+            RESPType::Integer(1)
+        );
+        assert_eq!(run(&db, &["get", "y"]), RESPType::Bulk(Bytes::from("2")));
+        assert_eq!(
+            run(&db, &["command", "getkeys", "mset", "a", "1", "b", "2"]),
+            RESPType::Array(vec![
+                RESPType::Bulk(Bytes::from("a")),
+                RESPType::Bulk(Bytes::from("b")),
+            ])
+        );
+    }
+
+    fn bulks(values: &[&str]) -> RESPType {
+        RESPType::Array(
+            values
+                .iter()
+                .map(|value| RESPType::Bulk(Bytes::from(value.to_string())))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let db = Db::new(2);
+        assert_eq!(run(&db, &["lpushx", "l", "a"]), RESPType::Integer(0));
+        assert_eq!(run(&db, &["rpush", "l", "b", "c"]), RESPType::Integer(2));
+        assert_eq!(run(&db, &["lpush", "l", "a", "z"]), RESPType::Integer(4));
+        assert_eq!(run(&db, &["rpushx", "l", "d"]), RESPType::Integer(5));
+        assert_eq!(run(&db, &["llen", "l"]), RESPType::Integer(5));
+        assert_eq!(
+            run(&db, &["lrange", "l", "0", "-1"]),
+            bulks(&["z", "a", "b", "c", "d"])
+        );
+
+        assert_eq!(run(&db, &["lpop", "l"]), RESPType::Bulk(Bytes::from("z")));
+        assert_eq!(run(&db, &["rpop", "l", "2"]), bulks(&["d", "c"]));
+        assert_eq!(run(&db, &["lpop", "l", "0"]), bulks(&[]));
+        assert_eq!(
+            run(&db, &["lpop", "l", "-1"]),
+            RESPType::Error("ERR value is out of range, must be positive".into())
+        );
+        assert_eq!(run(&db, &["rpop", "l", "5"]), bulks(&["b", "a"]));
+
+        // the list is gone with its last element
+        assert!(!db.exists(b"l"));
+        assert_eq!(run(&db, &["lpop", "l"]), RESPType::Null);
+        assert_eq!(run(&db, &["lpop", "l", "1"]), RESPType::NullArray);
+        assert_eq!(run(&db, &["llen", "l"]), RESPType::Integer(0));
+    }
+
+    #[test]
+    fn test_list_indexes() {
+        let db = Db::new(2);
+        run(&db, &["rpush", "l", "a", "b", "c", "d", "e"]);
+        let range = |start: &str, stop: &str| run(&db, &["lrange", "l", start, stop]);
+        assert_eq!(range("1", "2"), bulks(&["b", "c"]));
+        assert_eq!(range("-2", "100"), bulks(&["d", "e"]));
+        assert_eq!(range("-100", "0"), bulks(&["a"]));
+        assert_eq!(range("3", "1"), bulks(&[]));
+        assert_eq!(range("5", "10"), bulks(&[]));
+
+        assert_eq!(
+            run(&db, &["lindex", "l", "-1"]),
+            RESPType::Bulk(Bytes::from("e"))
+        );
+        assert_eq!(run(&db, &["lindex", "l", "5"]), RESPType::Null);
+        assert_eq!(
+            run(&db, &["lset", "l", "-2", "x"]),
+            RESPType::String("OK".into())
+        );
+        assert_eq!(
+            run(&db, &["lset", "l", "5", "x"]),
+            RESPType::Error("ERR index out of range".into())
+        );
+        assert_eq!(
+            run(&db, &["lset", "missing", "0", "x"]),
+            RESPType::Error("ERR no such key".into())
+        );
+
+        assert_eq!(
+            run(&db, &["ltrim", "l", "1", "-2"]),
+            RESPType::String("OK".into())
+        );
+        assert_eq!(range("0", "-1"), bulks(&["b", "c", "x"]));
+        run(&db, &["ltrim", "l", "2", "1"]);
+        assert!(!db.exists(b"l"));
+    }
+
+    #[test]
+    fn test_list_search_and_edit() {
+        let db = Db::new(2);
+        run(&db, &["rpush", "l", "a", "b", "a", "c", "a"]);
+        assert_eq!(run(&db, &["lpos", "l", "a"]), RESPType::Integer(0));
+        assert_eq!(
+            run(&db, &["lpos", "l", "a", "RANK", "2"]),
+            RESPType::Integer(2)
+        );
+        assert_eq!(
+            run(&db, &["lpos", "l", "a", "RANK", "-1"]),
+            RESPType::Integer(4)
+        );
+        assert_eq!(
+            run(&db, &["lpos", "l", "a", "COUNT", "0"]),
+            RESPType::Array(vec![
+                RESPType::Integer(0),
+                RESPType::Integer(2),
+                RESPType::Integer(4),
+            ])
+        );
+        assert_eq!(
+            run(&db, &["lpos", "l", "a", "COUNT", "0", "MAXLEN", "2"]),
+            RESPType::Array(vec![RESPType::Integer(0)])
+        );
+        assert_eq!(run(&db, &["lpos", "l", "x"]), RESPType::Null);
+        assert!(matches!(
+            run(&db, &["lpos", "l", "a", "RANK", "0"]),
+            RESPType::Error(ref e) if e.starts_with("ERR RANK can't be zero")
+        ));
+
+        assert_eq!(
+            run(&db, &["linsert", "l", "BEFORE", "c", "x"]),
+            RESPType::Integer(6)
+        );
+        assert_eq!(
+            run(&db, &["linsert", "l", "after", "c", "y"]),
+            RESPType::Integer(7)
+        );
+        assert_eq!(
+            run(&db, &["linsert", "l", "after", "nope", "y"]),
+            RESPType::Integer(-1)
+        );
+        assert_eq!(
+            run(&db, &["linsert", "missing", "after", "a", "y"]),
+            RESPType::Integer(0)
+        );
+        assert_eq!(
+            run(&db, &["linsert", "l", "middle", "a", "y"]),
+            RESPType::Error("ERR syntax error".into())
+        );
+
+        assert_eq!(run(&db, &["lrem", "l", "-2", "a"]), RESPType::Integer(2));
+        assert_eq!(
+            run(&db, &["lrange", "l", "0", "-1"]),
+            bulks(&["a", "b", "x", "c", "y"])
+        );
+        run(&db, &["rpush", "l", "b"]);
+        assert_eq!(run(&db, &["lrem", "l", "0", "b"]), RESPType::Integer(2));
+        assert_eq!(run(&db, &["lrem", "l", "1", "a"]), RESPType::Integer(1));
+        assert_eq!(
+            run(&db, &["lrange", "l", "0", "-1"]),
+            bulks(&["x", "c", "y"])
+        );
+    }
+
+    #[test]
+    fn test_list_moves() {
+        let db = Db::new(4);
+        run(&db, &["rpush", "src", "a", "b", "c"]);
+        assert_eq!(
+            run(&db, &["lmove", "src", "dst", "LEFT", "RIGHT"]),
+            RESPType::Bulk(Bytes::from("a"))
+        );
+        assert_eq!(
+            run(&db, &["rpoplpush", "src", "dst"]),
+            RESPType::Bulk(Bytes::from("c"))
+        );
+        assert_eq!(run(&db, &["lrange", "dst", "0", "-1"]), bulks(&["c", "a"]));
+        assert_eq!(
+            run(&db, &["lmove", "dst", "dst", "LEFT", "RIGHT"]),
+            RESPType::Bulk(Bytes::from("c"))
+        );
+        assert_eq!(run(&db, &["lrange", "dst", "0", "-1"]), bulks(&["a", "c"]));
+        assert_eq!(
+            run(&db, &["lmove", "missing", "dst", "LEFT", "LEFT"]),
+            RESPType::Null
+        );
+
+        // nothing is popped when the destination can't take the element
+        run(&db, &["set", "str", "x"]);
+        assert!(matches!(
+            run(&db, &["lmove", "src", "str", "LEFT", "LEFT"]),
+            RESPType::Error(ref e) if e.starts_with("WRONGTYPE")
+        ));
+        assert_eq!(run(&db, &["llen", "src"]), RESPType::Integer(1));
+
+        assert_eq!(
+            run(
+                &db,
+                &["lmpop", "3", "missing", "dst", "src", "RIGHT", "COUNT", "5"]
+            ),
+            RESPType::Array(vec![RESPType::Bulk(Bytes::from("dst")), bulks(&["c", "a"])])
+        );
+        assert_eq!(
+            run(&db, &["lmpop", "2", "missing", "src", "LEFT"]),
+            RESPType::Array(vec![RESPType::Bulk(Bytes::from("src")), bulks(&["b"])])
+        );
+        assert_eq!(
+            run(&db, &["lmpop", "1", "src", "LEFT"]),
+            RESPType::NullArray
+        );
+        assert_eq!(
+            run(&db, &["lmpop", "0", "src", "LEFT"]),
+            RESPType::Error("ERR numkeys should be greater than 0".into())
+        );
+        assert_eq!(
+            run(&db, &["lmpop", "4", "a", "b", "LEFT"]),
+            RESPType::Error("ERR Number of keys can't be greater than number of args".into())
+        );
+        assert_eq!(
+            run(&db, &["lmpop", "1", "a", "LEFT", "COUNT", "0"]),
+            RESPType::Error("ERR count should be greater than 0".into())
+        );
+        assert_eq!(
+            run(&db, &["lmpop", "1", "a", "UP"]),
+            RESPType::Error("ERR syntax error".into())
+        );
+        assert_eq!(
+            run(&db, &["command", "getkeys", "lmpop", "2", "a", "b", "LEFT"]),
+            bulks(&["a", "b"])
+        );
+    }
+
+    async fn run_blocking(db: &Db, args: &[&str]) -> RESPType {
+        let frame = RESPType::Array(
+            args.iter()
+                .map(|arg| RESPType::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        );
+        match Command::try_from(frame) {
+            Ok(Command::Bpop(cmd)) => cmd.response(db).await,
+            Ok(Command::Blmove(cmd)) => cmd.response(db).await,
+            Ok(Command::Blmpop(cmd)) => cmd.response(db).await,
+            Ok(Command::Bzpop(cmd)) => cmd.response(db).await,
+            Ok(Command::Xread(cmd)) => cmd.response(db).await,
+            Ok(Command::XreadGroup(cmd)) => cmd.response(db).await,
+            Ok(_) => panic!("unexpected command"),
+            Err(e) => e.into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_blocking_pops() {
+        let db = Db::new(4);
+        run(&db, &["rpush", "b", "x", "y"]);
+        assert_eq!(
+            run_blocking(&db, &["blpop", "a", "b", "0"]).await,
+            bulks(&["b", "x"])
+        );
+        assert_eq!(
+            run_blocking(&db, &["brpop", "a", "0.01"]).await,
+            RESPType::NullArray
+        );
+        assert_eq!(
+            run_blocking(
+                &db,
+                &["blmpop", "0.01", "2", "a", "b", "LEFT", "COUNT", "5"]
+            )
+            .await,
+            RESPType::Array(vec![RESPType::Bulk(Bytes::from("b")), bulks(&["y"])])
+        );
+        assert_eq!(
+            run_blocking(&db, &["blpop", "a", "-1"]).await,
+            RESPType::Error("ERR timeout is negative".into())
+        );
+        assert_eq!(
+            run_blocking(&db, &["blpop", "a", "soon"]).await,
+            RESPType::Error("ERR timeout is not a float or out of range".into())
+        );
+        run(&db, &["set", "s", "x"]);
+        assert!(matches!(
+            run_blocking(&db, &["blpop", "s", "0"]).await,
+            RESPType::Error(ref e) if e.starts_with("WRONGTYPE")
+        ));
+
+        // a push to a key wakes the client blocked on it
+        let blocked = {
+            let db = db.clone();
+            tokio::spawn(async move {
+                run_blocking(&db, &["blmove", "a", "c", "LEFT", "LEFT", "0"]).await
+            })
+        };
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        run(&db, &["lpush", "a", "z"]);
+        assert_eq!(blocked.await.unwrap(), RESPType::Bulk(Bytes::from("z")));
+        assert_eq!(run(&db, &["lrange", "c", "0", "-1"]), bulks(&["z"]));
+        assert!(!db.exists(b"a"));
+
+        // a destination of another type by the time the client is served is reported
+        let blocked = {
+            let db = db.clone();
+            tokio::spawn(async move {
+                run_blocking(&db, &["blmove", "a", "d", "LEFT", "LEFT", "0"]).await
+            })
+        };
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        run(&db, &["set", "d", "x"]);
+        run(&db, &["lpush", "a", "z"]);
+        assert!(matches!(
+            blocked.await.unwrap(),
+            RESPType::Error(ref e) if e.starts_with("WRONGTYPE")
+        ));
+        assert_eq!(run(&db, &["lrange", "a", "0", "-1"]), bulks(&["z"]));
+
+        // a key turning into another type keeps the client waiting for a list
+        let blocked = {
+            let db = db.clone();
+            tokio::spawn(async move { run_blocking(&db, &["blpop", "k", "0"]).await })
+        };
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        run(&db, &["set", "k", "v"]);
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        assert!(!blocked.is_finished());
+        run(&db, &["del", "k"]);
+        run(&db, &["rpush", "k", "x"]);
+        assert_eq!(blocked.await.unwrap(), bulks(&["k", "x"]));
+    }
+
+    #[test]
+    fn test_hashes() {
+        let db = Db::new(4);
+        assert_eq!(
+            run(&db, &["hset", "h", "a", "1", "b", "2"]),
+            RESPType::Integer(2)
+        );
+        assert_eq!(
+            run(&db, &["hset", "h", "a", "3", "c", "4"]),
+            RESPType::Integer(1)
+        );
+        assert_eq!(
+            run(&db, &["hmset", "h", "d", "5"]),
+            RESPType::String("OK".into())
+        );
+        assert_eq!(
+            run(&db, &["hset", "h", "a"]),
+            RESPType::Error("ERR wrong number of arguments for 'hset' command".into())
+        );
+        assert_eq!(
+            run(&db, &["hget", "h", "a"]),
+            RESPType::Bulk(Bytes::from("3"))
+        );
+        assert_eq!(run(&db, &["hget", "h", "x"]), RESPType::Null);
+        assert_eq!(
+            run(&db, &["hmget", "h", "b", "x"]),
+            RESPType::Array(vec![RESPType::Bulk(Bytes::from("2")), RESPType::Null])
+        );
+        assert_eq!(run(&db, &["hsetnx", "h", "b", "9"]), RESPType::Integer(0));
+        assert_eq!(run(&db, &["hsetnx", "h", "e", "9"]), RESPType::Integer(1));
+        assert_eq!(run(&db, &["hlen", "h"]), RESPType::Integer(5));
+        assert_eq!(run(&db, &["hexists", "h", "e"]), RESPType::Integer(1));
+        assert_eq!(run(&db, &["hstrlen", "h", "e"]), RESPType::Integer(1));
+        assert_eq!(run(&db, &["hstrlen", "h", "x"]), RESPType::Integer(0));
+
+        assert_eq!(
+            run(&db, &["hdel", "h", "d", "e", "x"]),
+            RESPType::Integer(2)
+        );
+        let sorted = |resp: RESPType| match resp {
+            RESPType::Array(mut arr) => {
+                arr.sort_by_key(|bulk| format!("{:?}", bulk));
+                RESPType::Array(arr)
+            }
+            other => panic!("unexpected reply {:?}", other),
+        };
+        assert_eq!(sorted(run(&db, &["hkeys", "h"])), bulks(&["a", "b", "c"]));
+        assert_eq!(sorted(run(&db, &["hvals", "h"])), bulks(&["2", "3", "4"]));
+        match run(&db, &["hgetall", "h"]) {
+            RESPType::Map(mut pairs) => {
+                pairs.sort_by_key(|(field, _)| format!("{:?}", field));
+                assert_eq!(
+                    pairs,
+                    vec![
+                        (
+                            RESPType::Bulk(Bytes::from("a")),
+                            RESPType::Bulk(Bytes::from("3"))
+                        ),
+                        (
+                            RESPType::Bulk(Bytes::from("b")),
+                            RESPType::Bulk(Bytes::from("2"))
+                        ),
+                        (
+                            RESPType::Bulk(Bytes::from("c")),
+                            RESPType::Bulk(Bytes::from("4"))
+                        ),
+                    ]
+                );
+            }
+            other => panic!("unexpected reply {:?}", other),
+        }
+
+        assert_eq!(
+            run(&db, &["hincrby", "h", "a", "10"]),
+            RESPType::Integer(13)
+        );
+        assert_eq!(
+            run(&db, &["hincrby", "h", "n", "-2"]),
+            RESPType::Integer(-2)
+        );
+        assert_eq!(
+            run(&db, &["hincrbyfloat", "h", "a", "0.5"]),
+            RESPType::Bulk(Bytes::from("13.5"))
+        );
+        assert_eq!(
+            run(&db, &["hincrby", "h", "a", "1"]),
+            RESPType::Error("ERR hash value is not an integer".into())
+        );
+        run(&db, &["hset", "h", "m", &i64::MAX.to_string()]);
+        assert_eq!(
+            run(&db, &["hincrby", "h", "m", "1"]),
+            RESPType::Error("ERR increment or decrement would overflow".into())
+        );
+
+        // deleting the last field deletes the key
+        run(&db, &["hset", "g", "a", "1"]);
+        assert_eq!(run(&db, &["hdel", "g", "a"]), RESPType::Integer(1));
+        assert!(!db.exists(b"g"));
+        assert_eq!(run(&db, &["hgetall", "g"]), RESPType::Map(vec![]));
+    }
+
+    #[test]
+    fn test_hash_field_expiry() {
+        let db = Db::new(4);
+        run(&db, &["hset", "h", "a", "1", "b", "2", "c", "3"]);
+        let ints = |values: &[i64]| {
+            RESPType::Array(values.iter().map(|v| RESPType::Integer(*v)).collect())
+        };
+
+        assert_eq!(
+            run(&db, &["hexpire", "h", "100", "FIELDS", "2", "a", "x"]),
+            ints(&[1, -2])
+        );
+        assert_eq!(
+            run(&db, &["hexpire", "h", "50", "NX", "FIELDS", "2", "a", "b"]),
+            ints(&[0, 1])
+        );
+        assert_eq!(
+            run(&db, &["hexpire", "h", "200", "GT", "FIELDS", "2", "a", "c"]),
+            ints(&[1, 0])
+        );
+        assert_eq!(
+            run(&db, &["httl", "h", "FIELDS", "3", "a", "c", "x"]),
+            ints(&[200, -1, -2])
+        );
+        match run(&db, &["hpttl", "h", "FIELDS", "1", "b"]) {
+            RESPType::Array(arr) => {
+                assert!(matches!(arr[..], [RESPType::Integer(ms)] if ms > 49_000 && ms <= 50_000))
+            }
+            other => panic!("unexpected reply {:?}", other),
+        }
+        assert_eq!(
+            run(&db, &["hpersist", "h", "FIELDS", "3", "a", "c", "x"]),
+            ints(&[1, -1, -2])
+        );
+        assert_eq!(
+            run(&db, &["hexpiretime", "nokey", "FIELDS", "1", "a"]),
+            ints(&[-2])
+        );
+
+        // overwriting a field clears its ttl, incrementing keeps it
+        run(&db, &["hpexpire", "h", "100000", "FIELDS", "2", "a", "b"]);
+        run(&db, &["hset", "h", "a", "5"]);
+        run(&db, &["hincrby", "h", "b", "1"]);
+        match run(&db, &["httl", "h", "FIELDS", "2", "a", "b"]) {
+            RESPType::Array(arr) => assert!(matches!(
+                arr[..],
+                [RESPType::Integer(-1), RESPType::Integer(ttl)] if ttl > 0
+            )),
+            other => panic!("unexpected reply {:?}", other),
+        }
+
+        // a time in the past deletes the field, and the key with its last field
+        assert_eq!(
+            run(&db, &["hexpireat", "h", "1", "FIELDS", "1", "c"]),
+            ints(&[2])
+        );
+        assert_eq!(run(&db, &["hexists", "h", "c"]), RESPType::Integer(0));
+        run(&db, &["hset", "g", "a", "1"]);
+        run(&db, &["hpexpire", "g", "5", "FIELDS", "1", "a"]);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        assert_eq!(run(&db, &["hlen", "g"]), RESPType::Integer(0));
+        assert!(!db.exists(b"g"));
+
+        for (args, err) in [
+            (
+                &["hexpire", "h", "-1", "FIELDS", "1", "a"][..],
+                "ERR invalid expire time, must be >= 0",
+            ),
+            (
+                &["hexpire", "h", "10", "FELDS", "1", "a"],
+                "ERR Mandatory argument FIELDS is missing or not at the right position",
+            ),
+            (
+                &["hexpire", "h", "10", "FIELDS", "0", "a"],
+                "ERR Parameter `numFields` should be greater than 0",
+            ),
+            (
+                &["httl", "h", "FIELDS", "2", "a"],
+                "ERR The `numfields` parameter must match the number of arguments",
+            ),
+            (
+                &["hpexpireat", "h", "281474976710656", "FIELDS", "1", "a"],
+                "ERR invalid expire time in 'hpexpireat' command",
+            ),
+        ] {
+            assert_eq!(run(&db, args), RESPType::Error(err.into()), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_hscan_and_hrandfield() {
+        let db = Db::new(4);
+        let mut fields = vec![];
+        for i in 0..100 {
+            let field = format!("f{}", i);
+            run(&db, &["hset", "h", &field, &i.to_string()]);
+            fields.push(field);
+        }
+
+        // a full iteration returns every field exactly once, even with fields added midway
+        let mut seen = vec![];
+        let mut cursor = "0".to_string();
+        loop {
+            let (next, page) = match run(&db, &["hscan", "h", &cursor, "COUNT", "7", "NOVALUES"]) {
+                RESPType::Array(mut arr) => match (arr.remove(0), arr.remove(0)) {
+                    (RESPType::Bulk(next), RESPType::Array(page)) => (next, page),
+                    other => panic!("unexpected reply {:?}", other),
+                },
+                other => panic!("unexpected reply {:?}", other),
+            };
+            run(&db, &["hset", "h", &format!("new{}", cursor), "x"]);
+            seen.extend(page.into_iter().filter_map(|field| match field {
+                RESPType::Bulk(field) if field.starts_with(b"f") => {
+                    Some(String::from_utf8(field.to_vec()).unwrap())
+                }
+                _ => None,
+            }));
+            cursor = String::from_utf8(next.to_vec()).unwrap();
+            if cursor == "0" {
+                break;
+            }
+        }
+        seen.sort();
+        fields.sort();
+        assert_eq!(seen, fields);
+
+        match run(&db, &["hscan", "h", "0", "MATCH", "f1?", "COUNT", "1000"]) {
+            RESPType::Array(arr) => match &arr[..] {
+                [RESPType::Bulk(cursor), RESPType::Array(page)] => {
+                    assert_eq!(&cursor[..], b"0");
+                    assert_eq!(page.len(), 20);
+                }
+                other => panic!("unexpected reply {:?}", other),
+            },
+            other => panic!("unexpected reply {:?}", other),
+        }
+        assert_eq!(
+            run(&db, &["hscan", "h", "x"]),
+            RESPType::Error("ERR invalid cursor".into())
+        );
+        assert_eq!(
+            run(&db, &["hscan", "h", "0", "COUNT", "0"]),
+            RESPType::Error("ERR syntax error".into())
+        );
+
+        run(&db, &["hset", "small", "a", "1", "b", "2"]);
+        let len = |resp: RESPType| match resp {
+            RESPType::Array(arr) => arr.len(),
+            other => panic!("unexpected reply {:?}", other),
+        };
+        assert!(matches!(
+            run(&db, &["hrandfield", "small"]),
+            RESPType::Bulk(_)
+        ));
+        assert_eq!(len(run(&db, &["hrandfield", "small", "5"])), 2);
+        assert_eq!(len(run(&db, &["hrandfield", "small", "-5"])), 5);
+        assert_eq!(
+            len(run(&db, &["hrandfield", "small", "1", "WITHVALUES"])),
+            2
+        );
+        assert_eq!(run(&db, &["hrandfield", "nokey"]), RESPType::Null);
+        assert_eq!(
+            run(&db, &["hrandfield", "nokey", "3"]),
+            RESPType::Array(vec![])
+        );
+
+        // a reply to a huge negative count can't be built, the shard must stay usable
+        for args in [
+            &["hrandfield", "small", "-4611686018427387903"][..],
+            &["hrandfield", "small", "-4611686018427387904", "WITHVALUES"],
+            &["hrandfield", "small", "-8388609", "WITHVALUES"],
+        ] {
+            assert_eq!(
+                run(&db, args),
+                RESPType::Error("ERR value is out of range".into())
+            );
+        }
+        assert_eq!(
+            len(run(&db, &["hrandfield", "small", "-3", "WITHVALUES"])),
+            6
+        );
+    }
+
+    /// Members of an array or set reply as sorted strings, for replies in no particular order.
+    fn sorted_members(resp: RESPType) -> Vec<String> {
+        let members = match resp {
+            RESPType::Array(members) | RESPType::Set(members) => members,
+            other => panic!("unexpected reply {:?}", other),
+        };
+        let mut members: Vec<_> = members
+            .into_iter()
+            .map(|member| match member {
+                RESPType::Bulk(member) => String::from_utf8(member.to_vec()).unwrap(),
+                other => panic!("unexpected member {:?}", other),
+            })
+            .collect();
+        members.sort();
+        members
+    }
+
+    #[test]
+    fn test_sets() {
+        let db = Db::new(4);
+        assert_eq!(
+            run(&db, &["sadd", "s", "a", "b", "a"]),
+            RESPType::Integer(2)
+        );
+        assert_eq!(run(&db, &["sadd", "s", "b", "c"]), RESPType::Integer(1));
+        assert_eq!(run(&db, &["scard", "s"]), RESPType::Integer(3));
+        assert_eq!(
+            sorted_members(run(&db, &["smembers", "s"])),
+            ["a", "b", "c"]
+        );
+        assert_eq!(run(&db, &["sismember", "s", "a"]), RESPType::Integer(1));
+        assert_eq!(run(&db, &["sismember", "s", "x"]), RESPType::Integer(0));
+        assert_eq!(
+            run(&db, &["smismember", "s", "x", "c"]),
+            RESPType::Array(vec![RESPType::Integer(0), RESPType::Integer(1)])
+        );
+        assert_eq!(run(&db, &["srem", "s", "a", "x"]), RESPType::Integer(1));
+        assert_eq!(run(&db, &["smembers", "nokey"]), RESPType::Set(vec![]));
+
+        // integer members read back exactly as they were written
+        run(&db, &["sadd", "ints", "10", "-3", "7", "10"]);
+        assert_eq!(
+            sorted_members(run(&db, &["smembers", "ints"])),
+            ["-3", "10", "7"]
+        );
+        run(&db, &["sadd", "ints", "07"]);
+        assert_eq!(run(&db, &["sismember", "ints", "7"]), RESPType::Integer(1));
+        assert_eq!(run(&db, &["sismember", "ints", "07"]), RESPType::Integer(1));
+        assert_eq!(run(&db, &["scard", "ints"]), RESPType::Integer(4));
+
+        assert_eq!(run(&db, &["smove", "s", "t", "b"]), RESPType::Integer(1));
+        assert_eq!(run(&db, &["smove", "s", "t", "b"]), RESPType::Integer(0));
+        assert_eq!(run(&db, &["smove", "t", "t", "b"]), RESPType::Integer(1));
+        assert_eq!(sorted_members(run(&db, &["smembers", "t"])), ["b"]);
+        run(&db, &["set", "str", "x"]);
+        assert!(matches!(
+            run(&db, &["smove", "s", "str", "c"]),
+            RESPType::Error(ref e) if e.starts_with("WRONGTYPE")
+        ));
+
+        // popping the last members deletes the key
+        assert!(matches!(run(&db, &["srandmember", "s"]), RESPType::Bulk(_)));
+        assert_eq!(sorted_members(run(&db, &["srandmember", "s", "5"])), ["c"]);
+        assert_eq!(
+            sorted_members(run(&db, &["srandmember", "s", "-3"])),
+            ["c", "c", "c"]
+        );
+        // a reply to a huge negative count can't be built, the shard must stay usable
+        for count in ["-9223372036854775807", "-9223372036854775808", "-16777217"] {
+            assert_eq!(
+                run(&db, &["srandmember", "s", count]),
+                RESPType::Error("ERR value is out of range".into())
+            );
+        }
+        assert_eq!(
+            run(&db, &["srandmember", "s", "-1"]),
+            RESPType::Array(vec![RESPType::Bulk(Bytes::from("c"))])
+        );
+        assert_eq!(run(&db, &["spop", "s"]), RESPType::Bulk(Bytes::from("c")));
+        assert!(!db.exists(b"s"));
+        assert_eq!(run(&db, &["spop", "s"]), RESPType::Null);
+        assert_eq!(run(&db, &["spop", "s", "2"]), RESPType::Set(vec![]));
+        assert_eq!(
+            run(&db, &["srandmember", "s", "2"]),
+            RESPType::Array(vec![])
+        );
+        assert_eq!(
+            sorted_members(run(&db, &["spop", "ints", "10"])),
+            ["-3", "07", "10", "7"]
+        );
+        assert!(!db.exists(b"ints"));
+        assert_eq!(
+            run(&db, &["spop", "t", "-1"]),
+            RESPType::Error("ERR value is out of range, must be positive".into())
+        );
+    }
+
+    #[test]
+    fn test_set_algebra() {
+        let db = Db::new(4);
+        run(&db, &["sadd", "a", "1", "2", "3", "x"]);
+        run(&db, &["sadd", "b", "2", "3", "4"]);
+        run(&db, &["sadd", "c", "3", "x", "y"]);
+
+        assert_eq!(sorted_members(run(&db, &["sinter", "a", "b"])), ["2", "3"]);
+        assert_eq!(sorted_members(run(&db, &["sinter", "a", "b", "c"])), ["3"]);
+        assert_eq!(run(&db, &["sinter", "a", "nokey"]), RESPType::Set(vec![]));
+        assert_eq!(
+            sorted_members(run(&db, &["sunion", "b", "c", "nokey"])),
+            ["2", "3", "4", "x", "y"]
+        );
+        assert_eq!(sorted_members(run(&db, &["sdiff", "a", "b", "c"])), ["1"]);
+        assert_eq!(run(&db, &["sdiff", "nokey", "a"]), RESPType::Set(vec![]));
+
+        // the STORE variants replace the destination whatever its type
+        run(&db, &["set", "dst", "x"]);
+        assert_eq!(
+            run(&db, &["sunionstore", "dst", "a", "b"]),
+            RESPType::Integer(5)
+        );
+        assert_eq!(
+            sorted_members(run(&db, &["smembers", "dst"])),
+            ["1", "2", "3", "4", "x"]
+        );
+        assert_eq!(
+            run(&db, &["sinterstore", "dst", "dst", "c"]),
+            RESPType::Integer(2)
+        );
+        assert_eq!(sorted_members(run(&db, &["smembers", "dst"])), ["3", "x"]);
+        assert_eq!(
+            run(&db, &["sdiffstore", "dst", "b", "b"]),
+            RESPType::Integer(0)
+        );
+        assert!(!db.exists(b"dst"));
+
+        assert_eq!(
+            run(&db, &["sintercard", "2", "a", "b"]),
+            RESPType::Integer(2)
+        );
+        assert_eq!(
+            run(&db, &["sintercard", "2", "a", "b", "LIMIT", "1"]),
+            RESPType::Integer(1)
+        );
+        assert_eq!(
+            run(&db, &["sintercard", "2", "a", "b", "LIMIT", "-1"]),
+            RESPType::Error("ERR LIMIT can't be negative".into())
+        );
+        assert_eq!(
+            run(&db, &["sintercard", "3", "a", "b"]),
+            RESPType::Error("ERR Number of keys can't be greater than number of args".into())
+        );
+
+        // a key of another type fails the command even after a missing key
+        run(&db, &["set", "str", "x"]);
+        for args in [
+            &["sinter", "nokey", "str"][..],
+            &["sunion", "a", "str"],
+            &["sdiff", "a", "str"],
+            &["sintercard", "2", "nokey", "str"],
+        ] {
+            assert!(
+                matches!(run(&db, args), RESPType::Error(ref e) if e.starts_with("WRONGTYPE")),
+                "{:?}",
+                args
+            );
+        }
+    }
+
+    #[test]
+    fn test_sscan() {
+        let db = Db::new(4);
+        let mut members: Vec<_> = (0..50).map(|i| i.to_string()).collect();
+        members.extend((0..50).map(|i| format!("m{}", i)));
+        for member in &members {
+            run(&db, &["sadd", "s", member]);
+        }
+
+        let mut seen = vec![];
+        let mut cursor = "0".to_string();
+        loop {
+            match run(&db, &["sscan", "s", &cursor, "COUNT", "9"]) {
+                RESPType::Array(mut arr) => {
+                    seen.extend(sorted_members(arr.remove(1)));
+                    cursor = sorted_members(RESPType::Array(arr)).remove(0);
+                }
+                other => panic!("unexpected reply {:?}", other),
+            }
+            if cursor == "0" {
+                break;
+            }
+        }
+        seen.sort();
+        members.sort();
+        assert_eq!(seen, members);
+
+        match run(&db, &["sscan", "s", "0", "MATCH", "m1*", "COUNT", "1000"]) {
+            RESPType::Array(mut arr) => assert_eq!(sorted_members(arr.remove(1)).len(), 11),
+            other => panic!("unexpected reply {:?}", other),
+        }
+    }
+
+    fn scored(members: &[(&str, f64)]) -> RESPType {
+        RESPType::Array(
+            members
+                .iter()
+                .flat_map(|(member, score)| {
+                    [
+                        RESPType::Bulk(Bytes::from(member.to_string())),
+                        RESPType::Double(*score),
+                    ]
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_zadd_options() {
+        let db = Db::new(2);
+        assert_eq!(
+            run(&db, &["zadd", "z", "XX", "1", "a"]),
+            RESPType::Integer(0)
+        );
+        assert!(!db.exists(b"z"));
+        assert_eq!(
+            run(&db, &["zadd", "z", "1", "a", "2", "b"]),
+            RESPType::Integer(2)
+        );
+        assert_eq!(
+            run(&db, &["zadd", "z", "NX", "5", "a", "3", "c"]),
+            RESPType::Integer(1)
+        );
+        assert_eq!(run(&db, &["zscore", "z", "a"]), RESPType::Double(1.0));
+
+        // GT and LT only move scores one way, CH counts the moved members too
+        assert_eq!(
+            run(&db, &["zadd", "z", "GT", "CH", "0", "a", "4", "b"]),
+            RESPType::Integer(1)
+        );
+        assert_eq!(
+            run(
+                &db,
+                &["zadd", "z", "LT", "CH", "0", "a", "5", "b", "4", "d"]
+            ),
+            RESPType::Integer(2)
+        );
+        assert_eq!(
+            run(&db, &["zmscore", "z", "a", "b", "nope"]),
+            RESPType::Array(vec![
+                RESPType::Double(0.0),
+                RESPType::Double(4.0),
+                RESPType::Null
+            ])
+        );
+
+        assert_eq!(
+            run(&db, &["zadd", "z", "INCR", "2.5", "a"]),
+            RESPType::Double(2.5)
+        );
+        assert_eq!(
+            run(&db, &["zadd", "z", "NX", "INCR", "1", "a"]),
+            RESPType::Null
+        );
+        assert_eq!(
+            run(&db, &["zincrby", "z", "-1", "new"]),
+            RESPType::Double(-1.0)
+        );
+        run(&db, &["zadd", "z", "+inf", "inf"]);
+        assert_eq!(
+            run(&db, &["zincrby", "z", "-inf", "inf"]),
+            RESPType::Error("ERR resulting score is not a number (NaN)".into())
+        );
+        assert_eq!(run(&db, &["zcard", "z"]), RESPType::Integer(6));
+
+        for (args, err) in [
+            (
+                &["zadd", "z", "NX", "XX", "1", "a"][..],
+                "ERR XX and NX options at the same time are not compatible",
+            ),
+            (
+                &["zadd", "z", "GT", "LT", "1", "a"],
+                "ERR GT, LT, and/or NX options at the same time are not compatible",
+            ),
+            (
+                &["zadd", "z", "INCR", "1", "a", "2", "b"],
+                "ERR INCR option supports a single increment-element pair",
+            ),
+            (&["zadd", "z", "1", "a", "2"], "ERR syntax error"),
+            (&["zadd", "z", "one", "a"], "ERR value is not a valid float"),
+        ] {
+            assert_eq!(run(&db, args), RESPType::Error(err.into()), "{:?}", args);
+        }
+
+        assert_eq!(
+            run(&db, &["zrem", "z", "a", "b", "nope"]),
+            RESPType::Integer(2)
+        );
+        assert_eq!(
+            run(&db, &["zrem", "z", "c", "d", "new", "inf"]),
+            RESPType::Integer(4)
+        );
+        assert!(!db.exists(b"z"));
+    }
+
+    #[test]
+    fn test_zset_ranges() {
+        let db = Db::new(2);
+        run(&db, &["zadd", "z", "1", "a", "2", "b", "2", "c", "3", "d"]);
+
+        assert_eq!(run(&db, &["zrank", "z", "c"]), RESPType::Integer(2));
+        assert_eq!(
+            run(&db, &["zrevrank", "z", "c", "WITHSCORE"]),
+            RESPType::Array(vec![RESPType::Integer(1), RESPType::Double(2.0)])
+        );
+        assert_eq!(run(&db, &["zrank", "z", "nope"]), RESPType::Null);
+
+        assert_eq!(
+            run(&db, &["zrange", "z", "0", "-1"]),
+            bulks(&["a", "b", "c", "d"])
+        );
+        assert_eq!(
+            run(&db, &["zrange", "z", "0", "1", "REV"]),
+            bulks(&["d", "c"])
+        );
+        assert_eq!(
+            run(&db, &["zrevrange", "z", "-2", "-1"]),
+            bulks(&["b", "a"])
+        );
+        assert_eq!(
+            run(&db, &["zrange", "z", "(1", "2", "BYSCORE", "WITHSCORES"]),
+            scored(&[("b", 2.0), ("c", 2.0)])
+        );
+        assert_eq!(
+            run(
+                &db,
+                &["zrange", "z", "+inf", "-inf", "BYSCORE", "REV", "LIMIT", "1", "2"]
+            ),
+            bulks(&["c", "b"])
+        );
+        assert_eq!(
+            run(
+                &db,
+                &["zrangebyscore", "z", "-inf", "+inf", "LIMIT", "-1", "2"]
+            ),
+            bulks(&[])
+        );
+        assert_eq!(
+            run(
+                &db,
+                &["zrevrangebyscore", "z", "2", "1", "LIMIT", "0", "-1"]
+            ),
+            bulks(&["c", "b", "a"])
+        );
+        assert_eq!(run(&db, &["zcount", "z", "2", "(3"]), RESPType::Integer(2));
+
+        run(
+            &db,
+            &["zadd", "lex", "0", "a", "0", "b", "0", "c", "0", "d"],
+        );
+        assert_eq!(
+            run(&db, &["zrange", "lex", "[b", "+", "BYLEX"]),
+            bulks(&["b", "c", "d"])
+        );
+        assert_eq!(
+            run(&db, &["zrevrangebylex", "lex", "(c", "-"]),
+            bulks(&["b", "a"])
+        );
+        assert_eq!(
+            run(&db, &["zlexcount", "lex", "(a", "[c"]),
+            RESPType::Integer(2)
+        );
+
+        for (args, err) in [
+            (&["zrange", "z", "0", "1", "LIMIT", "0", "1"][..], "ERR syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX"),
+            (&["zrange", "lex", "-", "+", "BYLEX", "WITHSCORES"], "ERR syntax error, WITHSCORES not supported in combination with BYLEX"),
+            (&["zrange", "z", "x", "1", "BYSCORE"], "ERR min or max is not a float"),
+            (&["zlexcount", "lex", "a", "+"], "ERR min or max not valid string range item"),
+            (&["zrangestore", "dst", "z", "0", "1", "WITHSCORES"], "ERR syntax error"),
+        ] {
+            assert_eq!(run(&db, args), RESPType::Error(err.into()), "{:?}", args);
+        }
+
+        assert_eq!(
+            run(&db, &["zrangestore", "dst", "z", "2", "+inf", "BYSCORE"]),
+            RESPType::Integer(3)
+        );
+        assert_eq!(
+            run(&db, &["zrange", "dst", "0", "-1", "WITHSCORES"]),
+            scored(&[("b", 2.0), ("c", 2.0), ("d", 3.0)])
+        );
+        assert_eq!(
+            run(&db, &["zrangestore", "dst", "z", "5", "10"]),
+            RESPType::Integer(0)
+        );
+        assert!(!db.exists(b"dst"));
+
+        assert_eq!(run(&db, &["zpopmin", "z"]), scored(&[("a", 1.0)]));
+        assert_eq!(
+            run(&db, &["zpopmax", "z", "2"]),
+            scored(&[("d", 3.0), ("c", 2.0)])
+        );
+        assert_eq!(run(&db, &["zpopmax", "z", "5"]), scored(&[("b", 2.0)]));
+        assert_eq!(run(&db, &["zpopmin", "z"]), RESPType::Array(vec![]));
+        assert!(!db.exists(b"z"));
+    }
+
+    #[test]
+    fn test_zset_algebra() {
+        let db = Db::new(4);
+        run(&db, &["zadd", "a", "1", "x", "2", "y", "3", "z"]);
+        run(&db, &["zadd", "b", "10", "y", "20", "z", "+inf", "w"]);
+        run(&db, &["sadd", "s", "z", "v"]);
+
+        assert_eq!(
+            run(&db, &["zunion", "2", "a", "b", "WITHSCORES"]),
+            scored(&[("x", 1.0), ("y", 12.0), ("z", 23.0), ("w", f64::INFINITY)])
+        );
+        assert_eq!(
+            run(
+                &db,
+                &[
+                    "zinter",
+                    "3",
+                    "a",
+                    "b",
+                    "s",
+                    "AGGREGATE",
+                    "MIN",
+                    "WITHSCORES"
+                ]
+            ),
+            scored(&[("z", 1.0)])
+        );
+        assert_eq!(run(&db, &["zdiff", "3", "a", "b", "nokey"]), bulks(&["x"]));
+
+        // an infinite score weighted by 0 counts as 0
+        assert_eq!(
+            run(
+                &db,
+                &["zunionstore", "dst", "2", "a", "b", "WEIGHTS", "2", "0"]
+            ),
+            RESPType::Integer(4)
+        );
+        assert_eq!(
+            run(&db, &["zrange", "dst", "0", "-1", "WITHSCORES"]),
+            scored(&[("w", 0.0), ("x", 2.0), ("y", 4.0), ("z", 6.0)])
+        );
+        assert_eq!(
+            run(
+                &db,
+                &["zinterstore", "dst", "2", "dst", "s", "AGGREGATE", "MAX"]
+            ),
+            RESPType::Integer(1)
+        );
+        assert_eq!(run(&db, &["zscore", "dst", "z"]), RESPType::Double(6.0));
+        assert_eq!(
+            run(&db, &["zdiffstore", "dst", "2", "a", "a"]),
+            RESPType::Integer(0)
+        );
+        assert!(!db.exists(b"dst"));
+
+        for (args, err) in [
+            (
+                &["zunionstore", "dst", "0", "a"][..],
+                "ERR at least 1 input key is needed for 'zunionstore' command",
+            ),
+            (
+                &["zunion", "2", "a", "b", "WEIGHTS", "1"],
+                "ERR syntax error",
+            ),
+            (
+                &["zinter", "2", "a", "b", "WEIGHTS", "1", "x"],
+                "ERR weight value is not a float",
+            ),
+            (&["zdiff", "1", "a", "AGGREGATE", "MIN"], "ERR syntax error"),
+        ] {
+            assert_eq!(run(&db, args), RESPType::Error(err.into()), "{:?}", args);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_blocking_zset_pops() {
+        let db = Db::new(4);
+        run(&db, &["zadd", "b", "1", "x", "2", "y"]);
+        assert_eq!(
+            run_blocking(&db, &["bzpopmax", "a", "b", "0"]).await,
+            RESPType::Array(vec![
+                RESPType::Bulk(Bytes::from("b")),
+                RESPType::Bulk(Bytes::from("y")),
+                RESPType::Double(2.0)
+            ])
+        );
+        assert_eq!(
+            run_blocking(&db, &["bzpopmin", "a", "0.01"]).await,
+            RESPType::NullArray
+        );
+
+        let waiter = tokio::spawn({
+            let db = db.clone();
+            async move { run_blocking(&db, &["bzpopmin", "a", "0"]).await }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        run(&db, &["zadd", "a", "5", "m"]);
+        assert_eq!(
+            waiter.await.unwrap(),
+            RESPType::Array(vec![
+                RESPType::Bulk(Bytes::from("a")),
+                RESPType::Bulk(Bytes::from("m")),
+                RESPType::Double(5.0)
+            ])
+        );
+        assert!(!db.exists(b"a"));
+    }
+
+    fn entry(id: &str, fields: &[&str]) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from(id.to_string())),
+            bulks(fields),
+        ])
+    }
+
+    #[test]
+    fn test_stream_entries() {
+        let db = Db::new(2);
+        assert_eq!(
+            run(&db, &["xadd", "s", "1-1", "a", "1"]),
+            RESPType::Bulk(Bytes::from("1-1"))
+        );
+        assert_eq!(
+            run(&db, &["xadd", "s", "1-*", "b", "2"]),
+            RESPType::Bulk(Bytes::from("1-2"))
+        );
+        assert_eq!(
+            run(&db, &["xadd", "s", "3", "c", "3", "d", "4"]),
+            RESPType::Bulk(Bytes::from("3-0"))
+        );
+        assert_eq!(run(&db, &["xlen", "s"]), RESPType::Integer(3));
+        for (args, err) in [
+            (
+                &["xadd", "s", "2-5", "a", "1"][..],
+                "ERR The ID specified in XADD is equal or smaller than the target stream top item",
+            ),
+            (
+                &["xadd", "new", "0-0", "a", "1"],
+                "ERR The ID specified in XADD must be greater than 0-0",
+            ),
+            (
+                &["xadd", "s", "x-1", "a", "1"],
+                "ERR Invalid stream ID specified as stream command argument",
+            ),
+            (
+                &["xadd", "s", "*", "a"],
+                "ERR wrong number of arguments for 'xadd' command",
+            ),
+            (
+                &["xadd", "s", "MAXLEN", "-1", "*", "a", "1"],
+                "ERR The MAXLEN argument must be >= 0.",
+            ),
+            (
+                &["xtrim", "s", "MAXLEN", "1", "LIMIT", "1"],
+                "ERR syntax error, LIMIT cannot be used without the special ~ option",
+            ),
+            (
+                &[
+                    "xrange",
+                    "s",
+                    "(18446744073709551615-18446744073709551615",
+                    "+",
+                ],
+                "ERR invalid start ID for the interval",
+            ),
+        ] {
+            assert_eq!(run(&db, args), RESPType::Error(err.into()), "{:?}", args);
+        }
+        // a rejected ID doesn't leave an empty stream behind
+        assert_eq!(run(&db, &["xlen", "new"]), RESPType::Integer(0));
+        assert_eq!(
+            run(&db, &["xadd", "new", "NOMKSTREAM", "*", "a", "1"]),
+            RESPType::Null
+        );
+
+        assert_eq!(
+            run(&db, &["xrange", "s", "1", "(3-0"]),
+            RESPType::Array(vec![entry("1-1", &["a", "1"]), entry("1-2", &["b", "2"])])
+        );
+        assert_eq!(
+            run(&db, &["xrevrange", "s", "+", "-", "COUNT", "2"]),
+            RESPType::Array(vec![
+                entry("3-0", &["c", "3", "d", "4"]),
+                entry("1-2", &["b", "2"])
+            ])
+        );
+        assert_eq!(run(&db, &["xdel", "s", "1-2", "9-9"]), RESPType::Integer(1));
+        assert_eq!(
+            run(&db, &["xrange", "s", "-", "+"]),
+            RESPType::Array(vec![
+                entry("1-1", &["a", "1"]),
+                entry("3-0", &["c", "3", "d", "4"])
+            ])
+        );
+
+        run(&db, &["xadd", "s", "MAXLEN", "=", "2", "4-0", "e", "5"]);
+        assert_eq!(run(&db, &["xlen", "s"]), RESPType::Integer(2));
+        assert_eq!(
+            run(&db, &["xtrim", "s", "MINID", "~", "4", "LIMIT", "10"]),
+            RESPType::Integer(1)
+        );
+        assert_eq!(
+            run(&db, &["xrange", "s", "-", "+"]),
+            RESPType::Array(vec![entry("4-0", &["e", "5"])])
+        );
+
+        // trimming to nothing keeps the stream and its last ID
+        assert_eq!(
+            run(&db, &["xtrim", "s", "MAXLEN", "0"]),
+            RESPType::Integer(1)
+        );
+        assert_eq!(run(&db, &["xlen", "s"]), RESPType::Integer(0));
+        assert_eq!(
+            run(&db, &["xadd", "s", "4-0", "a", "1"]),
+            RESPType::Error(
+                "ERR The ID specified in XADD is equal or smaller than the target stream top item"
+                    .into()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_groups() {
+        let db = Db::new(2);
+        assert_eq!(
+            run(&db, &["xgroup", "create", "s", "g", "$"]),
+            RESPType::Error("ERR The XGROUP subcommand requires the key to exist. Note that for CREATE you may want to use the MKSTREAM option to create an empty stream automatically.".into())
+        );
+        assert_eq!(
+            run(&db, &["xgroup", "create", "s", "g", "$", "MKSTREAM"]),
+            RESPType::String("OK".into())
+        );
+        assert_eq!(
+            run(&db, &["xgroup", "CREATE", "s", "g", "0"]),
+            RESPType::Error("BUSYGROUP Consumer Group name already exists".into())
+        );
+        run(&db, &["xadd", "s", "1-0", "a", "1"]);
+        run(&db, &["xadd", "s", "2-0", "b", "2"]);
+        run(&db, &["xadd", "s", "3-0", "c", "3"]);
+
+        let read = |entries: Vec<RESPType>| {
+            RESPType::Array(vec![RESPType::Array(vec![
+                RESPType::Bulk(Bytes::from("s")),
+                RESPType::Array(entries),
+            ])])
+        };
+        assert_eq!(
+            run_blocking(
+                &db,
+                &[
+                    "xreadgroup",
+                    "GROUP",
+                    "g",
+                    "alice",
+                    "COUNT",
+                    "2",
+                    "STREAMS",
+                    "s",
+                    ">"
+                ]
+            )
+            .await,
+            read(vec![entry("1-0", &["a", "1"]), entry("2-0", &["b", "2"])])
+        );
+        assert_eq!(
+            run_blocking(
+                &db,
+                &["xreadgroup", "GROUP", "g", "bob", "STREAMS", "s", ">"]
+            )
+            .await,
+            read(vec![entry("3-0", &["c", "3"])])
+        );
+        assert_eq!(
+            run_blocking(
+                &db,
+                &["xreadgroup", "GROUP", "g", "bob", "STREAMS", "s", ">"]
+            )
+            .await,
+            RESPType::NullArray
+        );
+        assert_eq!(
+            run_blocking(
+                &db,
+                &["xreadgroup", "GROUP", "nope", "bob", "STREAMS", "s", ">"]
+            )
+            .await,
+            RESPType::Error(
+                "NOGROUP No such key 's' or consumer group 'nope' in XREADGROUP with GROUP option"
+                    .into()
+            )
+        );
+
+        // the pending list hands out history again, with nil fields once deleted
+        run(&db, &["xdel", "s", "2-0"]);
+        assert_eq!(
+            run_blocking(
+                &db,
+                &["xreadgroup", "GROUP", "g", "alice", "STREAMS", "s", "0"]
+            )
+            .await,
+            read(vec![
+                entry("1-0", &["a", "1"]),
+                RESPType::Array(vec![RESPType::Bulk(Bytes::from("2-0")), RESPType::Null])
+            ])
+        );
+        assert_eq!(
+            run(&db, &["xpending", "s", "g"]),
+            RESPType::Array(vec![
+                RESPType::Integer(3),
+                RESPType::Bulk(Bytes::from("1-0")),
+                RESPType::Bulk(Bytes::from("3-0")),
+                RESPType::Array(vec![bulks(&["alice", "2"]), bulks(&["bob", "1"]),]),
+            ])
+        );
+        match run(&db, &["xpending", "s", "g", "-", "+", "10", "alice"]) {
+            RESPType::Array(pending) => {
+                assert_eq!(pending.len(), 2);
+                match &pending[0] {
+                    RESPType::Array(first) => {
+                        assert_eq!(first[0], RESPType::Bulk(Bytes::from("1-0")));
+                        assert_eq!(first[1], RESPType::Bulk(Bytes::from("alice")));
+                        assert_eq!(first[3], RESPType::Integer(2));
+                    }
+                    other => panic!("unexpected reply {:?}", other),
+                }
+            }
+            other => panic!("unexpected reply {:?}", other),
+        }
+
+        // nothing has been idle long enough, unless forced
+        assert_eq!(
+            run(&db, &["xclaim", "s", "g", "bob", "60000", "1-0"]),
+            RESPType::Array(vec![])
+        );
+        assert_eq!(
+            run(&db, &["xclaim", "s", "g", "bob", "0", "1-0", "JUSTID"]),
+            bulks(&["1-0"])
+        );
+        assert_eq!(
+            run(
+                &db,
+                &["xautoclaim", "s", "g", "carol", "0", "0", "COUNT", "1"]
+            ),
+            RESPType::Array(vec![
+                RESPType::Bulk(Bytes::from("2-0")),
+                RESPType::Array(vec![entry("1-0", &["a", "1"])]),
+                RESPType::Array(vec![]),
+            ])
+        );
+        // the deleted entry is dropped from the pending list rather than claimed
+        assert_eq!(
+            run(
+                &db,
+                &["xautoclaim", "s", "g", "carol", "0", "2-0", "JUSTID"]
+            ),
+            RESPType::Array(vec![
+                RESPType::Bulk(Bytes::from("0-0")),
+                bulks(&["3-0"]),
+                bulks(&["2-0"]),
+            ])
+        );
+
+        assert_eq!(
+            run(&db, &["xack", "s", "g", "1-0", "3-0", "9-0"]),
+            RESPType::Integer(2)
+        );
+        assert_eq!(
+            run(&db, &["xpending", "s", "g"]),
+            RESPType::Array(vec![
+                RESPType::Integer(0),
+                RESPType::Null,
+                RESPType::Null,
+                RESPType::NullArray
+            ])
+        );
+
+        match run(&db, &["xinfo", "groups", "s"]) {
+            RESPType::Array(groups) => match &groups[..] {
+                [RESPType::Map(group)] => {
+                    assert_eq!(group[0].1, RESPType::Bulk(Bytes::from("g")));
+                    assert_eq!(group[1].1, RESPType::Integer(3));
+                    assert_eq!(group[3].1, RESPType::Bulk(Bytes::from("3-0")));
+                    assert_eq!(group[5].1, RESPType::Integer(0));
+                }
+                other => panic!("unexpected reply {:?}", other),
+            },
+            other => panic!("unexpected reply {:?}", other),
+        }
+        assert_eq!(
+            run(&db, &["xgroup", "delconsumer", "s", "g", "alice"]),
+            RESPType::Integer(0)
+        );
+        assert_eq!(
+            run(&db, &["xgroup", "createconsumer", "s", "g", "dave"]),
+            RESPType::Integer(1)
+        );
+        match run(&db, &["xinfo", "stream", "s"]) {
+            RESPType::Map(info) => {
+                assert_eq!(info[0].1, RESPType::Integer(2));
+                assert_eq!(info[1].1, RESPType::Bulk(Bytes::from("3-0")));
+                assert_eq!(info[2].1, RESPType::Bulk(Bytes::from("2-0")));
+                assert_eq!(info[3].1, RESPType::Integer(3));
+            }
+            other => panic!("unexpected reply {:?}", other),
+        }
+        assert_eq!(
+            run(&db, &["xgroup", "destroy", "s", "g"]),
+            RESPType::Integer(1)
+        );
+        assert_eq!(run(&db, &["xinfo", "groups", "s"]), RESPType::Array(vec![]));
+    }
+
+    #[tokio::test]
+    async fn test_blocking_xread() {
+        let db = Db::new(4);
+        run(&db, &["xadd", "a", "1-0", "x", "1"]);
+        assert_eq!(
+            run_blocking(&db, &["xread", "STREAMS", "a", "b", "0", "0"]).await,
+            RESPType::Array(vec![RESPType::Array(vec![
+                RESPType::Bulk(Bytes::from("a")),
+                RESPType::Array(vec![entry("1-0", &["x", "1"])]),
+            ])])
+        );
+        assert_eq!(
+            run_blocking(&db, &["xread", "BLOCK", "10", "STREAMS", "a", "$"]).await,
+            RESPType::NullArray
+        );
+        assert_eq!(
+            run_blocking(&db, &["xread", "STREAMS", "a", "b", "0"]).await,
+            RESPType::Error("ERR Unbalanced 'xread' list of streams: for each stream key an ID or '$' must be specified.".into())
+        );
+
+        let waiter = tokio::spawn({
+            let db = db.clone();
+            async move { run_blocking(&db, &["xread", "BLOCK", "0", "STREAMS", "a", "$"]).await }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        run(&db, &["xadd", "a", "2-0", "y", "2"]);
+        assert_eq!(
+            waiter.await.unwrap(),
+            RESPType::Array(vec![RESPType::Array(vec![
+                RESPType::Bulk(Bytes::from("a")),
+                RESPType::Array(vec![entry("2-0", &["y", "2"])]),
+            ])])
+        );
+
+        run(&db, &["xgroup", "create", "a", "g", "$"]);
+        let waiter = tokio::spawn({
+            let db = db.clone();
+            async move {
+                run_blocking(
+                    &db,
+                    &[
+                        "xreadgroup",
+                        "GROUP",
+                        "g",
+                        "c",
+                        "BLOCK",
+                        "0",
+                        "STREAMS",
+                        "a",
+                        ">",
+                    ],
+                )
+                .await
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        run(&db, &["xadd", "a", "3-0", "z", "3"]);
+        assert_eq!(
+            waiter.await.unwrap(),
+            RESPType::Array(vec![RESPType::Array(vec![
+                RESPType::Bulk(Bytes::from("a")),
+                RESPType::Array(vec![entry("3-0", &["z", "3"])]),
+            ])])
+        );
+    }
+
+    #[test]
+    fn test_bitmaps() {
+        let db = Db::new(2);
+        assert_eq!(run(&db, &["setbit", "k", "7", "1"]), RESPType::Integer(0));
+        assert_eq!(run(&db, &["getbit", "k", "7"]), RESPType::Integer(1));
+        assert_eq!(run(&db, &["getbit", "k", "100"]), RESPType::Integer(0));
+        assert_eq!(run(&db, &["setbit", "k", "7", "0"]), RESPType::Integer(1));
+        assert_eq!(run(&db, &["setbit", "k", "17", "1"]), RESPType::Integer(0));
+        assert_eq!(run(&db, &["strlen", "k"]), RESPType::Integer(3));
+        for (args, err) in [
+            (
+                &["setbit", "k", "4294967296", "1"][..],
+                "ERR bit offset is not an integer or out of range",
+            ),
+            (
+                &["setbit", "k", "1", "2"],
+                "ERR bit is not an integer or out of range",
+            ),
+            (&["bitcount", "k", "0"], "ERR syntax error"),
+            (
+                &["bitpos", "k", "2"],
+                "ERR The bit argument must be 1 or 0.",
+            ),
+        ] {
+            assert_eq!(run(&db, args), RESPType::Error(err.into()), "{:?}", args);
+        }
+
+        run(&db, &["set", "s", "foobar"]);
+        assert_eq!(run(&db, &["bitcount", "s"]), RESPType::Integer(26));
+        assert_eq!(run(&db, &["bitcount", "s", "1", "1"]), RESPType::Integer(6));
+        assert_eq!(
+            run(&db, &["bitcount", "s", "5", "30", "BIT"]),
+            RESPType::Integer(17)
+        );
+        assert_eq!(
+            run(&db, &["bitcount", "s", "-1", "-3"]),
+            RESPType::Integer(0)
+        );
+        assert_eq!(run(&db, &["bitcount", "nope"]), RESPType::Integer(0));
+
+        // 0x00 0xff 0xf0
+        run(
+            &db,
+            &[
+                "bitfield", "p", "SET", "u8", "8", "255", "SET", "u8", "16", "240",
+            ],
+        );
+        assert_eq!(run(&db, &["bitpos", "p", "1"]), RESPType::Integer(8));
+        assert_eq!(run(&db, &["bitpos", "p", "1", "2"]), RESPType::Integer(16));
+        assert_eq!(run(&db, &["bitpos", "p", "0", "1"]), RESPType::Integer(20));
+        assert_eq!(
+            run(&db, &["bitpos", "p", "1", "7", "-3", "BIT"]),
+            RESPType::Integer(8)
+        );
+        // a string of ones only has a clear bit past its end when the range is open
+        run(&db, &["bitfield", "q", "SET", "u16", "0", "65535"]);
+        assert_eq!(run(&db, &["bitpos", "q", "0"]), RESPType::Integer(16));
+        assert_eq!(
+            run(&db, &["bitpos", "q", "0", "0", "-1"]),
+            RESPType::Integer(-1)
+        );
+        assert_eq!(run(&db, &["bitpos", "nope", "0"]), RESPType::Integer(0));
+        assert_eq!(run(&db, &["bitpos", "nope", "1"]), RESPType::Integer(-1));
+
+        // flipping bits in place leaves copies sharing the string alone
+        run(&db, &["copy", "s", "t"]);
+        run(&db, &["setbit", "s", "0", "1"]);
+        run(&db, &["bitfield", "s", "SET", "u8", "8", "0"]);
+        assert_eq!(
+            run(&db, &["get", "t"]),
+            RESPType::Bulk(Bytes::from("foobar"))
+        );
+        assert_eq!(
+            run(&db, &["get", "s"]),
+            RESPType::Bulk(Bytes::from_static(b"\xe6\x00obar"))
+        );
+    }
+
+    #[test]
+    fn test_bitop() {
+        let db = Db::new(2);
+        run(&db, &["set", "a", "ab"]);
+        run(&db, &["set", "b", "c"]);
+        for (op, result) in [("AND", &b"a\x00"[..]), ("or", b"cb"), ("XOR", b"\x02b")] {
+            assert_eq!(
+                run(&db, &["bitop", op, "d", "a", "b"]),
+                RESPType::Integer(2)
+            );
+            assert_eq!(
+                run(&db, &["get", "d"]),
+                RESPType::Bulk(Bytes::copy_from_slice(result)),
+                "{}",
+                op
+            );
+        }
+        assert_eq!(run(&db, &["bitop", "NOT", "d", "b"]), RESPType::Integer(1));
+        assert_eq!(
+            run(&db, &["get", "d"]),
+            RESPType::Bulk(Bytes::from_static(b"\x9c"))
+        );
+        assert_eq!(
+            run(&db, &["bitop", "NOT", "d", "a", "b"]),
+            RESPType::Error("ERR BITOP NOT must be called with a single source key.".into())
+        );
+        assert_eq!(
+            run(&db, &["bitop", "NAND", "d", "a"]),
+            RESPType::Error("ERR syntax error".into())
+        );
+
+        // an empty result deletes the destination
+        assert_eq!(
+            run(&db, &["bitop", "AND", "d", "nope", "nope2"]),
+            RESPType::Integer(0)
+        );
+        assert_eq!(run(&db, &["get", "d"]), RESPType::Null);
+    }
+
+    #[test]
+    fn test_bitfield() {
+        let db = Db::new(2);
+        let ints = |values: &[i64]| {
+            RESPType::Array(values.iter().map(|n| RESPType::Integer(*n)).collect())
+        };
+        assert_eq!(
+            run(
+                &db,
+                &["bitfield", "f", "SET", "i8", "0", "100", "INCRBY", "i8", "0", "100"]
+            ),
+            ints(&[0, -56])
+        );
+        assert_eq!(
+            run(
+                &db,
+                &["bitfield", "f", "OVERFLOW", "SAT", "INCRBY", "i8", "0", "-100"]
+            ),
+            ints(&[-128])
+        );
+        assert_eq!(
+            run(
+                &db,
+                &[
+                    "bitfield", "f", "OVERFLOW", "FAIL", "INCRBY", "i8", "0", "-1", "GET", "i8",
+                    "0"
+                ]
+            ),
+            RESPType::Array(vec![RESPType::Null, RESPType::Integer(-128)])
+        );
+        // each call starts over with wrapping, and # offsets count fields
+        assert_eq!(
+            run(
+                &db,
+                &["bitfield", "f", "SET", "u4", "#1", "17", "GET", "u4", "#1"]
+            ),
+            ints(&[0, 1])
+        );
+        assert_eq!(
+            run(&db, &["bitfield_ro", "f", "GET", "u8", "0"]),
+            ints(&[129])
+        );
+        assert_eq!(run(&db, &["bitfield", "f", "GET", "i64", "#2"]), ints(&[0]));
+        assert_eq!(run(&db, &["strlen", "f"]), RESPType::Integer(1));
+
+        // reads alone never create the key
+        assert_eq!(
+            run(&db, &["bitfield", "nope", "GET", "u8", "0"]),
+            ints(&[0])
+        );
+        assert_eq!(run(&db, &["strlen", "nope"]), RESPType::Integer(0));
+
+        for (args, err) in [
+            (
+                &["bitfield_ro", "f", "SET", "i8", "0", "1"][..],
+                "ERR BITFIELD_RO only supports the GET subcommand",
+            ),
+            (
+                &["bitfield", "f", "GET", "u64", "0"],
+                "ERR Invalid bitfield type. Use something like i16 u8. Note that u64 is not supported but i64 is.",
+            ),
+            (
+                &["bitfield", "f", "OVERFLOW", "MAYBE"],
+                "ERR Invalid OVERFLOW type specified",
+            ),
+            (&["bitfield", "f", "GET", "u8"], "ERR syntax error"),
+        ] {
+            assert_eq!(run(&db, args), RESPType::Error(err.into()), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_hyperloglog() {
+        let db = Db::new(4);
+        let byte = |value: &'static [u8]| RESPType::Bulk(Bytes::from_static(value));
+        assert_eq!(
+            run(&db, &["pfadd", "h", "a", "b", "c"]),
+            RESPType::Integer(1)
+        );
+        assert_eq!(run(&db, &["pfadd", "h", "a"]), RESPType::Integer(0));
+        assert_eq!(run(&db, &["pfcount", "h"]), RESPType::Integer(3));
+
+        // the cardinality is cached in the header until a write changes a register
+        assert_eq!(run(&db, &["getrange", "h", "15", "15"]), byte(b"\x00"));
+        run(&db, &["pfadd", "h", "a", "b", "c"]);
+        assert_eq!(run(&db, &["getrange", "h", "15", "15"]), byte(b"\x00"));
+        run(&db, &["pfadd", "h", "1", "2", "3"]);
+        assert_eq!(run(&db, &["getrange", "h", "15", "15"]), byte(b"\x80"));
+
+        assert_eq!(run(&db, &["pfadd", "empty"]), RESPType::Integer(1));
+        assert_eq!(
+            run(&db, &["pfcount", "empty", "nope"]),
+            RESPType::Integer(0)
+        );
+        run(&db, &["pfadd", "g", "c", "d", "e"]);
+        assert_eq!(run(&db, &["pfcount", "h", "g"]), RESPType::Integer(8));
+        assert_eq!(
+            run(&db, &["pfmerge", "m", "h", "g"]),
+            RESPType::String("OK".into())
+        );
+        assert_eq!(run(&db, &["pfcount", "m"]), RESPType::Integer(8));
+
+        let not_hll =
+            RESPType::Error("WRONGTYPE Key is not a valid HyperLogLog string value.".into());
+        run(&db, &["set", "s", "hello"]);
+        assert_eq!(run(&db, &["pfcount", "s"]), not_hll);
+        assert_eq!(run(&db, &["pfmerge", "m", "s"]), not_hll);
+        run(&db, &["append", "g", "hello"]);
+        assert_eq!(
+            run(&db, &["pfadd", "g", "f"]),
+            RESPType::Error("INVALIDOBJ Corrupted HLL object detected".into())
+        );
+        // a sparse value claiming to be dense has the wrong length
+        run(&db, &["setrange", "m", "4", "\x00"]);
+        assert_eq!(run(&db, &["pfcount", "m"]), not_hll);
+    }
+
+    #[test]
+    fn test_geo() {
+        let db = Db::new(4);
+        let bulk = |value: &str| RESPType::Bulk(Bytes::from(value.to_string()));
+        assert_eq!(
+            run(
+                &db,
+                &[
+                    "geoadd",
+                    "Sicily",
+                    "13.361389",
+                    "38.115556",
+                    "Palermo",
+                    "15.087269",
+                    "37.502669",
+                    "Catania"
+                ]
+            ),
+            RESPType::Integer(2)
+        );
+        assert_eq!(
+            run(&db, &["zscore", "Sicily", "Palermo"]),
+            RESPType::Double(3479099956230698.0)
+        );
+        assert_eq!(
+            run(&db, &["geodist", "Sicily", "Palermo", "Catania"]),
+            bulk("166274.1516")
+        );
+        assert_eq!(
+            run(&db, &["geodist", "Sicily", "Palermo", "Catania", "km"]),
+            bulk("166.2742")
+        );
+        assert_eq!(
+            run(&db, &["geodist", "Sicily", "Palermo", "Catania", "MI"]),
+            bulk("103.3182")
+        );
+        assert_eq!(
+            run(&db, &["geodist", "Sicily", "Foo", "Catania"]),
+            RESPType::Null
+        );
+        assert_eq!(
+            run(&db, &["geohash", "Sicily", "Palermo", "Catania", "Foo"]),
+            RESPType::Array(vec![
+                bulk("sqc8b49rny0"),
+                bulk("sqdtr74hyu0"),
+                RESPType::Null
+            ])
+        );
+        match run(&db, &["geopos", "Sicily", "Palermo", "Foo"]) {
+            RESPType::Array(positions) => match &positions[..] {
+                [RESPType::Array(position), RESPType::NullArray] => match position[..] {
+                    [RESPType::Double(longitude), RESPType::Double(latitude)] => {
+                        assert!((longitude - 13.361389).abs() < 1e-5);
+                        assert!((latitude - 38.115556).abs() < 1e-5);
+                    }
+                    _ => panic!("unexpected position {position:?}"),
+                },
+                _ => panic!("unexpected positions {positions:?}"),
+            },
+            resp => panic!("unexpected reply {resp:?}"),
+        }
+
+        assert_eq!(
+            run(&db, &["geoadd", "Sicily", "NX", "CH", "0", "0", "Palermo"]),
+            RESPType::Integer(0)
+        );
+        assert_eq!(
+            run(&db, &["geoadd", "Sicily", "0", "86", "Pole"]),
+            RESPType::Error("ERR invalid longitude,latitude pair 0.000000,86.000000".into())
+        );
+    }
+
+    #[test]
+    fn test_geosearch() {
+        let db = Db::new(4);
+        let bulk = |value: &str| RESPType::Bulk(Bytes::from(value.to_string()));
+        run(
+            &db,
+            &[
+                "geoadd",
+                "Sicily",
+                "13.361389",
+                "38.115556",
+                "Palermo",
+                "15.087269",
+                "37.502669",
+                "Catania",
+                "12.758489",
+                "38.788135",
+                "edge1",
+                "17.241510",
+                "38.788135",
+                "edge2",
+            ],
+        );
+
+        assert_eq!(
+            run(
+                &db,
+                &[
+                    "geosearch",
+                    "Sicily",
+                    "FROMLONLAT",
+                    "15",
+                    "37",
+                    "BYRADIUS",
+                    "200",
+                    "km",
+                    "ASC"
+                ]
+            ),
+            bulks(&["Catania", "Palermo"])
+        );
+        assert_eq!(
+            run(
+                &db,
+                &[
+                    "geosearch",
+                    "Sicily",
+                    "FROMLONLAT",
+                    "15",
+                    "37",
+                    "BYBOX",
+                    "400",
+                    "400",
+                    "km",
+                    "DESC",
+                    "WITHDIST"
+                ]
+            ),
+            RESPType::Array(vec![
+                RESPType::Array(vec![bulk("edge1"), bulk("279.7405")]),
+                RESPType::Array(vec![bulk("edge2"), bulk("279.7403")]),
+                RESPType::Array(vec![bulk("Palermo"), bulk("190.4424")]),
+                RESPType::Array(vec![bulk("Catania"), bulk("56.4413")]),
+            ])
+        );
+        assert_eq!(
+            run(
+                &db,
+                &[
+                    "geosearch",
+                    "Sicily",
+                    "FROMMEMBER",
+                    "Palermo",
+                    "BYRADIUS",
+                    "200",
+                    "km",
+                    "COUNT",
+                    "1",
+                    "WITHHASH"
+                ]
+            ),
+            RESPType::Array(vec![RESPType::Array(vec![
+                bulk("Palermo"),
+                RESPType::Integer(3479099956230698)
+            ])])
+        );
+
+        assert_eq!(
+            run(
+                &db,
+                &[
+                    "geosearchstore",
+                    "dist",
+                    "Sicily",
+                    "FROMLONLAT",
+                    "15",
+                    "37",
+                    "BYBOX",
+                    "400",
+                    "400",
+                    "km",
+                    "COUNT",
+                    "3",
+                    "STOREDIST"
+                ]
+            ),
+            RESPType::Integer(3)
+        );
+        assert_eq!(
+            run(&db, &["zrange", "dist", "0", "-1"]),
+            bulks(&["Catania", "Palermo", "edge2"])
+        );
+        assert_eq!(
+            run(&db, &["zscore", "dist", "Catania"]),
+            RESPType::Double(56.441_257_870_158_2)
+        );
+        assert_eq!(
+            run(
+                &db,
+                &[
+                    "geosearchstore",
+                    "dist",
+                    "nope",
+                    "FROMLONLAT",
+                    "15",
+                    "37",
+                    "BYRADIUS",
+                    "1",
+                    "m"
+                ]
+            ),
+            RESPType::Integer(0)
+        );
+        assert!(!db.exists(b"dist"));
+
+        for (args, err) in [
+            (
+                &[
+                    "geosearch",
+                    "Sicily",
+                    "FROMMEMBER",
+                    "Foo",
+                    "BYRADIUS",
+                    "1",
+                    "km",
+                ][..],
+                "ERR could not decode requested zset member",
+            ),
+            (
+                &[
+                    "geosearch",
+                    "Sicily",
+                    "BYRADIUS",
+                    "1",
+                    "km",
+                    "ASC",
+                    "WITHDIST",
+                ],
+                "ERR exactly one of FROMMEMBER or FROMLONLAT can be specified for geosearch",
+            ),
+            (
+                &[
+                    "geosearch",
+                    "Sicily",
+                    "FROMLONLAT",
+                    "15",
+                    "37",
+                    "ASC",
+                    "ANY",
+                ],
+                "ERR exactly one of BYRADIUS and BYBOX can be specified for geosearch",
+            ),
+            (
+                &[
+                    "geosearch",
+                    "Sicily",
+                    "FROMLONLAT",
+                    "15",
+                    "37",
+                    "BYRADIUS",
+                    "1",
+                    "km",
+                    "ANY",
+                ],
+                "ERR the ANY argument requires COUNT argument",
+            ),
+            (
+                &[
+                    "geosearch",
+                    "Sicily",
+                    "FROMLONLAT",
+                    "15",
+                    "37",
+                    "BYRADIUS",
+                    "1",
+                    "yd",
+                ],
+                "ERR unsupported unit provided. please use M, KM, FT, MI",
+            ),
+            (
+                &[
+                    "geosearchstore",
+                    "d",
+                    "Sicily",
+                    "FROMLONLAT",
+                    "15",
+                    "37",
+                    "BYRADIUS",
+                    "1",
+                    "km",
+                    "WITHDIST",
+                ],
+                "ERR syntax error",
+            ),
+        ] {
+            assert_eq!(run(&db, args), RESPType::Error(err.into()), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn test_keyspace() {
+        let db = Db::new(4);
+        run(&db, &["set", "a", "1"]);
+        run(&db, &["rpush", "l", "x"]);
+        assert_eq!(
+            run(&db, &["exists", "a", "l", "nope", "a"]),
+            RESPType::Integer(3)
+        );
+        assert_eq!(run(&db, &["touch", "a", "nope"]), RESPType::Integer(1));
+        assert_eq!(run(&db, &["type", "a"]), RESPType::String("string".into()));
+        assert_eq!(run(&db, &["type", "l"]), RESPType::String("list".into()));
+        assert_eq!(run(&db, &["type", "nope"]), RESPType::String("none".into()));
+        assert_eq!(run(&db, &["dbsize"]), RESPType::Integer(2));
+
+        assert_eq!(run(&db, &["del", "a", "nope", "a"]), RESPType::Integer(1));
+        let mut big = vec!["rpush", "big"];
+        big.extend(["x"; 200]);
+        run(&db, &big);
+        assert_eq!(run(&db, &["unlink", "l", "big"]), RESPType::Integer(2));
+        assert_eq!(run(&db, &["dbsize"]), RESPType::Integer(0));
+        assert_eq!(run(&db, &["randomkey"]), RESPType::Null);
+
+        run(&db, &["set", "a", "1", "EX", "100"]);
+        run(&db, &["rpush", "l", "x"]);
+        match run(&db, &["randomkey"]) {
+            RESPType::Bulk(key) => assert!(key == "a" || key == "l", "{key:?}"),
+            resp => panic!("unexpected reply {resp:?}"),
+        }
+        assert_eq!(
+            run(&db, &["flushdb", "ASYNC"]),
+            RESPType::String("OK".into())
+        );
+        assert_eq!(run(&db, &["dbsize"]), RESPType::Integer(0));
+        assert_eq!(
+            run(&db, &["flushall", "NOW"]),
+            RESPType::Error("ERR syntax error".into())
+        );
+    }
+
+    #[test]
+    fn test_rename_copy() {
+        let db = Db::new(4);
+        run(&db, &["set", "a", "1", "EX", "100"]);
+        assert_eq!(
+            run(&db, &["rename", "a", "b"]),
+            RESPType::String("OK".into())
+        );
+        assert!(!db.exists(b"a"));
+        assert_eq!(run(&db, &["get", "b"]), RESPType::Bulk(Bytes::from("1")));
+        assert_eq!(run(&db, &["ttl", "b"]), RESPType::Integer(100));
+        assert_eq!(
+            run(&db, &["rename", "nope", "b"]),
+            RESPType::Error("ERR no such key".into())
+        );
+        assert_eq!(
+            run(&db, &["rename", "b", "b"]),
+            RESPType::String("OK".into())
+        );
+        assert_eq!(run(&db, &["renamenx", "b", "b"]), RESPType::Integer(0));
+
+        run(&db, &["set", "c", "2"]);
+        assert_eq!(run(&db, &["renamenx", "b", "c"]), RESPType::Integer(0));
+        assert_eq!(run(&db, &["renamenx", "b", "d"]), RESPType::Integer(1));
+        // RENAME replaces the destination and its ttl
+        run(&db, &["expire", "c", "50"]);
+        run(&db, &["rename", "d", "c"]);
+        assert_eq!(run(&db, &["get", "c"]), RESPType::Bulk(Bytes::from("1")));
+        assert_eq!(run(&db, &["ttl", "c"]), RESPType::Integer(100));
+
+        run(&db, &["rpush", "l", "x"]);
+        assert_eq!(run(&db, &["copy", "l", "m"]), RESPType::Integer(1));
+        assert_eq!(run(&db, &["copy", "c", "m"]), RESPType::Integer(0));
+        run(&db, &["rpush", "m", "y"]);
+        assert_eq!(run(&db, &["llen", "l"]), RESPType::Integer(1));
+        assert_eq!(
+            run(&db, &["copy", "c", "m", "REPLACE"]),
+            RESPType::Integer(1)
+        );
+        assert_eq!(run(&db, &["get", "m"]), RESPType::Bulk(Bytes::from("1")));
+        assert_eq!(run(&db, &["ttl", "m"]), RESPType::Integer(100));
+        assert_eq!(run(&db, &["copy", "nope", "m"]), RESPType::Integer(0));
+        assert_eq!(
+            run(&db, &["copy", "m", "m"]),
+            RESPType::Error("ERR source and destination objects are the same".into())
+        );
+    }
+
+    #[test]
+    fn test_rename_across_shards() {
+        let db = Db::new(16);
+        run(&db, &["set", "x", "1"]);
+        run(&db, &["set", "y", "2"]);
+
+        // renames in opposite directions lock the same two shards, which must not deadlock
+        let handles: Vec<_> = [("x", "y"), ("y", "x"), ("x", "y"), ("y", "x")]
+            .into_iter()
+            .map(|(from, to)| {
+                let db = db.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..1000 {
+                        run(&db, &["rename", from, to]);
+                        run(&db, &["copy", to, from]);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert!(db.exists(b"x") || db.exists(b"y"));
+    }
+
+    #[test]
+    fn test_keys_scan() {
+        let db = Db::new(4);
+        for i in 0..50 {
+            run(&db, &["set", &format!("key:{i}"), "1"]);
+        }
+        run(&db, &["rpush", "list", "x"]);
+        run(&db, &["set", "gone", "1", "PX", "1"]);
+        std::thread::sleep(Duration::from_millis(5));
+
+        let sorted = |resp: RESPType| match resp {
+            RESPType::Array(arr) => {
+                let mut keys: Vec<_> = arr
+                    .into_iter()
+                    .map(|key| match key {
+                        RESPType::Bulk(key) => key,
+                        key => panic!("unexpected key {key:?}"),
+                    })
+                    .collect();
+                keys.sort();
+                keys
+            }
+            resp => panic!("unexpected reply {resp:?}"),
+        };
+        assert_eq!(sorted(run(&db, &["keys", "*"])).len(), 51);
+        assert_eq!(sorted(run(&db, &["keys", "key:?"])).len(), 10);
+        assert_eq!(run(&db, &["keys", "l[aeiou]st"]), bulks(&["list"]));
+        assert_eq!(run(&db, &["keys", "nope*"]), bulks(&[]));
+
+        // walks every page, collecting what each call returns
+        let scan = |extra: &[&str], during: &dyn Fn(usize)| {
+            let (mut cursor, mut keys, mut calls) = (String::from("0"), vec![], 0);
+            loop {
+                let mut args = vec!["scan", &cursor];
+                args.extend(extra);
+                let (next, page) = match run(&db, &args) {
+                    RESPType::Array(arr) => match &arr[..] {
+                        [RESPType::Bulk(next), page] => (next.clone(), page.clone()),
+                        _ => panic!("unexpected reply {arr:?}"),
+                    },
+                    resp => panic!("unexpected reply {resp:?}"),
+                };
+                keys.extend(sorted(page));
+                calls += 1;
+                during(calls);
+                cursor = String::from_utf8(next.to_vec()).unwrap();
+                if cursor == "0" {
+                    keys.sort();
+                    return (keys, calls);
+                }
+            }
+        };
+        let (keys, calls) = scan(&["COUNT", "7"], &|_| {});
+        assert_eq!(keys, sorted(run(&db, &["keys", "*"])));
+        assert!(calls > 1);
+        let (keys, _) = scan(&["MATCH", "key:1*", "COUNT", "100"], &|_| {});
+        assert_eq!(keys.len(), 11);
+        let (keys, _) = scan(&["TYPE", "LIST"], &|_| {});
+        assert_eq!(keys, vec![Bytes::from("list")]);
+        let (keys, _) = scan(&["TYPE", "nope"], &|_| {});
+        assert!(keys.is_empty());
+
+        // keys present throughout are returned even while the shards keep growing
+        let (keys, _) = scan(&["COUNT", "5"], &|calls| {
+            if calls <= 3 {
+                for i in 0..100 {
+                    run(&db, &["set", &format!("new:{calls}:{i}"), "1"]);
+                }
+            }
+        });
+        let original: Vec<_> = (0..50).map(|i| Bytes::from(format!("key:{i}"))).collect();
+        assert!(original.iter().all(|key| keys.contains(key)));
+
+        // or while keys are deleted and recreated on both sides of the cursor
+        let (keys, _) = scan(&["COUNT", "3"], &|calls| {
+            run(&db, &["del", &format!("key:{}", 49 - calls % 10)]);
+            run(&db, &["del", &format!("new:1:{calls}")]);
+            run(&db, &["set", &format!("new:1:{}", calls - 1), "1"]);
+        });
+        assert!(original[..40].iter().all(|key| keys.contains(key)));
+
+        assert_eq!(
+            run(&db, &["scan", "abc"]),
+            RESPType::Error("ERR invalid cursor".into())
+        );
+        assert_eq!(
+            run(&db, &["scan", "0", "COUNT", "0"]),
+            RESPType::Error("ERR syntax error".into())
+        );
+        assert_eq!(
+            run(&db, &["scan", "0", "TYPE"]),
+            RESPType::Error("ERR syntax error".into())
+        );
+    }
+
+    #[test]
+    fn test_databases() {
+        let db = Db::new(4);
+        let mut selected = db.clone();
+        assert_eq!(
+            Select::new(1).response(&mut selected),
+            RESPType::String("OK".into())
+        );
+        assert_eq!(selected.index(), 1);
+        assert_eq!(
+            Select::new(16).response(&mut selected),
+            RESPType::Error("ERR DB index is out of range".into())
+        );
+        assert_eq!(selected.index(), 1);
+        assert_eq!(
+            run(&db, &["select", "x"]),
+            RESPType::Error("ERR value is not an integer or out of range".into())
+        );
+
+        run(&db, &["set", "a", "1", "EX", "100"]);
+        run(&db, &["set", "b", "2"]);
+        assert_eq!(run(&selected, &["get", "a"]), RESPType::Null);
+        assert_eq!(run(&db, &["move", "a", "1"]), RESPType::Integer(1));
+        assert_eq!(run(&db, &["move", "a", "1"]), RESPType::Integer(0));
+        assert_eq!(
+            run(&selected, &["get", "a"]),
+            RESPType::Bulk(Bytes::from("1"))
+        );
+        assert!(matches!(run(&selected, &["ttl", "a"]), RESPType::Integer(ttl) if ttl > 90));
+        run(&db, &["set", "a", "3"]);
+        assert_eq!(run(&selected, &["move", "a", "0"]), RESPType::Integer(0));
+        assert_eq!(
+            run(&db, &["move", "b", "0"]),
+            RESPType::Error("ERR source and destination objects are the same".into())
+        );
+        assert_eq!(
+            run(&db, &["move", "b", "-1"]),
+            RESPType::Error("ERR DB index is out of range".into())
+        );
+
+        // COPY into another database may keep the key name
+        assert_eq!(
+            run(&db, &["copy", "b", "b", "DB", "1"]),
+            RESPType::Integer(1)
+        );
+        assert_eq!(
+            run(&db, &["copy", "b", "a", "DB", "1"]),
+            RESPType::Integer(0)
+        );
+        assert_eq!(
+            run(&db, &["copy", "b", "a", "DB", "1", "REPLACE"]),
+            RESPType::Integer(1)
+        );
+        assert_eq!(
+            run(&selected, &["get", "a"]),
+            RESPType::Bulk(Bytes::from("2"))
+        );
+        assert_eq!(
+            run(&db, &["copy", "b", "b", "DB", "0"]),
+            RESPType::Error("ERR source and destination objects are the same".into())
+        );
+        assert_eq!(
+            run(&db, &["copy", "b", "c", "DB", "99"]),
+            RESPType::Error("ERR DB index is out of range".into())
+        );
+        assert_eq!(
+            run(&db, &["copy", "b", "c", "DB"]),
+            RESPType::Error("ERR syntax error".into())
+        );
+
+        assert_eq!(run(&db, &["dbsize"]), RESPType::Integer(2));
+        assert_eq!(run(&selected, &["dbsize"]), RESPType::Integer(2));
+        run(&selected, &["hset", "h", "f", "v"]);
+        run(&selected, &["hexpire", "h", "100", "FIELDS", "1", "f"]);
+        run(&selected, &["expire", "b", "100"]);
+        let info = match run(&db, &["info", "keyspace"]) {
+            RESPType::Bulk(info) => String::from_utf8(info.to_vec()).unwrap(),
+            resp => panic!("unexpected reply {resp:?}"),
+        };
+        let lines: Vec<_> = info.split_terminator("\r\n").collect();
+        assert_eq!(
+            lines[..2],
+            ["# Keyspace", "db0:keys=2,expires=0,avg_ttl=0,subexpiry=0"]
+        );
+        let avg_ttl = lines[2]
+            .strip_prefix("db1:keys=3,expires=1,avg_ttl=")
+            .and_then(|rest| rest.strip_suffix(",subexpiry=1"))
+            .and_then(|avg_ttl| avg_ttl.parse::<u64>().ok());
+        assert!(matches!(avg_ttl, Some(90_000..=100_000)), "{info}");
+        assert_eq!(lines.len(), 3);
+
+        assert_eq!(
+            run(&db, &["swapdb", "0", "1"]),
+            RESPType::String("OK".into())
+        );
+        assert_eq!(run(&db, &["dbsize"]), RESPType::Integer(3));
+        assert_eq!(run(&db, &["get", "b"]), RESPType::Bulk(Bytes::from("2")));
+        assert_eq!(run(&selected, &["exists", "h"]), RESPType::Integer(0));
+        assert_eq!(db.stats().subexpiry, 1);
+        assert_eq!(selected.stats().subexpiry, 0);
+        run(&db, &["hpersist", "h", "FIELDS", "1", "f"]);
+        assert_eq!(db.stats().subexpiry, 0);
+        assert_eq!(
+            run(&db, &["swapdb", "x", "1"]),
+            RESPType::Error("ERR invalid first DB index".into())
+        );
+        assert_eq!(
+            run(&db, &["swapdb", "0", "16"]),
+            RESPType::Error("ERR DB index is out of range".into())
+        );
+
+        run(&selected, &["flushdb"]);
+        assert_eq!(run(&db, &["dbsize"]), RESPType::Integer(3));
+        run(&selected, &["set", "x", "1"]);
+        run(&selected, &["flushall"]);
+        assert_eq!(run(&db, &["dbsize"]), RESPType::Integer(0));
+        assert_eq!(run(&selected, &["dbsize"]), RESPType::Integer(0));
+    }
+
+    #[test]
+    fn test_maxmemory() {
+        // the error the request would be refused with, if any
+        let check = |db: &Db, args: &[&str]| {
+            let frame = RESPType::Array(
+                args.iter()
+                    .map(|arg| RESPType::Bulk(Bytes::from(arg.to_string())))
+                    .collect(),
+            );
+            check_memory(&frame, db).err().map(RESPType::from)
+        };
+        let oom = Some(RESPType::Error(
+            "OOM command not allowed when used memory > 'maxmemory'.".into(),
+        ));
+
+        let db = Db::new(4);
+        for i in 0..20 {
+            run(&db, &["set", &format!("p:{i}"), "value"]);
+            run(&db, &["set", &format!("v:{i}"), "value", "EX", "100"]);
+        }
+
+        // nothing may be evicted, so only commands that can't grow the dataset still run
+        db.set_maxmemory(db.used_memory() - 1);
+        assert_eq!(check(&db, &["set", "a", "1"]), oom);
+        assert_eq!(check(&db, &["get", "p:0"]), None);
+        assert_eq!(check(&db, &["del", "p:0"]), None);
+        assert_eq!(check(&db, &["nope"]), None);
+        run(&db, &["del", "p:0"]);
+        assert_eq!(check(&db, &["set", "a", "1"]), None);
+
+        // volatile policies leave keys without a ttl alone
+        db.set_eviction_policy(EvictionPolicy::VolatileLru);
+        db.set_maxmemory(db.used_memory() / 3);
+        assert_eq!(check(&db, &["set", "a", "1"]), oom);
+        assert_eq!(run(&db, &["keys", "v:*"]), bulks(&[]));
+        assert_eq!(run(&db, &["dbsize"]), RESPType::Integer(19));
+        assert_eq!(db.evicted_keys(), 20);
+
+        db.set_eviction_policy(EvictionPolicy::AllKeysRandom);
+        assert_eq!(check(&db, &["set", "a", "1"]), None);
+        assert!(db.used_memory() <= db.maxmemory());
+        assert!(matches!(
+            run(&db, &["dbsize"]),
+            RESPType::Integer(left) if (1..19).contains(&left)
+        ));
+
+        // volatile-ttl goes by the ttl alone, which a single shard makes exact
+        let db = Db::with_databases(1, 1);
+        db.set_eviction_policy(EvictionPolicy::VolatileTtl);
+        run(&db, &["set", "b", "1", "EX", "200"]);
+        run(&db, &["set", "a", "1", "EX", "100"]);
+        run(&db, &["set", "c", "1", "EX", "300"]);
+        db.set_maxmemory(db.used_memory() - 1);
+        assert_eq!(check(&db, &["set", "d", "1"]), None);
+        assert_eq!(run(&db, &["exists", "a", "b", "c"]), RESPType::Integer(2));
+        assert_eq!(run(&db, &["exists", "a"]), RESPType::Integer(0));
+    }
+
+    #[test]
+    fn test_object() {
+        let db = Db::new(4);
+        run(&db, &["set", "a", "1"]);
+        assert_eq!(run(&db, &["object", "idletime", "a"]), RESPType::Integer(0));
+        assert_eq!(run(&db, &["object", "idletime", "nope"]), RESPType::Null);
+        assert!(matches!(
+            run(&db, &["object", "freq", "a"]),
+            RESPType::Error(e) if e.starts_with("ERR An LFU maxmemory policy is not selected")
+        ));
+        assert_eq!(
+            run(&db, &["object", "help", "a"]),
+            RESPType::Error("ERR unknown subcommand 'help'. Try OBJECT HELP.".into())
+        );
+
+        // new keys start at 5, the first access always counts
+        db.set_eviction_policy(EvictionPolicy::AllKeysLfu);
+        assert_eq!(run(&db, &["object", "freq", "a"]), RESPType::Integer(5));
+        run(&db, &["exists", "a"]);
+        run(&db, &["type", "a"]);
+        assert_eq!(run(&db, &["object", "freq", "a"]), RESPType::Integer(5));
+        run(&db, &["touch", "a"]);
+        assert_eq!(run(&db, &["object", "freq", "a"]), RESPType::Integer(6));
+        for _ in 0..1000 {
+            run(&db, &["get", "a"]);
+        }
+        assert!(matches!(
+            run(&db, &["object", "freq", "a"]),
+            RESPType::Integer(freq) if (7..50).contains(&freq)
+        ));
+        // overwriting the value is one more access, not a new key
+        run(&db, &["set", "a", "2"]);
+        assert!(matches!(
+            run(&db, &["object", "freq", "a"]),
+            RESPType::Integer(freq) if (7..50).contains(&freq)
+        ));
+        assert!(matches!(
+            run(&db, &["object", "idletime", "a"]),
+            RESPType::Error(e) if e.starts_with("ERR An LFU maxmemory policy is selected")
+        ));
+
+        match run(&db, &["info", "memory"]) {
+            RESPType::Bulk(info) => {
+                let info = String::from_utf8(info.to_vec()).unwrap();
+                assert!(info.starts_with("# Memory\r\nused_memory:"), "{info}");
+                assert!(info.ends_with("maxmemory:0\r\nmaxmemory_policy:allkeys-lfu\r\n"));
+            }
+            resp => panic!("unexpected reply {resp:?}"),
+        }
+    }
+
+    #[test]
+    fn test_wrong_type() {
+        let db = Db::new(2);
+        let wrongtype = RESPType::Error(
+            "WRONGTYPE Operation against a key holding the wrong kind of value".into(),
+        );
+        run(&db, &["set", "s", "a"]);
+        run(&db, &["rpush", "l", "a"]);
+
+        for args in [
+            &["lpush", "s", "b"][..],
+            &["lpop", "s"],
+            &["llen", "s"],
+            &["lrange", "s", "0", "-1"],
+            &["lindex", "s", "0"],
+            &["lset", "s", "0", "b"],
+            &["lrem", "s", "0", "a"],
+            &["ltrim", "s", "0", "1"],
+            &["linsert", "s", "BEFORE", "a", "b"],
+            &["lpos", "s", "a"],
+            &["lmpop", "1", "s", "LEFT"],
+            &["get", "l"],
+            &["append", "l", "b"],
+            &["incr", "l"],
+            &["strlen", "l"],
+            &["getrange", "l", "0", "1"],
+            &["set", "l", "b", "GET"],
+            &["hset", "s", "a", "1"],
+            &["hget", "l", "a"],
+            &["hgetall", "s"],
+            &["hincrby", "s", "a", "1"],
+            &["hscan", "s", "0"],
+            &["httl", "s", "FIELDS", "1", "a"],
+            &["sadd", "s", "a"],
+            &["smembers", "l"],
+            &["sismember", "s", "a"],
+            &["spop", "l"],
+            &["sscan", "s", "0"],
+            &["zadd", "s", "1", "a"],
+            &["zscore", "l", "a"],
+            &["zrange", "s", "0", "-1"],
+            &["zpopmin", "l"],
+            &["zunion", "2", "nokey", "l"],
+            &["xadd", "s", "*", "a", "1"],
+            &["xlen", "l"],
+            &["xrange", "s", "-", "+"],
+            &["xgroup", "create", "l", "g", "$"],
+            &["xinfo", "stream", "s"],
+            &["setbit", "l", "0", "1"],
+            &["getbit", "l", "0"],
+            &["bitcount", "l"],
+            &["bitpos", "l", "1"],
+            &["bitop", "AND", "d", "l"],
+            &["bitfield", "l", "GET", "u8", "0"],
+            &["pfadd", "l", "a"],
+            &["pfcount", "l"],
+            &["pfmerge", "l"],
+            &["geoadd", "s", "0", "0", "a"],
+            &["geodist", "s", "a", "b"],
+            &[
+                "geosearch",
+                "s",
+                "FROMLONLAT",
+                "0",
+                "0",
+                "BYRADIUS",
+                "1",
+                "m",
+            ],
+        ] {
+            assert_eq!(run(&db, args), wrongtype, "{:?}", args);
+        }
+
+        // plain SET overwrites whatever type was there
+        assert_eq!(run(&db, &["set", "l", "b"]), RESPType::String("OK".into()));
+        assert_eq!(run(&db, &["get", "l"]), RESPType::Bulk(Bytes::from("b")));
+    }
+
+    #[test]
+    fn test_command_introspection() {
+        let db = Db::new(2);
+        assert_eq!(
+            run(&db, &["command", "count"]),
+            RESPType::Integer(crate::cmd::commands().len() as i64)
+        );
+
+        let info = match run(&db, &["COMMAND", "INFO", "get", "nope"]) {
+            RESPType::Array(info) => info,
+            other => panic!("unexpected reply {:?}", other),
+        };
+        assert_eq!(info[1], RESPType::Null);
+        match &info[0] {
+            RESPType::Array(get) => {
+                assert_eq!(get[0], RESPType::Bulk(Bytes::from("get")));
+                assert_eq!(get[1], RESPType::Integer(2));
+                assert_eq!(get[3..6], vec![RESPType::Integer(1); 3]);
+            }
+            other => panic!("unexpected reply {:?}", other),
+        }
+
+        assert!(matches!(
+            run(&db, &["command", "docs", "set"]),
+            RESPType::Map(ref docs) if docs.len() == 1
+        ));
+        assert_eq!(
+            run(
+                &db,
+                &["command", "getkeys", "set", "key", "value", "EX", "10"]
+            ),
+            RESPType::Array(vec![RESPType::Bulk(Bytes::from("key"))])
+        );
+        assert_eq!(
+            run(&db, &["command", "getkeys", "ping"]),
+            RESPType::Error("ERR The command has no key arguments".into())
+        );
+        assert_eq!(
+            run(&db, &["command", "getkeys", "get"]),
+            RESPType::Error("ERR Invalid number of arguments specified for command".into())
+        );
+
+        match run(&db, &["command", "help"]) {
+            RESPType::Array(lines) => {
+                assert!(
+                    matches!(&lines[0], RESPType::String(s) if s.starts_with("COMMAND <subcommand>"))
+                );
+                assert_eq!(lines[lines.len() - 2], RESPType::String("HELP".into()));
+            }
+            other => panic!("unexpected reply {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_binary_keys_and_values() {
+        let db = Db::new(2);
+        let key = Bytes::from_static(b"\x00\xff\r\nkey");
+        let value = Bytes::from((0..=255u8).collect::<Vec<u8>>());
+
+        let set = RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("set")),
+            RESPType::Bulk(key.clone()),
+            RESPType::Bulk(value.clone()),
+        ]);
+        match Command::try_from(set) {
+            Ok(Command::Set(cmd)) => assert_eq!(cmd.response(&db), RESPType::String("OK".into())),
+            _ => panic!("expected set command"),
+        }
+
+        let get = RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("get")),
+            RESPType::Bulk(key.clone()),
+        ]);
+        match Command::try_from(get) {
+            Ok(Command::Get(cmd)) => assert_eq!(cmd.response(&db), RESPType::Bulk(value)),
+            _ => panic!("expected get command"),
+        }
+        assert!(!db.exists(b"\x00\xff"));
     }
 }
@@ -0,0 +1,279 @@
+use bytes::Bytes;
+
+use crate::{Error, RESPType};
+
+use super::{commands, lookup, resolve, CommandSpec};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandInfoKind {
+    All,
+    Count,
+    List,
+    Info(Vec<Bytes>),
+    Docs(Vec<Bytes>),
+    GetKeys(Vec<Bytes>),
+    Help,
+}
+
+/// COMMAND and its COUNT, LIST, INFO, DOCS, GETKEYS and HELP subcommands.
+pub struct CommandInfo {
+    kind: CommandInfoKind,
+}
+
+impl CommandInfo {
+    pub fn new(kind: CommandInfoKind) -> Self {
+        CommandInfo { kind }
+    }
+
+    pub fn response(&self) -> RESPType {
+        match &self.kind {
+            CommandInfoKind::All => RESPType::Array(commands().iter().map(info).collect()),
+            CommandInfoKind::Count => RESPType::Integer(commands().len() as i64),
+            CommandInfoKind::List => RESPType::Array(
+                commands()
+                    .iter()
+                    .map(|spec| RESPType::Bulk(Bytes::from(spec.name)))
+                    .collect(),
+            ),
+            CommandInfoKind::Info(names) if names.is_empty() => {
+                RESPType::Array(commands().iter().map(info).collect())
+            }
+            CommandInfoKind::Info(names) => RESPType::Array(
+                names
+                    .iter()
+                    .map(|name| match lookup(name) {
+                        Some(spec) => info(spec),
+                        None => RESPType::Null,
+                    })
+                    .collect(),
+            ),
+            CommandInfoKind::Docs(names) if names.is_empty() => {
+                RESPType::Map(commands().iter().map(docs).collect())
+            }
+            // unknown names are left out rather than answered with null
+            CommandInfoKind::Docs(names) => RESPType::Map(
+                names
+                    .iter()
+                    .filter_map(|name| lookup(name))
+                    .map(docs)
+                    .collect(),
+            ),
+            CommandInfoKind::GetKeys(args) => get_keys(args),
+            CommandInfoKind::Help => RESPType::Array(
+                HELP.iter()
+                    .map(|line| RESPType::String(line.to_string()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+const HELP: &[&str] = &[
+    "COMMAND <subcommand> [<arg> [value] [opt] ...]. Subcommands are:",
+    "(no subcommand)",
+    "    Return details about all Redis commands.",
+    "COUNT",
+    "    Return the total number of commands in this Redis server.",
+    "LIST",
+    "    Return a list of all commands in this Redis server.",
+    "INFO [<command-name> ...]",
+    "    Return details about multiple Redis commands.",
+    "    If no command names are given, documentation details for all",
+    "    commands are returned.",
+    "DOCS [<command-name> ...]",
+    "    Return documentation details about multiple Redis commands.",
+    "    If no command names are given, documentation details for all",
+    "    commands are returned.",
+    "GETKEYS <full-command>",
+    "    Return the keys from a full Redis command.",
+    "HELP",
+    "    Print this help.",
+];
+
+// private helper functions
+fn info(spec: &CommandSpec) -> RESPType {
+    RESPType::Array(vec![
+        RESPType::Bulk(Bytes::from(spec.name)),
+        RESPType::Integer(spec.arity),
+        RESPType::Set(
+            spec.flags
+                .iter()
+                .map(|flag| flag.name())
+                .chain(spec.movable_keys().then_some("movablekeys"))
+                .map(|flag| RESPType::String(flag.into()))
+                .collect(),
+        ),
+        RESPType::Integer(spec.first_key),
+        RESPType::Integer(spec.last_key),
+        RESPType::Integer(spec.step),
+        RESPType::Set(
+            spec.categories
+                .iter()
+                .map(|category| RESPType::String(category.to_string()))
+                .collect(),
+        ),
+        RESPType::Array(
+            spec.tips
+                .iter()
+                .map(|tip| RESPType::Bulk(Bytes::from(*tip)))
+                .collect(),
+        ),
+        key_specs(spec),
+        RESPType::Array(spec.subcommands.iter().map(info).collect()),
+    ])
+}
+
+fn key_specs(spec: &CommandSpec) -> RESPType {
+    let mut specs = vec![];
+    if spec.first_key > 0 {
+        // the range end is stored relative to the first key
+        let last_key = if spec.last_key < 0 {
+            spec.last_key
+        } else {
+            spec.last_key - spec.first_key
+        };
+        specs.push(key_spec(
+            spec.key_flags,
+            index_search(spec.first_key),
+            "range",
+            vec![
+                (bulk("lastkey"), RESPType::Integer(last_key)),
+                (bulk("keystep"), RESPType::Integer(spec.step)),
+                (bulk("limit"), RESPType::Integer(0)),
+            ],
+        ));
+    }
+    if spec.numkeys > 0 {
+        specs.push(key_spec(
+            spec.numkeys_flags,
+            index_search(spec.numkeys),
+            "keynum",
+            vec![
+                (bulk("keynumidx"), RESPType::Integer(0)),
+                (bulk("firstkey"), RESPType::Integer(1)),
+                (bulk("keystep"), RESPType::Integer(1)),
+            ],
+        ));
+    }
+    if !spec.keyword.is_empty() {
+        // the keys take up half of the arguments after the keyword
+        specs.push(key_spec(
+            spec.keyword_flags,
+            RESPType::Map(vec![
+                (bulk("type"), bulk("keyword")),
+                (
+                    bulk("spec"),
+                    RESPType::Map(vec![
+                        (bulk("keyword"), bulk(spec.keyword)),
+                        (bulk("startfrom"), RESPType::Integer(1)),
+                    ]),
+                ),
+            ]),
+            "range",
+            vec![
+                (bulk("lastkey"), RESPType::Integer(-1)),
+                (bulk("keystep"), RESPType::Integer(1)),
+                (bulk("limit"), RESPType::Integer(2)),
+            ],
+        ));
+    }
+    RESPType::Array(specs)
+}
+
+fn index_search(index: i64) -> RESPType {
+    RESPType::Map(vec![
+        (bulk("type"), bulk("index")),
+        (
+            bulk("spec"),
+            RESPType::Map(vec![(bulk("index"), RESPType::Integer(index))]),
+        ),
+    ])
+}
+
+fn key_spec(
+    flags: &[&str],
+    begin_search: RESPType,
+    find_type: &'static str,
+    find_spec: Vec<(RESPType, RESPType)>,
+) -> RESPType {
+    RESPType::Map(vec![
+        (
+            bulk("flags"),
+            RESPType::Set(
+                flags
+                    .iter()
+                    .map(|flag| RESPType::String(flag.to_string()))
+                    .collect(),
+            ),
+        ),
+        (bulk("begin_search"), begin_search),
+        (
+            bulk("find_keys"),
+            RESPType::Map(vec![
+                (bulk("type"), bulk(find_type)),
+                (bulk("spec"), RESPType::Map(find_spec)),
+            ]),
+        ),
+    ])
+}
+
+fn docs(spec: &CommandSpec) -> (RESPType, RESPType) {
+    let mut doc = vec![
+        (bulk("summary"), bulk(spec.summary)),
+        (bulk("since"), bulk(spec.since)),
+        (bulk("group"), bulk(spec.group)),
+        (bulk("complexity"), bulk(spec.complexity)),
+    ];
+    if !spec.subcommands.is_empty() {
+        doc.push((
+            bulk("subcommands"),
+            RESPType::Map(spec.subcommands.iter().map(docs).collect()),
+        ));
+    }
+
+    (bulk(spec.name), RESPType::Map(doc))
+}
+
+fn get_keys(args: &[Bytes]) -> RESPType {
+    let spec = match resolve(args) {
+        Ok(spec) => spec,
+        Err(Error::WrongArity(_)) => {
+            return Error::command("Invalid number of arguments specified for command").into()
+        }
+        Err(_) => return Error::command("Invalid command specified").into(),
+    };
+
+    let keys = spec.key_positions(args);
+    if keys.is_empty() {
+        return Error::command("The command has no key arguments").into();
+    }
+    RESPType::Array(
+        keys.into_iter()
+            .map(|idx| RESPType::Bulk(args[idx].clone()))
+            .collect(),
+    )
+}
+
+fn bulk(s: &'static str) -> RESPType {
+    RESPType::Bulk(Bytes::from(s))
+}
+
+impl From<CommandInfo> for RESPType {
+    fn from(info: CommandInfo) -> RESPType {
+        let mut arr = vec![RESPType::Bulk(Bytes::from("command"))];
+
+        let (sub, args) = match info.kind {
+            CommandInfoKind::All => return RESPType::Array(arr),
+            CommandInfoKind::Count => ("COUNT", vec![]),
+            CommandInfoKind::List => ("LIST", vec![]),
+            CommandInfoKind::Info(names) => ("INFO", names),
+            CommandInfoKind::Docs(names) => ("DOCS", names),
+            CommandInfoKind::GetKeys(args) => ("GETKEYS", args),
+            CommandInfoKind::Help => ("HELP", vec![]),
+        };
+        arr.push(RESPType::Bulk(Bytes::from(sub)));
+        arr.extend(args.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,87 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, Error, RESPType};
+
+use super::select::select_db;
+
+/// COPY, duplicating a value together with its ttl.
+pub struct CopyKey {
+    source: Bytes,
+    destination: Bytes,
+    db: Option<i64>,
+    replace: bool,
+}
+
+impl CopyKey {
+    pub fn new(source: Bytes, destination: Bytes) -> Self {
+        CopyKey {
+            source,
+            destination,
+            db: None,
+            replace: false,
+        }
+    }
+
+    /// Overwrite an existing destination instead of leaving it alone.
+    pub fn replace(mut self, replace: bool) -> Self {
+        self.replace = replace;
+        self
+    }
+
+    /// Copy into database `db` instead of the selected one.
+    pub fn db(mut self, db: i64) -> Self {
+        self.db = Some(db);
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let target = match self.db.map(|index| select_db(db, index)).transpose() {
+            Err(e) => return e.into(),
+            Ok(target) => target.filter(|target| target.index() != db.index()),
+        };
+        if let Some(target) = target {
+            return db.update_across(&self.source, &target, &self.destination, |slot, dest| {
+                self.copy(slot, dest)
+            });
+        }
+        if self.source == self.destination {
+            return Error::command("source and destination objects are the same").into();
+        }
+
+        let mut locked = db.lock(&[&self.source, &self.destination]);
+        let entry = locked.update(&self.source, |slot| slot.clone());
+        locked.update(&self.destination, |slot| self.copy(&entry, slot))
+    }
+
+    fn copy(&self, source: &Option<Entry>, destination: &mut Option<Entry>) -> RESPType {
+        let entry = match source {
+            None => return RESPType::Integer(0),
+            Some(entry) => entry,
+        };
+        if destination.is_some() && !self.replace {
+            return RESPType::Integer(0);
+        }
+        *destination = Some(entry.clone());
+        RESPType::Integer(1)
+    }
+}
+
+impl From<CopyKey> for RESPType {
+    fn from(copy: CopyKey) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("copy")),
+            RESPType::Bulk(copy.source),
+            RESPType::Bulk(copy.destination),
+        ];
+        if let Some(db) = copy.db {
+            arr.push(RESPType::Bulk(Bytes::from("DB")));
+            arr.push(RESPType::Bulk(Bytes::from(db.to_string())));
+        }
+        if copy.replace {
+            arr.push(RESPType::Bulk(Bytes::from("REPLACE")));
+        }
+
+        RESPType::Array(arr)
+    }
+}
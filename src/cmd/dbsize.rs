@@ -0,0 +1,22 @@
+use bytes::Bytes;
+
+use crate::{Db, RESPType};
+
+#[derive(Default)]
+pub struct Dbsize;
+
+impl Dbsize {
+    pub fn new() -> Self {
+        Dbsize
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        RESPType::Integer(db.len() as i64)
+    }
+}
+
+impl From<Dbsize> for RESPType {
+    fn from(_: Dbsize) -> RESPType {
+        RESPType::Array(vec![RESPType::Bulk(Bytes::from("dbsize"))])
+    }
+}
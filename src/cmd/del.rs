@@ -0,0 +1,51 @@
+use bytes::Bytes;
+
+use crate::db::free_lazily;
+use crate::{Db, RESPType};
+
+/// DEL, or UNLINK which frees large values on a background thread.
+pub struct Del {
+    keys: Vec<Bytes>,
+    unlink: bool,
+}
+
+impl Del {
+    pub fn new(keys: Vec<Bytes>) -> Self {
+        Del {
+            keys,
+            unlink: false,
+        }
+    }
+
+    pub fn unlink(mut self, unlink: bool) -> Self {
+        self.unlink = unlink;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let mut locked = db.lock(&self.keys);
+        let removed: Vec<_> = self
+            .keys
+            .iter()
+            .filter_map(|key| locked.update(key, Option::take))
+            .map(|entry| entry.value)
+            .collect();
+        drop(locked);
+
+        let deleted = removed.len();
+        if self.unlink {
+            free_lazily(removed);
+        }
+        RESPType::Integer(deleted as i64)
+    }
+}
+
+impl From<Del> for RESPType {
+    fn from(del: Del) -> RESPType {
+        let name = if del.unlink { "unlink" } else { "del" };
+        let mut arr = vec![RESPType::Bulk(Bytes::from(name))];
+        arr.extend(del.keys.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
@@ -18,6 +18,7 @@ impl Echo {
     }
 }
 
+#[allow(clippy::from_over_into, clippy::unnecessary_unwrap)]
 impl Into<RESPType> for Echo {
     fn into(self) -> RESPType {
         let mut arr = vec![];
@@ -0,0 +1,43 @@
+use bytes::Bytes;
+
+use crate::{Db, RESPType};
+
+/// EXISTS, or TOUCH which has the same reply. A key given several times counts every time.
+pub struct Exists {
+    keys: Vec<Bytes>,
+    touch: bool,
+}
+
+impl Exists {
+    pub fn new(keys: Vec<Bytes>) -> Self {
+        Exists { keys, touch: false }
+    }
+
+    pub fn touch(mut self, touch: bool) -> Self {
+        self.touch = touch;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        // only TOUCH counts as an access to the keys
+        let existing = self
+            .keys
+            .iter()
+            .filter(|key| match self.touch {
+                true => db.update(key, |slot| slot.is_some()),
+                false => db.exists(key),
+            })
+            .count();
+        RESPType::Integer(existing as i64)
+    }
+}
+
+impl From<Exists> for RESPType {
+    fn from(exists: Exists) -> RESPType {
+        let name = if exists.touch { "touch" } else { "exists" };
+        let mut arr = vec![RESPType::Bulk(Bytes::from(name))];
+        arr.extend(exists.keys.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,115 @@
+use bytes::Bytes;
+
+use crate::db::now_ms;
+use crate::{Db, Error, RESPType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpireUnit {
+    Seconds,
+    Milliseconds,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpireCondition {
+    Nx,
+    Xx,
+    Gt,
+    Lt,
+}
+
+/// EXPIRE, PEXPIRE, EXPIREAT and PEXPIREAT.
+pub struct Expire {
+    key: Bytes,
+    time: i64,
+    unit: ExpireUnit,
+    absolute: bool,
+    condition: Option<ExpireCondition>,
+}
+
+impl Expire {
+    pub fn new(key: Bytes, time: i64, unit: ExpireUnit, absolute: bool) -> Self {
+        Expire {
+            key,
+            time,
+            unit,
+            absolute,
+            condition: None,
+        }
+    }
+
+    pub fn condition(mut self, condition: ExpireCondition) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    pub fn name(&self) -> &'static str {
+        match (self.unit, self.absolute) {
+            (ExpireUnit::Seconds, false) => "expire",
+            (ExpireUnit::Milliseconds, false) => "pexpire",
+            (ExpireUnit::Seconds, true) => "expireat",
+            (ExpireUnit::Milliseconds, true) => "pexpireat",
+        }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let now = now_ms() as i128;
+        let millis = match self.unit {
+            ExpireUnit::Seconds => self.time as i128 * 1000,
+            ExpireUnit::Milliseconds => self.time as i128,
+        };
+        let when = if self.absolute { millis } else { now + millis };
+        if when > i64::MAX as i128 || when < i64::MIN as i128 {
+            return Error::command(format!("invalid expire time in '{}' command", self.name()))
+                .into();
+        }
+
+        db.update(&self.key, |slot| {
+            let entry = match slot {
+                None => return RESPType::Integer(0),
+                Some(entry) => entry,
+            };
+
+            // a persistent key behaves as if its ttl were infinite
+            let allowed = match (self.condition, entry.expires_at) {
+                (None, _) => true,
+                (Some(ExpireCondition::Nx), current) => current.is_none(),
+                (Some(ExpireCondition::Xx), current) => current.is_some(),
+                (Some(ExpireCondition::Gt), None) => false,
+                (Some(ExpireCondition::Gt), Some(current)) => when > current as i128,
+                (Some(ExpireCondition::Lt), None) => true,
+                (Some(ExpireCondition::Lt), Some(current)) => when < current as i128,
+            };
+            if !allowed {
+                return RESPType::Integer(0);
+            }
+
+            if when <= now {
+                *slot = None;
+            } else {
+                entry.expires_at = Some(when as u64);
+            }
+            RESPType::Integer(1)
+        })
+    }
+}
+
+impl From<Expire> for RESPType {
+    fn from(expire: Expire) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from(expire.name())),
+            RESPType::Bulk(expire.key),
+            RESPType::Bulk(Bytes::from(expire.time.to_string())),
+        ];
+
+        let condition = match expire.condition {
+            None => return RESPType::Array(arr),
+            Some(ExpireCondition::Nx) => "NX",
+            Some(ExpireCondition::Xx) => "XX",
+            Some(ExpireCondition::Gt) => "GT",
+            Some(ExpireCondition::Lt) => "LT",
+        };
+        arr.push(RESPType::Bulk(Bytes::from(condition)));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,44 @@
+use bytes::Bytes;
+
+use crate::{Db, RESPType};
+
+/// FLUSHDB and FLUSHALL. ASYNC releases the memory on a background thread.
+pub struct Flush {
+    all: bool,
+    lazy: bool,
+}
+
+impl Flush {
+    /// `all` flushes every database, like FLUSHALL.
+    pub fn new(all: bool) -> Self {
+        Flush { all, lazy: false }
+    }
+
+    pub fn lazy(mut self, lazy: bool) -> Self {
+        self.lazy = lazy;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        if !self.all {
+            db.flush(self.lazy);
+            return RESPType::String("OK".into());
+        }
+        for index in 0..db.databases() {
+            let db = db.select(index).expect("index is below the count");
+            db.flush(self.lazy);
+        }
+        RESPType::String("OK".into())
+    }
+}
+
+impl From<Flush> for RESPType {
+    fn from(flush: Flush) -> RESPType {
+        let name = if flush.all { "flushall" } else { "flushdb" };
+        let mode = if flush.lazy { "ASYNC" } else { "SYNC" };
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from(name)),
+            RESPType::Bulk(Bytes::from(mode)),
+        ])
+    }
+}
@@ -0,0 +1,225 @@
+use std::f64::consts::FRAC_PI_2;
+
+/// Bits per coordinate, both together give the 52 bit scores redis stores.
+const GEO_STEP_MAX: u32 = 26;
+
+// the latitude limits of web mercator, which is what redis accepts
+const GEO_LAT_MIN: f64 = -85.051_128_78;
+const GEO_LAT_MAX: f64 = 85.051_128_78;
+const GEO_LONG_MIN: f64 = -180.0;
+const GEO_LONG_MAX: f64 = 180.0;
+
+const EARTH_RADIUS_IN_METERS: f64 = 6_372_797.560_856;
+
+const GEOALPHABET: &[u8] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// Unit distances are given and returned in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeoUnit {
+    Meters,
+    Kilometers,
+    Feet,
+    Miles,
+}
+
+impl GeoUnit {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GeoUnit::Meters => "m",
+            GeoUnit::Kilometers => "km",
+            GeoUnit::Feet => "ft",
+            GeoUnit::Miles => "mi",
+        }
+    }
+
+    pub(crate) fn to_meters(self) -> f64 {
+        match self {
+            GeoUnit::Meters => 1.0,
+            GeoUnit::Kilometers => 1000.0,
+            GeoUnit::Feet => 0.3048,
+            GeoUnit::Miles => 1609.34,
+        }
+    }
+}
+
+/// Whether redis accepts the coordinates, latitudes near the poles can't be indexed.
+pub(crate) fn valid(longitude: f64, latitude: f64) -> bool {
+    (GEO_LONG_MIN..=GEO_LONG_MAX).contains(&longitude)
+        && (GEO_LAT_MIN..=GEO_LAT_MAX).contains(&latitude)
+}
+
+/// The score a member at the coordinates is stored with, `valid` must hold for them.
+pub(crate) fn encode(longitude: f64, latitude: f64) -> f64 {
+    let (lat, long) = cell(longitude, latitude, GEO_LAT_MIN, GEO_LAT_MAX, GEO_STEP_MAX);
+    interleave(lat, long) as f64
+}
+
+/// The coordinates at the center of the area a score describes.
+pub(crate) fn decode(score: f64) -> (f64, f64) {
+    let bits = score as u64;
+    let (lat, long) = (squash(bits), squash(bits >> 1));
+    let cells = (1u64 << GEO_STEP_MAX) as f64;
+    let lat_scale = GEO_LAT_MAX - GEO_LAT_MIN;
+    let long_scale = GEO_LONG_MAX - GEO_LONG_MIN;
+
+    let lat_min = GEO_LAT_MIN + (lat as f64 / cells) * lat_scale;
+    let lat_max = GEO_LAT_MIN + ((lat + 1) as f64 / cells) * lat_scale;
+    let long_min = GEO_LONG_MIN + (long as f64 / cells) * long_scale;
+    let long_max = GEO_LONG_MIN + ((long + 1) as f64 / cells) * long_scale;
+    (
+        ((long_min + long_max) / 2.0).clamp(GEO_LONG_MIN, GEO_LONG_MAX),
+        ((lat_min + lat_max) / 2.0).clamp(GEO_LAT_MIN, GEO_LAT_MAX),
+    )
+}
+
+/// The standard 11 character geohash of a score. Scores use the mercator latitude limits,
+/// so the position is encoded again against the full -90 to 90 range.
+pub(crate) fn geohash(score: f64) -> String {
+    let (longitude, latitude) = decode(score);
+    let (lat, long) = cell(longitude, latitude, -90.0, 90.0, GEO_STEP_MAX);
+    let bits = interleave(lat, long);
+    (0..11)
+        .map(|i| match i {
+            // 52 bits only make 10 characters, the last one is always 0 like in redis
+            10 => GEOALPHABET[0] as char,
+            i => GEOALPHABET[((bits >> (52 - (i + 1) * 5)) & 0x1f) as usize] as char,
+        })
+        .collect()
+}
+
+/// Great circle distance in meters with the haversine formula.
+pub(crate) fn distance(long1: f64, lat1: f64, long2: f64, lat2: f64) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let u = ((lat2 - lat1) / 2.0).sin();
+    let v = ((long2.to_radians() - long1.to_radians()) / 2.0).sin();
+    2.0 * EARTH_RADIUS_IN_METERS * (u * u + lat1.cos() * lat2.cos() * v * v).sqrt().asin()
+}
+
+/// Distance in meters between two latitudes along a meridian.
+pub(crate) fn lat_distance(lat1: f64, lat2: f64) -> f64 {
+    EARTH_RADIUS_IN_METERS * (lat2.to_radians() - lat1.to_radians()).abs()
+}
+
+/// The score ranges `[min, max)` of the areas covering every point whose distance from the
+/// center is within `width` meters along its parallel and `height` meters along the meridian.
+///
+/// Like redis this picks the finest precision at which at most 3 areas per axis cover the
+/// bounding box, so a search only walks the few sorted set ranges around the center.
+pub(crate) fn search_ranges(
+    longitude: f64,
+    latitude: f64,
+    width: f64,
+    height: f64,
+) -> Vec<(f64, f64)> {
+    let lat_delta = (height / EARTH_RADIUS_IN_METERS).to_degrees();
+    // the parallel furthest from the equator is where a distance spans the most longitude
+    let far_lat = (latitude.abs() + lat_delta).min(90.0).to_radians();
+    let half_angle = width / (2.0 * EARTH_RADIUS_IN_METERS);
+    let spread = half_angle.sin() / far_lat.cos();
+    let long_delta = match half_angle < FRAC_PI_2 && spread < 1.0 {
+        true => (2.0 * spread.asin()).to_degrees(),
+        false => 180.0,
+    };
+
+    let lat_lo = (latitude - lat_delta).max(GEO_LAT_MIN);
+    let lat_hi = (latitude + lat_delta).min(GEO_LAT_MAX);
+    for step in (0..=GEO_STEP_MAX).rev() {
+        let cells = 1i64 << step;
+        let lat_size = (GEO_LAT_MAX - GEO_LAT_MIN) / cells as f64;
+        let long_size = (GEO_LONG_MAX - GEO_LONG_MIN) / cells as f64;
+        let lat_cells = (
+            ((lat_lo - GEO_LAT_MIN) / lat_size).floor() as i64,
+            (((lat_hi - GEO_LAT_MIN) / lat_size).floor() as i64).min(cells - 1),
+        );
+        let long_cells = match long_delta >= 180.0 {
+            true => (0, cells - 1),
+            false => (
+                ((longitude - long_delta - GEO_LONG_MIN) / long_size).floor() as i64,
+                ((longitude + long_delta - GEO_LONG_MIN) / long_size).floor() as i64,
+            ),
+        };
+        if step > 0 && (lat_cells.1 - lat_cells.0 >= 3 || long_cells.1 - long_cells.0 >= 3) {
+            continue;
+        }
+
+        // areas past the antimeridian wrap around to the other side
+        let mut areas: Vec<_> = (lat_cells.0..=lat_cells.1)
+            .flat_map(|lat| {
+                (long_cells.0..=long_cells.1).map(move |long| (lat, long.rem_euclid(cells)))
+            })
+            .collect();
+        areas.sort_unstable();
+        areas.dedup();
+
+        let shift = 2 * (GEO_STEP_MAX - step);
+        return areas
+            .into_iter()
+            .map(|(lat, long)| {
+                let bits = interleave(lat as u32, long as u32);
+                ((bits << shift) as f64, ((bits + 1) << shift) as f64)
+            })
+            .collect();
+    }
+    unreachable!("a single area covers the whole world")
+}
+
+/// Latitude and longitude offsets of the area holding the coordinates, at `step` bits each.
+fn cell(longitude: f64, latitude: f64, lat_min: f64, lat_max: f64, step: u32) -> (u32, u32) {
+    let cells = (1u64 << step) as f64;
+    let lat = (latitude - lat_min) / (lat_max - lat_min) * cells;
+    let long = (longitude - GEO_LONG_MIN) / (GEO_LONG_MAX - GEO_LONG_MIN) * cells;
+    (lat as u32, long as u32)
+}
+
+/// Latitude bits go to the even positions and longitude bits to the odd ones.
+fn interleave(lat: u32, long: u32) -> u64 {
+    spread(lat) | (spread(long) << 1)
+}
+
+fn spread(value: u32) -> u64 {
+    (0..32).fold(0, |bits, i| bits | (((value as u64 >> i) & 1) << (2 * i)))
+}
+
+fn squash(bits: u64) -> u64 {
+    (0..32).fold(0, |value, i| value | (((bits >> (2 * i)) & 1) << i))
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoding() {
+        // scores and hashes as redis computes them
+        let palermo = encode(13.361389, 38.115556);
+        assert_eq!(palermo, 3479099956230698.0);
+        assert_eq!(geohash(palermo), "sqc8b49rny0");
+        let (longitude, latitude) = decode(palermo);
+        assert!((longitude - 13.361389).abs() < 1e-5);
+        assert!((latitude - 38.115556).abs() < 1e-5);
+
+        let catania = encode(15.087269, 37.502669);
+        assert_eq!(catania, 3479447370796909.0);
+        assert_eq!(geohash(catania), "sqdtr74hyu0");
+        let (long2, lat2) = decode(catania);
+        let meters = distance(longitude, latitude, long2, lat2);
+        assert_eq!(format!("{meters:.4}"), "166274.1516");
+    }
+
+    #[test]
+    fn test_search_ranges() {
+        let (longitude, latitude) = (13.361389, 38.115556);
+        let score = encode(longitude, latitude);
+        for meters in [1.0, 100.0, 10_000.0, 1_000_000.0, 30_000_000.0] {
+            let ranges = search_ranges(longitude, latitude, meters, meters);
+            assert!(ranges.len() <= 9);
+            assert!(ranges.iter().any(|&(min, max)| min <= score && score < max));
+        }
+
+        // a box across the antimeridian includes areas on both sides
+        let east = encode(179.99, 0.0);
+        let ranges = search_ranges(-179.99, 0.0, 5000.0, 5000.0);
+        assert!(ranges.iter().any(|&(min, max)| min <= east && east < max));
+    }
+}
@@ -0,0 +1,81 @@
+use bytes::Bytes;
+
+use crate::{Db, Error, RESPType};
+
+use super::geo;
+use super::{SetCondition, Zadd};
+
+/// GEOADD, a ZADD of members scored with the geohash of their coordinates.
+pub struct Geoadd {
+    key: Bytes,
+    members: Vec<(f64, f64, Bytes)>,
+    condition: Option<SetCondition>,
+    changed: bool,
+}
+
+impl Geoadd {
+    /// `members` are longitude, latitude and name triples.
+    pub fn new(key: Bytes, members: Vec<(f64, f64, Bytes)>) -> Self {
+        Geoadd {
+            key,
+            members,
+            condition: None,
+            changed: false,
+        }
+    }
+
+    /// Only add new members with NX, or only update existing ones with XX.
+    pub fn condition(mut self, condition: SetCondition) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    /// Count updated members in the reply as well as added ones.
+    pub fn changed(mut self, changed: bool) -> Self {
+        self.changed = changed;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let mut members = Vec::with_capacity(self.members.len());
+        for (longitude, latitude, member) in &self.members {
+            if !geo::valid(*longitude, *latitude) {
+                return Error::command(format!(
+                    "invalid longitude,latitude pair {longitude:.6},{latitude:.6}"
+                ))
+                .into();
+            }
+            members.push((geo::encode(*longitude, *latitude), member.clone()));
+        }
+
+        let mut zadd = Zadd::new(self.key.clone(), members).changed(self.changed);
+        if let Some(condition) = self.condition {
+            zadd = zadd.condition(condition);
+        }
+        zadd.response(db)
+    }
+}
+
+impl From<Geoadd> for RESPType {
+    fn from(geoadd: Geoadd) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("geoadd")),
+            RESPType::Bulk(geoadd.key),
+        ];
+        match geoadd.condition {
+            None => {}
+            Some(SetCondition::Nx) => arr.push(RESPType::Bulk(Bytes::from("NX"))),
+            Some(SetCondition::Xx) => arr.push(RESPType::Bulk(Bytes::from("XX"))),
+        }
+        if geoadd.changed {
+            arr.push(RESPType::Bulk(Bytes::from("CH")));
+        }
+        for (longitude, latitude, member) in geoadd.members {
+            arr.push(RESPType::Bulk(Bytes::from(longitude.to_string())));
+            arr.push(RESPType::Bulk(Bytes::from(latitude.to_string())));
+            arr.push(RESPType::Bulk(member));
+        }
+
+        RESPType::Array(arr)
+    }
+}
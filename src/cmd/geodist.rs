@@ -0,0 +1,66 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+use super::geo::{self, GeoUnit};
+
+/// GEODIST, the distance between two members or null if either is missing.
+pub struct Geodist {
+    key: Bytes,
+    member1: Bytes,
+    member2: Bytes,
+    unit: GeoUnit,
+}
+
+impl Geodist {
+    pub fn new(key: Bytes, member1: Bytes, member2: Bytes) -> Self {
+        Geodist {
+            key,
+            member1,
+            member2,
+            unit: GeoUnit::Meters,
+        }
+    }
+
+    pub fn unit(mut self, unit: GeoUnit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let zset = match slot.as_ref().map(Entry::zset) {
+                None => return RESPType::Null,
+                Some(Err(e)) => return e.into(),
+                Some(Ok(zset)) => zset,
+            };
+            match (zset.score(&self.member1), zset.score(&self.member2)) {
+                (Some(score1), Some(score2)) => {
+                    let (long1, lat1) = geo::decode(score1);
+                    let (long2, lat2) = geo::decode(score2);
+                    let meters = geo::distance(long1, lat1, long2, lat2);
+                    distance_bulk(meters / self.unit.to_meters())
+                }
+                _ => RESPType::Null,
+            }
+        })
+    }
+}
+
+/// Distances are always replied as strings with 4 decimals, even to RESP3 clients.
+pub(crate) fn distance_bulk(distance: f64) -> RESPType {
+    RESPType::Bulk(Bytes::from(format!("{distance:.4}")))
+}
+
+impl From<Geodist> for RESPType {
+    fn from(geodist: Geodist) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("geodist")),
+            RESPType::Bulk(geodist.key),
+            RESPType::Bulk(geodist.member1),
+            RESPType::Bulk(geodist.member2),
+            RESPType::Bulk(Bytes::from(geodist.unit.name())),
+        ])
+    }
+}
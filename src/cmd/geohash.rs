@@ -0,0 +1,48 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+use super::geo;
+
+/// GEOHASH, the standard geohash string of each member, null for missing ones.
+pub struct Geohash {
+    key: Bytes,
+    members: Vec<Bytes>,
+}
+
+impl Geohash {
+    pub fn new(key: Bytes, members: Vec<Bytes>) -> Self {
+        Geohash { key, members }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let zset = match slot.as_ref().map(Entry::zset).transpose() {
+                Err(e) => return e.into(),
+                Ok(zset) => zset,
+            };
+            let hashes = self
+                .members
+                .iter()
+                .map(|member| match zset.and_then(|zset| zset.score(member)) {
+                    None => RESPType::Null,
+                    Some(score) => RESPType::Bulk(Bytes::from(geo::geohash(score))),
+                })
+                .collect();
+            RESPType::Array(hashes)
+        })
+    }
+}
+
+impl From<Geohash> for RESPType {
+    fn from(geohash: Geohash) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("geohash")),
+            RESPType::Bulk(geohash.key),
+        ];
+        arr.extend(geohash.members.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,57 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+use super::geo;
+
+/// GEOPOS, the longitude and latitude of each member, null for missing ones.
+pub struct Geopos {
+    key: Bytes,
+    members: Vec<Bytes>,
+}
+
+impl Geopos {
+    pub fn new(key: Bytes, members: Vec<Bytes>) -> Self {
+        Geopos { key, members }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let zset = match slot.as_ref().map(Entry::zset).transpose() {
+                Err(e) => return e.into(),
+                Ok(zset) => zset,
+            };
+            let positions = self
+                .members
+                .iter()
+                .map(|member| match zset.and_then(|zset| zset.score(member)) {
+                    None => RESPType::NullArray,
+                    Some(score) => position(score),
+                })
+                .collect();
+            RESPType::Array(positions)
+        })
+    }
+}
+
+/// The coordinates a score decodes to, as a longitude and latitude pair.
+pub(crate) fn position(score: f64) -> RESPType {
+    let (longitude, latitude) = geo::decode(score);
+    RESPType::Array(vec![
+        RESPType::Double(longitude),
+        RESPType::Double(latitude),
+    ])
+}
+
+impl From<Geopos> for RESPType {
+    fn from(geopos: Geopos) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("geopos")),
+            RESPType::Bulk(geopos.key),
+        ];
+        arr.extend(geopos.members.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
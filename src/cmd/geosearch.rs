@@ -0,0 +1,281 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, ScoreBound, Value, ZSet};
+use crate::{Db, Error, RESPType};
+
+use super::geo::{self, GeoUnit};
+use super::geodist::distance_bulk;
+use super::geopos::position;
+
+/// Center of a GEOSEARCH, an existing member or explicit longitude and latitude.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeoOrigin {
+    Member(Bytes),
+    Coordinates(f64, f64),
+}
+
+/// Area a GEOSEARCH matches, a radius or a width and height box around the center.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeoShape {
+    Radius(f64, GeoUnit),
+    Box(f64, f64, GeoUnit),
+}
+
+impl GeoShape {
+    fn unit(&self) -> GeoUnit {
+        match *self {
+            GeoShape::Radius(_, unit) | GeoShape::Box(_, _, unit) => unit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeoSort {
+    Asc,
+    Desc,
+}
+
+/// GEOSEARCH, or GEOSEARCHSTORE when given a destination.
+pub struct Geosearch {
+    key: Bytes,
+    origin: GeoOrigin,
+    shape: GeoShape,
+    sort: Option<GeoSort>,
+    count: Option<(usize, bool)>,
+    with_coord: bool,
+    with_dist: bool,
+    with_hash: bool,
+    destination: Option<Bytes>,
+    store_dist: bool,
+}
+
+impl Geosearch {
+    pub fn new(key: Bytes, origin: GeoOrigin, shape: GeoShape) -> Self {
+        Geosearch {
+            key,
+            origin,
+            shape,
+            sort: None,
+            count: None,
+            with_coord: false,
+            with_dist: false,
+            with_hash: false,
+            destination: None,
+            store_dist: false,
+        }
+    }
+
+    /// Order the matches by distance from the center, they come in index order otherwise.
+    pub fn sort(mut self, sort: GeoSort) -> Self {
+        self.sort = Some(sort);
+        self
+    }
+
+    /// Return at most `count` matches, the closest ones unless `any` allows stopping at
+    /// the first `count` found.
+    pub fn count(mut self, count: usize, any: bool) -> Self {
+        self.count = Some((count, any));
+        self
+    }
+
+    pub fn with_coord(mut self, with_coord: bool) -> Self {
+        self.with_coord = with_coord;
+        self
+    }
+
+    pub fn with_dist(mut self, with_dist: bool) -> Self {
+        self.with_dist = with_dist;
+        self
+    }
+
+    pub fn with_hash(mut self, with_hash: bool) -> Self {
+        self.with_hash = with_hash;
+        self
+    }
+
+    /// Store the matches at `destination`, replacing whatever is there, instead of replying
+    /// with them. They keep their geohash scores unless `store_dist` scores them by distance.
+    pub fn store(mut self, destination: Bytes, store_dist: bool) -> Self {
+        self.destination = Some(destination);
+        self.store_dist = store_dist;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let mut keys = vec![&self.key];
+        keys.extend(&self.destination);
+        let mut locked = db.lock(&keys);
+
+        let found = locked.update(&self.key, |slot| {
+            slot.as_ref()
+                .map(|entry| entry.zset().and_then(|zset| self.search(zset)))
+                .transpose()
+        });
+        let found = match found {
+            Err(e) => return e.into(),
+            Ok(found) => found.unwrap_or_default(),
+        };
+
+        match &self.destination {
+            None => {
+                let plain = !(self.with_coord || self.with_dist || self.with_hash);
+                let arr = found
+                    .into_iter()
+                    .map(|(member, score, distance)| {
+                        if plain {
+                            return RESPType::Bulk(member);
+                        }
+                        let mut item = vec![RESPType::Bulk(member)];
+                        if self.with_dist {
+                            item.push(distance_bulk(distance));
+                        }
+                        if self.with_hash {
+                            item.push(RESPType::Integer(score as i64));
+                        }
+                        if self.with_coord {
+                            item.push(position(score));
+                        }
+                        RESPType::Array(item)
+                    })
+                    .collect();
+                RESPType::Array(arr)
+            }
+            Some(destination) => {
+                let len = found.len();
+                let mut zset = ZSet::default();
+                for (member, score, distance) in found {
+                    zset.insert(member, if self.store_dist { distance } else { score });
+                }
+                locked.update(destination, |slot| {
+                    *slot = Some(Entry::new(Value::ZSet(zset)));
+                });
+                RESPType::Integer(len as i64)
+            }
+        }
+    }
+
+    /// Members within the shape with their score and distance from the center in the
+    /// shape's unit.
+    fn search(&self, zset: &ZSet) -> crate::Result<Vec<(Bytes, f64, f64)>> {
+        let (longitude, latitude) = match &self.origin {
+            GeoOrigin::Coordinates(longitude, latitude) => (*longitude, *latitude),
+            GeoOrigin::Member(member) => match zset.score(member) {
+                None => return Err(Error::command("could not decode requested zset member")),
+                Some(score) => geo::decode(score),
+            },
+        };
+        let meters = self.shape.unit().to_meters();
+        // how far a match may be along its parallel and along the meridian
+        let (width, height) = match self.shape {
+            GeoShape::Radius(radius, _) => (radius * meters, radius * meters),
+            GeoShape::Box(width, height, _) => (width * meters / 2.0, height * meters / 2.0),
+        };
+
+        let limit = match self.count {
+            Some((count, true)) => count,
+            _ => usize::MAX,
+        };
+        let mut found = vec![];
+        'areas: for (min, max) in geo::search_ranges(longitude, latitude, width, height) {
+            let ranks = zset.score_ranks(&ScoreBound::Inclusive(min), &ScoreBound::Exclusive(max));
+            for (member, score) in zset.range(ranks, false, 0, None) {
+                let (long, lat) = geo::decode(score);
+                let distance = geo::distance(longitude, latitude, long, lat);
+                let within = match self.shape {
+                    GeoShape::Radius(..) => distance <= width,
+                    GeoShape::Box(..) => {
+                        geo::lat_distance(lat, latitude) <= height
+                            && geo::distance(long, lat, longitude, lat) <= width
+                    }
+                };
+                if !within {
+                    continue;
+                }
+
+                found.push((member, score, distance / meters));
+                if found.len() == limit {
+                    break 'areas;
+                }
+            }
+        }
+
+        // a count without ANY wants the closest matches
+        let sort = match (self.sort, self.count) {
+            (None, Some((_, false))) => Some(GeoSort::Asc),
+            (sort, _) => sort,
+        };
+        match sort {
+            None => {}
+            Some(GeoSort::Asc) => found.sort_by(|a, b| a.2.total_cmp(&b.2)),
+            Some(GeoSort::Desc) => found.sort_by(|a, b| b.2.total_cmp(&a.2)),
+        }
+        if let Some((count, _)) = self.count {
+            found.truncate(count);
+        }
+        Ok(found)
+    }
+}
+
+impl From<Geosearch> for RESPType {
+    fn from(geosearch: Geosearch) -> RESPType {
+        let bulk = |value: String| RESPType::Bulk(Bytes::from(value));
+        let mut arr = vec![];
+        match geosearch.destination {
+            None => arr.push(RESPType::Bulk(Bytes::from("geosearch"))),
+            Some(destination) => {
+                arr.push(RESPType::Bulk(Bytes::from("geosearchstore")));
+                arr.push(RESPType::Bulk(destination));
+            }
+        }
+        arr.push(RESPType::Bulk(geosearch.key));
+
+        match geosearch.origin {
+            GeoOrigin::Member(member) => {
+                arr.push(RESPType::Bulk(Bytes::from("FROMMEMBER")));
+                arr.push(RESPType::Bulk(member));
+            }
+            GeoOrigin::Coordinates(longitude, latitude) => {
+                arr.push(RESPType::Bulk(Bytes::from("FROMLONLAT")));
+                arr.push(bulk(longitude.to_string()));
+                arr.push(bulk(latitude.to_string()));
+            }
+        }
+        match geosearch.shape {
+            GeoShape::Radius(radius, unit) => {
+                arr.push(RESPType::Bulk(Bytes::from("BYRADIUS")));
+                arr.push(bulk(radius.to_string()));
+                arr.push(RESPType::Bulk(Bytes::from(unit.name())));
+            }
+            GeoShape::Box(width, height, unit) => {
+                arr.push(RESPType::Bulk(Bytes::from("BYBOX")));
+                arr.push(bulk(width.to_string()));
+                arr.push(bulk(height.to_string()));
+                arr.push(RESPType::Bulk(Bytes::from(unit.name())));
+            }
+        }
+        match geosearch.sort {
+            None => {}
+            Some(GeoSort::Asc) => arr.push(RESPType::Bulk(Bytes::from("ASC"))),
+            Some(GeoSort::Desc) => arr.push(RESPType::Bulk(Bytes::from("DESC"))),
+        }
+        if let Some((count, any)) = geosearch.count {
+            arr.push(RESPType::Bulk(Bytes::from("COUNT")));
+            arr.push(bulk(count.to_string()));
+            if any {
+                arr.push(RESPType::Bulk(Bytes::from("ANY")));
+            }
+        }
+        for (set, name) in [
+            (geosearch.with_coord, "WITHCOORD"),
+            (geosearch.with_dist, "WITHDIST"),
+            (geosearch.with_hash, "WITHHASH"),
+            (geosearch.store_dist, "STOREDIST"),
+        ] {
+            if set {
+                arr.push(RESPType::Bulk(Bytes::from(name)));
+            }
+        }
+
+        RESPType::Array(arr)
+    }
+}
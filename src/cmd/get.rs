@@ -0,0 +1,30 @@
+use bytes::Bytes;
+
+use crate::{Db, RESPType};
+
+pub struct Get {
+    key: Bytes,
+}
+
+impl Get {
+    pub fn new(key: Bytes) -> Self {
+        Get { key }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        match db.get(&self.key) {
+            Err(e) => e.into(),
+            Ok(None) => RESPType::Null,
+            Ok(Some(val)) => RESPType::Bulk(val),
+        }
+    }
+}
+
+impl From<Get> for RESPType {
+    fn from(get: Get) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("get")),
+            RESPType::Bulk(get.key),
+        ])
+    }
+}
@@ -0,0 +1,46 @@
+use bytes::Bytes;
+
+use crate::{Db, RESPType};
+
+pub struct Getbit {
+    key: Bytes,
+    offset: u64,
+}
+
+impl Getbit {
+    pub fn new(key: Bytes, offset: u64) -> Self {
+        Getbit { key, offset }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        match db.get(&self.key) {
+            Err(e) => e.into(),
+            Ok(value) => {
+                let bit = get_bit(value.as_deref().unwrap_or_default(), self.offset);
+                RESPType::Integer(bit as i64)
+            }
+        }
+    }
+}
+
+/// The bit at `offset`, counting from the most significant bit of the first byte. Bits
+/// past the end of the string are 0.
+pub(crate) fn get_bit(value: &[u8], offset: u64) -> bool {
+    match usize::try_from(offset / 8)
+        .ok()
+        .and_then(|byte| value.get(byte))
+    {
+        None => false,
+        Some(byte) => byte & (0x80 >> (offset % 8)) != 0,
+    }
+}
+
+impl From<Getbit> for RESPType {
+    fn from(getbit: Getbit) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("getbit")),
+            RESPType::Bulk(getbit.key),
+            RESPType::Bulk(Bytes::from(getbit.offset.to_string())),
+        ])
+    }
+}
@@ -0,0 +1,35 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct GetDel {
+    key: Bytes,
+}
+
+impl GetDel {
+    pub fn new(key: Bytes) -> Self {
+        GetDel { key }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let value = match slot.as_ref().map(Entry::string) {
+                None => return RESPType::Null,
+                Some(Err(e)) => return e.into(),
+                Some(Ok(value)) => value.clone(),
+            };
+            *slot = None;
+            RESPType::Bulk(value)
+        })
+    }
+}
+
+impl From<GetDel> for RESPType {
+    fn from(getdel: GetDel) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("getdel")),
+            RESPType::Bulk(getdel.key),
+        ])
+    }
+}
@@ -0,0 +1,91 @@
+use bytes::Bytes;
+
+use crate::db::{now_ms, Entry};
+use crate::{Db, RESPType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GetExExpiry {
+    Ex(u64),
+    Px(u64),
+    ExAt(u64),
+    PxAt(u64),
+    Persist,
+}
+
+pub struct GetEx {
+    key: Bytes,
+    expiry: Option<GetExExpiry>,
+}
+
+impl GetEx {
+    pub fn new(key: Bytes) -> Self {
+        GetEx { key, expiry: None }
+    }
+
+    pub fn expiry(mut self, expiry: GetExExpiry) -> Self {
+        self.expiry = Some(expiry);
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let now = now_ms();
+        let expires_at = match self.expiry {
+            None => return get(db, &self.key),
+            Some(GetExExpiry::Persist) => None,
+            Some(GetExExpiry::Ex(secs)) => Some(now.saturating_add(secs.saturating_mul(1000))),
+            Some(GetExExpiry::Px(ms)) => Some(now.saturating_add(ms)),
+            Some(GetExExpiry::ExAt(secs)) => Some(secs.saturating_mul(1000)),
+            Some(GetExExpiry::PxAt(ms)) => Some(ms),
+        };
+
+        db.update(&self.key, |slot| {
+            let value = match slot.as_ref().map(Entry::string) {
+                None => return RESPType::Null,
+                Some(Err(e)) => return e.into(),
+                Some(Ok(value)) => value.clone(),
+            };
+            match expires_at {
+                // a deadline in the past deletes the key right away
+                Some(when) if when <= now => *slot = None,
+                _ => {
+                    if let Some(entry) = slot {
+                        entry.expires_at = expires_at;
+                    }
+                }
+            }
+            RESPType::Bulk(value)
+        })
+    }
+}
+
+fn get(db: &Db, key: &[u8]) -> RESPType {
+    match db.get(key) {
+        Err(e) => e.into(),
+        Ok(None) => RESPType::Null,
+        Ok(Some(val)) => RESPType::Bulk(val),
+    }
+}
+
+impl From<GetEx> for RESPType {
+    fn from(getex: GetEx) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("getex")),
+            RESPType::Bulk(getex.key),
+        ];
+
+        let (name, time) = match getex.expiry {
+            None => return RESPType::Array(arr),
+            Some(GetExExpiry::Persist) => ("PERSIST", None),
+            Some(GetExExpiry::Ex(t)) => ("EX", Some(t)),
+            Some(GetExExpiry::Px(t)) => ("PX", Some(t)),
+            Some(GetExExpiry::ExAt(t)) => ("EXAT", Some(t)),
+            Some(GetExExpiry::PxAt(t)) => ("PXAT", Some(t)),
+        };
+        arr.push(RESPType::Bulk(Bytes::from(name)));
+        if let Some(time) = time {
+            arr.push(RESPType::Bulk(Bytes::from(time.to_string())));
+        }
+
+        RESPType::Array(arr)
+    }
+}
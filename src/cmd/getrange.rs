@@ -0,0 +1,53 @@
+use bytes::Bytes;
+
+use crate::{Db, RESPType};
+
+pub struct GetRange {
+    key: Bytes,
+    start: i64,
+    end: i64,
+}
+
+impl GetRange {
+    pub fn new(key: Bytes, start: i64, end: i64) -> Self {
+        GetRange { key, start, end }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let value = match db.get(&self.key) {
+            Err(e) => return e.into(),
+            Ok(None) => return RESPType::Bulk(Bytes::new()),
+            Ok(Some(value)) => value,
+        };
+
+        let len = value.len() as i64;
+        let (mut start, mut end) = (self.start, self.end);
+        if start < 0 && end < 0 && start > end {
+            return RESPType::Bulk(Bytes::new());
+        }
+        // negative offsets count back from the end of the string
+        if start < 0 {
+            start = (len + start).max(0);
+        }
+        if end < 0 {
+            end = (len + end).max(0);
+        }
+        end = end.min(len - 1);
+        if start > end || len == 0 {
+            return RESPType::Bulk(Bytes::new());
+        }
+
+        RESPType::Bulk(value.slice(start as usize..=end as usize))
+    }
+}
+
+impl From<GetRange> for RESPType {
+    fn from(getrange: GetRange) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("getrange")),
+            RESPType::Bulk(getrange.key),
+            RESPType::Bulk(Bytes::from(getrange.start.to_string())),
+            RESPType::Bulk(Bytes::from(getrange.end.to_string())),
+        ])
+    }
+}
@@ -0,0 +1,134 @@
+/// Matches `string` against a glob style `pattern` the way redis does for KEYS and the MATCH
+/// option of the scan commands: `*`, `?`, `[...]` classes with `^` and ranges, and `\` to
+/// escape a special character.
+pub(crate) fn glob_match(pattern: &[u8], string: &[u8], nocase: bool) -> bool {
+    let eq = |a: u8, b: u8| a == b || nocase && a.eq_ignore_ascii_case(&b);
+    let (mut p, mut s) = (0, 0);
+    // where to resume after the last star when the rest fails to match
+    let mut backtrack = None;
+
+    while s < string.len() {
+        if p < pattern.len() {
+            match pattern[p] {
+                b'*' => {
+                    while p < pattern.len() && pattern[p] == b'*' {
+                        p += 1;
+                    }
+                    if p == pattern.len() {
+                        return true;
+                    }
+                    backtrack = Some((p, s));
+                    continue;
+                }
+                b'?' => {
+                    p += 1;
+                    s += 1;
+                    continue;
+                }
+                b'[' => {
+                    let (matched, next) = class(pattern, p + 1, string[s], nocase);
+                    if matched {
+                        p = next;
+                        s += 1;
+                        continue;
+                    }
+                }
+                b'\\' if p + 1 < pattern.len() => {
+                    if eq(pattern[p + 1], string[s]) {
+                        p += 2;
+                        s += 1;
+                        continue;
+                    }
+                }
+                c => {
+                    if eq(c, string[s]) {
+                        p += 1;
+                        s += 1;
+                        continue;
+                    }
+                }
+            }
+        }
+
+        // let the last star swallow one more character and try again
+        match backtrack {
+            None => return false,
+            Some((star_p, star_s)) => {
+                p = star_p;
+                s = star_s + 1;
+                backtrack = Some((star_p, s));
+            }
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == b'*')
+}
+
+/// Matches `c` against the class starting at `p`, right after its `[`. Returns whether it
+/// matched and the position following the closing `]`.
+fn class(pattern: &[u8], mut p: usize, c: u8, nocase: bool) -> (bool, usize) {
+    let fold = |c: u8| if nocase { c.to_ascii_lowercase() } else { c };
+    let negate = pattern.get(p) == Some(&b'^');
+    if negate {
+        p += 1;
+    }
+
+    let mut matched = false;
+    // an unterminated class extends to the end of the pattern
+    while p < pattern.len() && pattern[p] != b']' {
+        if pattern[p] == b'\\' && p + 1 < pattern.len() {
+            p += 1;
+            matched |= pattern[p] == c;
+        } else if p + 2 < pattern.len() && pattern[p + 1] == b'-' {
+            let (mut start, mut end) = (fold(pattern[p]), fold(pattern[p + 2]));
+            if start > end {
+                std::mem::swap(&mut start, &mut end);
+            }
+            matched |= (start..=end).contains(&fold(c));
+            p += 2;
+        } else {
+            matched |= fold(pattern[p]) == fold(c);
+        }
+        p += 1;
+    }
+
+    (matched != negate, (p + 1).min(pattern.len()))
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, string: &str) -> bool {
+        glob_match(pattern.as_bytes(), string.as_bytes(), false)
+    }
+
+    #[test]
+    fn test_wildcards() {
+        assert!(matches("*", ""));
+        assert!(matches("*", "anything"));
+        assert!(matches("user:*", "user:42"));
+        assert!(!matches("user:*", "users"));
+        assert!(matches("h?llo", "hello"));
+        assert!(!matches("h?llo", "hllo"));
+        assert!(matches("*a*b*c", "xxaxxbxxc"));
+        assert!(!matches("*a*b*c", "xxaxxbxxcx"));
+        assert!(matches("a**", "a"));
+    }
+
+    #[test]
+    fn test_classes_and_escapes() {
+        assert!(matches("h[ae]llo", "hallo"));
+        assert!(!matches("h[ae]llo", "hillo"));
+        assert!(matches("h[^e]llo", "hallo"));
+        assert!(!matches("h[^e]llo", "hello"));
+        assert!(matches("h[a-b]llo", "hbllo"));
+        assert!(matches("h[b-a]llo", "hallo"));
+        assert!(matches("a\\*b", "a*b"));
+        assert!(!matches("a\\*b", "axb"));
+        assert!(matches("[\\]]", "]"));
+        assert!(glob_match(b"HELLO*", b"hello world", true));
+        assert!(!glob_match(b"HELLO*", b"hello world", false));
+    }
+}
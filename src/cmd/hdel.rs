@@ -0,0 +1,44 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Hdel {
+    key: Bytes,
+    fields: Vec<Bytes>,
+}
+
+impl Hdel {
+    pub fn new(key: Bytes, fields: Vec<Bytes>) -> Self {
+        Hdel { key, fields }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let hash = match slot.as_mut().map(Entry::hash_mut) {
+                None => return RESPType::Integer(0),
+                Some(Err(e)) => return e.into(),
+                Some(Ok(hash)) => hash,
+            };
+
+            let removed = self
+                .fields
+                .iter()
+                .filter(|field| hash.remove(field))
+                .count();
+            RESPType::Integer(removed as i64)
+        })
+    }
+}
+
+impl From<Hdel> for RESPType {
+    fn from(hdel: Hdel) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("hdel")),
+            RESPType::Bulk(hdel.key),
+        ];
+        arr.extend(hdel.fields.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
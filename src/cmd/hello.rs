@@ -0,0 +1,119 @@
+use bytes::Bytes;
+
+use crate::resp::Protocol;
+use crate::{Connection, Error, RESPType};
+
+pub struct Hello {
+    protover: Option<i64>,
+    auth: Option<(Bytes, Bytes)>,
+    setname: Option<Bytes>,
+}
+
+impl Hello {
+    pub fn new(protover: Option<i64>) -> Self {
+        Hello {
+            protover,
+            auth: None,
+            setname: None,
+        }
+    }
+
+    pub fn auth(mut self, username: Bytes, password: Bytes) -> Self {
+        self.auth = Some((username, password));
+        self
+    }
+
+    pub fn setname(mut self, name: Bytes) -> Self {
+        self.setname = Some(name);
+        self
+    }
+
+    /// Switches the connection to the requested protocol and describes the server.
+    pub fn response(&self, connection: &mut Connection) -> RESPType {
+        let protocol = match self.protover {
+            None => connection.protocol(),
+            Some(2) => Protocol::Resp2,
+            Some(3) => Protocol::Resp3,
+            Some(_) => return Error::server("NOPROTO", "unsupported protocol version").into(),
+        };
+
+        // there are no users besides the passwordless default one
+        if let Some((username, _)) = &self.auth {
+            if &username[..] != b"default" {
+                return Error::server(
+                    "WRONGPASS",
+                    "invalid username-password pair or user is disabled.",
+                )
+                .into();
+            }
+        }
+
+        if let Some(name) = &self.setname {
+            if name.iter().any(|c| *c <= b' ' || *c > b'~') {
+                return Error::command(
+                    "Client names cannot contain spaces, newlines or special characters.",
+                )
+                .into();
+            }
+            connection.set_name(Some(name.clone()));
+        }
+
+        connection.set_protocol(protocol);
+
+        let proto = match protocol {
+            Protocol::Resp2 => 2,
+            Protocol::Resp3 => 3,
+        };
+        RESPType::Map(vec![
+            (
+                RESPType::Bulk(Bytes::from("server")),
+                RESPType::Bulk(Bytes::from("redis")),
+            ),
+            (
+                RESPType::Bulk(Bytes::from("version")),
+                RESPType::Bulk(Bytes::from(crate::REDIS_VERSION)),
+            ),
+            (
+                RESPType::Bulk(Bytes::from("proto")),
+                RESPType::Integer(proto),
+            ),
+            (
+                RESPType::Bulk(Bytes::from("id")),
+                RESPType::Integer(connection.id() as i64),
+            ),
+            (
+                RESPType::Bulk(Bytes::from("mode")),
+                RESPType::Bulk(Bytes::from("standalone")),
+            ),
+            (
+                RESPType::Bulk(Bytes::from("role")),
+                RESPType::Bulk(Bytes::from("master")),
+            ),
+            (
+                RESPType::Bulk(Bytes::from("modules")),
+                RESPType::Array(vec![]),
+            ),
+        ])
+    }
+}
+
+impl From<Hello> for RESPType {
+    fn from(hello: Hello) -> RESPType {
+        let mut arr = vec![RESPType::Bulk(Bytes::from("hello"))];
+
+        if let Some(protover) = hello.protover {
+            arr.push(RESPType::Bulk(Bytes::from(protover.to_string())));
+        }
+        if let Some((username, password)) = hello.auth {
+            arr.push(RESPType::Bulk(Bytes::from("AUTH")));
+            arr.push(RESPType::Bulk(username));
+            arr.push(RESPType::Bulk(password));
+        }
+        if let Some(name) = hello.setname {
+            arr.push(RESPType::Bulk(Bytes::from("SETNAME")));
+            arr.push(RESPType::Bulk(name));
+        }
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,33 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Hexists {
+    key: Bytes,
+    field: Bytes,
+}
+
+impl Hexists {
+    pub fn new(key: Bytes, field: Bytes) -> Self {
+        Hexists { key, field }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| match slot.as_ref().map(Entry::hash) {
+            None => RESPType::Integer(0),
+            Some(Err(e)) => e.into(),
+            Some(Ok(hash)) => RESPType::Integer(hash.get(&self.field).is_some() as i64),
+        })
+    }
+}
+
+impl From<Hexists> for RESPType {
+    fn from(hexists: Hexists) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("hexists")),
+            RESPType::Bulk(hexists.key),
+            RESPType::Bulk(hexists.field),
+        ])
+    }
+}
@@ -0,0 +1,141 @@
+use bytes::Bytes;
+
+use crate::db::{now_ms, Entry};
+use crate::{Db, Error, RESPType};
+
+use super::{ExpireCondition, ExpireUnit};
+
+/// Largest field expiry redis accepts, in unix milliseconds.
+const MAX_FIELD_EXPIRY: i64 = (1 << 48) - 1;
+
+/// HEXPIRE, HPEXPIRE, HEXPIREAT and HPEXPIREAT, replying with one code per field: -2 if the
+/// field doesn't exist, 0 if the condition wasn't met, 1 if the expiry was set and 2 if the
+/// field was deleted right away.
+pub struct Hexpire {
+    key: Bytes,
+    time: i64,
+    unit: ExpireUnit,
+    absolute: bool,
+    condition: Option<ExpireCondition>,
+    fields: Vec<Bytes>,
+}
+
+impl Hexpire {
+    pub fn new(
+        key: Bytes,
+        time: i64,
+        unit: ExpireUnit,
+        absolute: bool,
+        fields: Vec<Bytes>,
+    ) -> Self {
+        Hexpire {
+            key,
+            time,
+            unit,
+            absolute,
+            condition: None,
+            fields,
+        }
+    }
+
+    pub fn condition(mut self, condition: ExpireCondition) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    pub fn name(&self) -> &'static str {
+        match (self.unit, self.absolute) {
+            (ExpireUnit::Seconds, false) => "hexpire",
+            (ExpireUnit::Milliseconds, false) => "hpexpire",
+            (ExpireUnit::Seconds, true) => "hexpireat",
+            (ExpireUnit::Milliseconds, true) => "hpexpireat",
+        }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        if self.time < 0 {
+            return Error::command("invalid expire time, must be >= 0").into();
+        }
+        let now = now_ms() as i64;
+        let millis = match self.unit {
+            ExpireUnit::Seconds => self.time.checked_mul(1000),
+            ExpireUnit::Milliseconds => Some(self.time),
+        };
+        let when = match millis {
+            Some(millis) if self.absolute => Some(millis),
+            Some(millis) => millis.checked_add(now),
+            None => None,
+        };
+        let when = match when {
+            Some(when) if when <= MAX_FIELD_EXPIRY => when,
+            _ => {
+                return Error::command(format!("invalid expire time in '{}' command", self.name()))
+                    .into()
+            }
+        };
+
+        db.update(&self.key, |slot| {
+            let hash = match slot.as_mut().map(Entry::hash_mut) {
+                None => return RESPType::Array(vec![RESPType::Integer(-2); self.fields.len()]),
+                Some(Err(e)) => return e.into(),
+                Some(Ok(hash)) => hash,
+            };
+
+            let codes = self.fields.iter().map(|field| {
+                let current = match hash.expires_at(field) {
+                    None => return RESPType::Integer(-2),
+                    Some(current) => current,
+                };
+
+                // a persistent field behaves as if its ttl were infinite
+                let allowed = match (self.condition, current) {
+                    (None, _) => true,
+                    (Some(ExpireCondition::Nx), current) => current.is_none(),
+                    (Some(ExpireCondition::Xx), current) => current.is_some(),
+                    (Some(ExpireCondition::Gt), None) => false,
+                    (Some(ExpireCondition::Gt), Some(current)) => when > current as i64,
+                    (Some(ExpireCondition::Lt), None) => true,
+                    (Some(ExpireCondition::Lt), Some(current)) => when < current as i64,
+                };
+                if !allowed {
+                    return RESPType::Integer(0);
+                }
+
+                if when <= now {
+                    hash.remove(field);
+                    RESPType::Integer(2)
+                } else {
+                    hash.set_expiry(field, Some(when as u64));
+                    RESPType::Integer(1)
+                }
+            });
+            RESPType::Array(codes.collect())
+        })
+    }
+}
+
+impl From<Hexpire> for RESPType {
+    fn from(hexpire: Hexpire) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from(hexpire.name())),
+            RESPType::Bulk(hexpire.key),
+            RESPType::Bulk(Bytes::from(hexpire.time.to_string())),
+        ];
+        if let Some(condition) = hexpire.condition {
+            let condition = match condition {
+                ExpireCondition::Nx => "NX",
+                ExpireCondition::Xx => "XX",
+                ExpireCondition::Gt => "GT",
+                ExpireCondition::Lt => "LT",
+            };
+            arr.push(RESPType::Bulk(Bytes::from(condition)));
+        }
+        arr.push(RESPType::Bulk(Bytes::from("FIELDS")));
+        arr.push(RESPType::Bulk(Bytes::from(
+            hexpire.fields.len().to_string(),
+        )));
+        arr.extend(hexpire.fields.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,36 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Hget {
+    key: Bytes,
+    field: Bytes,
+}
+
+impl Hget {
+    pub fn new(key: Bytes, field: Bytes) -> Self {
+        Hget { key, field }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| match slot.as_ref().map(Entry::hash) {
+            None => RESPType::Null,
+            Some(Err(e)) => e.into(),
+            Some(Ok(hash)) => match hash.get(&self.field) {
+                None => RESPType::Null,
+                Some(value) => RESPType::Bulk(value.clone()),
+            },
+        })
+    }
+}
+
+impl From<Hget> for RESPType {
+    fn from(hget: Hget) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("hget")),
+            RESPType::Bulk(hget.key),
+            RESPType::Bulk(hget.field),
+        ])
+    }
+}
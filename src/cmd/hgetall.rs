@@ -0,0 +1,71 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HgetAllKind {
+    Keys,
+    Values,
+    All,
+}
+
+/// HKEYS, HVALS and HGETALL.
+pub struct HgetAll {
+    key: Bytes,
+    kind: HgetAllKind,
+}
+
+impl HgetAll {
+    pub fn new(key: Bytes, kind: HgetAllKind) -> Self {
+        HgetAll { key, kind }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self.kind {
+            HgetAllKind::Keys => "hkeys",
+            HgetAllKind::Values => "hvals",
+            HgetAllKind::All => "hgetall",
+        }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let hash = match slot.as_ref().map(Entry::hash) {
+                None => None,
+                Some(Err(e)) => return e.into(),
+                Some(Ok(hash)) => Some(hash),
+            };
+            let fields = hash.into_iter().flat_map(|hash| hash.iter());
+
+            match self.kind {
+                HgetAllKind::Keys => RESPType::Array(
+                    fields
+                        .map(|(field, _)| RESPType::Bulk(field.clone()))
+                        .collect(),
+                ),
+                HgetAllKind::Values => RESPType::Array(
+                    fields
+                        .map(|(_, value)| RESPType::Bulk(value.clone()))
+                        .collect(),
+                ),
+                HgetAllKind::All => RESPType::Map(
+                    fields
+                        .map(|(field, value)| {
+                            (RESPType::Bulk(field.clone()), RESPType::Bulk(value.clone()))
+                        })
+                        .collect(),
+                ),
+            }
+        })
+    }
+}
+
+impl From<HgetAll> for RESPType {
+    fn from(hgetall: HgetAll) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from(hgetall.name())),
+            RESPType::Bulk(hgetall.key),
+        ])
+    }
+}
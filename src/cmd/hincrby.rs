@@ -0,0 +1,54 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, Hash, Value};
+use crate::{Db, Error, RESPType};
+
+use super::incr::parse_int;
+
+pub struct Hincrby {
+    key: Bytes,
+    field: Bytes,
+    delta: i64,
+}
+
+impl Hincrby {
+    pub fn new(key: Bytes, field: Bytes, delta: i64) -> Self {
+        Hincrby { key, field, delta }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let entry = slot.get_or_insert_with(|| Entry::new(Value::Hash(Hash::default())));
+            let hash = match entry.hash_mut() {
+                Err(e) => return e.into(),
+                Ok(hash) => hash,
+            };
+
+            let current = match hash.get(&self.field) {
+                None => 0,
+                Some(value) => match parse_int(value) {
+                    None => return Error::command("hash value is not an integer").into(),
+                    Some(n) => n,
+                },
+            };
+            let value = match current.checked_add(self.delta) {
+                None => return Error::command("increment or decrement would overflow").into(),
+                Some(value) => value,
+            };
+
+            hash.update(self.field.clone(), Bytes::from(value.to_string()));
+            RESPType::Integer(value)
+        })
+    }
+}
+
+impl From<Hincrby> for RESPType {
+    fn from(hincrby: Hincrby) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("hincrby")),
+            RESPType::Bulk(hincrby.key),
+            RESPType::Bulk(hincrby.field),
+            RESPType::Bulk(Bytes::from(hincrby.delta.to_string())),
+        ])
+    }
+}
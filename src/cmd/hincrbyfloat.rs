@@ -0,0 +1,55 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, Hash, Value};
+use crate::{Db, Error, RESPType};
+
+use super::incrbyfloat::{format_float, format_sum, parse_float};
+
+pub struct HincrbyFloat {
+    key: Bytes,
+    field: Bytes,
+    delta: f64,
+}
+
+impl HincrbyFloat {
+    pub fn new(key: Bytes, field: Bytes, delta: f64) -> Self {
+        HincrbyFloat { key, field, delta }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let entry = slot.get_or_insert_with(|| Entry::new(Value::Hash(Hash::default())));
+            let hash = match entry.hash_mut() {
+                Err(e) => return e.into(),
+                Ok(hash) => hash,
+            };
+
+            let current = match hash.get(&self.field) {
+                None => 0.0,
+                Some(value) => match parse_float(value) {
+                    None => return Error::command("hash value is not a float").into(),
+                    Some(n) => n,
+                },
+            };
+            let value = current + self.delta;
+            if !value.is_finite() {
+                return Error::command("increment would produce NaN or Infinity").into();
+            }
+
+            let bytes = Bytes::from(format_sum(current, self.delta));
+            hash.update(self.field.clone(), bytes.clone());
+            RESPType::Bulk(bytes)
+        })
+    }
+}
+
+impl From<HincrbyFloat> for RESPType {
+    fn from(hincrbyfloat: HincrbyFloat) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("hincrbyfloat")),
+            RESPType::Bulk(hincrbyfloat.key),
+            RESPType::Bulk(hincrbyfloat.field),
+            RESPType::Bulk(Bytes::from(format_float(hincrbyfloat.delta))),
+        ])
+    }
+}
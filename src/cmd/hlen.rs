@@ -0,0 +1,31 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Hlen {
+    key: Bytes,
+}
+
+impl Hlen {
+    pub fn new(key: Bytes) -> Self {
+        Hlen { key }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| match slot.as_ref().map(Entry::hash) {
+            None => RESPType::Integer(0),
+            Some(Err(e)) => e.into(),
+            Some(Ok(hash)) => RESPType::Integer(hash.len() as i64),
+        })
+    }
+}
+
+impl From<Hlen> for RESPType {
+    fn from(hlen: Hlen) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("hlen")),
+            RESPType::Bulk(hlen.key),
+        ])
+    }
+}
@@ -0,0 +1,47 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Hmget {
+    key: Bytes,
+    fields: Vec<Bytes>,
+}
+
+impl Hmget {
+    pub fn new(key: Bytes, fields: Vec<Bytes>) -> Self {
+        Hmget { key, fields }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let hash = match slot.as_ref().map(Entry::hash) {
+                None => None,
+                Some(Err(e)) => return e.into(),
+                Some(Ok(hash)) => Some(hash),
+            };
+
+            RESPType::Array(
+                self.fields
+                    .iter()
+                    .map(|field| match hash.and_then(|hash| hash.get(field)) {
+                        None => RESPType::Null,
+                        Some(value) => RESPType::Bulk(value.clone()),
+                    })
+                    .collect(),
+            )
+        })
+    }
+}
+
+impl From<Hmget> for RESPType {
+    fn from(hmget: Hmget) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("hmget")),
+            RESPType::Bulk(hmget.key),
+        ];
+        arr.extend(hmget.fields.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,54 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+/// HPERSIST, replying with one code per field: -2 if the field doesn't exist, -1 if it had
+/// no expiry and 1 if the expiry was removed.
+pub struct Hpersist {
+    key: Bytes,
+    fields: Vec<Bytes>,
+}
+
+impl Hpersist {
+    pub fn new(key: Bytes, fields: Vec<Bytes>) -> Self {
+        Hpersist { key, fields }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let hash = match slot.as_mut().map(Entry::hash_mut) {
+                None => return RESPType::Array(vec![RESPType::Integer(-2); self.fields.len()]),
+                Some(Err(e)) => return e.into(),
+                Some(Ok(hash)) => hash,
+            };
+
+            let codes = self
+                .fields
+                .iter()
+                .map(|field| match hash.expires_at(field) {
+                    None => RESPType::Integer(-2),
+                    Some(None) => RESPType::Integer(-1),
+                    Some(Some(_)) => {
+                        hash.set_expiry(field, None);
+                        RESPType::Integer(1)
+                    }
+                });
+            RESPType::Array(codes.collect())
+        })
+    }
+}
+
+impl From<Hpersist> for RESPType {
+    fn from(hpersist: Hpersist) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("hpersist")),
+            RESPType::Bulk(hpersist.key),
+            RESPType::Bulk(Bytes::from("FIELDS")),
+            RESPType::Bulk(Bytes::from(hpersist.fields.len().to_string())),
+        ];
+        arr.extend(hpersist.fields.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,95 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::random;
+use crate::{Db, RESPType};
+
+/// HRANDFIELD, replying with a single field unless given a count. A negative count allows
+/// the same field to be returned several times.
+pub struct HrandField {
+    key: Bytes,
+    count: Option<i64>,
+    with_values: bool,
+}
+
+impl HrandField {
+    pub fn new(key: Bytes) -> Self {
+        HrandField {
+            key,
+            count: None,
+            with_values: false,
+        }
+    }
+
+    pub fn count(mut self, count: i64) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Reply with the value following each field, only allowed together with a count.
+    pub fn with_values(mut self, with_values: bool) -> Self {
+        self.with_values = with_values;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let hash = match slot.as_ref().map(Entry::hash) {
+                None if self.count.is_some() => return RESPType::Array(vec![]),
+                None => return RESPType::Null,
+                Some(Err(e)) => return e.into(),
+                Some(Ok(hash)) => hash,
+            };
+            let count = match self.count {
+                None => {
+                    let (field, _) = hash.random().expect("hashes are never empty");
+                    return RESPType::Bulk(field.clone());
+                }
+                Some(count) => count,
+            };
+            let mut arr = vec![];
+            let mut reply = |(field, value): (&Bytes, &Bytes)| {
+                arr.push(RESPType::Bulk(field.clone()));
+                if self.with_values {
+                    arr.push(RESPType::Bulk(value.clone()));
+                }
+            };
+
+            if count < 0 {
+                // fields may repeat, so each pick is independent
+                for _ in 0..count.unsigned_abs() {
+                    if let Some(pick) = hash.random() {
+                        reply(pick);
+                    }
+                }
+            } else {
+                // partial shuffle, every field is picked at most once
+                let mut fields: Vec<_> = hash.iter().collect();
+                let count = fields.len().min(count as usize);
+                for i in 0..count {
+                    let j = i + random::below(fields.len() - i);
+                    fields.swap(i, j);
+                }
+                fields.into_iter().take(count).for_each(reply);
+            }
+            RESPType::Array(arr)
+        })
+    }
+}
+
+impl From<HrandField> for RESPType {
+    fn from(hrandfield: HrandField) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("hrandfield")),
+            RESPType::Bulk(hrandfield.key),
+        ];
+        if let Some(count) = hrandfield.count {
+            arr.push(RESPType::Bulk(Bytes::from(count.to_string())));
+            if hrandfield.with_values {
+                arr.push(RESPType::Bulk(Bytes::from("WITHVALUES")));
+            }
+        }
+
+        RESPType::Array(arr)
+    }
+}
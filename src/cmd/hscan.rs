@@ -0,0 +1,98 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+use super::glob::glob_match;
+
+/// HSCAN, iterating a hash a page at a time with a cursor that survives modifications.
+pub struct Hscan {
+    key: Bytes,
+    cursor: u64,
+    pattern: Option<Bytes>,
+    count: usize,
+    no_values: bool,
+}
+
+impl Hscan {
+    pub fn new(key: Bytes, cursor: u64) -> Self {
+        Hscan {
+            key,
+            cursor,
+            pattern: None,
+            count: 10,
+            no_values: false,
+        }
+    }
+
+    /// Only return fields matching the glob style `pattern`.
+    pub fn pattern(mut self, pattern: Bytes) -> Self {
+        self.pattern = Some(pattern);
+        self
+    }
+
+    /// Hint of how many fields to visit per call.
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    pub fn no_values(mut self, no_values: bool) -> Self {
+        self.no_values = no_values;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let hash = match slot.as_ref().map(Entry::hash) {
+                None => return scan_reply(0, vec![]),
+                Some(Err(e)) => return e.into(),
+                Some(Ok(hash)) => hash,
+            };
+
+            let (cursor, fields) = hash.scan(self.cursor, self.count);
+            let mut arr = vec![];
+            for (field, value) in fields {
+                if let Some(pattern) = &self.pattern {
+                    if !glob_match(pattern, field, false) {
+                        continue;
+                    }
+                }
+                arr.push(RESPType::Bulk(field.clone()));
+                if !self.no_values {
+                    arr.push(RESPType::Bulk(value.clone()));
+                }
+            }
+            scan_reply(cursor, arr)
+        })
+    }
+}
+
+/// The two element reply shared by the scan commands.
+pub(crate) fn scan_reply(cursor: u64, members: Vec<RESPType>) -> RESPType {
+    RESPType::Array(vec![
+        RESPType::Bulk(Bytes::from(cursor.to_string())),
+        RESPType::Array(members),
+    ])
+}
+
+impl From<Hscan> for RESPType {
+    fn from(hscan: Hscan) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("hscan")),
+            RESPType::Bulk(hscan.key),
+            RESPType::Bulk(Bytes::from(hscan.cursor.to_string())),
+        ];
+        if let Some(pattern) = hscan.pattern {
+            arr.push(RESPType::Bulk(Bytes::from("MATCH")));
+            arr.push(RESPType::Bulk(pattern));
+        }
+        arr.push(RESPType::Bulk(Bytes::from("COUNT")));
+        arr.push(RESPType::Bulk(Bytes::from(hscan.count.to_string())));
+        if hscan.no_values {
+            arr.push(RESPType::Bulk(Bytes::from("NOVALUES")));
+        }
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,61 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, Hash, Value};
+use crate::{Db, RESPType};
+
+/// HSET, and HMSET which only differs in replying OK.
+pub struct Hset {
+    key: Bytes,
+    pairs: Vec<(Bytes, Bytes)>,
+    legacy: bool,
+}
+
+impl Hset {
+    pub fn new(key: Bytes, pairs: Vec<(Bytes, Bytes)>) -> Self {
+        Hset {
+            key,
+            pairs,
+            legacy: false,
+        }
+    }
+
+    /// Reply OK as HMSET does instead of the number of fields added.
+    pub fn legacy(mut self, legacy: bool) -> Self {
+        self.legacy = legacy;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let entry = slot.get_or_insert_with(|| Entry::new(Value::Hash(Hash::default())));
+            let hash = match entry.hash_mut() {
+                Err(e) => return e.into(),
+                Ok(hash) => hash,
+            };
+
+            let mut added = 0;
+            for (field, value) in &self.pairs {
+                if hash.insert(field.clone(), value.clone()) {
+                    added += 1;
+                }
+            }
+            match self.legacy {
+                true => RESPType::String("OK".into()),
+                false => RESPType::Integer(added),
+            }
+        })
+    }
+}
+
+impl From<Hset> for RESPType {
+    fn from(hset: Hset) -> RESPType {
+        let name = if hset.legacy { "hmset" } else { "hset" };
+        let mut arr = vec![RESPType::Bulk(Bytes::from(name)), RESPType::Bulk(hset.key)];
+        for (field, value) in hset.pairs {
+            arr.push(RESPType::Bulk(field));
+            arr.push(RESPType::Bulk(value));
+        }
+
+        RESPType::Array(arr)
+    }
+}
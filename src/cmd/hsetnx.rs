@@ -0,0 +1,43 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, Hash, Value};
+use crate::{Db, RESPType};
+
+pub struct HsetNx {
+    key: Bytes,
+    field: Bytes,
+    value: Bytes,
+}
+
+impl HsetNx {
+    pub fn new(key: Bytes, field: Bytes, value: Bytes) -> Self {
+        HsetNx { key, field, value }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let entry = slot.get_or_insert_with(|| Entry::new(Value::Hash(Hash::default())));
+            let hash = match entry.hash_mut() {
+                Err(e) => return e.into(),
+                Ok(hash) => hash,
+            };
+
+            if hash.get(&self.field).is_some() {
+                return RESPType::Integer(0);
+            }
+            hash.insert(self.field.clone(), self.value.clone());
+            RESPType::Integer(1)
+        })
+    }
+}
+
+impl From<HsetNx> for RESPType {
+    fn from(hsetnx: HsetNx) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("hsetnx")),
+            RESPType::Bulk(hsetnx.key),
+            RESPType::Bulk(hsetnx.field),
+            RESPType::Bulk(hsetnx.value),
+        ])
+    }
+}
@@ -0,0 +1,35 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Hstrlen {
+    key: Bytes,
+    field: Bytes,
+}
+
+impl Hstrlen {
+    pub fn new(key: Bytes, field: Bytes) -> Self {
+        Hstrlen { key, field }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| match slot.as_ref().map(Entry::hash) {
+            None => RESPType::Integer(0),
+            Some(Err(e)) => e.into(),
+            Some(Ok(hash)) => {
+                RESPType::Integer(hash.get(&self.field).map_or(0, |value| value.len()) as i64)
+            }
+        })
+    }
+}
+
+impl From<Hstrlen> for RESPType {
+    fn from(hstrlen: Hstrlen) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("hstrlen")),
+            RESPType::Bulk(hstrlen.key),
+            RESPType::Bulk(hstrlen.field),
+        ])
+    }
+}
@@ -0,0 +1,72 @@
+use bytes::Bytes;
+
+use crate::db::{now_ms, Entry};
+use crate::{Db, RESPType};
+
+use super::TtlKind;
+
+/// HTTL, HPTTL, HEXPIRETIME and HPEXPIRETIME, replying with one value per field: -2 if the
+/// field doesn't exist and -1 if it has no expiry.
+pub struct Httl {
+    key: Bytes,
+    kind: TtlKind,
+    fields: Vec<Bytes>,
+}
+
+impl Httl {
+    pub fn new(key: Bytes, kind: TtlKind, fields: Vec<Bytes>) -> Self {
+        Httl { key, kind, fields }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self.kind {
+            TtlKind::Ttl => "httl",
+            TtlKind::Pttl => "hpttl",
+            TtlKind::ExpireTime => "hexpiretime",
+            TtlKind::PexpireTime => "hpexpiretime",
+        }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let now = now_ms();
+        db.update(&self.key, |slot| {
+            let hash = match slot.as_ref().map(Entry::hash) {
+                None => return RESPType::Array(vec![RESPType::Integer(-2); self.fields.len()]),
+                Some(Err(e)) => return e.into(),
+                Some(Ok(hash)) => hash,
+            };
+
+            let values = self.fields.iter().map(|field| {
+                let when = match hash.expires_at(field) {
+                    None => return RESPType::Integer(-2),
+                    Some(None) => return RESPType::Integer(-1),
+                    Some(Some(when)) => when,
+                };
+                let remaining = when.saturating_sub(now);
+
+                let value = match self.kind {
+                    TtlKind::Ttl => (remaining + 500) / 1000,
+                    TtlKind::Pttl => remaining,
+                    TtlKind::ExpireTime => when / 1000,
+                    TtlKind::PexpireTime => when,
+                };
+                RESPType::Integer(value as i64)
+            });
+            RESPType::Array(values.collect())
+        })
+    }
+}
+
+impl From<Httl> for RESPType {
+    fn from(httl: Httl) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from(httl.name())),
+            RESPType::Bulk(httl.key),
+            RESPType::Bulk(Bytes::from("FIELDS")),
+            RESPType::Bulk(Bytes::from(httl.fields.len().to_string())),
+        ];
+        arr.extend(httl.fields.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
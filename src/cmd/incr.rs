@@ -0,0 +1,61 @@
+use bytes::Bytes;
+
+use crate::db::{set_keep_ttl, Entry, Value};
+use crate::{Db, Error, RESPType};
+
+/// INCR, DECR, INCRBY and DECRBY, all expressed as adding `delta` to the stored integer.
+pub struct Incr {
+    key: Bytes,
+    delta: i64,
+}
+
+impl Incr {
+    pub fn new(key: Bytes, delta: i64) -> Self {
+        Incr { key, delta }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let current = match slot.as_ref().map(Entry::string) {
+                None => 0,
+                Some(Err(e)) => return e.into(),
+                Some(Ok(value)) => match parse_int(value) {
+                    None => return Error::NotInteger.into(),
+                    Some(n) => n,
+                },
+            };
+            let value = match current.checked_add(self.delta) {
+                None => return Error::command("increment or decrement would overflow").into(),
+                Some(value) => value,
+            };
+
+            set_keep_ttl(slot, Value::String(Bytes::from(value.to_string())));
+            RESPType::Integer(value)
+        })
+    }
+}
+
+/// Parses a stored value the way redis does, rejecting signs, spaces and leading zeros.
+pub(crate) fn parse_int(value: &[u8]) -> Option<i64> {
+    let digits = value.strip_prefix(b"-").unwrap_or(value);
+    match digits {
+        [] => return None,
+        [b'0'] if digits.len() == value.len() => return Some(0),
+        [b'0', ..] => return None,
+        _ => {}
+    }
+    if digits.len() > 20 || !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    std::str::from_utf8(value).ok()?.parse().ok()
+}
+
+impl From<Incr> for RESPType {
+    fn from(incr: Incr) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("incrby")),
+            RESPType::Bulk(incr.key),
+            RESPType::Bulk(Bytes::from(incr.delta.to_string())),
+        ])
+    }
+}
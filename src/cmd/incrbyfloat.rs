@@ -0,0 +1,221 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use bytes::Bytes;
+
+use crate::db::{set_keep_ttl, Entry, Value};
+use crate::{Db, Error, RESPType};
+
+pub struct IncrByFloat {
+    key: Bytes,
+    delta: f64,
+}
+
+impl IncrByFloat {
+    pub fn new(key: Bytes, delta: f64) -> Self {
+        IncrByFloat { key, delta }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let current = match slot.as_ref().map(Entry::string) {
+                None => 0.0,
+                Some(Err(e)) => return e.into(),
+                Some(Ok(value)) => match parse_float(value) {
+                    None => return Error::command("value is not a valid float").into(),
+                    Some(n) => n,
+                },
+            };
+            let value = current + self.delta;
+            if !value.is_finite() {
+                return Error::command("increment would produce NaN or Infinity").into();
+            }
+
+            let bytes = Bytes::from(format_sum(current, self.delta));
+            set_keep_ttl(slot, Value::String(bytes.clone()));
+            RESPType::Bulk(bytes)
+        })
+    }
+}
+
+/// Parses a float argument or stored value, NaN is never a valid input.
+pub(crate) fn parse_float(value: &[u8]) -> Option<f64> {
+    let value: f64 = std::str::from_utf8(value).ok()?.parse().ok()?;
+    if value.is_nan() {
+        return None;
+    }
+    Some(value)
+}
+
+/// Formats a float in the shortest form that parses back to it, without exponent or
+/// trailing zeros.
+pub(crate) fn format_float(value: f64) -> String {
+    if value == 0.0 {
+        // avoid storing "-0"
+        return "0".into();
+    }
+    value.to_string()
+}
+
+/// Formats `a + b` the way redis stores the result of INCRBYFLOAT, to 17 significant digits
+/// without trailing zeros. Redis adds in long double, whose extra precision rounds away the
+/// binary noise of sums like 0.1 + 0.2. Adding the shortest decimal forms of both operands
+/// exactly before rounding gives the same digits.
+pub(crate) fn format_sum(a: f64, b: f64) -> String {
+    let (a, b) = (Decimal::from(a), Decimal::from(b));
+    let exp = a.exp.min(b.exp);
+    let (a_digits, b_digits) = (a.aligned(exp), b.aligned(exp));
+    let (negative, digits) = match (a.negative == b.negative, cmp_digits(&a_digits, &b_digits)) {
+        (true, _) => (a.negative, add_digits(&a_digits, &b_digits)),
+        (false, Ordering::Less) => (b.negative, sub_digits(&b_digits, &a_digits)),
+        (false, _) => (a.negative, sub_digits(&a_digits, &b_digits)),
+    };
+    Decimal {
+        negative,
+        digits,
+        exp,
+    }
+    .rounded(17)
+    .to_string()
+}
+
+/// `digits` × 10^`exp`, the digits most significant first.
+struct Decimal {
+    negative: bool,
+    digits: Vec<u8>,
+    exp: i32,
+}
+
+impl From<f64> for Decimal {
+    /// The shortest decimal that parses back to `value`.
+    fn from(value: f64) -> Self {
+        let formatted = format!("{:e}", value.abs());
+        let (mantissa, exp) = formatted
+            .split_once('e')
+            .expect("formatted with an exponent");
+        let digits: Vec<u8> = mantissa
+            .bytes()
+            .filter_map(|c| c.checked_sub(b'0'))
+            .collect();
+        let exp = exp.parse::<i32>().expect("formatted exponent") - (digits.len() as i32 - 1);
+        Decimal {
+            negative: value.is_sign_negative(),
+            digits,
+            exp,
+        }
+    }
+}
+
+impl Decimal {
+    /// The digits scaled so the last one is worth 10^`exp`, which must not exceed `self.exp`.
+    fn aligned(&self, exp: i32) -> Vec<u8> {
+        let mut digits = self.digits.clone();
+        digits.resize(digits.len() + (self.exp - exp) as usize, 0);
+        digits
+    }
+
+    /// Rounded to `precision` significant digits, half to even, without leading or trailing
+    /// zeros.
+    fn rounded(mut self, precision: usize) -> Decimal {
+        let leading = self.digits.iter().take_while(|&&d| d == 0).count();
+        self.digits.drain(..leading);
+        if self.digits.len() > precision {
+            let dropped = self.digits.split_off(precision);
+            self.exp += dropped.len() as i32;
+            let rest = dropped[1..].iter().any(|&d| d != 0);
+            let odd = self.digits[precision - 1] % 2 == 1;
+            if dropped[0] > 5 || (dropped[0] == 5 && (rest || odd)) {
+                self.digits = add_digits(&self.digits, &[1]);
+                if self.digits.len() > precision {
+                    self.digits.pop();
+                    self.exp += 1;
+                }
+            }
+        }
+        while self.digits.last() == Some(&0) {
+            self.digits.pop();
+            self.exp += 1;
+        }
+        self
+    }
+}
+
+impl fmt::Display for Decimal {
+    /// Like `%.17g`, plain unless the exponent is below -4 or above 16.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.digits.is_empty() {
+            // avoid storing "-0"
+            return f.write_str("0");
+        }
+        if self.negative {
+            f.write_str("-")?;
+        }
+        let digits: String = self.digits.iter().map(|d| (b'0' + d) as char).collect();
+        let len = digits.len() as i32;
+        let sci = self.exp + len - 1;
+        if !(-4..17).contains(&sci) {
+            let (first, rest) = digits.split_at(1);
+            let point = if rest.is_empty() { "" } else { "." };
+            let sign = if sci < 0 { '-' } else { '+' };
+            return write!(f, "{first}{point}{rest}e{sign}{:02}", sci.abs());
+        }
+        match self.exp {
+            exp if exp >= 0 => write!(f, "{digits}{}", "0".repeat(exp as usize)),
+            exp if exp + len > 0 => {
+                let (int, frac) = digits.split_at((exp + len) as usize);
+                write!(f, "{int}.{frac}")
+            }
+            exp => write!(f, "0.{}{digits}", "0".repeat(-(exp + len) as usize)),
+        }
+    }
+}
+
+/// Compares digit strings of the same scale by value.
+fn cmp_digits(a: &[u8], b: &[u8]) -> Ordering {
+    let trim = |digits: &[u8]| digits.len() - digits.iter().take_while(|&&d| d == 0).count();
+    let (a, b) = (&a[a.len() - trim(a)..], &b[b.len() - trim(b)..]);
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+fn add_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut sum = vec![];
+    let mut carry = 0;
+    let (mut a, mut b) = (a.iter().rev(), b.iter().rev());
+    loop {
+        let digit = match (a.next(), b.next()) {
+            (None, None) => break,
+            (x, y) => x.unwrap_or(&0) + y.unwrap_or(&0) + carry,
+        };
+        sum.push(digit % 10);
+        carry = digit / 10;
+    }
+    if carry > 0 {
+        sum.push(carry);
+    }
+    sum.reverse();
+    sum
+}
+
+/// `a - b`, where `a` is at least `b`.
+fn sub_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut diff = vec![];
+    let mut borrow = 0;
+    let mut b = b.iter().rev();
+    for &x in a.iter().rev() {
+        let y = b.next().unwrap_or(&0) + borrow;
+        borrow = u8::from(x < y);
+        diff.push(x + borrow * 10 - y);
+    }
+    diff.reverse();
+    diff
+}
+
+impl From<IncrByFloat> for RESPType {
+    fn from(incr: IncrByFloat) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("incrbyfloat")),
+            RESPType::Bulk(incr.key),
+            RESPType::Bulk(Bytes::from(format_float(incr.delta))),
+        ])
+    }
+}
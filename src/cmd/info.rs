@@ -0,0 +1,75 @@
+use std::fmt::Write as _;
+
+use bytes::Bytes;
+
+use crate::{Db, RESPType, REDIS_VERSION};
+
+/// INFO, a text report of the requested sections. Only the server, memory, stats and keyspace
+/// sections exist, unknown ones are left out like in redis.
+pub struct Info {
+    sections: Vec<Bytes>,
+}
+
+impl Info {
+    /// No sections asks for the default ones.
+    pub fn new(sections: Vec<Bytes>) -> Self {
+        Info { sections }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let all = self.sections.is_empty()
+            || self.sections.iter().any(|section| {
+                [&b"default"[..], b"all", b"everything"]
+                    .iter()
+                    .any(|name| section.eq_ignore_ascii_case(name))
+            });
+        let wants = |name: &str| {
+            all || self
+                .sections
+                .iter()
+                .any(|section| section.eq_ignore_ascii_case(name.as_bytes()))
+        };
+
+        let mut sections = vec![];
+        if wants("server") {
+            sections.push(format!(
+                "# Server\r\nredis_version:{REDIS_VERSION}\r\nredis_mode:standalone\r\n"
+            ));
+        }
+        if wants("memory") {
+            sections.push(format!(
+                "# Memory\r\nused_memory:{}\r\nmaxmemory:{}\r\nmaxmemory_policy:{}\r\n",
+                db.used_memory(),
+                db.maxmemory(),
+                db.eviction_policy().name()
+            ));
+        }
+        if wants("stats") {
+            sections.push(format!("# Stats\r\nevicted_keys:{}\r\n", db.evicted_keys()));
+        }
+        if wants("keyspace") {
+            let mut keyspace = String::from("# Keyspace\r\n");
+            for index in 0..db.databases() {
+                let stats = db.select(index).expect("index is below the count").stats();
+                if stats.keys > 0 {
+                    let _ = write!(
+                        keyspace,
+                        "db{index}:keys={},expires={},avg_ttl={},subexpiry={}\r\n",
+                        stats.keys, stats.expires, stats.avg_ttl, stats.subexpiry
+                    );
+                }
+            }
+            sections.push(keyspace);
+        }
+        RESPType::Bulk(Bytes::from(sections.join("\r\n")))
+    }
+}
+
+impl From<Info> for RESPType {
+    fn from(info: Info) -> RESPType {
+        let mut arr = vec![RESPType::Bulk(Bytes::from("info"))];
+        arr.extend(info.sections.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,44 @@
+use bytes::Bytes;
+
+use crate::{Db, RESPType};
+
+use super::glob::glob_match;
+
+/// KEYS, every key matching a glob style pattern.
+///
+/// The shards are walked one at a time, so other clients are only held up by the shard being
+/// read rather than by the whole keyspace.
+pub struct Keys {
+    pattern: Bytes,
+}
+
+impl Keys {
+    pub fn new(pattern: Bytes) -> Self {
+        Keys { pattern }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        // `*` is by far the most common pattern, skip matching it against every key
+        let all = &self.pattern[..] == b"*";
+        let mut keys = vec![];
+        for idx in 0..db.shard_count() {
+            db.scan_shard(idx, |entries| {
+                keys.extend(
+                    entries
+                        .filter(|(key, _)| all || glob_match(&self.pattern, key, false))
+                        .map(|(key, _)| RESPType::Bulk(key.clone())),
+                );
+            });
+        }
+        RESPType::Array(keys)
+    }
+}
+
+impl From<Keys> for RESPType {
+    fn from(keys: Keys) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("keys")),
+            RESPType::Bulk(keys.pattern),
+        ])
+    }
+}
@@ -0,0 +1,30 @@
+use bytes::Bytes;
+
+use crate::{Db, RESPType};
+
+/// TYPE, the type of the value at a key or `none`.
+pub struct Type {
+    key: Bytes,
+}
+
+impl Type {
+    pub fn new(key: Bytes) -> Self {
+        Type { key }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let name = db.peek(&self.key, |entry| {
+            entry.map_or("none", |entry| entry.value.type_name())
+        });
+        RESPType::String(name.into())
+    }
+}
+
+impl From<Type> for RESPType {
+    fn from(kind: Type) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("type")),
+            RESPType::Bulk(kind.key),
+        ])
+    }
+}
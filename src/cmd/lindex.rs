@@ -0,0 +1,49 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Lindex {
+    key: Bytes,
+    index: i64,
+}
+
+impl Lindex {
+    pub fn new(key: Bytes, index: i64) -> Self {
+        Lindex { key, index }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let list = match slot.as_ref().map(Entry::list) {
+                None => return RESPType::Null,
+                Some(Err(e)) => return e.into(),
+                Some(Ok(list)) => list,
+            };
+
+            match index(self.index, list.len()).and_then(|idx| list.get(idx)) {
+                None => RESPType::Null,
+                Some(value) => RESPType::Bulk(value.clone()),
+            }
+        })
+    }
+}
+
+/// Resolves an index where negative values count back from the end.
+pub(crate) fn index(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 { len as i64 + index } else { index };
+    if index < 0 || index >= len as i64 {
+        return None;
+    }
+    Some(index as usize)
+}
+
+impl From<Lindex> for RESPType {
+    fn from(lindex: Lindex) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("lindex")),
+            RESPType::Bulk(lindex.key),
+            RESPType::Bulk(Bytes::from(lindex.index.to_string())),
+        ])
+    }
+}
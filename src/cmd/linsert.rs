@@ -0,0 +1,55 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Linsert {
+    key: Bytes,
+    before: bool,
+    pivot: Bytes,
+    value: Bytes,
+}
+
+impl Linsert {
+    /// Inserts `value` next to the first occurrence of `pivot`, before it if `before` is set.
+    pub fn new(key: Bytes, before: bool, pivot: Bytes, value: Bytes) -> Self {
+        Linsert {
+            key,
+            before,
+            pivot,
+            value,
+        }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let list = match slot.as_mut().map(Entry::list_mut) {
+                None => return RESPType::Integer(0),
+                Some(Err(e)) => return e.into(),
+                Some(Ok(list)) => list,
+            };
+
+            match list.iter().position(|value| *value == self.pivot) {
+                None => RESPType::Integer(-1),
+                Some(idx) => {
+                    let idx = if self.before { idx } else { idx + 1 };
+                    list.insert(idx, self.value.clone());
+                    RESPType::Integer(list.len() as i64)
+                }
+            }
+        })
+    }
+}
+
+impl From<Linsert> for RESPType {
+    fn from(linsert: Linsert) -> RESPType {
+        let position = if linsert.before { "BEFORE" } else { "AFTER" };
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("linsert")),
+            RESPType::Bulk(linsert.key),
+            RESPType::Bulk(Bytes::from(position)),
+            RESPType::Bulk(linsert.pivot),
+            RESPType::Bulk(linsert.value),
+        ])
+    }
+}
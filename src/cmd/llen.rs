@@ -0,0 +1,31 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Llen {
+    key: Bytes,
+}
+
+impl Llen {
+    pub fn new(key: Bytes) -> Self {
+        Llen { key }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| match slot.as_ref().map(Entry::list) {
+            None => RESPType::Integer(0),
+            Some(Err(e)) => e.into(),
+            Some(Ok(list)) => RESPType::Integer(list.len() as i64),
+        })
+    }
+}
+
+impl From<Llen> for RESPType {
+    fn from(llen: Llen) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("llen")),
+            RESPType::Bulk(llen.key),
+        ])
+    }
+}
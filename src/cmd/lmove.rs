@@ -0,0 +1,93 @@
+use std::collections::VecDeque;
+
+use bytes::Bytes;
+
+use crate::db::{Entry, Locked, Value};
+use crate::{Db, RESPType};
+
+use super::ListEnd;
+
+/// LMOVE, and RPOPLPUSH as its right to left special case.
+pub struct Lmove {
+    source: Bytes,
+    destination: Bytes,
+    from: ListEnd,
+    to: ListEnd,
+}
+
+impl Lmove {
+    pub fn new(source: Bytes, destination: Bytes, from: ListEnd, to: ListEnd) -> Self {
+        Lmove {
+            source,
+            destination,
+            from,
+            to,
+        }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let mut locked = db.lock(&[&self.source, &self.destination]);
+        match move_element(
+            &mut locked,
+            &self.source,
+            &self.destination,
+            self.from,
+            self.to,
+        ) {
+            Err(e) => e.into(),
+            Ok(None) => RESPType::Null,
+            Ok(Some(value)) => RESPType::Bulk(value),
+        }
+    }
+}
+
+/// Pops from `source` and pushes onto `destination`, both of which must be locked. Nothing
+/// is popped unless the destination can take the element.
+pub(crate) fn move_element(
+    locked: &mut Locked<'_>,
+    source: &[u8],
+    destination: &[u8],
+    from: ListEnd,
+    to: ListEnd,
+) -> crate::Result<Option<Bytes>> {
+    let exists = locked.update(source, |slot| {
+        slot.as_ref()
+            .map(|entry| entry.list().map(|list| !list.is_empty()))
+            .transpose()
+    })?;
+    if exists != Some(true) {
+        return Ok(None);
+    }
+    locked.update(destination, |slot| {
+        slot.as_ref().map(Entry::list).transpose().map(|_| ())
+    })?;
+
+    let value = locked.update(source, |slot| {
+        slot.as_mut()
+            .and_then(|entry| entry.list_mut().ok())
+            .and_then(|list| from.pop(list))
+    });
+    if let Some(value) = &value {
+        locked.update(destination, |slot| {
+            if let Ok(list) = slot
+                .get_or_insert_with(|| Entry::new(Value::List(VecDeque::new())))
+                .list_mut()
+            {
+                to.push(list, value.clone());
+            }
+        });
+    }
+    Ok(value)
+}
+
+impl From<Lmove> for RESPType {
+    fn from(lmove: Lmove) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("lmove")),
+            RESPType::Bulk(lmove.source),
+            RESPType::Bulk(lmove.destination),
+            RESPType::Bulk(Bytes::from(lmove.from.name())),
+            RESPType::Bulk(Bytes::from(lmove.to.name())),
+        ])
+    }
+}
@@ -0,0 +1,78 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, Locked};
+use crate::{Db, RESPType};
+
+use super::ListEnd;
+
+/// Pops up to `count` elements from the first non-empty list among `keys`.
+pub struct Lmpop {
+    keys: Vec<Bytes>,
+    end: ListEnd,
+    count: usize,
+}
+
+impl Lmpop {
+    pub fn new(keys: Vec<Bytes>, end: ListEnd) -> Self {
+        Lmpop {
+            keys,
+            end,
+            count: 1,
+        }
+    }
+
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let mut locked = db.lock(&self.keys);
+        match pop_first(&mut locked, &self.keys, self.end, self.count) {
+            Err(e) => e.into(),
+            Ok(None) => RESPType::NullArray,
+            Ok(Some((key, values))) => RESPType::Array(vec![
+                RESPType::Bulk(key),
+                RESPType::Array(values.into_iter().map(RESPType::Bulk).collect()),
+            ]),
+        }
+    }
+}
+
+/// Pops from the first of `keys` holding a non-empty list, all of which must be locked.
+pub(crate) fn pop_first(
+    locked: &mut Locked<'_>,
+    keys: &[Bytes],
+    end: ListEnd,
+    count: usize,
+) -> crate::Result<Option<(Bytes, Vec<Bytes>)>> {
+    for key in keys {
+        let popped = locked.update(key, |slot| {
+            let list = match slot.as_mut().map(Entry::list_mut).transpose()? {
+                None => return Ok(None),
+                Some(list) => list,
+            };
+            let values: Vec<_> = std::iter::from_fn(|| end.pop(list)).take(count).collect();
+            Ok::<_, crate::Error>(Some(values).filter(|values| !values.is_empty()))
+        })?;
+        if let Some(values) = popped {
+            return Ok(Some((key.clone(), values)));
+        }
+    }
+    Ok(None)
+}
+
+impl From<Lmpop> for RESPType {
+    fn from(lmpop: Lmpop) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("lmpop")),
+            RESPType::Bulk(Bytes::from(lmpop.keys.len().to_string())),
+        ];
+        arr.extend(lmpop.keys.into_iter().map(RESPType::Bulk));
+        arr.push(RESPType::Bulk(Bytes::from(lmpop.end.name())));
+        arr.push(RESPType::Bulk(Bytes::from("COUNT")));
+        arr.push(RESPType::Bulk(Bytes::from(lmpop.count.to_string())));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,114 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Lpos {
+    key: Bytes,
+    value: Bytes,
+    rank: i64,
+    count: Option<usize>,
+    maxlen: usize,
+}
+
+impl Lpos {
+    pub fn new(key: Bytes, value: Bytes) -> Self {
+        Lpos {
+            key,
+            value,
+            rank: 1,
+            count: None,
+            maxlen: 0,
+        }
+    }
+
+    /// Skips to the `rank`th match, negative ranks search from the tail. Must not be zero.
+    pub fn rank(mut self, rank: i64) -> Self {
+        self.rank = rank;
+        self
+    }
+
+    /// Returns up to `count` matches instead of the first one, zero returns all of them.
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Compares at most `maxlen` elements, zero compares the whole list.
+    pub fn maxlen(mut self, maxlen: usize) -> Self {
+        self.maxlen = maxlen;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let list = match slot.as_ref().map(Entry::list) {
+                None => return self.no_match(),
+                Some(Err(e)) => return e.into(),
+                Some(Ok(list)) => list,
+            };
+
+            let maxlen = if self.maxlen == 0 {
+                list.len()
+            } else {
+                self.maxlen
+            };
+            let indexes: Box<dyn Iterator<Item = usize>> = if self.rank > 0 {
+                Box::new(0..list.len())
+            } else {
+                Box::new((0..list.len()).rev())
+            };
+            let skip = (self.rank.unsigned_abs() - 1)
+                .try_into()
+                .unwrap_or(usize::MAX);
+            let take = match self.count {
+                None => 1,
+                Some(0) => usize::MAX,
+                Some(count) => count,
+            };
+
+            let matches: Vec<_> = indexes
+                .take(maxlen)
+                .filter(|idx| list[*idx] == self.value)
+                .skip(skip)
+                .take(take)
+                .map(|idx| RESPType::Integer(idx as i64))
+                .collect();
+            match self.count {
+                Some(_) => RESPType::Array(matches),
+                None => matches.into_iter().next().unwrap_or(RESPType::Null),
+            }
+        })
+    }
+
+    fn no_match(&self) -> RESPType {
+        match self.count {
+            None => RESPType::Null,
+            Some(_) => RESPType::Array(vec![]),
+        }
+    }
+}
+
+impl From<Lpos> for RESPType {
+    fn from(lpos: Lpos) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("lpos")),
+            RESPType::Bulk(lpos.key),
+            RESPType::Bulk(lpos.value),
+        ];
+        if lpos.rank != 1 {
+            arr.push(RESPType::Bulk(Bytes::from("RANK")));
+            arr.push(RESPType::Bulk(Bytes::from(lpos.rank.to_string())));
+        }
+        if let Some(count) = lpos.count {
+            arr.push(RESPType::Bulk(Bytes::from("COUNT")));
+            arr.push(RESPType::Bulk(Bytes::from(count.to_string())));
+        }
+        if lpos.maxlen != 0 {
+            arr.push(RESPType::Bulk(Bytes::from("MAXLEN")));
+            arr.push(RESPType::Bulk(Bytes::from(lpos.maxlen.to_string())));
+        }
+
+        RESPType::Array(arr)
+    }
+}
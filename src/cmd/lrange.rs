@@ -0,0 +1,67 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Lrange {
+    key: Bytes,
+    start: i64,
+    stop: i64,
+}
+
+impl Lrange {
+    pub fn new(key: Bytes, start: i64, stop: i64) -> Self {
+        Lrange { key, start, stop }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let list = match slot.as_ref().map(Entry::list) {
+                None => return RESPType::Array(vec![]),
+                Some(Err(e)) => return e.into(),
+                Some(Ok(list)) => list,
+            };
+
+            RESPType::Array(match range(self.start, self.stop, list.len()) {
+                None => vec![],
+                Some((start, stop)) => list
+                    .range(start..=stop)
+                    .cloned()
+                    .map(RESPType::Bulk)
+                    .collect(),
+            })
+        })
+    }
+}
+
+/// Resolves an inclusive range where negative indexes count back from the end, `None` if
+/// it selects nothing.
+pub(crate) fn range(start: i64, stop: i64, len: usize) -> Option<(usize, usize)> {
+    let len = len as i64;
+    let start = if start < 0 {
+        (len + start).max(0)
+    } else {
+        start
+    };
+    let stop = if stop < 0 {
+        len + stop
+    } else {
+        stop.min(len - 1)
+    };
+
+    if start > stop || start >= len {
+        return None;
+    }
+    Some((start as usize, stop as usize))
+}
+
+impl From<Lrange> for RESPType {
+    fn from(lrange: Lrange) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("lrange")),
+            RESPType::Bulk(lrange.key),
+            RESPType::Bulk(Bytes::from(lrange.start.to_string())),
+            RESPType::Bulk(Bytes::from(lrange.stop.to_string())),
+        ])
+    }
+}
@@ -0,0 +1,61 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Lrem {
+    key: Bytes,
+    count: i64,
+    value: Bytes,
+}
+
+impl Lrem {
+    /// Removes `count` occurrences of `value` starting from the head, or from the tail when
+    /// `count` is negative. Zero removes all of them.
+    pub fn new(key: Bytes, count: i64, value: Bytes) -> Self {
+        Lrem { key, count, value }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let list = match slot.as_mut().map(Entry::list_mut) {
+                None => return RESPType::Integer(0),
+                Some(Err(e)) => return e.into(),
+                Some(Ok(list)) => list,
+            };
+
+            let limit = match self.count {
+                0 => usize::MAX,
+                n => n.unsigned_abs().try_into().unwrap_or(usize::MAX),
+            };
+            let mut matches: Vec<usize> = list
+                .iter()
+                .enumerate()
+                .filter(|(_, value)| **value == self.value)
+                .map(|(idx, _)| idx)
+                .collect();
+            if self.count < 0 {
+                matches.reverse();
+            }
+            matches.truncate(limit);
+            matches.sort_unstable();
+
+            // remove back to front so the remaining indexes stay valid
+            for idx in matches.iter().rev() {
+                list.remove(*idx);
+            }
+            RESPType::Integer(matches.len() as i64)
+        })
+    }
+}
+
+impl From<Lrem> for RESPType {
+    fn from(lrem: Lrem) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("lrem")),
+            RESPType::Bulk(lrem.key),
+            RESPType::Bulk(Bytes::from(lrem.count.to_string())),
+            RESPType::Bulk(lrem.value),
+        ])
+    }
+}
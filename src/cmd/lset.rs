@@ -0,0 +1,47 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, Error, RESPType};
+
+use super::lindex::index;
+
+pub struct Lset {
+    key: Bytes,
+    index: i64,
+    value: Bytes,
+}
+
+impl Lset {
+    pub fn new(key: Bytes, index: i64, value: Bytes) -> Self {
+        Lset { key, index, value }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let list = match slot.as_mut().map(Entry::list_mut) {
+                None => return Error::command("no such key").into(),
+                Some(Err(e)) => return e.into(),
+                Some(Ok(list)) => list,
+            };
+
+            match index(self.index, list.len()) {
+                None => Error::command("index out of range").into(),
+                Some(idx) => {
+                    list[idx] = self.value.clone();
+                    RESPType::String("OK".into())
+                }
+            }
+        })
+    }
+}
+
+impl From<Lset> for RESPType {
+    fn from(lset: Lset) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("lset")),
+            RESPType::Bulk(lset.key),
+            RESPType::Bulk(Bytes::from(lset.index.to_string())),
+            RESPType::Bulk(lset.value),
+        ])
+    }
+}
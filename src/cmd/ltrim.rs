@@ -0,0 +1,46 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+use super::lrange::range;
+
+pub struct Ltrim {
+    key: Bytes,
+    start: i64,
+    stop: i64,
+}
+
+impl Ltrim {
+    pub fn new(key: Bytes, start: i64, stop: i64) -> Self {
+        Ltrim { key, start, stop }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            match slot.as_mut().map(Entry::list_mut) {
+                None => {}
+                Some(Err(e)) => return e.into(),
+                Some(Ok(list)) => match range(self.start, self.stop, list.len()) {
+                    None => list.clear(),
+                    Some((start, stop)) => {
+                        list.truncate(stop + 1);
+                        list.drain(..start);
+                    }
+                },
+            }
+            RESPType::String("OK".into())
+        })
+    }
+}
+
+impl From<Ltrim> for RESPType {
+    fn from(ltrim: Ltrim) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("ltrim")),
+            RESPType::Bulk(ltrim.key),
+            RESPType::Bulk(Bytes::from(ltrim.start.to_string())),
+            RESPType::Bulk(Bytes::from(ltrim.stop.to_string())),
+        ])
+    }
+}
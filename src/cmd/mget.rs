@@ -0,0 +1,39 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Mget {
+    keys: Vec<Bytes>,
+}
+
+impl Mget {
+    pub fn new(keys: Vec<Bytes>) -> Self {
+        Mget { keys }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let mut locked = db.lock(&self.keys);
+        RESPType::Array(
+            self.keys
+                .iter()
+                .map(|key| {
+                    // keys of other types read as missing rather than failing the batch
+                    locked.update(key, |slot| match slot.as_ref().map(Entry::string) {
+                        Some(Ok(value)) => RESPType::Bulk(value.clone()),
+                        _ => RESPType::Null,
+                    })
+                })
+                .collect(),
+        )
+    }
+}
+
+impl From<Mget> for RESPType {
+    fn from(mget: Mget) -> RESPType {
+        let mut arr = vec![RESPType::Bulk(Bytes::from("mget"))];
+        arr.extend(mget.keys.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
@@ -8,7 +8,359 @@ mod get;
 pub use get::Get;
 
 mod set;
-pub use set::Set;
+pub use set::{Set, SetCondition, SetExpiry};
+
+mod setnx;
+pub use setnx::SetNx;
+
+mod getdel;
+pub use getdel::GetDel;
+
+mod getex;
+pub use getex::{GetEx, GetExExpiry};
+
+mod incr;
+pub use incr::Incr;
+
+mod incrbyfloat;
+pub use incrbyfloat::IncrByFloat;
+
+mod append;
+pub use append::Append;
+
+mod strlen;
+pub use strlen::Strlen;
+
+mod getrange;
+pub use getrange::GetRange;
+
+mod setrange;
+pub use setrange::SetRange;
+
+mod mget;
+pub use mget::Mget;
+
+mod mset;
+pub use mset::Mset;
+
+mod msetnx;
+pub use msetnx::MsetNx;
+
+mod push;
+pub use push::{ListEnd, Push};
+
+mod pop;
+pub use pop::Pop;
+
+mod llen;
+pub use llen::Llen;
+
+mod lrange;
+pub use lrange::Lrange;
+
+mod lindex;
+pub use lindex::Lindex;
+
+mod lset;
+pub use lset::Lset;
+
+mod lrem;
+pub use lrem::Lrem;
+
+mod ltrim;
+pub use ltrim::Ltrim;
+
+mod linsert;
+pub use linsert::Linsert;
+
+mod lpos;
+pub use lpos::Lpos;
+
+mod lmove;
+pub use lmove::Lmove;
+
+mod lmpop;
+pub use lmpop::Lmpop;
+
+mod bpop;
+pub use bpop::Bpop;
+
+mod blmove;
+pub use blmove::Blmove;
+
+mod blmpop;
+pub use blmpop::Blmpop;
+
+mod hset;
+pub use hset::Hset;
+
+mod hsetnx;
+pub use hsetnx::HsetNx;
+
+mod hget;
+pub use hget::Hget;
+
+mod hmget;
+pub use hmget::Hmget;
+
+mod hdel;
+pub use hdel::Hdel;
+
+mod hexists;
+pub use hexists::Hexists;
+
+mod hlen;
+pub use hlen::Hlen;
+
+mod hstrlen;
+pub use hstrlen::Hstrlen;
+
+mod hgetall;
+pub use hgetall::{HgetAll, HgetAllKind};
+
+mod hincrby;
+pub use hincrby::Hincrby;
+
+mod hincrbyfloat;
+pub use hincrbyfloat::HincrbyFloat;
+
+mod hrandfield;
+pub use hrandfield::HrandField;
+
+mod hscan;
+pub use hscan::Hscan;
+
+mod hexpire;
+pub use hexpire::Hexpire;
+
+mod httl;
+pub use httl::Httl;
+
+mod hpersist;
+pub use hpersist::Hpersist;
+
+mod sadd;
+pub use sadd::Sadd;
+
+mod srem;
+pub use srem::Srem;
+
+mod scard;
+pub use scard::Scard;
+
+mod smembers;
+pub use smembers::Smembers;
+
+mod sismember;
+pub use sismember::Sismember;
+
+mod smismember;
+pub use smismember::Smismember;
+
+mod spop;
+pub use spop::Spop;
+
+mod srandmember;
+pub use srandmember::SrandMember;
+
+mod smove;
+pub use smove::Smove;
+
+mod sscan;
+pub use sscan::Sscan;
+
+mod setop;
+pub use setop::{SetOp, SetOpKind};
+
+mod sintercard;
+pub use sintercard::SinterCard;
+
+mod zadd;
+pub use zadd::{ScoreComparison, Zadd};
+
+mod zrem;
+pub use zrem::Zrem;
+
+mod zscore;
+pub use zscore::Zscore;
+
+mod zmscore;
+pub use zmscore::Zmscore;
+
+mod zincrby;
+pub use zincrby::Zincrby;
+
+mod zcard;
+pub use zcard::Zcard;
+
+mod zrank;
+pub use zrank::Zrank;
+
+mod zcount;
+pub use zcount::{Zcount, ZlexCount};
+
+mod zrange;
+pub use crate::db::{LexBound, ScoreBound};
+pub use zrange::{Zrange, ZrangeBy};
+
+mod zpop;
+pub use zpop::Zpop;
+
+mod bzpop;
+pub use bzpop::Bzpop;
+
+mod zsetop;
+pub use zsetop::{Aggregate, ZsetOp, ZsetOpKind};
+
+mod xtrim;
+pub use crate::db::{StreamId, TrimStrategy};
+pub use xtrim::{StreamTrim, Xtrim};
+
+mod xadd;
+pub use xadd::{Xadd, XaddId};
+
+mod xrange;
+pub use xrange::Xrange;
+
+mod xlen;
+pub use xlen::Xlen;
+
+mod xdel;
+pub use xdel::Xdel;
+
+mod xread;
+pub use xread::{StartId, Xread};
+
+mod xreadgroup;
+pub use xreadgroup::XreadGroup;
+
+mod xgroup;
+pub use xgroup::{Xgroup, XgroupOp};
+
+mod xack;
+pub use xack::Xack;
+
+mod xpending;
+pub use xpending::Xpending;
+
+mod xclaim;
+pub use xclaim::Xclaim;
+
+mod xautoclaim;
+pub use xautoclaim::XautoClaim;
+
+mod xinfo;
+pub use xinfo::{Xinfo, XinfoKind};
+
+mod setbit;
+pub use setbit::Setbit;
+
+mod getbit;
+pub use getbit::Getbit;
+
+mod bitcount;
+pub use bitcount::{BitUnit, Bitcount};
+
+mod bitpos;
+pub use bitpos::Bitpos;
+
+mod bitop;
+pub use bitop::{Bitop, BitopKind};
+
+mod bitfield;
+pub use bitfield::{Bitfield, BitfieldOp, BitfieldType, Overflow};
+
+mod pfadd;
+pub use pfadd::Pfadd;
+
+mod pfcount;
+pub use pfcount::Pfcount;
+
+mod pfmerge;
+pub use pfmerge::Pfmerge;
+
+mod geo;
+pub use geo::GeoUnit;
+
+mod geoadd;
+pub use geoadd::Geoadd;
+
+mod geodist;
+pub use geodist::Geodist;
+
+mod geopos;
+pub use geopos::Geopos;
+
+mod geohash;
+pub use geohash::Geohash;
+
+mod geosearch;
+pub use geosearch::{GeoOrigin, GeoShape, GeoSort, Geosearch};
+
+mod del;
+pub use del::Del;
+
+mod exists;
+pub use exists::Exists;
+
+mod keytype;
+pub use keytype::Type;
+
+mod rename;
+pub use rename::Rename;
+
+mod copy;
+pub use copy::CopyKey;
+
+mod dbsize;
+pub use dbsize::Dbsize;
+
+mod flush;
+pub use flush::Flush;
+
+mod randomkey;
+pub use randomkey::RandomKey;
+
+mod keys;
+pub use keys::Keys;
+
+mod scan;
+pub use scan::Scan;
+
+mod select;
+pub use select::Select;
+
+mod movekey;
+pub use movekey::Move;
+
+mod swapdb;
+pub use swapdb::Swapdb;
+
+mod info;
+pub use info::Info;
+
+mod object;
+pub use object::{Object, ObjectKind};
+
+mod expire;
+pub use expire::{Expire, ExpireCondition, ExpireUnit};
+
+mod ttl;
+pub use ttl::{Ttl, TtlKind};
+
+mod persist;
+pub use persist::Persist;
+
+mod glob;
+
+mod hello;
+pub use hello::Hello;
 
 mod command;
-pub use command::Command;
+pub use command::{check_memory, Command};
+
+mod command_info;
+pub use command_info::{CommandInfo, CommandInfoKind};
+
+mod registry;
+pub use registry::{commands, lookup, resolve, CommandFlag, CommandSpec};
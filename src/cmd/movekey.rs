@@ -0,0 +1,45 @@
+use bytes::Bytes;
+
+use crate::{Db, Error, RESPType};
+
+use super::select::select_db;
+
+/// MOVE, transferring a key with its ttl to another database unless it exists there.
+pub struct Move {
+    key: Bytes,
+    db: i64,
+}
+
+impl Move {
+    pub fn new(key: Bytes, db: i64) -> Self {
+        Move { key, db }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let target = match select_db(db, self.db) {
+            Err(e) => return e.into(),
+            Ok(target) => target,
+        };
+        if target.index() == db.index() {
+            return Error::command("source and destination objects are the same").into();
+        }
+
+        db.update_across(&self.key, &target, &self.key, |slot, target_slot| {
+            if slot.is_none() || target_slot.is_some() {
+                return RESPType::Integer(0);
+            }
+            *target_slot = slot.take();
+            RESPType::Integer(1)
+        })
+    }
+}
+
+impl From<Move> for RESPType {
+    fn from(mv: Move) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("move")),
+            RESPType::Bulk(mv.key),
+            RESPType::Bulk(Bytes::from(mv.db.to_string())),
+        ])
+    }
+}
@@ -0,0 +1,46 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, Locked, Value};
+use crate::{Db, RESPType};
+
+pub struct Mset {
+    pairs: Vec<(Bytes, Bytes)>,
+}
+
+impl Mset {
+    pub fn new(pairs: Vec<(Bytes, Bytes)>) -> Self {
+        Mset { pairs }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let keys: Vec<_> = self.pairs.iter().map(|(key, _)| key).collect();
+        let mut locked = db.lock(&keys);
+        set_all(&mut locked, &self.pairs);
+
+        RESPType::String("OK".into())
+    }
+}
+
+/// Stores every pair without an expiry, later pairs win over earlier ones with the same key.
+pub(crate) fn set_all(locked: &mut Locked<'_>, pairs: &[(Bytes, Bytes)]) {
+    for (key, value) in pairs {
+        locked.update(key, |slot| {
+            // like SET, an overwritten key keeps its access history
+            let entry = slot.get_or_insert_with(|| Entry::new(Value::String(Bytes::new())));
+            entry.value = Value::String(value.clone());
+            entry.expires_at = None;
+        });
+    }
+}
+
+impl From<Mset> for RESPType {
+    fn from(mset: Mset) -> RESPType {
+        let mut arr = vec![RESPType::Bulk(Bytes::from("mset"))];
+        for (key, value) in mset.pairs {
+            arr.push(RESPType::Bulk(key));
+            arr.push(RESPType::Bulk(value));
+        }
+
+        RESPType::Array(arr)
+    }
+}
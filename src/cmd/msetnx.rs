@@ -0,0 +1,43 @@
+use bytes::Bytes;
+
+use crate::{Db, RESPType};
+
+use super::mset::set_all;
+
+pub struct MsetNx {
+    pairs: Vec<(Bytes, Bytes)>,
+}
+
+impl MsetNx {
+    pub fn new(pairs: Vec<(Bytes, Bytes)>) -> Self {
+        MsetNx { pairs }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let keys: Vec<_> = self.pairs.iter().map(|(key, _)| key).collect();
+        let mut locked = db.lock(&keys);
+
+        // nothing is written unless every key is missing
+        if keys
+            .iter()
+            .any(|key| locked.update(key, |slot| slot.is_some()))
+        {
+            return RESPType::Integer(0);
+        }
+        set_all(&mut locked, &self.pairs);
+
+        RESPType::Integer(1)
+    }
+}
+
+impl From<MsetNx> for RESPType {
+    fn from(msetnx: MsetNx) -> RESPType {
+        let mut arr = vec![RESPType::Bulk(Bytes::from("msetnx"))];
+        for (key, value) in msetnx.pairs {
+            arr.push(RESPType::Bulk(key));
+            arr.push(RESPType::Bulk(value));
+        }
+
+        RESPType::Array(arr)
+    }
+}
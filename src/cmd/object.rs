@@ -0,0 +1,70 @@
+use bytes::Bytes;
+
+use crate::db::now_ms;
+use crate::{Db, Error, RESPType};
+
+/// Policy caveat redis appends to the errors of OBJECT FREQ and IDLETIME.
+const POLICY_SWITCH_NOTE: &str = "Please note that when switching between policies at runtime \
+                                  LRU and LFU data will take some time to adjust.";
+
+pub enum ObjectKind {
+    Freq,
+    IdleTime,
+}
+
+/// OBJECT FREQ or IDLETIME, the eviction data of a key. Neither counts as an access.
+pub struct Object {
+    key: Bytes,
+    kind: ObjectKind,
+}
+
+impl Object {
+    pub fn new(key: Bytes, kind: ObjectKind) -> Self {
+        Object { key, kind }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        // only the data the policy ranks keys by is meaningful
+        let lfu = db.eviction_policy().is_lfu();
+        match (&self.kind, lfu) {
+            (ObjectKind::Freq, false) => {
+                return Error::command(format!(
+                    "An LFU maxmemory policy is not selected, access frequency not tracked. \
+                     {POLICY_SWITCH_NOTE}"
+                ))
+                .into()
+            }
+            (ObjectKind::IdleTime, true) => {
+                return Error::command(format!(
+                    "An LFU maxmemory policy is selected, idle time not tracked. \
+                     {POLICY_SWITCH_NOTE}"
+                ))
+                .into()
+            }
+            _ => {}
+        }
+
+        let now = now_ms();
+        db.peek(&self.key, |entry| match entry {
+            None => RESPType::Null,
+            Some(entry) => match self.kind {
+                ObjectKind::Freq => RESPType::Integer(entry.frequency(now) as i64),
+                ObjectKind::IdleTime => RESPType::Integer((entry.idle_ms(now) / 1000) as i64),
+            },
+        })
+    }
+}
+
+impl From<Object> for RESPType {
+    fn from(object: Object) -> RESPType {
+        let kind = match object.kind {
+            ObjectKind::Freq => "FREQ",
+            ObjectKind::IdleTime => "IDLETIME",
+        };
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("object")),
+            RESPType::Bulk(Bytes::from(kind)),
+            RESPType::Bulk(object.key),
+        ])
+    }
+}
@@ -0,0 +1,32 @@
+use bytes::Bytes;
+
+use crate::{Db, RESPType};
+
+pub struct Persist {
+    key: Bytes,
+}
+
+impl Persist {
+    pub fn new(key: Bytes) -> Self {
+        Persist { key }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| match slot {
+            Some(entry) if entry.expires_at.is_some() => {
+                entry.expires_at = None;
+                RESPType::Integer(1)
+            }
+            _ => RESPType::Integer(0),
+        })
+    }
+}
+
+impl From<Persist> for RESPType {
+    fn from(persist: Persist) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("persist")),
+            RESPType::Bulk(persist.key),
+        ])
+    }
+}
@@ -0,0 +1,55 @@
+use bytes::Bytes;
+
+use crate::db::{set_keep_ttl, Entry, Hll, Value};
+use crate::{Db, RESPType};
+
+/// PFADD, creating the HyperLogLog if the key doesn't exist.
+pub struct Pfadd {
+    key: Bytes,
+    elements: Vec<Bytes>,
+}
+
+impl Pfadd {
+    pub fn new(key: Bytes, elements: Vec<Bytes>) -> Self {
+        Pfadd { key, elements }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let (mut hll, mut changed) = match stored_hll(slot) {
+                Err(e) => return e.into(),
+                Ok(Some(hll)) => (hll, false),
+                Ok(None) => (Hll::default(), true),
+            };
+            for element in &self.elements {
+                changed |= hll.add(element);
+            }
+
+            if changed {
+                set_keep_ttl(slot, Value::String(hll.encode()));
+            }
+            RESPType::Integer(changed as i64)
+        })
+    }
+}
+
+/// The HyperLogLog stored in `slot`, `None` if the key doesn't exist.
+pub(crate) fn stored_hll(slot: &Option<Entry>) -> crate::Result<Option<Hll>> {
+    match slot.as_ref().map(Entry::string) {
+        None => Ok(None),
+        Some(Err(e)) => Err(e),
+        Some(Ok(value)) => Hll::parse(value).map(Some),
+    }
+}
+
+impl From<Pfadd> for RESPType {
+    fn from(pfadd: Pfadd) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("pfadd")),
+            RESPType::Bulk(pfadd.key),
+        ];
+        arr.extend(pfadd.elements.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
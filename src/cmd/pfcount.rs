@@ -0,0 +1,60 @@
+use bytes::Bytes;
+
+use crate::db::{cache_count, set_keep_ttl, Entry, Hll, Value};
+use crate::{Db, RESPType};
+
+use super::pfadd::stored_hll;
+
+/// PFCOUNT. A single key answers from the cardinality cached in its header, refreshing
+/// the cache when stale; several keys are estimated from the union of their registers.
+pub struct Pfcount {
+    keys: Vec<Bytes>,
+}
+
+impl Pfcount {
+    pub fn new(keys: Vec<Bytes>) -> Self {
+        Pfcount { keys }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        if let [key] = &self.keys[..] {
+            return db.update(key, |slot| {
+                let hll = match stored_hll(slot) {
+                    Err(e) => return e.into(),
+                    Ok(None) => return RESPType::Integer(0),
+                    Ok(Some(hll)) => hll,
+                };
+                if let Some(count) = hll.cached() {
+                    return RESPType::Integer(count as i64);
+                }
+
+                let count = hll.estimate();
+                if let Some(Ok(value)) = slot.as_ref().map(Entry::string) {
+                    let value = cache_count(value, count);
+                    set_keep_ttl(slot, Value::String(value));
+                }
+                RESPType::Integer(count as i64)
+            });
+        }
+
+        let mut locked = db.lock(&self.keys);
+        let mut union = Hll::default();
+        for key in &self.keys {
+            match locked.update(key, |slot| stored_hll(slot)) {
+                Err(e) => return e.into(),
+                Ok(None) => {}
+                Ok(Some(hll)) => union.merge(&hll),
+            }
+        }
+        RESPType::Integer(union.estimate() as i64)
+    }
+}
+
+impl From<Pfcount> for RESPType {
+    fn from(pfcount: Pfcount) -> RESPType {
+        let mut arr = vec![RESPType::Bulk(Bytes::from("pfcount"))];
+        arr.extend(pfcount.keys.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
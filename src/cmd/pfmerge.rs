@@ -0,0 +1,54 @@
+use bytes::Bytes;
+
+use crate::db::{set_keep_ttl, Hll, Value};
+use crate::{Db, RESPType};
+
+use super::pfadd::stored_hll;
+
+/// PFMERGE, storing the union of the sources and the destination's own registers at the
+/// destination. The result is dense as soon as any input is.
+pub struct Pfmerge {
+    destination: Bytes,
+    sources: Vec<Bytes>,
+}
+
+impl Pfmerge {
+    pub fn new(destination: Bytes, sources: Vec<Bytes>) -> Self {
+        Pfmerge {
+            destination,
+            sources,
+        }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let mut keys = vec![self.destination.clone()];
+        keys.extend(self.sources.iter().cloned());
+        let mut locked = db.lock(&keys);
+
+        let mut union = Hll::default();
+        for key in &keys {
+            match locked.update(key, |slot| stored_hll(slot)) {
+                Err(e) => return e.into(),
+                Ok(None) => {}
+                Ok(Some(hll)) => union.merge(&hll),
+            }
+        }
+
+        locked.update(&self.destination, |slot| {
+            set_keep_ttl(slot, Value::String(union.encode()));
+        });
+        RESPType::String("OK".into())
+    }
+}
+
+impl From<Pfmerge> for RESPType {
+    fn from(pfmerge: Pfmerge) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("pfmerge")),
+            RESPType::Bulk(pfmerge.destination),
+        ];
+        arr.extend(pfmerge.sources.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
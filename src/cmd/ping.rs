@@ -19,6 +19,7 @@ impl Ping {
     }
 }
 
+#[allow(clippy::from_over_into, clippy::unnecessary_unwrap)]
 impl Into<RESPType> for Ping {
     fn into(self) -> RESPType {
         let mut arr = vec![];
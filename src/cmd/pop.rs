@@ -0,0 +1,68 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+use super::ListEnd;
+
+/// LPOP and RPOP, which reply with an array instead of a single element when given a count.
+pub struct Pop {
+    key: Bytes,
+    end: ListEnd,
+    count: Option<usize>,
+}
+
+impl Pop {
+    pub fn new(key: Bytes, end: ListEnd) -> Self {
+        Pop {
+            key,
+            end,
+            count: None,
+        }
+    }
+
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let list = match slot.as_mut().map(Entry::list_mut) {
+                // with a count the reply is an array, even when there is none
+                None if self.count.is_some() => return RESPType::NullArray,
+                None => return RESPType::Null,
+                Some(Err(e)) => return e.into(),
+                Some(Ok(list)) => list,
+            };
+
+            match self.count {
+                None => match self.end.pop(list) {
+                    None => RESPType::Null,
+                    Some(value) => RESPType::Bulk(value),
+                },
+                Some(count) => RESPType::Array(
+                    std::iter::from_fn(|| self.end.pop(list))
+                        .take(count)
+                        .map(RESPType::Bulk)
+                        .collect(),
+                ),
+            }
+        })
+    }
+}
+
+impl From<Pop> for RESPType {
+    fn from(pop: Pop) -> RESPType {
+        let name = match pop.end {
+            ListEnd::Left => "lpop",
+            ListEnd::Right => "rpop",
+        };
+        let mut arr = vec![RESPType::Bulk(Bytes::from(name)), RESPType::Bulk(pop.key)];
+        if let Some(count) = pop.count {
+            arr.push(RESPType::Bulk(Bytes::from(count.to_string())));
+        }
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::VecDeque;
+
+use bytes::Bytes;
+
+use crate::db::{Entry, Value};
+use crate::{Db, RESPType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListEnd {
+    Left,
+    Right,
+}
+
+impl ListEnd {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ListEnd::Left => "LEFT",
+            ListEnd::Right => "RIGHT",
+        }
+    }
+
+    pub(crate) fn push(&self, list: &mut VecDeque<Bytes>, value: Bytes) {
+        match self {
+            ListEnd::Left => list.push_front(value),
+            ListEnd::Right => list.push_back(value),
+        }
+    }
+
+    pub(crate) fn pop(&self, list: &mut VecDeque<Bytes>) -> Option<Bytes> {
+        match self {
+            ListEnd::Left => list.pop_front(),
+            ListEnd::Right => list.pop_back(),
+        }
+    }
+}
+
+/// LPUSH, RPUSH and, when restricted to existing lists, LPUSHX and RPUSHX.
+pub struct Push {
+    key: Bytes,
+    values: Vec<Bytes>,
+    end: ListEnd,
+    existing: bool,
+}
+
+impl Push {
+    pub fn new(key: Bytes, values: Vec<Bytes>, end: ListEnd) -> Self {
+        Push {
+            key,
+            values,
+            end,
+            existing: false,
+        }
+    }
+
+    /// Only push if the list already exists.
+    pub fn existing(mut self, existing: bool) -> Self {
+        self.existing = existing;
+        self
+    }
+
+    pub fn name(&self) -> &'static str {
+        match (self.end, self.existing) {
+            (ListEnd::Left, false) => "lpush",
+            (ListEnd::Right, false) => "rpush",
+            (ListEnd::Left, true) => "lpushx",
+            (ListEnd::Right, true) => "rpushx",
+        }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            if slot.is_none() && self.existing {
+                return RESPType::Integer(0);
+            }
+
+            let entry = slot.get_or_insert_with(|| Entry::new(Value::List(VecDeque::new())));
+            let list = match entry.list_mut() {
+                Err(e) => return e.into(),
+                Ok(list) => list,
+            };
+            for value in &self.values {
+                self.end.push(list, value.clone());
+            }
+            RESPType::Integer(list.len() as i64)
+        })
+    }
+}
+
+impl From<Push> for RESPType {
+    fn from(push: Push) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from(push.name())),
+            RESPType::Bulk(push.key),
+        ];
+        arr.extend(push.values.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,22 @@
+use bytes::Bytes;
+
+use crate::{Db, RESPType};
+
+#[derive(Default)]
+pub struct RandomKey;
+
+impl RandomKey {
+    pub fn new() -> Self {
+        RandomKey
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.random_key().map_or(RESPType::Null, RESPType::Bulk)
+    }
+}
+
+impl From<RandomKey> for RESPType {
+    fn from(_: RandomKey) -> RESPType {
+        RESPType::Array(vec![RESPType::Bulk(Bytes::from("randomkey"))])
+    }
+}
@@ -0,0 +1,2595 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use bytes::Bytes;
+
+use crate::{Error, RESPType};
+
+use super::command::*;
+use super::{
+    CommandInfo, CommandInfoKind, ExpireUnit, HgetAllKind, ListEnd, Mset, MsetNx, SetOpKind,
+    TtlKind, ZsetOpKind,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandFlag {
+    Write,
+    Readonly,
+    Denyoom,
+    Admin,
+    Noscript,
+    Blocking,
+    Loading,
+    Stale,
+    Fast,
+    NoAuth,
+    AllowBusy,
+}
+
+impl CommandFlag {
+    /// Name of the flag as reported by redis.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CommandFlag::Write => "write",
+            CommandFlag::Readonly => "readonly",
+            CommandFlag::Denyoom => "denyoom",
+            CommandFlag::Admin => "admin",
+            CommandFlag::Noscript => "noscript",
+            CommandFlag::Blocking => "blocking",
+            CommandFlag::Loading => "loading",
+            CommandFlag::Stale => "stale",
+            CommandFlag::Fast => "fast",
+            CommandFlag::NoAuth => "no_auth",
+            CommandFlag::AllowBusy => "allow_busy",
+        }
+    }
+}
+
+/// Static description of a command together with the parser building it from a request.
+pub struct CommandSpec {
+    pub name: &'static str,
+    /// Number of arguments including the command name, negative means at least that many.
+    pub arity: i64,
+    pub flags: &'static [CommandFlag],
+    /// Position of the first key, 0 if the command takes none.
+    pub first_key: i64,
+    /// Position of the last key, negative counts back from the last argument.
+    pub last_key: i64,
+    pub step: i64,
+    /// Position of the argument counting the keys that follow it, for commands like LMPOP
+    /// whose keys can't be found from the arity alone. 0 if the keys are at fixed positions.
+    pub numkeys: i64,
+    /// Key spec flags such as `RW` and `update`, describing how the keys are accessed.
+    pub key_flags: &'static [&'static str],
+    /// Key spec flags of the keys counted by the `numkeys` argument.
+    pub numkeys_flags: &'static [&'static str],
+    /// Keyword followed by the keys and then as many other arguments, as the IDs after
+    /// `STREAMS` in XREAD. Empty if the command has no such keyword.
+    pub keyword: &'static str,
+    /// Key spec flags of the keys following the keyword.
+    pub keyword_flags: &'static [&'static str],
+    pub categories: &'static [&'static str],
+    pub tips: &'static [&'static str],
+    pub group: &'static str,
+    pub since: &'static str,
+    pub summary: &'static str,
+    pub complexity: &'static str,
+    pub subcommands: &'static [CommandSpec],
+    pub(crate) parse: fn(Vec<RESPType>) -> crate::Result<Command>,
+}
+
+// public functions
+impl CommandSpec {
+    /// Whether a request with `argc` arguments, name included, satisfies the arity.
+    pub fn accepts(&self, argc: usize) -> bool {
+        let argc = argc as i64;
+        if self.arity < 0 {
+            argc >= -self.arity
+        } else {
+            argc == self.arity
+        }
+    }
+
+    pub fn has_flag(&self, flag: CommandFlag) -> bool {
+        self.flags.contains(&flag)
+    }
+
+    /// Whether the key positions depend on the arguments rather than the arity.
+    pub fn movable_keys(&self) -> bool {
+        self.numkeys > 0 || !self.keyword.is_empty()
+    }
+
+    /// Indexes of the keys in the request `args`.
+    pub fn key_positions(&self, args: &[Bytes]) -> Vec<usize> {
+        let mut positions = vec![];
+        if self.first_key > 0 {
+            let argc = args.len() as i64;
+            let last = if self.last_key < 0 {
+                argc + self.last_key
+            } else {
+                self.last_key.min(argc - 1)
+            };
+            positions.extend(
+                (self.first_key..=last)
+                    .step_by(self.step.max(1) as usize)
+                    .map(|idx| idx as usize),
+            );
+        }
+        if self.numkeys > 0 {
+            let idx = self.numkeys as usize;
+            let count = args
+                .get(idx)
+                .and_then(|count| atoi::atoi::<usize>(count))
+                .unwrap_or(0);
+            positions.extend((idx + 1..args.len()).take(count));
+        }
+        if !self.keyword.is_empty() {
+            let found = args
+                .iter()
+                .skip(1)
+                .position(|arg| arg.eq_ignore_ascii_case(self.keyword.as_bytes()));
+            if let Some(idx) = found.map(|idx| idx + 2) {
+                positions.extend(idx..idx + (args.len() - idx) / 2);
+            }
+        }
+        positions
+    }
+}
+
+// private table building functions
+impl CommandSpec {
+    const fn new(
+        name: &'static str,
+        arity: i64,
+        parse: fn(Vec<RESPType>) -> crate::Result<Command>,
+    ) -> Self {
+        CommandSpec {
+            name,
+            arity,
+            flags: &[],
+            first_key: 0,
+            last_key: 0,
+            step: 0,
+            numkeys: 0,
+            key_flags: &[],
+            numkeys_flags: &[],
+            keyword: "",
+            keyword_flags: &[],
+            categories: &[],
+            tips: &[],
+            group: "",
+            since: "",
+            summary: "",
+            complexity: "",
+            subcommands: &[],
+            parse,
+        }
+    }
+
+    const fn flags(mut self, flags: &'static [CommandFlag]) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    const fn keys(
+        mut self,
+        first: i64,
+        last: i64,
+        step: i64,
+        flags: &'static [&'static str],
+    ) -> Self {
+        self.first_key = first;
+        self.last_key = last;
+        self.step = step;
+        self.key_flags = flags;
+        self
+    }
+
+    /// Keys counted by the argument at `index` and following it, as in `LMPOP numkeys key ...`.
+    /// Can be combined with fixed keys before `index`, like the destination of ZUNIONSTORE.
+    const fn numkeys(mut self, index: i64, flags: &'static [&'static str]) -> Self {
+        self.numkeys = index;
+        self.numkeys_flags = flags;
+        self
+    }
+
+    /// Keys following `keyword`, each paired with an argument after the last key.
+    const fn keyword(mut self, keyword: &'static str, flags: &'static [&'static str]) -> Self {
+        self.keyword = keyword;
+        self.keyword_flags = flags;
+        self
+    }
+
+    const fn acl(mut self, categories: &'static [&'static str]) -> Self {
+        self.categories = categories;
+        self
+    }
+
+    const fn tips(mut self, tips: &'static [&'static str]) -> Self {
+        self.tips = tips;
+        self
+    }
+
+    const fn doc(
+        mut self,
+        group: &'static str,
+        since: &'static str,
+        summary: &'static str,
+        complexity: &'static str,
+    ) -> Self {
+        self.group = group;
+        self.since = since;
+        self.summary = summary;
+        self.complexity = complexity;
+        self
+    }
+
+    const fn subcommands(mut self, subcommands: &'static [CommandSpec]) -> Self {
+        self.subcommands = subcommands;
+        self
+    }
+}
+
+use CommandFlag::*;
+
+static COMMAND_SUBCOMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("command|count", 2, |_| {
+        Ok(Command::CommandInfo(CommandInfo::new(
+            CommandInfoKind::Count,
+        )))
+    })
+    .flags(&[Loading, Stale])
+    .acl(&["@slow", "@connection"])
+    .doc("server", "2.8.13", "Returns a count of commands.", "O(1)"),
+    CommandSpec::new("command|docs", -2, |arr| {
+        Ok(Command::CommandInfo(CommandInfo::new(
+            CommandInfoKind::Docs(bytes_args(&arr[2..])?),
+        )))
+    })
+    .flags(&[Loading, Stale])
+    .acl(&["@slow", "@connection"])
+    .tips(&["nondeterministic_output_order"])
+    .doc(
+        "server",
+        "7.0.0",
+        "Returns documentary information about one, multiple or all commands.",
+        "O(N) where N is the number of commands to look up",
+    ),
+    CommandSpec::new("command|getkeys", -3, |arr| {
+        Ok(Command::CommandInfo(CommandInfo::new(
+            CommandInfoKind::GetKeys(bytes_args(&arr[2..])?),
+        )))
+    })
+    .flags(&[Loading, Stale])
+    .acl(&["@slow", "@connection"])
+    .doc(
+        "server",
+        "2.8.13",
+        "Extracts the key names from an arbitrary command.",
+        "O(N) where N is the number of arguments to the command",
+    ),
+    CommandSpec::new("command|help", 2, |_| {
+        Ok(Command::CommandInfo(CommandInfo::new(
+            CommandInfoKind::Help,
+        )))
+    })
+    .flags(&[Loading, Stale])
+    .acl(&["@slow", "@connection"])
+    .doc(
+        "server",
+        "5.0.0",
+        "Returns helpful text about the different subcommands.",
+        "O(1)",
+    ),
+    CommandSpec::new("command|info", -2, |arr| {
+        Ok(Command::CommandInfo(CommandInfo::new(
+            CommandInfoKind::Info(bytes_args(&arr[2..])?),
+        )))
+    })
+    .flags(&[Loading, Stale])
+    .acl(&["@slow", "@connection"])
+    .tips(&["nondeterministic_output_order"])
+    .doc(
+        "server",
+        "2.8.13",
+        "Returns information about one, multiple or all commands.",
+        "O(N) where N is the number of commands to look up",
+    ),
+    CommandSpec::new("command|list", -2, |_| {
+        Ok(Command::CommandInfo(CommandInfo::new(
+            CommandInfoKind::List,
+        )))
+    })
+    .flags(&[Loading, Stale])
+    .acl(&["@slow", "@connection"])
+    .tips(&["nondeterministic_output_order"])
+    .doc(
+        "server",
+        "7.0.0",
+        "Returns a list of command names.",
+        "O(N) where N is the total number of Redis commands",
+    ),
+];
+
+static XGROUP_SUBCOMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("xgroup|create", -5, |arr| {
+        Ok(Command::Xgroup(try_xgroup(arr)?))
+    })
+    .flags(&[Write, Denyoom])
+    .keys(2, 2, 1, &["RW", "insert"])
+    .acl(&["@write", "@stream", "@slow"])
+    .doc("stream", "5.0.0", "Creates a consumer group.", "O(1)"),
+    CommandSpec::new("xgroup|setid", -5, |arr| {
+        Ok(Command::Xgroup(try_xgroup(arr)?))
+    })
+    .flags(&[Write])
+    .keys(2, 2, 1, &["RW", "update"])
+    .acl(&["@write", "@stream", "@slow"])
+    .doc(
+        "stream",
+        "5.0.0",
+        "Sets the last-delivered ID of a consumer group.",
+        "O(1)",
+    ),
+    CommandSpec::new("xgroup|destroy", 4, |arr| {
+        Ok(Command::Xgroup(try_xgroup(arr)?))
+    })
+    .flags(&[Write])
+    .keys(2, 2, 1, &["RW", "delete"])
+    .acl(&["@write", "@stream", "@slow"])
+    .doc(
+        "stream",
+        "5.0.0",
+        "Destroys a consumer group.",
+        "O(N) where N is the number of entries in the group's pending entries list (PEL).",
+    ),
+    CommandSpec::new("xgroup|createconsumer", 5, |arr| {
+        Ok(Command::Xgroup(try_xgroup(arr)?))
+    })
+    .flags(&[Write, Denyoom])
+    .keys(2, 2, 1, &["RW", "insert"])
+    .acl(&["@write", "@stream", "@slow"])
+    .doc(
+        "stream",
+        "6.2.0",
+        "Creates a consumer in a consumer group.",
+        "O(1)",
+    ),
+    CommandSpec::new("xgroup|delconsumer", 5, |arr| {
+        Ok(Command::Xgroup(try_xgroup(arr)?))
+    })
+    .flags(&[Write])
+    .keys(2, 2, 1, &["RW", "delete"])
+    .acl(&["@write", "@stream", "@slow"])
+    .doc(
+        "stream",
+        "5.0.0",
+        "Deletes a consumer from a consumer group.",
+        "O(1)",
+    ),
+];
+
+static XINFO_SUBCOMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("xinfo|stream", -3, |arr| {
+        Ok(Command::Xinfo(try_xinfo(arr)?))
+    })
+    .flags(&[Readonly])
+    .keys(2, 2, 1, &["RO", "access"])
+    .acl(&["@read", "@stream", "@slow"])
+    .doc(
+        "stream",
+        "5.0.0",
+        "Returns information about a stream.",
+        "O(1)",
+    ),
+    CommandSpec::new("xinfo|groups", 3, |arr| Ok(Command::Xinfo(try_xinfo(arr)?)))
+        .flags(&[Readonly])
+        .keys(2, 2, 1, &["RO", "access"])
+        .acl(&["@read", "@stream", "@slow"])
+        .doc(
+            "stream",
+            "5.0.0",
+            "Returns a list of the consumer groups of a stream.",
+            "O(1)",
+        ),
+    CommandSpec::new("xinfo|consumers", 4, |arr| {
+        Ok(Command::Xinfo(try_xinfo(arr)?))
+    })
+    .flags(&[Readonly])
+    .keys(2, 2, 1, &["RO", "access"])
+    .acl(&["@read", "@stream", "@slow"])
+    .tips(&["nondeterministic_output"])
+    .doc(
+        "stream",
+        "5.0.0",
+        "Returns a list of the consumers in a consumer group.",
+        "O(1)",
+    ),
+];
+
+static OBJECT_SUBCOMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("object|freq", 3, |arr| {
+        Ok(Command::Object(try_object(arr)?))
+    })
+    .flags(&[Readonly])
+    .keys(2, 2, 1, &["RO"])
+    .acl(&["@keyspace", "@read", "@slow"])
+    .tips(&["nondeterministic_output"])
+    .doc(
+        "generic",
+        "4.0.0",
+        "Returns the logarithmic access frequency counter of a Redis object.",
+        "O(1)",
+    ),
+    CommandSpec::new("object|idletime", 3, |arr| {
+        Ok(Command::Object(try_object(arr)?))
+    })
+    .flags(&[Readonly])
+    .keys(2, 2, 1, &["RO"])
+    .acl(&["@keyspace", "@read", "@slow"])
+    .tips(&["nondeterministic_output"])
+    .doc(
+        "generic",
+        "2.2.3",
+        "Returns the time since the last access to a Redis object.",
+        "O(1)",
+    ),
+];
+
+static COMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("ping", -1, |arr| Ok(Command::Ping(try_ping(arr)?)))
+        .flags(&[Fast])
+        .acl(&["@fast", "@connection"])
+        .tips(&["request_policy:all_shards", "response_policy:all_succeeded"])
+        .doc(
+            "connection",
+            "1.0.0",
+            "Returns the server's liveliness response.",
+            "O(1)",
+        ),
+    CommandSpec::new("echo", 2, |arr| Ok(Command::Echo(try_echo(arr)?)))
+        .flags(&[Fast])
+        .acl(&["@fast", "@connection"])
+        .doc("connection", "1.0.0", "Returns the given string.", "O(1)"),
+    CommandSpec::new("get", 2, |arr| Ok(Command::Get(try_get(arr)?)))
+        .flags(&[Readonly, Fast])
+        .keys(1, 1, 1, &["RO", "access"])
+        .acl(&["@read", "@string", "@fast"])
+        .doc("string", "1.0.0", "Returns the string value of a key.", "O(1)"),
+    CommandSpec::new("set", -3, |arr| Ok(Command::Set(try_set(arr)?)))
+        .flags(&[Write, Denyoom])
+        .keys(1, 1, 1, &["RW", "access", "update", "variable_flags"])
+        .acl(&["@write", "@string", "@slow"])
+        .doc(
+            "string",
+            "1.0.0",
+            "Sets the string value of a key, ignoring its type. The key is created if it doesn't exist.",
+            "O(1)",
+        ),
+    CommandSpec::new("setnx", 3, |arr| Ok(Command::SetNx(try_setnx(arr)?)))
+        .flags(&[Write, Denyoom, Fast])
+        .keys(1, 1, 1, &["OW", "insert"])
+        .acl(&["@write", "@string", "@fast"])
+        .doc(
+            "string",
+            "1.0.0",
+            "Set the string value of a key only when the key doesn't exist.",
+            "O(1)",
+        ),
+    CommandSpec::new("getset", 3, |arr| Ok(Command::Set(try_getset(arr)?)))
+        .flags(&[Write, Denyoom, Fast])
+        .keys(1, 1, 1, &["RW", "access", "update"])
+        .acl(&["@write", "@string", "@fast"])
+        .doc(
+            "string",
+            "1.0.0",
+            "Returns the previous string value of a key after setting it to a new value.",
+            "O(1)",
+        ),
+    CommandSpec::new("getdel", 2, |arr| Ok(Command::GetDel(try_getdel(arr)?)))
+        .flags(&[Write, Fast])
+        .keys(1, 1, 1, &["RW", "access", "delete"])
+        .acl(&["@write", "@string", "@fast"])
+        .doc(
+            "string",
+            "6.2.0",
+            "Returns the string value of a key after deleting the key.",
+            "O(1)",
+        ),
+    CommandSpec::new("getex", -2, |arr| Ok(Command::GetEx(try_getex(arr)?)))
+        .flags(&[Write, Fast])
+        .keys(1, 1, 1, &["RW", "access", "update"])
+        .acl(&["@write", "@string", "@fast"])
+        .doc(
+            "string",
+            "6.2.0",
+            "Returns the string value of a key after setting its expiration time.",
+            "O(1)",
+        ),
+    CommandSpec::new("incr", 2, |arr| Ok(Command::Incr(try_incr(arr, false)?)))
+        .flags(&[Write, Denyoom, Fast])
+        .keys(1, 1, 1, &["RW", "access", "update"])
+        .acl(&["@write", "@string", "@fast"])
+        .doc(
+            "string",
+            "1.0.0",
+            "Increments the integer value of a key by one. Uses 0 as initial value if the key doesn't exist.",
+            "O(1)",
+        ),
+    CommandSpec::new("decr", 2, |arr| Ok(Command::Incr(try_incr(arr, true)?)))
+        .flags(&[Write, Denyoom, Fast])
+        .keys(1, 1, 1, &["RW", "access", "update"])
+        .acl(&["@write", "@string", "@fast"])
+        .doc(
+            "string",
+            "1.0.0",
+            "Decrements the integer value of a key by one. Uses 0 as initial value if the key doesn't exist.",
+            "O(1)",
+        ),
+    CommandSpec::new("incrby", 3, |arr| Ok(Command::Incr(try_incr(arr, false)?)))
+        .flags(&[Write, Denyoom, Fast])
+        .keys(1, 1, 1, &["RW", "access", "update"])
+        .acl(&["@write", "@string", "@fast"])
+        .doc(
+            "string",
+            "1.0.0",
+            "Increments the integer value of a key by a number. Uses 0 as initial value if the key doesn't exist.",
+            "O(1)",
+        ),
+    CommandSpec::new("decrby", 3, |arr| Ok(Command::Incr(try_incr(arr, true)?)))
+        .flags(&[Write, Denyoom, Fast])
+        .keys(1, 1, 1, &["RW", "access", "update"])
+        .acl(&["@write", "@string", "@fast"])
+        .doc(
+            "string",
+            "1.0.0",
+            "Decrements a number from the integer value of a key. Uses 0 as initial value if the key doesn't exist.",
+            "O(1)",
+        ),
+    CommandSpec::new("incrbyfloat", 3, |arr| {
+        Ok(Command::IncrByFloat(try_incrbyfloat(arr)?))
+    })
+    .flags(&[Write, Denyoom, Fast])
+    .keys(1, 1, 1, &["RW", "access", "update"])
+    .acl(&["@write", "@string", "@fast"])
+    .doc(
+        "string",
+        "2.6.0",
+        "Increment the floating point value of a key by a number. Uses 0 as initial value if the key doesn't exist.",
+        "O(1)",
+    ),
+    CommandSpec::new("append", 3, |arr| Ok(Command::Append(try_append(arr)?)))
+        .flags(&[Write, Denyoom, Fast])
+        .keys(1, 1, 1, &["RW", "insert"])
+        .acl(&["@write", "@string", "@fast"])
+        .doc(
+            "string",
+            "2.0.0",
+            "Appends a string to the value of a key. Creates the key if it doesn't exist.",
+            "O(1). The amortized time complexity is O(1) assuming the appended value is small and the already present value is of any size, since the dynamic string library used by Redis will double the free space available on every reallocation.",
+        ),
+    CommandSpec::new("strlen", 2, |arr| Ok(Command::Strlen(try_strlen(arr)?)))
+        .flags(&[Readonly, Fast])
+        .keys(1, 1, 1, &["RO"])
+        .acl(&["@read", "@string", "@fast"])
+        .doc(
+            "string",
+            "2.2.0",
+            "Returns the length of a string value.",
+            "O(1)",
+        ),
+    CommandSpec::new("getrange", 4, |arr| Ok(Command::GetRange(try_getrange(arr)?)))
+        .flags(&[Readonly])
+        .keys(1, 1, 1, &["RO", "access"])
+        .acl(&["@read", "@string", "@slow"])
+        .doc(
+            "string",
+            "2.4.0",
+            "Returns a substring of the string stored at a key.",
+            "O(N) where N is the length of the returned string. The complexity is ultimately determined by the returned length, but because creating a substring from an existing string is very cheap, it can be considered O(1) for small strings.",
+        ),
+    CommandSpec::new("setrange", 4, |arr| Ok(Command::SetRange(try_setrange(arr)?)))
+        .flags(&[Write, Denyoom])
+        .keys(1, 1, 1, &["RW", "update"])
+        .acl(&["@write", "@string", "@slow"])
+        .doc(
+            "string",
+            "2.2.0",
+            "Overwrites a part of a string value with another by an offset. Creates the key if it doesn't exist.",
+            "O(1), not counting the time taken to copy the new string in place. Usually, this string is very small so the amortized complexity is O(1). Otherwise, complexity is O(M) with M being the length of the value argument.",
+        ),
+    CommandSpec::new("mget", -2, |arr| Ok(Command::Mget(try_mget(arr)?)))
+        .flags(&[Readonly, Fast])
+        .keys(1, -1, 1, &["RO", "access"])
+        .acl(&["@read", "@string", "@fast"])
+        .tips(&["request_policy:multi_shard"])
+        .doc(
+            "string",
+            "1.0.0",
+            "Atomically returns the string values of one or more keys.",
+            "O(N) where N is the number of keys to retrieve.",
+        ),
+    CommandSpec::new("mset", -3, |arr| Ok(Command::Mset(Mset::new(pairs_arg(&arr, 1)?))))
+        .flags(&[Write, Denyoom])
+        .keys(1, -1, 2, &["OW", "update"])
+        .acl(&["@write", "@string", "@slow"])
+        .tips(&["request_policy:multi_shard", "response_policy:all_succeeded"])
+        .doc(
+            "string",
+            "1.0.1",
+            "Atomically creates or modifies the string values of one or more keys.",
+            "O(N) where N is the number of keys to set.",
+        ),
+    CommandSpec::new("msetnx", -3, |arr| {
+        Ok(Command::MsetNx(MsetNx::new(pairs_arg(&arr, 1)?)))
+    })
+    .flags(&[Write, Denyoom])
+    .keys(1, -1, 2, &["OW", "insert"])
+    .acl(&["@write", "@string", "@slow"])
+    .doc(
+        "string",
+        "1.0.1",
+        "Atomically modifies the string values of one or more keys only when all keys don't exist.",
+        "O(N) where N is the number of keys to set.",
+    ),
+    CommandSpec::new("lpush", -3, |arr| {
+        Ok(Command::Push(try_push(arr, ListEnd::Left, false)?))
+    })
+    .flags(&[Write, Denyoom, Fast])
+    .keys(1, 1, 1, &["RW", "insert"])
+    .acl(&["@write", "@list", "@fast"])
+    .doc(
+        "list",
+        "1.0.0",
+        "Prepends one or more elements to a list. Creates the key if it doesn't exist.",
+        "O(1) for each element added, so O(N) to add N elements when the command is called with multiple arguments.",
+    ),
+    CommandSpec::new("rpush", -3, |arr| {
+        Ok(Command::Push(try_push(arr, ListEnd::Right, false)?))
+    })
+    .flags(&[Write, Denyoom, Fast])
+    .keys(1, 1, 1, &["RW", "insert"])
+    .acl(&["@write", "@list", "@fast"])
+    .doc(
+        "list",
+        "1.0.0",
+        "Appends one or more elements to a list. Creates the key if it doesn't exist.",
+        "O(1) for each element added, so O(N) to add N elements when the command is called with multiple arguments.",
+    ),
+    CommandSpec::new("lpushx", -3, |arr| {
+        Ok(Command::Push(try_push(arr, ListEnd::Left, true)?))
+    })
+    .flags(&[Write, Denyoom, Fast])
+    .keys(1, 1, 1, &["RW", "insert"])
+    .acl(&["@write", "@list", "@fast"])
+    .doc(
+        "list",
+        "2.2.0",
+        "Prepends one or more elements to a list only when the list exists.",
+        "O(1) for each element added, so O(N) to add N elements when the command is called with multiple arguments.",
+    ),
+    CommandSpec::new("rpushx", -3, |arr| {
+        Ok(Command::Push(try_push(arr, ListEnd::Right, true)?))
+    })
+    .flags(&[Write, Denyoom, Fast])
+    .keys(1, 1, 1, &["RW", "insert"])
+    .acl(&["@write", "@list", "@fast"])
+    .doc(
+        "list",
+        "2.2.0",
+        "Appends an element to a list only when the list exists.",
+        "O(1) for each element added, so O(N) to add N elements when the command is called with multiple arguments.",
+    ),
+    CommandSpec::new("lpop", -2, |arr| Ok(Command::Pop(try_pop(arr, ListEnd::Left)?)))
+        .flags(&[Write, Fast])
+        .keys(1, 1, 1, &["RW", "access", "delete"])
+        .acl(&["@write", "@list", "@fast"])
+        .doc(
+            "list",
+            "1.0.0",
+            "Returns the first elements in a list after removing it. Deletes the list if the last element was popped.",
+            "O(N) where N is the number of elements returned",
+        ),
+    CommandSpec::new("rpop", -2, |arr| Ok(Command::Pop(try_pop(arr, ListEnd::Right)?)))
+        .flags(&[Write, Fast])
+        .keys(1, 1, 1, &["RW", "access", "delete"])
+        .acl(&["@write", "@list", "@fast"])
+        .doc(
+            "list",
+            "1.0.0",
+            "Returns and removes the last elements of a list. Deletes the list if the last element was popped.",
+            "O(N) where N is the number of elements returned",
+        ),
+    CommandSpec::new("llen", 2, |arr| Ok(Command::Llen(try_llen(arr)?)))
+        .flags(&[Readonly, Fast])
+        .keys(1, 1, 1, &["RO"])
+        .acl(&["@read", "@list", "@fast"])
+        .doc("list", "1.0.0", "Returns the length of a list.", "O(1)"),
+    CommandSpec::new("lrange", 4, |arr| Ok(Command::Lrange(try_lrange(arr)?)))
+        .flags(&[Readonly])
+        .keys(1, 1, 1, &["RO", "access"])
+        .acl(&["@read", "@list", "@slow"])
+        .doc(
+            "list",
+            "1.0.0",
+            "Returns a range of elements from a list.",
+            "O(S+N) where S is the distance of start offset from HEAD for small lists, from nearest end (HEAD or TAIL) for large lists; and N is the number of elements in the specified range.",
+        ),
+    CommandSpec::new("lindex", 3, |arr| Ok(Command::Lindex(try_lindex(arr)?)))
+        .flags(&[Readonly])
+        .keys(1, 1, 1, &["RO", "access"])
+        .acl(&["@read", "@list", "@slow"])
+        .doc(
+            "list",
+            "1.0.0",
+            "Returns an element from a list by its index.",
+            "O(N) where N is the number of elements to traverse to get to the element at index. This makes asking for the first or the last element of the list O(1).",
+        ),
+    CommandSpec::new("lset", 4, |arr| Ok(Command::Lset(try_lset(arr)?)))
+        .flags(&[Write, Denyoom])
+        .keys(1, 1, 1, &["RW", "update"])
+        .acl(&["@write", "@list", "@slow"])
+        .doc(
+            "list",
+            "1.0.0",
+            "Sets the value of an element in a list by its index.",
+            "O(N) where N is the length of the list. Setting either the first or the last element of the list is O(1).",
+        ),
+    CommandSpec::new("lrem", 4, |arr| Ok(Command::Lrem(try_lrem(arr)?)))
+        .flags(&[Write])
+        .keys(1, 1, 1, &["RW", "delete"])
+        .acl(&["@write", "@list", "@slow"])
+        .doc(
+            "list",
+            "1.0.0",
+            "Removes elements from a list. Deletes the list if the last element was removed.",
+            "O(N+M) where N is the length of the list and M is the number of elements removed.",
+        ),
+    CommandSpec::new("ltrim", 4, |arr| Ok(Command::Ltrim(try_ltrim(arr)?)))
+        .flags(&[Write])
+        .keys(1, 1, 1, &["RW", "delete"])
+        .acl(&["@write", "@list", "@slow"])
+        .doc(
+            "list",
+            "1.0.0",
+            "Removes elements from both ends a list. Deletes the list if all elements were trimmed.",
+            "O(N) where N is the number of elements to be removed by the operation.",
+        ),
+    CommandSpec::new("linsert", 5, |arr| Ok(Command::Linsert(try_linsert(arr)?)))
+        .flags(&[Write, Denyoom])
+        .keys(1, 1, 1, &["RW", "insert"])
+        .acl(&["@write", "@list", "@slow"])
+        .doc(
+            "list",
+            "2.2.0",
+            "Inserts an element before or after another element in a list.",
+            "O(N) where N is the number of elements to traverse before seeing the value pivot. This means that inserting somewhere on the left end on the list (head) can be considered O(1) and inserting somewhere on the right end (tail) is O(N).",
+        ),
+    CommandSpec::new("lpos", -3, |arr| Ok(Command::Lpos(try_lpos(arr)?)))
+        .flags(&[Readonly])
+        .keys(1, 1, 1, &["RO"])
+        .acl(&["@read", "@list", "@slow"])
+        .doc(
+            "list",
+            "6.0.6",
+            "Returns the index of matching elements in a list.",
+            "O(N) where N is the number of elements in the list, for the average case. When searching for elements near the head or the tail of the list, or when the MAXLEN option is provided, the command may run in constant time.",
+        ),
+    CommandSpec::new("lmove", 5, |arr| Ok(Command::Lmove(try_lmove(arr)?)))
+        .flags(&[Write, Denyoom])
+        .keys(1, 2, 1, &["RW", "access", "delete", "insert"])
+        .acl(&["@write", "@list", "@slow"])
+        .doc(
+            "list",
+            "6.2.0",
+            "Returns an element after popping it from one list and pushing it to another. Deletes the list if the last element was moved.",
+            "O(1)",
+        ),
+    CommandSpec::new("rpoplpush", 3, |arr| Ok(Command::Lmove(try_rpoplpush(arr)?)))
+        .flags(&[Write, Denyoom])
+        .keys(1, 2, 1, &["RW", "access", "delete", "insert"])
+        .acl(&["@write", "@list", "@slow"])
+        .doc(
+            "list",
+            "1.2.0",
+            "Returns the last element of a list after removing and pushing it to another list. Deletes the list if the last element was popped.",
+            "O(1)",
+        ),
+    CommandSpec::new("lmpop", -4, |arr| Ok(Command::Lmpop(try_lmpop(arr)?)))
+        .flags(&[Write])
+        .numkeys(1, &["RW", "access", "delete"])
+        .acl(&["@write", "@list", "@slow"])
+        .doc(
+            "list",
+            "7.0.0",
+            "Returns multiple elements from a list after removing them. Deletes the list if the last element was popped.",
+            "O(N+M) where N is the number of provided keys and M is the number of elements returned.",
+        ),
+    CommandSpec::new("blpop", -3, |arr| Ok(Command::Bpop(try_bpop(arr, ListEnd::Left)?)))
+        .flags(&[Write, Blocking])
+        .keys(1, -2, 1, &["RW", "access", "delete"])
+        .acl(&["@write", "@list", "@slow", "@blocking"])
+        .doc(
+            "list",
+            "2.0.0",
+            "Removes and returns the first element in a list. Blocks until an element is available otherwise. Deletes the list if the last element was popped.",
+            "O(N) where N is the number of provided keys.",
+        ),
+    CommandSpec::new("brpop", -3, |arr| {
+        Ok(Command::Bpop(try_bpop(arr, ListEnd::Right)?))
+    })
+    .flags(&[Write, Blocking])
+    .keys(1, -2, 1, &["RW", "access", "delete"])
+    .acl(&["@write", "@list", "@slow", "@blocking"])
+    .doc(
+        "list",
+        "2.0.0",
+        "Removes and returns the last element in a list. Blocks until an element is available otherwise. Deletes the list if the last element was popped.",
+        "O(N) where N is the number of provided keys.",
+    ),
+    CommandSpec::new("blmove", 6, |arr| Ok(Command::Blmove(try_blmove(arr)?)))
+        .flags(&[Write, Denyoom, Blocking])
+        .keys(1, 2, 1, &["RW", "access", "delete", "insert"])
+        .acl(&["@write", "@list", "@slow", "@blocking"])
+        .doc(
+            "list",
+            "6.2.0",
+            "Pops an element from a list, pushes it to another list and returns it. Blocks until an element is available otherwise. Deletes the list if the last element was moved.",
+            "O(1)",
+        ),
+    CommandSpec::new("brpoplpush", 4, |arr| {
+        Ok(Command::Blmove(try_brpoplpush(arr)?))
+    })
+    .flags(&[Write, Denyoom, Blocking])
+    .keys(1, 2, 1, &["RW", "access", "delete", "insert"])
+    .acl(&["@write", "@list", "@slow", "@blocking"])
+    .doc(
+        "list",
+        "2.2.0",
+        "Pops an element from a list, pushes it to another list and returns it. Block until an element is available otherwise. Deletes the list if the last element was popped.",
+        "O(1)",
+    ),
+    CommandSpec::new("blmpop", -5, |arr| Ok(Command::Blmpop(try_blmpop(arr)?)))
+        .flags(&[Write, Blocking])
+        .numkeys(2, &["RW", "access", "delete"])
+        .acl(&["@write", "@list", "@slow", "@blocking"])
+        .doc(
+            "list",
+            "7.0.0",
+            "Pops the first element from one of multiple lists. Blocks until an element is available otherwise. Deletes the list if the last element was popped.",
+            "O(N+M) where N is the number of provided keys and M is the number of elements returned.",
+        ),
+    CommandSpec::new("hset", -4, |arr| {
+        Ok(Command::Hset(try_hset(arr, false)?))
+    })
+    .flags(&[Write, Denyoom, Fast])
+    .keys(1, 1, 1, &["RW", "update", "insert"])
+    .acl(&["@write", "@hash", "@fast"])
+    .doc(
+        "hash",
+        "2.0.0",
+        "Creates or modifies the value of a field in a hash.",
+        "O(1) for each field/value pair added, so O(N) to add N field/value pairs when the command is called with multiple field/value pairs.",
+    ),
+    CommandSpec::new("hmset", -4, |arr| {
+        Ok(Command::Hset(try_hset(arr, true)?))
+    })
+    .flags(&[Write, Denyoom, Fast])
+    .keys(1, 1, 1, &["RW", "update"])
+    .acl(&["@write", "@hash", "@fast"])
+    .doc(
+        "hash",
+        "2.0.0",
+        "Sets the values of multiple fields.",
+        "O(N) where N is the number of fields being set.",
+    ),
+    CommandSpec::new("hsetnx", 4, |arr| {
+        Ok(Command::HsetNx(try_hsetnx(arr)?))
+    })
+    .flags(&[Write, Denyoom, Fast])
+    .keys(1, 1, 1, &["RW", "insert"])
+    .acl(&["@write", "@hash", "@fast"])
+    .doc(
+        "hash",
+        "2.0.0",
+        "Sets the value of a field in a hash only when the field doesn't exist.",
+        "O(1)",
+    ),
+    CommandSpec::new("hget", 3, |arr| {
+        Ok(Command::Hget(try_hget(arr)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@hash", "@fast"])
+    .doc(
+        "hash",
+        "2.0.0",
+        "Returns the value of a field in a hash.",
+        "O(1)",
+    ),
+    CommandSpec::new("hmget", -3, |arr| {
+        Ok(Command::Hmget(try_hmget(arr)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@hash", "@fast"])
+    .doc(
+        "hash",
+        "2.0.0",
+        "Returns the values of all fields in a hash.",
+        "O(N) where N is the number of fields being requested.",
+    ),
+    CommandSpec::new("hdel", -3, |arr| {
+        Ok(Command::Hdel(try_hdel(arr)?))
+    })
+    .flags(&[Write, Fast])
+    .keys(1, 1, 1, &["RW", "delete"])
+    .acl(&["@write", "@hash", "@fast"])
+    .doc(
+        "hash",
+        "2.0.0",
+        "Deletes one or more fields and their values from a hash. Deletes the hash if no fields remain.",
+        "O(N) where N is the number of fields to be removed.",
+    ),
+    CommandSpec::new("hexists", 3, |arr| {
+        Ok(Command::Hexists(try_hexists(arr)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO"])
+    .acl(&["@read", "@hash", "@fast"])
+    .doc(
+        "hash",
+        "2.0.0",
+        "Determines whether a field exists in a hash.",
+        "O(1)",
+    ),
+    CommandSpec::new("hlen", 2, |arr| {
+        Ok(Command::Hlen(try_hlen(arr)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO"])
+    .acl(&["@read", "@hash", "@fast"])
+    .doc(
+        "hash",
+        "2.0.0",
+        "Returns the number of fields in a hash.",
+        "O(1)",
+    ),
+    CommandSpec::new("hstrlen", 3, |arr| {
+        Ok(Command::Hstrlen(try_hstrlen(arr)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO"])
+    .acl(&["@read", "@hash", "@fast"])
+    .doc(
+        "hash",
+        "3.2.0",
+        "Returns the length of the value of a field.",
+        "O(1)",
+    ),
+    CommandSpec::new("hkeys", 2, |arr| {
+        Ok(Command::HgetAll(try_hgetall(arr, HgetAllKind::Keys)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@hash", "@slow"])
+    .tips(&["nondeterministic_output_order"])
+    .doc(
+        "hash",
+        "2.0.0",
+        "Returns all fields in a hash.",
+        "O(N) where N is the size of the hash.",
+    ),
+    CommandSpec::new("hvals", 2, |arr| {
+        Ok(Command::HgetAll(try_hgetall(arr, HgetAllKind::Values)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@hash", "@slow"])
+    .tips(&["nondeterministic_output_order"])
+    .doc(
+        "hash",
+        "2.0.0",
+        "Returns all values in a hash.",
+        "O(N) where N is the size of the hash.",
+    ),
+    CommandSpec::new("hgetall", 2, |arr| {
+        Ok(Command::HgetAll(try_hgetall(arr, HgetAllKind::All)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@hash", "@slow"])
+    .tips(&["nondeterministic_output_order"])
+    .doc(
+        "hash",
+        "2.0.0",
+        "Returns all fields and values in a hash.",
+        "O(N) where N is the size of the hash.",
+    ),
+    CommandSpec::new("hincrby", 4, |arr| {
+        Ok(Command::Hincrby(try_hincrby(arr)?))
+    })
+    .flags(&[Write, Denyoom, Fast])
+    .keys(1, 1, 1, &["RW", "access", "update"])
+    .acl(&["@write", "@hash", "@fast"])
+    .doc(
+        "hash",
+        "2.0.0",
+        "Increments the integer value of a field in a hash by a number. Uses 0 as initial value if the field doesn't exist.",
+        "O(1)",
+    ),
+    CommandSpec::new("hincrbyfloat", 4, |arr| {
+        Ok(Command::HincrbyFloat(try_hincrbyfloat(arr)?))
+    })
+    .flags(&[Write, Denyoom, Fast])
+    .keys(1, 1, 1, &["RW", "access", "update"])
+    .acl(&["@write", "@hash", "@fast"])
+    .doc(
+        "hash",
+        "2.6.0",
+        "Increments the floating point value of a field by a number. Uses 0 as initial value if the field doesn't exist.",
+        "O(1)",
+    ),
+    CommandSpec::new("hrandfield", -2, |arr| {
+        Ok(Command::HrandField(try_hrandfield(arr)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@hash", "@slow"])
+    .tips(&["nondeterministic_output"])
+    .doc(
+        "hash",
+        "6.2.0",
+        "Returns one or more random fields from a hash.",
+        "O(N) where N is the number of fields returned",
+    ),
+    CommandSpec::new("hscan", -3, |arr| {
+        Ok(Command::Hscan(try_hscan(arr)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@hash", "@slow"])
+    .tips(&["nondeterministic_output"])
+    .doc(
+        "hash",
+        "2.8.0",
+        "Iterates over fields and values of a hash.",
+        "O(1) for every call. O(N) for a complete iteration, including enough command calls for the cursor to return back to 0. N is the number of elements inside the collection.",
+    ),
+    CommandSpec::new("hexpire", -6, |arr| {
+        Ok(Command::Hexpire(try_hexpire(arr, ExpireUnit::Seconds, false)?))
+    })
+    .flags(&[Write, Denyoom, Fast])
+    .keys(1, 1, 1, &["RW", "update"])
+    .acl(&["@write", "@hash", "@fast"])
+    .doc(
+        "hash",
+        "7.4.0",
+        "Set expiry for hash field using relative time to expire (seconds)",
+        "O(N) where N is the number of specified fields",
+    ),
+    CommandSpec::new("hpexpire", -6, |arr| {
+        Ok(Command::Hexpire(try_hexpire(arr, ExpireUnit::Milliseconds, false)?))
+    })
+    .flags(&[Write, Denyoom, Fast])
+    .keys(1, 1, 1, &["RW", "update"])
+    .acl(&["@write", "@hash", "@fast"])
+    .doc(
+        "hash",
+        "7.4.0",
+        "Set expiry for hash field using relative time to expire (milliseconds)",
+        "O(N) where N is the number of specified fields",
+    ),
+    CommandSpec::new("hexpireat", -6, |arr| {
+        Ok(Command::Hexpire(try_hexpire(arr, ExpireUnit::Seconds, true)?))
+    })
+    .flags(&[Write, Denyoom, Fast])
+    .keys(1, 1, 1, &["RW", "update"])
+    .acl(&["@write", "@hash", "@fast"])
+    .doc(
+        "hash",
+        "7.4.0",
+        "Set expiry for hash field using an absolute Unix timestamp (seconds)",
+        "O(N) where N is the number of specified fields",
+    ),
+    CommandSpec::new("hpexpireat", -6, |arr| {
+        Ok(Command::Hexpire(try_hexpire(arr, ExpireUnit::Milliseconds, true)?))
+    })
+    .flags(&[Write, Denyoom, Fast])
+    .keys(1, 1, 1, &["RW", "update"])
+    .acl(&["@write", "@hash", "@fast"])
+    .doc(
+        "hash",
+        "7.4.0",
+        "Set expiry for hash field using an absolute Unix timestamp (milliseconds)",
+        "O(N) where N is the number of specified fields",
+    ),
+    CommandSpec::new("httl", -5, |arr| {
+        Ok(Command::Httl(try_httl(arr, TtlKind::Ttl)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@hash", "@fast"])
+    .tips(&["nondeterministic_output"])
+    .doc(
+        "hash",
+        "7.4.0",
+        "Returns the TTL in seconds of a hash field.",
+        "O(N) where N is the number of specified fields",
+    ),
+    CommandSpec::new("hpttl", -5, |arr| {
+        Ok(Command::Httl(try_httl(arr, TtlKind::Pttl)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@hash", "@fast"])
+    .tips(&["nondeterministic_output"])
+    .doc(
+        "hash",
+        "7.4.0",
+        "Returns the TTL in milliseconds of a hash field.",
+        "O(N) where N is the number of specified fields",
+    ),
+    CommandSpec::new("hexpiretime", -5, |arr| {
+        Ok(Command::Httl(try_httl(arr, TtlKind::ExpireTime)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@hash", "@fast"])
+    .tips(&["nondeterministic_output"])
+    .doc(
+        "hash",
+        "7.4.0",
+        "Returns the expiration time of a hash field as a Unix timestamp, in seconds.",
+        "O(N) where N is the number of specified fields",
+    ),
+    CommandSpec::new("hpexpiretime", -5, |arr| {
+        Ok(Command::Httl(try_httl(arr, TtlKind::PexpireTime)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@hash", "@fast"])
+    .tips(&["nondeterministic_output"])
+    .doc(
+        "hash",
+        "7.4.0",
+        "Returns the expiration time of a hash field as a Unix timestamp, in msec.",
+        "O(N) where N is the number of specified fields",
+    ),
+    CommandSpec::new("hpersist", -5, |arr| {
+        Ok(Command::Hpersist(try_hpersist(arr)?))
+    })
+    .flags(&[Write, Fast])
+    .keys(1, 1, 1, &["RW", "update"])
+    .acl(&["@write", "@hash", "@fast"])
+    .doc(
+        "hash",
+        "7.4.0",
+        "Removes the expiration time for each specified field",
+        "O(N) where N is the number of specified fields",
+    ),
+    CommandSpec::new("sadd", -3, |arr| {
+        Ok(Command::Sadd(try_sadd(arr)?))
+    })
+    .flags(&[Write, Denyoom, Fast])
+    .keys(1, 1, 1, &["RW", "insert"])
+    .acl(&["@write", "@set", "@fast"])
+    .doc(
+        "set",
+        "1.0.0",
+        "Adds one or more members to a set. Creates the key if it doesn't exist.",
+        "O(1) for each element added, so O(N) to add N elements when the command is called with multiple arguments.",
+    ),
+    CommandSpec::new("srem", -3, |arr| {
+        Ok(Command::Srem(try_srem(arr)?))
+    })
+    .flags(&[Write, Fast])
+    .keys(1, 1, 1, &["RW", "delete"])
+    .acl(&["@write", "@set", "@fast"])
+    .doc(
+        "set",
+        "1.0.0",
+        "Removes one or more members from a set. Deletes the set if the last member was removed.",
+        "O(N) where N is the number of members to be removed.",
+    ),
+    CommandSpec::new("scard", 2, |arr| {
+        Ok(Command::Scard(try_scard(arr)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO"])
+    .acl(&["@read", "@set", "@fast"])
+    .doc(
+        "set",
+        "1.0.0",
+        "Returns the number of members in a set.",
+        "O(1)",
+    ),
+    CommandSpec::new("smembers", 2, |arr| {
+        Ok(Command::Smembers(try_smembers(arr)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@set", "@slow"])
+    .tips(&["nondeterministic_output_order"])
+    .doc(
+        "set",
+        "1.0.0",
+        "Returns all members of a set.",
+        "O(N) where N is the set cardinality.",
+    ),
+    CommandSpec::new("sismember", 3, |arr| {
+        Ok(Command::Sismember(try_sismember(arr)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO"])
+    .acl(&["@read", "@set", "@fast"])
+    .doc(
+        "set",
+        "1.0.0",
+        "Determines whether a member belongs to a set.",
+        "O(1)",
+    ),
+    CommandSpec::new("smismember", -3, |arr| {
+        Ok(Command::Smismember(try_smismember(arr)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO"])
+    .acl(&["@read", "@set", "@fast"])
+    .doc(
+        "set",
+        "6.2.0",
+        "Determines whether multiple members belong to a set.",
+        "O(N) where N is the number of elements being checked for membership",
+    ),
+    CommandSpec::new("spop", -2, |arr| {
+        Ok(Command::Spop(try_spop(arr)?))
+    })
+    .flags(&[Write, Fast])
+    .keys(1, 1, 1, &["RW", "access", "delete"])
+    .acl(&["@write", "@set", "@fast"])
+    .tips(&["nondeterministic_output"])
+    .doc(
+        "set",
+        "1.0.0",
+        "Returns one or more random members from a set after removing them. Deletes the set if the last member was popped.",
+        "Without the count argument O(1), otherwise O(N) where N is the value of the passed count.",
+    ),
+    CommandSpec::new("srandmember", -2, |arr| {
+        Ok(Command::SrandMember(try_srandmember(arr)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@set", "@slow"])
+    .tips(&["nondeterministic_output"])
+    .doc(
+        "set",
+        "1.0.0",
+        "Get one or multiple random members from a set",
+        "Without the count argument O(1), otherwise O(N) where N is the absolute value of the passed count.",
+    ),
+    CommandSpec::new("smove", 4, |arr| {
+        Ok(Command::Smove(try_smove(arr)?))
+    })
+    .flags(&[Write, Fast])
+    .keys(1, 2, 1, &["RW", "access", "delete", "insert"])
+    .acl(&["@write", "@set", "@fast"])
+    .doc(
+        "set",
+        "1.0.0",
+        "Moves a member from one set to another.",
+        "O(1)",
+    ),
+    CommandSpec::new("sscan", -3, |arr| {
+        Ok(Command::Sscan(try_sscan(arr)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@set", "@slow"])
+    .tips(&["nondeterministic_output"])
+    .doc(
+        "set",
+        "2.8.0",
+        "Iterates over members of a set.",
+        "O(1) for every call. O(N) for a complete iteration, including enough command calls for the cursor to return back to 0. N is the number of elements inside the collection.",
+    ),
+    CommandSpec::new("sinter", -2, |arr| {
+        Ok(Command::SetOp(try_setop(arr, SetOpKind::Inter, false)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, -1, 1, &["RO", "access"])
+    .acl(&["@read", "@set", "@slow"])
+    .tips(&["nondeterministic_output_order"])
+    .doc(
+        "set",
+        "1.0.0",
+        "Returns the intersect of multiple sets.",
+        "O(N*M) worst case where N is the cardinality of the smallest set and M is the number of sets.",
+    ),
+    CommandSpec::new("sunion", -2, |arr| {
+        Ok(Command::SetOp(try_setop(arr, SetOpKind::Union, false)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, -1, 1, &["RO", "access"])
+    .acl(&["@read", "@set", "@slow"])
+    .tips(&["nondeterministic_output_order"])
+    .doc(
+        "set",
+        "1.0.0",
+        "Returns the union of multiple sets.",
+        "O(N) where N is the total number of elements in all given sets.",
+    ),
+    CommandSpec::new("sdiff", -2, |arr| {
+        Ok(Command::SetOp(try_setop(arr, SetOpKind::Diff, false)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, -1, 1, &["RO", "access"])
+    .acl(&["@read", "@set", "@slow"])
+    .tips(&["nondeterministic_output_order"])
+    .doc(
+        "set",
+        "1.0.0",
+        "Returns the difference of multiple sets.",
+        "O(N) where N is the total number of elements in all given sets.",
+    ),
+    CommandSpec::new("sinterstore", -3, |arr| {
+        Ok(Command::SetOp(try_setop(arr, SetOpKind::Inter, true)?))
+    })
+    .flags(&[Write, Denyoom])
+    .keys(1, -1, 1, &["RW", "access", "update"])
+    .acl(&["@write", "@set", "@slow"])
+    .doc(
+        "set",
+        "1.0.0",
+        "Stores the intersect of multiple sets in a key.",
+        "O(N*M) worst case where N is the cardinality of the smallest set and M is the number of sets.",
+    ),
+    CommandSpec::new("sunionstore", -3, |arr| {
+        Ok(Command::SetOp(try_setop(arr, SetOpKind::Union, true)?))
+    })
+    .flags(&[Write, Denyoom])
+    .keys(1, -1, 1, &["RW", "access", "update"])
+    .acl(&["@write", "@set", "@slow"])
+    .doc(
+        "set",
+        "1.0.0",
+        "Stores the union of multiple sets in a key.",
+        "O(N) where N is the total number of elements in all given sets.",
+    ),
+    CommandSpec::new("sdiffstore", -3, |arr| {
+        Ok(Command::SetOp(try_setop(arr, SetOpKind::Diff, true)?))
+    })
+    .flags(&[Write, Denyoom])
+    .keys(1, -1, 1, &["RW", "access", "update"])
+    .acl(&["@write", "@set", "@slow"])
+    .doc(
+        "set",
+        "1.0.0",
+        "Stores the difference of multiple sets in a key.",
+        "O(N) where N is the total number of elements in all given sets.",
+    ),
+    CommandSpec::new("sintercard", -3, |arr| {
+        Ok(Command::SinterCard(try_sintercard(arr)?))
+    })
+    .flags(&[Readonly])
+    .numkeys(1, &["RO", "access"])
+    .acl(&["@read", "@set", "@slow"])
+    .doc(
+        "set",
+        "7.0.0",
+        "Returns the number of members of the intersect of multiple sets.",
+        "O(N*M) worst case where N is the cardinality of the smallest set and M is the number of sets.",
+    ),
+    CommandSpec::new("zadd", -4, |arr| {
+        Ok(Command::Zadd(try_zadd(arr)?))
+    })
+    .flags(&[Write, Denyoom, Fast])
+    .keys(1, 1, 1, &["RW", "update"])
+    .acl(&["@write", "@sortedset", "@fast"])
+    .doc(
+        "sorted_set",
+        "1.2.0",
+        "Adds one or more members to a sorted set, or updates their scores. Creates the key if it doesn't exist.",
+        "O(log(N)) for each item added, where N is the number of elements in the sorted set.",
+    ),
+    CommandSpec::new("zrem", -3, |arr| {
+        Ok(Command::Zrem(try_zrem(arr)?))
+    })
+    .flags(&[Write, Fast])
+    .keys(1, 1, 1, &["RW", "delete"])
+    .acl(&["@write", "@sortedset", "@fast"])
+    .doc(
+        "sorted_set",
+        "1.2.0",
+        "Removes one or more members from a sorted set. Deletes the sorted set if all members were removed.",
+        "O(M*log(N)) with N being the number of elements in the sorted set and M the number of elements to be removed.",
+    ),
+    CommandSpec::new("zscore", 3, |arr| {
+        Ok(Command::Zscore(try_zscore(arr)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@sortedset", "@fast"])
+    .doc(
+        "sorted_set",
+        "1.2.0",
+        "Returns the score of a member in a sorted set.",
+        "O(1)",
+    ),
+    CommandSpec::new("zmscore", -3, |arr| {
+        Ok(Command::Zmscore(try_zmscore(arr)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@sortedset", "@fast"])
+    .doc(
+        "sorted_set",
+        "6.2.0",
+        "Returns the score of one or more members in a sorted set.",
+        "O(N) where N is the number of members being requested.",
+    ),
+    CommandSpec::new("zincrby", 4, |arr| {
+        Ok(Command::Zincrby(try_zincrby(arr)?))
+    })
+    .flags(&[Write, Denyoom, Fast])
+    .keys(1, 1, 1, &["RW", "access", "update"])
+    .acl(&["@write", "@sortedset", "@fast"])
+    .doc(
+        "sorted_set",
+        "1.2.0",
+        "Increments the score of a member in a sorted set.",
+        "O(log(N)) where N is the number of elements in the sorted set.",
+    ),
+    CommandSpec::new("zcard", 2, |arr| {
+        Ok(Command::Zcard(try_zcard(arr)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@sortedset", "@fast"])
+    .doc(
+        "sorted_set",
+        "1.2.0",
+        "Returns the number of members in a sorted set.",
+        "O(1)",
+    ),
+    CommandSpec::new("zrank", -3, |arr| {
+        Ok(Command::Zrank(try_zrank(arr, false)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@sortedset", "@fast"])
+    .doc(
+        "sorted_set",
+        "2.0.0",
+        "Returns the index of a member in a sorted set ordered by ascending scores.",
+        "O(log(N))",
+    ),
+    CommandSpec::new("zrevrank", -3, |arr| {
+        Ok(Command::Zrank(try_zrank(arr, true)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@sortedset", "@fast"])
+    .doc(
+        "sorted_set",
+        "2.0.0",
+        "Returns the index of a member in a sorted set ordered by descending scores.",
+        "O(log(N))",
+    ),
+    CommandSpec::new("zcount", 4, |arr| {
+        Ok(Command::Zcount(try_zcount(arr)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@sortedset", "@fast"])
+    .doc(
+        "sorted_set",
+        "2.0.0",
+        "Returns the count of members in a sorted set that have scores within a range.",
+        "O(log(N)) with N being the number of elements in the sorted set.",
+    ),
+    CommandSpec::new("zlexcount", 4, |arr| {
+        Ok(Command::ZlexCount(try_zlexcount(arr)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@sortedset", "@fast"])
+    .doc(
+        "sorted_set",
+        "2.8.9",
+        "Returns the number of members in a sorted set within a lexicographical range.",
+        "O(log(N)) with N being the number of elements in the sorted set.",
+    ),
+    CommandSpec::new("zrange", -4, |arr| {
+        Ok(Command::Zrange(try_zrange(arr, false)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@sortedset", "@slow"])
+    .doc(
+        "sorted_set",
+        "1.2.0",
+        "Returns members in a sorted set within a range of indexes.",
+        "O(log(N)+M) with N being the number of elements in the sorted set and M the number of elements returned.",
+    ),
+    CommandSpec::new("zrangestore", -5, |arr| {
+        Ok(Command::Zrange(try_zrange(arr, true)?))
+    })
+    .flags(&[Write, Denyoom])
+    .keys(1, 2, 1, &["RW", "access", "update"])
+    .acl(&["@write", "@sortedset", "@slow"])
+    .doc(
+        "sorted_set",
+        "6.2.0",
+        "Stores a range of members from sorted set in a key.",
+        "O(log(N)+M) with N being the number of elements in the sorted set and M the number of elements stored into the destination key.",
+    ),
+    CommandSpec::new("zrevrange", -4, |arr| {
+        Ok(Command::Zrange(try_zrange_legacy(arr, None, true)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@sortedset", "@slow"])
+    .doc(
+        "sorted_set",
+        "1.2.0",
+        "Returns members in a sorted set within a range of indexes in reverse order.",
+        "O(log(N)+M) with N being the number of elements in the sorted set and M the number of elements returned.",
+    ),
+    CommandSpec::new("zrangebyscore", -4, |arr| {
+        Ok(Command::Zrange(try_zrange_legacy(arr, Some("BYSCORE"), false)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@sortedset", "@slow"])
+    .doc(
+        "sorted_set",
+        "1.0.5",
+        "Returns members in a sorted set within a range of scores.",
+        "O(log(N)+M) with N being the number of elements in the sorted set and M the number of elements returned.",
+    ),
+    CommandSpec::new("zrevrangebyscore", -4, |arr| {
+        Ok(Command::Zrange(try_zrange_legacy(arr, Some("BYSCORE"), true)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@sortedset", "@slow"])
+    .doc(
+        "sorted_set",
+        "2.2.0",
+        "Returns members in a sorted set within a range of scores in reverse order.",
+        "O(log(N)+M) with N being the number of elements in the sorted set and M the number of elements returned.",
+    ),
+    CommandSpec::new("zrangebylex", -4, |arr| {
+        Ok(Command::Zrange(try_zrange_legacy(arr, Some("BYLEX"), false)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@sortedset", "@slow"])
+    .doc(
+        "sorted_set",
+        "2.8.9",
+        "Returns members in a sorted set within a lexicographical range.",
+        "O(log(N)+M) with N being the number of elements in the sorted set and M the number of elements returned.",
+    ),
+    CommandSpec::new("zrevrangebylex", -4, |arr| {
+        Ok(Command::Zrange(try_zrange_legacy(arr, Some("BYLEX"), true)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@sortedset", "@slow"])
+    .doc(
+        "sorted_set",
+        "2.8.9",
+        "Returns members in a sorted set within a lexicographical range in reverse order.",
+        "O(log(N)+M) with N being the number of elements in the sorted set and M the number of elements returned.",
+    ),
+    CommandSpec::new("zpopmin", -2, |arr| {
+        Ok(Command::Zpop(try_zpop(arr, false)?))
+    })
+    .flags(&[Write, Fast])
+    .keys(1, 1, 1, &["RW", "access", "delete"])
+    .acl(&["@write", "@sortedset", "@fast"])
+    .doc(
+        "sorted_set",
+        "5.0.0",
+        "Returns the lowest-scoring members from a sorted set after removing them. Deletes the sorted set if the last member was popped.",
+        "O(log(N)*M) with N being the number of elements in the sorted set, and M being the number of elements popped.",
+    ),
+    CommandSpec::new("zpopmax", -2, |arr| {
+        Ok(Command::Zpop(try_zpop(arr, true)?))
+    })
+    .flags(&[Write, Fast])
+    .keys(1, 1, 1, &["RW", "access", "delete"])
+    .acl(&["@write", "@sortedset", "@fast"])
+    .doc(
+        "sorted_set",
+        "5.0.0",
+        "Returns the highest-scoring members from a sorted set after removing them. Deletes the sorted set if the last member was popped.",
+        "O(log(N)*M) with N being the number of elements in the sorted set, and M being the number of elements popped.",
+    ),
+    CommandSpec::new("bzpopmin", -3, |arr| {
+        Ok(Command::Bzpop(try_bzpop(arr, false)?))
+    })
+    .flags(&[Write, Fast, Blocking])
+    .keys(1, -2, 1, &["RW", "access", "delete"])
+    .acl(&["@write", "@sortedset", "@fast", "@blocking"])
+    .doc(
+        "sorted_set",
+        "5.0.0",
+        "Removes and returns the member with the lowest score from one or more sorted sets. Blocks until a member is available otherwise. Deletes the sorted set if the last element was popped.",
+        "O(log(N)) with N being the number of elements in the sorted set.",
+    ),
+    CommandSpec::new("bzpopmax", -3, |arr| {
+        Ok(Command::Bzpop(try_bzpop(arr, true)?))
+    })
+    .flags(&[Write, Fast, Blocking])
+    .keys(1, -2, 1, &["RW", "access", "delete"])
+    .acl(&["@write", "@sortedset", "@fast", "@blocking"])
+    .doc(
+        "sorted_set",
+        "5.0.0",
+        "Removes and returns the member with the highest score from one or more sorted sets. Blocks until a member is available otherwise. Deletes the sorted set if the last element was popped.",
+        "O(log(N)) with N being the number of elements in the sorted set.",
+    ),
+    CommandSpec::new("zunion", -3, |arr| {
+        Ok(Command::ZsetOp(try_zsetop(arr, ZsetOpKind::Union, false)?))
+    })
+    .flags(&[Readonly])
+    .numkeys(1, &["RO", "access"])
+    .acl(&["@read", "@sortedset", "@slow"])
+    .doc(
+        "sorted_set",
+        "6.2.0",
+        "Returns the union of multiple sorted sets.",
+        "O(N)+O(M*log(M)) with N being the sum of the sizes of the input sorted sets, and M being the number of elements in the resulting sorted set.",
+    ),
+    CommandSpec::new("zinter", -3, |arr| {
+        Ok(Command::ZsetOp(try_zsetop(arr, ZsetOpKind::Inter, false)?))
+    })
+    .flags(&[Readonly])
+    .numkeys(1, &["RO", "access"])
+    .acl(&["@read", "@sortedset", "@slow"])
+    .doc(
+        "sorted_set",
+        "6.2.0",
+        "Returns the intersect of multiple sorted sets.",
+        "O(N*K)+O(M*log(M)) worst case with N being the smallest input sorted set, K being the number of input sorted sets and M being the number of elements in the resulting sorted set.",
+    ),
+    CommandSpec::new("zdiff", -3, |arr| {
+        Ok(Command::ZsetOp(try_zsetop(arr, ZsetOpKind::Diff, false)?))
+    })
+    .flags(&[Readonly])
+    .numkeys(1, &["RO", "access"])
+    .acl(&["@read", "@sortedset", "@slow"])
+    .doc(
+        "sorted_set",
+        "6.2.0",
+        "Returns the difference between multiple sorted sets.",
+        "O(L + (N-K)log(N)) worst case where L is the total number of elements in all the sets, N is the size of the first set, and K is the size of the result set.",
+    ),
+    CommandSpec::new("zunionstore", -4, |arr| {
+        Ok(Command::ZsetOp(try_zsetop(arr, ZsetOpKind::Union, true)?))
+    })
+    .flags(&[Write, Denyoom])
+    .keys(1, 1, 1, &["OW", "update"])
+    .numkeys(2, &["RO", "access"])
+    .acl(&["@write", "@sortedset", "@slow"])
+    .doc(
+        "sorted_set",
+        "2.0.0",
+        "Stores the union of multiple sorted sets in a key.",
+        "O(N)+O(M*log(M)) with N being the sum of the sizes of the input sorted sets, and M being the number of elements in the resulting sorted set.",
+    ),
+    CommandSpec::new("zinterstore", -4, |arr| {
+        Ok(Command::ZsetOp(try_zsetop(arr, ZsetOpKind::Inter, true)?))
+    })
+    .flags(&[Write, Denyoom])
+    .keys(1, 1, 1, &["OW", "update"])
+    .numkeys(2, &["RO", "access"])
+    .acl(&["@write", "@sortedset", "@slow"])
+    .doc(
+        "sorted_set",
+        "2.0.0",
+        "Stores the intersect of multiple sorted sets in a key.",
+        "O(N*K)+O(M*log(M)) worst case with N being the smallest input sorted set, K being the number of input sorted sets and M being the number of elements in the resulting sorted set.",
+    ),
+    CommandSpec::new("zdiffstore", -4, |arr| {
+        Ok(Command::ZsetOp(try_zsetop(arr, ZsetOpKind::Diff, true)?))
+    })
+    .flags(&[Write, Denyoom])
+    .keys(1, 1, 1, &["OW", "update"])
+    .numkeys(2, &["RO", "access"])
+    .acl(&["@write", "@sortedset", "@slow"])
+    .doc(
+        "sorted_set",
+        "6.2.0",
+        "Stores the difference of multiple sorted sets in a key.",
+        "O(L + (N-K)log(N)) worst case where L is the total number of elements in all the sets, N is the size of the first set, and K is the size of the result set.",
+    ),
+    CommandSpec::new("xadd", -5, |arr| {
+        Ok(Command::Xadd(try_xadd(arr)?))
+    })
+    .flags(&[Write, Denyoom, Fast])
+    .keys(1, 1, 1, &["RW", "insert"])
+    .acl(&["@write", "@stream", "@fast"])
+    .tips(&["nondeterministic_output"])
+    .doc(
+        "stream",
+        "5.0.0",
+        "Appends a new message to a stream. Creates the key if it doesn't exist.",
+        "O(1) when adding a new entry, O(N) when trimming where N being the number of entries evicted.",
+    ),
+    CommandSpec::new("xtrim", -4, |arr| {
+        Ok(Command::Xtrim(try_xtrim(arr)?))
+    })
+    .flags(&[Write])
+    .keys(1, 1, 1, &["RW", "delete"])
+    .acl(&["@write", "@stream", "@slow"])
+    .tips(&["nondeterministic_output"])
+    .doc(
+        "stream",
+        "5.0.0",
+        "Deletes messages from the beginning of a stream.",
+        "O(N), with N being the number of evicted entries.",
+    ),
+    CommandSpec::new("xrange", -4, |arr| {
+        Ok(Command::Xrange(try_xrange(arr, false)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@stream", "@slow"])
+    .doc(
+        "stream",
+        "5.0.0",
+        "Returns the messages from a stream within a range of IDs.",
+        "O(N) with N being the number of elements being returned. If N is constant (e.g. always asking for the first 10 elements with COUNT), you can consider it O(1).",
+    ),
+    CommandSpec::new("xrevrange", -4, |arr| {
+        Ok(Command::Xrange(try_xrange(arr, true)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@stream", "@slow"])
+    .doc(
+        "stream",
+        "5.0.0",
+        "Returns the messages from a stream within a range of IDs in reverse order.",
+        "O(N) with N being the number of elements returned. If N is constant (e.g. always asking for the first 10 elements with COUNT), you can consider it O(1).",
+    ),
+    CommandSpec::new("xlen", 2, |arr| {
+        Ok(Command::Xlen(try_xlen(arr)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO"])
+    .acl(&["@read", "@stream", "@fast"])
+    .doc(
+        "stream",
+        "5.0.0",
+        "Return the number of messages in a stream.",
+        "O(1)",
+    ),
+    CommandSpec::new("xdel", -3, |arr| {
+        Ok(Command::Xdel(try_xdel(arr)?))
+    })
+    .flags(&[Write, Fast])
+    .keys(1, 1, 1, &["RW", "delete"])
+    .acl(&["@write", "@stream", "@fast"])
+    .doc(
+        "stream",
+        "5.0.0",
+        "Returns the number of messages after removing them from a stream.",
+        "O(1) for each single item to delete in the stream, regardless of the stream size.",
+    ),
+    CommandSpec::new("xread", -4, |arr| {
+        Ok(Command::Xread(try_xread(arr)?))
+    })
+    .flags(&[Readonly, Blocking])
+    .keyword("STREAMS", &["RO", "access"])
+    .acl(&["@read", "@stream", "@slow", "@blocking"])
+    .doc(
+        "stream",
+        "5.0.0",
+        "Returns messages from multiple streams with IDs greater than the ones requested. Blocks until a message is available otherwise.",
+        "O(N) with N being the number of elements being returned per stream.",
+    ),
+    CommandSpec::new("xreadgroup", -7, |arr| {
+        Ok(Command::XreadGroup(try_xreadgroup(arr)?))
+    })
+    .flags(&[Write, Blocking])
+    .keyword("STREAMS", &["RW", "access", "update"])
+    .acl(&["@write", "@stream", "@slow", "@blocking"])
+    .doc(
+        "stream",
+        "5.0.0",
+        "Returns new or historical messages from a stream for a consumer in a group. Blocks until a message is available otherwise.",
+        "For each stream mentioned: O(M) with M being the number of elements returned. If M is constant (e.g. always asking for the first 10 elements with COUNT), you can consider it O(1).",
+    ),
+    // requests always resolve to a subcommand, the container alone fails the arity check
+    CommandSpec::new("xgroup", -2, |_| Err(Error::WrongArity("xgroup".into())))
+    .acl(&["@slow"])
+    .doc(
+        "stream",
+        "5.0.0",
+        "A container for consumer groups commands.",
+        "Depends on subcommand.",
+    )
+    .subcommands(XGROUP_SUBCOMMANDS),
+    CommandSpec::new("xack", -4, |arr| {
+        Ok(Command::Xack(try_xack(arr)?))
+    })
+    .flags(&[Write, Fast])
+    .keys(1, 1, 1, &["RW", "update"])
+    .acl(&["@write", "@stream", "@fast"])
+    .doc(
+        "stream",
+        "5.0.0",
+        "Returns the number of messages that were successfully acknowledged by the consumer group member of a stream.",
+        "O(1) for each message ID processed.",
+    ),
+    CommandSpec::new("xpending", -3, |arr| {
+        Ok(Command::Xpending(try_xpending(arr)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@stream", "@slow"])
+    .tips(&["nondeterministic_output"])
+    .doc(
+        "stream",
+        "5.0.0",
+        "Returns the information and entries from a stream consumer group's pending entries list.",
+        "O(N) with N being the number of elements returned, so asking for a small fixed number of entries per call is O(1).",
+    ),
+    CommandSpec::new("xclaim", -6, |arr| {
+        Ok(Command::Xclaim(try_xclaim(arr)?))
+    })
+    .flags(&[Write, Fast])
+    .keys(1, 1, 1, &["RW", "update"])
+    .acl(&["@write", "@stream", "@fast"])
+    .tips(&["nondeterministic_output"])
+    .doc(
+        "stream",
+        "5.0.0",
+        "Changes, or acquires, ownership of a message in a consumer group, as if the message was delivered a consumer group member.",
+        "O(log N) with N being the number of messages in the PEL of the consumer group.",
+    ),
+    CommandSpec::new("xautoclaim", -6, |arr| {
+        Ok(Command::XautoClaim(try_xautoclaim(arr)?))
+    })
+    .flags(&[Write, Fast])
+    .keys(1, 1, 1, &["RW", "update"])
+    .acl(&["@write", "@stream", "@fast"])
+    .tips(&["nondeterministic_output"])
+    .doc(
+        "stream",
+        "6.2.0",
+        "Changes, or acquires, ownership of messages in a consumer group, as if the messages were delivered to as consumer group member.",
+        "O(1) if COUNT is small.",
+    ),
+    // requests always resolve to a subcommand, the container alone fails the arity check
+    CommandSpec::new("xinfo", -2, |_| Err(Error::WrongArity("xinfo".into())))
+    .acl(&["@slow"])
+    .doc(
+        "stream",
+        "5.0.0",
+        "A container for stream introspection commands.",
+        "Depends on subcommand.",
+    )
+    .subcommands(XINFO_SUBCOMMANDS),
+    CommandSpec::new("setbit", 4, |arr| {
+        Ok(Command::Setbit(try_setbit(arr)?))
+    })
+    .flags(&[Write, Denyoom])
+    .keys(1, 1, 1, &["RW", "access", "update"])
+    .acl(&["@write", "@bitmap", "@slow"])
+    .doc(
+        "bitmap",
+        "2.2.0",
+        "Sets or clears the bit at offset of the string value. Creates the key if it doesn't exist.",
+        "O(1)",
+    ),
+    CommandSpec::new("getbit", 3, |arr| {
+        Ok(Command::Getbit(try_getbit(arr)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@bitmap", "@fast"])
+    .doc(
+        "bitmap",
+        "2.2.0",
+        "Returns a bit value by offset.",
+        "O(1)",
+    ),
+    CommandSpec::new("bitcount", -2, |arr| {
+        Ok(Command::Bitcount(try_bitcount(arr)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@bitmap", "@slow"])
+    .doc(
+        "bitmap",
+        "2.6.0",
+        "Counts the number of set bits (population counting) in a string.",
+        "O(N)",
+    ),
+    CommandSpec::new("bitpos", -3, |arr| {
+        Ok(Command::Bitpos(try_bitpos(arr)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@bitmap", "@slow"])
+    .doc(
+        "bitmap",
+        "2.8.7",
+        "Finds the first set (1) or clear (0) bit in a string.",
+        "O(N)",
+    ),
+    CommandSpec::new("bitop", -4, |arr| {
+        Ok(Command::Bitop(try_bitop(arr)?))
+    })
+    .flags(&[Write, Denyoom])
+    .keys(2, -1, 1, &["OW", "update"])
+    .acl(&["@write", "@bitmap", "@slow"])
+    .doc(
+        "bitmap",
+        "2.6.0",
+        "Performs bitwise operations on multiple strings, and stores the result.",
+        "O(N)",
+    ),
+    CommandSpec::new("bitfield", -2, |arr| {
+        Ok(Command::Bitfield(try_bitfield(arr, false)?))
+    })
+    .flags(&[Write, Denyoom])
+    .keys(1, 1, 1, &["RW", "update", "access"])
+    .acl(&["@write", "@bitmap", "@slow"])
+    .doc(
+        "bitmap",
+        "3.2.0",
+        "Performs arbitrary bitfield integer operations on strings.",
+        "O(1) for each subcommand specified",
+    ),
+    CommandSpec::new("bitfield_ro", -2, |arr| {
+        Ok(Command::Bitfield(try_bitfield(arr, true)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@bitmap", "@fast"])
+    .doc(
+        "bitmap",
+        "6.0.0",
+        "Performs arbitrary read-only bitfield integer operations on strings.",
+        "O(1) for each subcommand specified",
+    ),
+    CommandSpec::new("pfadd", -2, |arr| {
+        Ok(Command::Pfadd(try_pfadd(arr)?))
+    })
+    .flags(&[Write, Denyoom, Fast])
+    .keys(1, 1, 1, &["RW", "insert"])
+    .acl(&["@write", "@hyperloglog", "@fast"])
+    .doc(
+        "hyperloglog",
+        "2.8.9",
+        "Adds elements to a HyperLogLog key. Creates the key if it doesn't exist.",
+        "O(1) to add every element.",
+    ),
+    // readonly like in redis, though a stale cached cardinality is written back to the key
+    CommandSpec::new("pfcount", -2, |arr| {
+        Ok(Command::Pfcount(try_pfcount(arr)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, -1, 1, &["RW", "access"])
+    .acl(&["@read", "@hyperloglog", "@slow"])
+    .doc(
+        "hyperloglog",
+        "2.8.9",
+        "Returns the approximated cardinality of the set(s) observed by the HyperLogLog key(s).",
+        "O(1) with a very small average constant time when called with a single key. O(N) with N being the number of keys, and much bigger constant times, when called with multiple keys.",
+    ),
+    CommandSpec::new("pfmerge", -2, |arr| {
+        Ok(Command::Pfmerge(try_pfmerge(arr)?))
+    })
+    .flags(&[Write, Denyoom])
+    .keys(1, -1, 1, &["RW", "access", "insert"])
+    .acl(&["@write", "@hyperloglog", "@slow"])
+    .doc(
+        "hyperloglog",
+        "2.8.9",
+        "Merges one or more HyperLogLog values into a single key.",
+        "O(N) to merge N HyperLogLogs, but with high constant times.",
+    ),
+    CommandSpec::new("geoadd", -5, |arr| {
+        Ok(Command::Geoadd(try_geoadd(arr)?))
+    })
+    .flags(&[Write, Denyoom])
+    .keys(1, 1, 1, &["RW", "update"])
+    .acl(&["@write", "@geo", "@slow"])
+    .doc(
+        "geo",
+        "3.2.0",
+        "Adds one or more members to a geospatial index. The key is created if it doesn't exist.",
+        "O(log(N)) for each item added, where N is the number of elements in the sorted set.",
+    ),
+    CommandSpec::new("geodist", -4, |arr| {
+        Ok(Command::Geodist(try_geodist(arr)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@geo", "@slow"])
+    .doc(
+        "geo",
+        "3.2.0",
+        "Returns the distance between two members of a geospatial index.",
+        "O(1)",
+    ),
+    CommandSpec::new("geohash", -2, |arr| {
+        Ok(Command::Geohash(try_geohash(arr)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@geo", "@slow"])
+    .doc(
+        "geo",
+        "3.2.0",
+        "Returns members from a geospatial index as geohash strings.",
+        "O(1) for each member requested.",
+    ),
+    CommandSpec::new("geopos", -2, |arr| {
+        Ok(Command::Geopos(try_geopos(arr)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@geo", "@slow"])
+    .doc(
+        "geo",
+        "3.2.0",
+        "Returns the longitude and latitude of members from a geospatial index.",
+        "O(1) for each member requested.",
+    ),
+    CommandSpec::new("geosearch", -7, |arr| {
+        Ok(Command::Geosearch(try_geosearch(arr, false)?))
+    })
+    .flags(&[Readonly])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@read", "@geo", "@slow"])
+    .doc(
+        "geo",
+        "6.2.0",
+        "Queries a geospatial index for members inside an area of a box or a circle.",
+        "O(N+log(M)) where N is the number of elements in the grid-aligned bounding box area around the shape provided as the filter and M is the number of items inside the shape",
+    ),
+    CommandSpec::new("geosearchstore", -8, |arr| {
+        Ok(Command::Geosearch(try_geosearch(arr, true)?))
+    })
+    .flags(&[Write, Denyoom])
+    .keys(1, 2, 1, &["RW", "access", "update"])
+    .acl(&["@write", "@geo", "@slow"])
+    .doc(
+        "geo",
+        "6.2.0",
+        "Queries a geospatial index for members inside an area of a box or a circle, optionally stores the result.",
+        "O(N+log(M)) where N is the number of elements in the grid-aligned bounding box area around the shape provided as the filter and M is the number of items inside the shape",
+    ),
+    CommandSpec::new("del", -2, |arr| {
+        Ok(Command::Del(try_del(arr, false)?))
+    })
+    .flags(&[Write])
+    .keys(1, -1, 1, &["RM", "delete"])
+    .acl(&["@keyspace", "@write", "@slow"])
+    .tips(&["request_policy:multi_shard", "response_policy:agg_sum"])
+    .doc(
+        "generic",
+        "1.0.0",
+        "Deletes one or more keys.",
+        "O(N) where N is the number of keys that will be removed. When a key to remove holds a value other than a string, the individual complexity for this key is O(M) where M is the number of elements in the list, set, sorted set or hash. Removing a single key that holds a string value is O(1).",
+    ),
+    CommandSpec::new("unlink", -2, |arr| {
+        Ok(Command::Del(try_del(arr, true)?))
+    })
+    .flags(&[Write, Fast])
+    .keys(1, -1, 1, &["RM", "delete"])
+    .acl(&["@keyspace", "@write", "@fast"])
+    .tips(&["request_policy:multi_shard", "response_policy:agg_sum"])
+    .doc(
+        "generic",
+        "4.0.0",
+        "Asynchronously deletes one or more keys.",
+        "O(1) for each key removed regardless of its size. Then the command does O(N) work in a different thread in order to reclaim memory, where N is the number of allocations the deleted objects where composed of.",
+    ),
+    CommandSpec::new("exists", -2, |arr| {
+        Ok(Command::Exists(try_exists(arr, false)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, -1, 1, &["RO"])
+    .acl(&["@keyspace", "@read", "@fast"])
+    .tips(&["request_policy:multi_shard", "response_policy:agg_sum"])
+    .doc(
+        "generic",
+        "1.0.0",
+        "Determines whether one or more keys exist.",
+        "O(N) where N is the number of keys to check.",
+    ),
+    CommandSpec::new("touch", -2, |arr| {
+        Ok(Command::Exists(try_exists(arr, true)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, -1, 1, &["RO"])
+    .acl(&["@keyspace", "@read", "@fast"])
+    .tips(&["request_policy:multi_shard", "response_policy:agg_sum"])
+    .doc(
+        "generic",
+        "3.2.1",
+        "Returns the number of existing keys out of those specified after updating the time they were last accessed.",
+        "O(N) where N is the number of keys that will be touched.",
+    ),
+    CommandSpec::new("type", 2, |arr| {
+        Ok(Command::Type(try_type(arr)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO"])
+    .acl(&["@keyspace", "@read", "@fast"])
+    .doc(
+        "generic",
+        "1.0.0",
+        "Determines the type of value stored at a key.",
+        "O(1)",
+    ),
+    CommandSpec::new("rename", 3, |arr| {
+        Ok(Command::Rename(try_rename(arr, false)?))
+    })
+    .flags(&[Write])
+    .keys(1, 2, 1, &["RW", "access", "delete", "update"])
+    .acl(&["@keyspace", "@write", "@slow"])
+    .doc(
+        "generic",
+        "1.0.0",
+        "Renames a key and overwrites the destination.",
+        "O(1)",
+    ),
+    CommandSpec::new("renamenx", 3, |arr| {
+        Ok(Command::Rename(try_rename(arr, true)?))
+    })
+    .flags(&[Write, Fast])
+    .keys(1, 2, 1, &["RW", "access", "delete", "insert"])
+    .acl(&["@keyspace", "@write", "@fast"])
+    .doc(
+        "generic",
+        "1.0.0",
+        "Renames a key only when the target key name doesn't exist.",
+        "O(1)",
+    ),
+    CommandSpec::new("copy", -3, |arr| {
+        Ok(Command::Copy(try_copy(arr)?))
+    })
+    .flags(&[Write, Denyoom])
+    .keys(1, 2, 1, &["RW", "access", "update"])
+    .acl(&["@keyspace", "@write", "@slow"])
+    .doc(
+        "generic",
+        "6.2.0",
+        "Copies the value of a key to a new key.",
+        "O(N) worst case for collections, where N is the number of nested items. O(1) for string values.",
+    ),
+    CommandSpec::new("randomkey", 1, |arr| {
+        Ok(Command::RandomKey(try_randomkey(arr)?))
+    })
+    .flags(&[Readonly])
+    .acl(&["@keyspace", "@read", "@slow"])
+    .tips(&["request_policy:all_shards", "response_policy:special", "nondeterministic_output"])
+    .doc(
+        "generic",
+        "1.0.0",
+        "Returns a random key name from the database.",
+        "O(1)",
+    ),
+    CommandSpec::new("dbsize", 1, |arr| {
+        Ok(Command::Dbsize(try_dbsize(arr)?))
+    })
+    .flags(&[Readonly, Fast])
+    .acl(&["@keyspace", "@read", "@fast"])
+    .tips(&["request_policy:all_shards", "response_policy:agg_sum"])
+    .doc(
+        "server",
+        "1.0.0",
+        "Returns the number of keys in the database.",
+        "O(1)",
+    ),
+    CommandSpec::new("flushdb", -1, |arr| {
+        Ok(Command::Flush(try_flush(arr, false)?))
+    })
+    .flags(&[Write])
+    .acl(&["@keyspace", "@write", "@slow", "@dangerous"])
+    .tips(&["request_policy:all_shards", "response_policy:all_succeeded"])
+    .doc(
+        "server",
+        "1.0.0",
+        "Remove all keys from the current database.",
+        "O(N) where N is the number of keys in the selected database",
+    ),
+    CommandSpec::new("flushall", -1, |arr| {
+        Ok(Command::Flush(try_flush(arr, true)?))
+    })
+    .flags(&[Write])
+    .acl(&["@keyspace", "@write", "@slow", "@dangerous"])
+    .tips(&["request_policy:all_shards", "response_policy:all_succeeded"])
+    .doc(
+        "server",
+        "1.0.0",
+        "Removes all keys from all databases.",
+        "O(N) where N is the total number of keys in all databases",
+    ),
+    CommandSpec::new("keys", 2, |arr| {
+        Ok(Command::Keys(try_keys(arr)?))
+    })
+    .flags(&[Readonly])
+    .acl(&["@keyspace", "@read", "@slow", "@dangerous"])
+    .tips(&["request_policy:all_shards", "nondeterministic_output_order"])
+    .doc(
+        "generic",
+        "1.0.0",
+        "Returns all key names that match a pattern.",
+        "O(N) with N being the number of keys in the database, under the assumption that the key names in the database and the given pattern have limited length.",
+    ),
+    CommandSpec::new("scan", -2, |arr| {
+        Ok(Command::Scan(try_scan(arr)?))
+    })
+    .flags(&[Readonly])
+    .acl(&["@keyspace", "@read", "@slow"])
+    .tips(&["nondeterministic_output", "request_policy:special", "response_policy:special"])
+    .doc(
+        "generic",
+        "2.8.0",
+        "Iterates over the key names in the database.",
+        "O(1) for every call. O(N) for a complete iteration, including enough command calls for the cursor to return back to 0. N is the number of elements inside the collection.",
+    ),
+    CommandSpec::new("select", 2, |arr| {
+        Ok(Command::Select(try_select(arr)?))
+    })
+    .flags(&[Loading, Stale, Fast])
+    .acl(&["@keyspace", "@fast"])
+    .doc(
+        "connection",
+        "1.0.0",
+        "Changes the selected database.",
+        "O(1)",
+    ),
+    CommandSpec::new("move", 3, |arr| {
+        Ok(Command::Move(try_move(arr)?))
+    })
+    .flags(&[Write, Fast])
+    .keys(1, 1, 1, &["RW", "access", "delete"])
+    .acl(&["@keyspace", "@write", "@fast"])
+    .doc(
+        "generic",
+        "1.0.0",
+        "Moves a key to another database.",
+        "O(1)",
+    ),
+    CommandSpec::new("swapdb", 3, |arr| {
+        Ok(Command::Swapdb(try_swapdb(arr)?))
+    })
+    .flags(&[Write, Fast])
+    .acl(&["@keyspace", "@write", "@fast", "@dangerous"])
+    .doc(
+        "server",
+        "4.0.0",
+        "Swaps two Redis databases.",
+        "O(N) where N is the count of clients watching or blocking on keys from both databases.",
+    ),
+    CommandSpec::new("info", -1, |arr| {
+        Ok(Command::Info(try_info(arr)?))
+    })
+    .flags(&[Loading, Stale])
+    .acl(&["@slow", "@dangerous"])
+    .tips(&["nondeterministic_output", "request_policy:all_shards", "response_policy:special"])
+    .doc(
+        "server",
+        "1.0.0",
+        "Returns information and statistics about the server.",
+        "O(1)",
+    ),
+    // requests always resolve to a subcommand, the container alone fails the arity check
+    CommandSpec::new("object", -2, |_| Err(Error::WrongArity("object".into())))
+    .acl(&["@slow"])
+    .doc(
+        "generic",
+        "2.2.3",
+        "A container for object introspection commands.",
+        "Depends on subcommand.",
+    )
+    .subcommands(OBJECT_SUBCOMMANDS),
+    CommandSpec::new("expire", -3, |arr| {
+        Ok(Command::Expire(try_expire(arr, ExpireUnit::Seconds, false)?))
+    })
+    .flags(&[Write, Fast])
+    .keys(1, 1, 1, &["RW", "update"])
+    .acl(&["@keyspace", "@write", "@fast"])
+    .doc(
+        "generic",
+        "1.0.0",
+        "Sets the expiration time of a key in seconds.",
+        "O(1)",
+    ),
+    CommandSpec::new("pexpire", -3, |arr| {
+        Ok(Command::Expire(try_expire(arr, ExpireUnit::Milliseconds, false)?))
+    })
+    .flags(&[Write, Fast])
+    .keys(1, 1, 1, &["RW", "update"])
+    .acl(&["@keyspace", "@write", "@fast"])
+    .doc(
+        "generic",
+        "2.6.0",
+        "Sets the expiration time of a key in milliseconds.",
+        "O(1)",
+    ),
+    CommandSpec::new("expireat", -3, |arr| {
+        Ok(Command::Expire(try_expire(arr, ExpireUnit::Seconds, true)?))
+    })
+    .flags(&[Write, Fast])
+    .keys(1, 1, 1, &["RW", "update"])
+    .acl(&["@keyspace", "@write", "@fast"])
+    .doc(
+        "generic",
+        "1.2.0",
+        "Sets the expiration time of a key to a Unix timestamp.",
+        "O(1)",
+    ),
+    CommandSpec::new("pexpireat", -3, |arr| {
+        Ok(Command::Expire(try_expire(arr, ExpireUnit::Milliseconds, true)?))
+    })
+    .flags(&[Write, Fast])
+    .keys(1, 1, 1, &["RW", "update"])
+    .acl(&["@keyspace", "@write", "@fast"])
+    .doc(
+        "generic",
+        "2.6.0",
+        "Sets the expiration time of a key to a Unix milliseconds timestamp.",
+        "O(1)",
+    ),
+    CommandSpec::new("ttl", 2, |arr| Ok(Command::Ttl(try_ttl(arr, TtlKind::Ttl)?)))
+        .flags(&[Readonly, Fast])
+        .keys(1, 1, 1, &["RO", "access"])
+        .acl(&["@keyspace", "@read", "@fast"])
+        .tips(&["nondeterministic_output"])
+        .doc(
+            "generic",
+            "1.0.0",
+            "Returns the expiration time in seconds of a key.",
+            "O(1)",
+        ),
+    CommandSpec::new("pttl", 2, |arr| Ok(Command::Ttl(try_ttl(arr, TtlKind::Pttl)?)))
+        .flags(&[Readonly, Fast])
+        .keys(1, 1, 1, &["RO", "access"])
+        .acl(&["@keyspace", "@read", "@fast"])
+        .tips(&["nondeterministic_output"])
+        .doc(
+            "generic",
+            "2.6.0",
+            "Returns the expiration time in milliseconds of a key.",
+            "O(1)",
+        ),
+    CommandSpec::new("expiretime", 2, |arr| {
+        Ok(Command::Ttl(try_ttl(arr, TtlKind::ExpireTime)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@keyspace", "@read", "@fast"])
+    .doc(
+        "generic",
+        "7.0.0",
+        "Returns the expiration time of a key as a Unix timestamp.",
+        "O(1)",
+    ),
+    CommandSpec::new("pexpiretime", 2, |arr| {
+        Ok(Command::Ttl(try_ttl(arr, TtlKind::PexpireTime)?))
+    })
+    .flags(&[Readonly, Fast])
+    .keys(1, 1, 1, &["RO", "access"])
+    .acl(&["@keyspace", "@read", "@fast"])
+    .doc(
+        "generic",
+        "7.0.0",
+        "Returns the expiration time of a key as a Unix milliseconds timestamp.",
+        "O(1)",
+    ),
+    CommandSpec::new("persist", 2, |arr| Ok(Command::Persist(try_persist(arr)?)))
+        .flags(&[Write, Fast])
+        .keys(1, 1, 1, &["RW", "update"])
+        .acl(&["@keyspace", "@write", "@fast"])
+        .doc(
+            "generic",
+            "2.2.0",
+            "Removes the expiration time of a key.",
+            "O(1)",
+        ),
+    CommandSpec::new("hello", -1, |arr| Ok(Command::Hello(try_hello(arr)?)))
+        .flags(&[Noscript, Loading, Stale, Fast, NoAuth, AllowBusy])
+        .acl(&["@fast", "@connection"])
+        .doc(
+            "connection",
+            "6.0.0",
+            "Handshakes with the Redis server.",
+            "O(1)",
+        ),
+    CommandSpec::new("command", -1, |_| {
+        Ok(Command::CommandInfo(CommandInfo::new(CommandInfoKind::All)))
+    })
+    .flags(&[Loading, Stale])
+    .acl(&["@slow", "@connection"])
+    .tips(&["nondeterministic_output_order"])
+    .doc(
+        "server",
+        "2.8.13",
+        "Returns detailed information about all commands.",
+        "O(N) where N is the total number of Redis commands",
+    )
+    .subcommands(COMMAND_SUBCOMMANDS),
+];
+
+/// Every top level command the server implements.
+pub fn commands() -> &'static [CommandSpec] {
+    COMMANDS
+}
+
+/// Finds the command called `name`, ignoring case. Subcommands are found by their full
+/// name, e.g. `command|info`.
+pub fn lookup(name: &[u8]) -> Option<&'static CommandSpec> {
+    static INDEX: OnceLock<HashMap<&'static [u8], &'static CommandSpec>> = OnceLock::new();
+    let index = INDEX.get_or_init(|| {
+        COMMANDS
+            .iter()
+            .chain(COMMANDS.iter().flat_map(|spec| spec.subcommands))
+            .map(|spec| (spec.name.as_bytes(), spec))
+            .collect()
+    });
+
+    // every command name is short, anything longer can't match
+    if name.len() > 64 {
+        return None;
+    }
+    index.get(&name.to_ascii_lowercase()[..]).copied()
+}
+
+/// Finds the spec handling the request `args`, descending into the subcommands of container
+/// commands, and checks the request's arity against it.
+pub fn resolve(args: &[Bytes]) -> crate::Result<&'static CommandSpec> {
+    let spec = match args.first().and_then(|name| lookup(name)) {
+        Some(spec) => spec,
+        None => return Err(unknown_command(args)),
+    };
+
+    let spec = match (spec.subcommands.is_empty(), args.get(1)) {
+        (false, Some(sub)) => {
+            let mut name = spec.name.as_bytes().to_vec();
+            name.push(b'|');
+            name.extend_from_slice(sub);
+            match lookup(&name) {
+                Some(sub) => sub,
+                None => {
+                    return Err(Error::command(format!(
+                        "unknown subcommand '{}'. Try {} HELP.",
+                        truncate(sub, 128),
+                        spec.name.to_uppercase()
+                    )))
+                }
+            }
+        }
+        _ => spec,
+    };
+
+    if !spec.accepts(args.len()) {
+        return Err(Error::WrongArity(spec.name.into()));
+    }
+    Ok(spec)
+}
+
+/// Builds the reply redis gives for an unknown command, quoting its first arguments.
+fn unknown_command(args: &[Bytes]) -> Error {
+    let mut quoted = String::new();
+    for arg in args.iter().skip(1) {
+        if quoted.len() >= 128 {
+            break;
+        }
+        quoted.push('\'');
+        quoted.push_str(&truncate(arg, 128 - quoted.len()));
+        quoted.push_str("' ");
+    }
+
+    let name = args.first().map(|name| truncate(name, 128));
+    Error::command(format!(
+        "unknown command '{}', with args beginning with: {}",
+        name.unwrap_or_default(),
+        quoted
+    ))
+}
+
+fn truncate(arg: &[u8], len: usize) -> String {
+    String::from_utf8_lossy(arg).chars().take(len).collect()
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<Bytes> {
+        args.iter()
+            .map(|arg| Bytes::from(arg.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_lookup_ignores_case() {
+        assert_eq!(lookup(b"get").map(|spec| spec.name), Some("get"));
+        assert_eq!(lookup(b"GET").map(|spec| spec.name), Some("get"));
+        assert_eq!(
+            lookup(b"PExpireAt").map(|spec| spec.name),
+            Some("pexpireat")
+        );
+        assert_eq!(
+            lookup(b"Command|Info").map(|spec| spec.name),
+            Some("command|info")
+        );
+        assert!(lookup(b"nope").is_none());
+    }
+
+    #[test]
+    fn test_arity() {
+        let set = lookup(b"set").unwrap();
+        assert!(!set.accepts(2));
+        assert!(set.accepts(3));
+        assert!(set.accepts(8));
+
+        let get = lookup(b"get").unwrap();
+        assert!(!get.accepts(1));
+        assert!(get.accepts(2));
+        assert!(!get.accepts(3));
+    }
+
+    #[test]
+    fn test_resolve_subcommands() {
+        assert_eq!(resolve(&args(&["command"])).unwrap().name, "command");
+        assert_eq!(
+            resolve(&args(&["COMMAND", "count"])).unwrap().name,
+            "command|count"
+        );
+        assert_eq!(
+            resolve(&args(&["command", "count", "x"]))
+                .err()
+                .unwrap()
+                .to_string(),
+            "ERR wrong number of arguments for 'command|count' command"
+        );
+        assert_eq!(
+            resolve(&args(&["command", "nope"]))
+                .err()
+                .unwrap()
+                .to_string(),
+            "ERR unknown subcommand 'nope'. Try COMMAND HELP."
+        );
+        assert_eq!(
+            resolve(&args(&["command", "help"])).unwrap().name,
+            "command|help"
+        );
+    }
+
+    #[test]
+    fn test_key_positions() {
+        let get = args(&["get", "a"]);
+        assert_eq!(lookup(b"get").unwrap().key_positions(&get), vec![1]);
+        let set = args(&["set", "a", "b", "EX", "10"]);
+        assert_eq!(lookup(b"set").unwrap().key_positions(&set), vec![1]);
+        let ping = args(&["ping", "a"]);
+        assert!(lookup(b"ping").unwrap().key_positions(&ping).is_empty());
+        let lmpop = args(&["lmpop", "2", "a", "b", "LEFT"]);
+        assert_eq!(lookup(b"lmpop").unwrap().key_positions(&lmpop), vec![2, 3]);
+        let xread = args(&["xread", "COUNT", "1", "streams", "a", "b", "0", "0"]);
+        assert_eq!(lookup(b"xread").unwrap().key_positions(&xread), vec![4, 5]);
+    }
+
+    #[test]
+    fn test_names_are_unique() {
+        let mut names: Vec<_> = commands().iter().map(|spec| spec.name).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), commands().len());
+    }
+}
@@ -0,0 +1,62 @@
+use bytes::Bytes;
+
+use crate::{Db, Error, RESPType};
+
+/// RENAME, or RENAMENX which leaves an existing destination alone. The value keeps its ttl.
+pub struct Rename {
+    key: Bytes,
+    new_key: Bytes,
+    nx: bool,
+}
+
+impl Rename {
+    pub fn new(key: Bytes, new_key: Bytes) -> Self {
+        Rename {
+            key,
+            new_key,
+            nx: false,
+        }
+    }
+
+    pub fn nx(mut self, nx: bool) -> Self {
+        self.nx = nx;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        // both shards are locked together in the db's global order, so renames in opposite
+        // directions can't deadlock
+        let mut locked = db.lock(&[&self.key, &self.new_key]);
+        if !locked.update(&self.key, |slot| slot.is_some()) {
+            return Error::command("no such key").into();
+        }
+        if self.key == self.new_key {
+            return self.reply(!self.nx);
+        }
+        if self.nx && locked.update(&self.new_key, |slot| slot.is_some()) {
+            return self.reply(false);
+        }
+
+        let entry = locked.update(&self.key, Option::take);
+        locked.update(&self.new_key, |slot| *slot = entry);
+        self.reply(true)
+    }
+
+    fn reply(&self, renamed: bool) -> RESPType {
+        match self.nx {
+            false => RESPType::String("OK".into()),
+            true => RESPType::Integer(renamed as i64),
+        }
+    }
+}
+
+impl From<Rename> for RESPType {
+    fn from(rename: Rename) -> RESPType {
+        let name = if rename.nx { "renamenx" } else { "rename" };
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from(name)),
+            RESPType::Bulk(rename.key),
+            RESPType::Bulk(rename.new_key),
+        ])
+    }
+}
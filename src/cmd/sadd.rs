@@ -0,0 +1,44 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, Set, Value};
+use crate::{Db, RESPType};
+
+pub struct Sadd {
+    key: Bytes,
+    members: Vec<Bytes>,
+}
+
+impl Sadd {
+    pub fn new(key: Bytes, members: Vec<Bytes>) -> Self {
+        Sadd { key, members }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let entry = slot.get_or_insert_with(|| Entry::new(Value::Set(Set::default())));
+            let set = match entry.set_mut() {
+                Err(e) => return e.into(),
+                Ok(set) => set,
+            };
+
+            let added = self
+                .members
+                .iter()
+                .filter(|member| set.insert((*member).clone()))
+                .count();
+            RESPType::Integer(added as i64)
+        })
+    }
+}
+
+impl From<Sadd> for RESPType {
+    fn from(sadd: Sadd) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("sadd")),
+            RESPType::Bulk(sadd.key),
+        ];
+        arr.extend(sadd.members.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,107 @@
+use bytes::Bytes;
+
+use crate::db::POSITION_BITS;
+use crate::{Db, RESPType};
+
+use super::glob::glob_match;
+use super::hscan::scan_reply;
+
+/// The cursor keeps the shard in its top bits and the position within it in the rest.
+const SHARD_SHIFT: u32 = POSITION_BITS;
+
+/// SCAN, iterating the keyspace a page at a time with a stateless cursor.
+///
+/// Shards are visited in order, one lock at a time, and each one is walked by the hash
+/// position of its keys rather than by where they sit in its table. Keys never change shard
+/// and positions don't depend on the table, so every key present for the whole iteration is
+/// returned at least once however the shards change in between calls. Shards keep their keys
+/// ordered by position, so a call only visits the keys it returns.
+pub struct Scan {
+    cursor: u64,
+    pattern: Option<Bytes>,
+    count: usize,
+    kind: Option<Bytes>,
+}
+
+impl Scan {
+    pub fn new(cursor: u64) -> Self {
+        Scan {
+            cursor,
+            pattern: None,
+            count: 10,
+            kind: None,
+        }
+    }
+
+    /// Only return keys matching the glob style `pattern`.
+    pub fn pattern(mut self, pattern: Bytes) -> Self {
+        self.pattern = Some(pattern);
+        self
+    }
+
+    /// Hint of how many keys to visit per call.
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Only return keys holding this type of value, as named by TYPE.
+    pub fn kind(mut self, kind: Bytes) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let mut shard = (self.cursor >> SHARD_SHIFT) as usize;
+        let mut cursor = self.cursor & ((1 << SHARD_SHIFT) - 1);
+        let mut visited = 0;
+        let mut keys = vec![];
+
+        while shard < db.shard_count() && visited < self.count {
+            let next = db.scan_shard_page(shard, cursor, self.count - visited, |key, entry| {
+                visited += 1;
+                let pattern = self.pattern.as_ref();
+                let kind = self.kind.as_ref();
+                if pattern.is_none_or(|pattern| glob_match(pattern, key, false))
+                    && kind.is_none_or(|kind| {
+                        kind.eq_ignore_ascii_case(entry.value.type_name().as_bytes())
+                    })
+                {
+                    keys.push(RESPType::Bulk(key.clone()));
+                }
+            });
+
+            if next != 0 {
+                return scan_reply(((shard as u64) << SHARD_SHIFT) | next, keys);
+            }
+            shard += 1;
+            cursor = 0;
+        }
+
+        match shard < db.shard_count() {
+            true => scan_reply((shard as u64) << SHARD_SHIFT, keys),
+            false => scan_reply(0, keys),
+        }
+    }
+}
+
+impl From<Scan> for RESPType {
+    fn from(scan: Scan) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("scan")),
+            RESPType::Bulk(Bytes::from(scan.cursor.to_string())),
+        ];
+        if let Some(pattern) = scan.pattern {
+            arr.push(RESPType::Bulk(Bytes::from("MATCH")));
+            arr.push(RESPType::Bulk(pattern));
+        }
+        arr.push(RESPType::Bulk(Bytes::from("COUNT")));
+        arr.push(RESPType::Bulk(Bytes::from(scan.count.to_string())));
+        if let Some(kind) = scan.kind {
+            arr.push(RESPType::Bulk(Bytes::from("TYPE")));
+            arr.push(RESPType::Bulk(kind));
+        }
+
+        RESPType::Array(arr)
+    }
+}
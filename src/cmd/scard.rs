@@ -0,0 +1,31 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Scard {
+    key: Bytes,
+}
+
+impl Scard {
+    pub fn new(key: Bytes) -> Self {
+        Scard { key }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| match slot.as_ref().map(Entry::set) {
+            None => RESPType::Integer(0),
+            Some(Err(e)) => e.into(),
+            Some(Ok(set)) => RESPType::Integer(set.len() as i64),
+        })
+    }
+}
+
+impl From<Scard> for RESPType {
+    fn from(scard: Scard) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("scard")),
+            RESPType::Bulk(scard.key),
+        ])
+    }
+}
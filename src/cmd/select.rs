@@ -0,0 +1,42 @@
+use bytes::Bytes;
+
+use crate::{Db, Error, RESPType};
+
+/// SELECT, switching the connection to another database.
+pub struct Select {
+    index: i64,
+}
+
+impl Select {
+    pub fn new(index: i64) -> Self {
+        Select { index }
+    }
+
+    /// Points `db` at the requested database, the handle is the connection's selection.
+    pub fn response(&self, db: &mut Db) -> RESPType {
+        match select_db(db, self.index) {
+            Err(e) => e.into(),
+            Ok(selected) => {
+                *db = selected;
+                RESPType::String("OK".into())
+            }
+        }
+    }
+}
+
+/// A handle on database `index`, or the error redis gives for an index out of range.
+pub(crate) fn select_db(db: &Db, index: i64) -> crate::Result<Db> {
+    usize::try_from(index)
+        .ok()
+        .and_then(|index| db.select(index))
+        .ok_or_else(|| Error::command("DB index is out of range"))
+}
+
+impl From<Select> for RESPType {
+    fn from(select: Select) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("select")),
+            RESPType::Bulk(Bytes::from(select.index.to_string())),
+        ])
+    }
+}
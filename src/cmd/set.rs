@@ -0,0 +1,133 @@
+use bytes::Bytes;
+
+use crate::db::{now_ms, Entry, Value};
+use crate::{Db, RESPType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetExpiry {
+    Ex(u64),
+    Px(u64),
+    ExAt(u64),
+    PxAt(u64),
+    KeepTtl,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetCondition {
+    Nx,
+    Xx,
+}
+
+pub struct Set {
+    key: Bytes,
+    value: Bytes,
+    expiry: Option<SetExpiry>,
+    condition: Option<SetCondition>,
+    get: bool,
+}
+
+impl Set {
+    pub fn new(key: Bytes, value: Bytes) -> Self {
+        Set {
+            key,
+            value,
+            expiry: None,
+            condition: None,
+            get: false,
+        }
+    }
+
+    pub fn expiry(mut self, expiry: SetExpiry) -> Self {
+        self.expiry = Some(expiry);
+        self
+    }
+
+    pub fn condition(mut self, condition: SetCondition) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    pub fn get(mut self, get: bool) -> Self {
+        self.get = get;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let now = now_ms();
+        let expires_at = match self.expiry {
+            None | Some(SetExpiry::KeepTtl) => None,
+            Some(SetExpiry::Ex(secs)) => Some(now.saturating_add(secs.saturating_mul(1000))),
+            Some(SetExpiry::Px(ms)) => Some(now.saturating_add(ms)),
+            Some(SetExpiry::ExAt(secs)) => Some(secs.saturating_mul(1000)),
+            Some(SetExpiry::PxAt(ms)) => Some(ms),
+        };
+
+        db.update(&self.key, |slot| {
+            // with GET the old value is returned, so it has to be a string
+            let old = match slot.as_ref().map(Entry::string) {
+                Some(Err(e)) if self.get => return e.into(),
+                Some(Ok(value)) => Some(value.clone()),
+                _ => None,
+            };
+            let write = match self.condition {
+                None => true,
+                Some(SetCondition::Nx) => slot.is_none(),
+                Some(SetCondition::Xx) => slot.is_some(),
+            };
+
+            if write {
+                let expires_at = match self.expiry {
+                    Some(SetExpiry::KeepTtl) => slot.as_ref().and_then(|entry| entry.expires_at),
+                    _ => expires_at,
+                };
+                // an overwritten key keeps its access history, `update` already counted this
+                // access to it
+                let entry = slot.get_or_insert_with(|| Entry::new(Value::String(Bytes::new())));
+                entry.value = Value::String(self.value.clone());
+                entry.expires_at = expires_at;
+            }
+
+            match (self.get, write) {
+                (true, _) => match old {
+                    None => RESPType::Null,
+                    Some(val) => RESPType::Bulk(val),
+                },
+                (false, true) => RESPType::String("OK".into()),
+                (false, false) => RESPType::Null,
+            }
+        })
+    }
+}
+
+impl From<Set> for RESPType {
+    fn from(set: Set) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("set")),
+            RESPType::Bulk(set.key),
+            RESPType::Bulk(set.value),
+        ];
+
+        match set.condition {
+            None => {}
+            Some(SetCondition::Nx) => arr.push(RESPType::Bulk(Bytes::from("NX"))),
+            Some(SetCondition::Xx) => arr.push(RESPType::Bulk(Bytes::from("XX"))),
+        }
+        if set.get {
+            arr.push(RESPType::Bulk(Bytes::from("GET")));
+        }
+        let (name, time) = match set.expiry {
+            None => return RESPType::Array(arr),
+            Some(SetExpiry::KeepTtl) => ("KEEPTTL", None),
+            Some(SetExpiry::Ex(t)) => ("EX", Some(t)),
+            Some(SetExpiry::Px(t)) => ("PX", Some(t)),
+            Some(SetExpiry::ExAt(t)) => ("EXAT", Some(t)),
+            Some(SetExpiry::PxAt(t)) => ("PXAT", Some(t)),
+        };
+        arr.push(RESPType::Bulk(Bytes::from(name)));
+        if let Some(time) = time {
+            arr.push(RESPType::Bulk(Bytes::from(time.to_string())));
+        }
+
+        RESPType::Array(arr)
+    }
+}
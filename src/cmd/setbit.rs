@@ -0,0 +1,60 @@
+use bytes::Bytes;
+
+use crate::db::{set_keep_ttl, Entry, Value, MAX_STRING_LEN};
+use crate::{Db, RESPType};
+
+use super::append::too_long;
+use super::getbit::get_bit;
+
+/// SETBIT, growing the string with zero bytes up to the bit if needed.
+pub struct Setbit {
+    key: Bytes,
+    offset: u64,
+    value: bool,
+}
+
+impl Setbit {
+    pub fn new(key: Bytes, offset: u64, value: bool) -> Self {
+        Setbit { key, offset, value }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let current = match slot.as_mut().map(Entry::string_mut) {
+                None => None,
+                Some(Err(e)) => return e.into(),
+                Some(Ok(value)) => Some(value),
+            };
+            let byte = (self.offset / 8) as usize;
+            if byte >= MAX_STRING_LEN {
+                return too_long();
+            }
+
+            // the bitmap is taken out of the entry and flipped in place, only copied if shared
+            let mut value = Vec::from(current.map(std::mem::take).unwrap_or_default());
+            let old = get_bit(&value, self.offset);
+            if value.len() <= byte {
+                value.resize(byte + 1, 0);
+            }
+            let mask = 0x80 >> (self.offset % 8);
+            match self.value {
+                true => value[byte] |= mask,
+                false => value[byte] &= !mask,
+            }
+
+            set_keep_ttl(slot, Value::String(value.into()));
+            RESPType::Integer(old as i64)
+        })
+    }
+}
+
+impl From<Setbit> for RESPType {
+    fn from(setbit: Setbit) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("setbit")),
+            RESPType::Bulk(setbit.key),
+            RESPType::Bulk(Bytes::from(setbit.offset.to_string())),
+            RESPType::Bulk(Bytes::from(if setbit.value { "1" } else { "0" })),
+        ])
+    }
+}
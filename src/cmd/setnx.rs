@@ -0,0 +1,35 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, Value};
+use crate::{Db, RESPType};
+
+pub struct SetNx {
+    key: Bytes,
+    value: Bytes,
+}
+
+impl SetNx {
+    pub fn new(key: Bytes, value: Bytes) -> Self {
+        SetNx { key, value }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| match slot {
+            Some(_) => RESPType::Integer(0),
+            None => {
+                *slot = Some(Entry::new(Value::String(self.value.clone())));
+                RESPType::Integer(1)
+            }
+        })
+    }
+}
+
+impl From<SetNx> for RESPType {
+    fn from(setnx: SetNx) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("setnx")),
+            RESPType::Bulk(setnx.key),
+            RESPType::Bulk(setnx.value),
+        ])
+    }
+}
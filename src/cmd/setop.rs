@@ -0,0 +1,165 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, Locked, Set, Value};
+use crate::{Db, RESPType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetOpKind {
+    Inter,
+    Union,
+    Diff,
+}
+
+/// SINTER, SUNION and SDIFF, or their STORE variants when given a destination.
+pub struct SetOp {
+    kind: SetOpKind,
+    keys: Vec<Bytes>,
+    destination: Option<Bytes>,
+}
+
+impl SetOp {
+    pub fn new(kind: SetOpKind, keys: Vec<Bytes>) -> Self {
+        SetOp {
+            kind,
+            keys,
+            destination: None,
+        }
+    }
+
+    /// Store the result at `destination`, replacing whatever is there, instead of replying
+    /// with it.
+    pub fn store(mut self, destination: Bytes) -> Self {
+        self.destination = Some(destination);
+        self
+    }
+
+    pub fn name(&self) -> &'static str {
+        match (self.kind, self.destination.is_some()) {
+            (SetOpKind::Inter, false) => "sinter",
+            (SetOpKind::Union, false) => "sunion",
+            (SetOpKind::Diff, false) => "sdiff",
+            (SetOpKind::Inter, true) => "sinterstore",
+            (SetOpKind::Union, true) => "sunionstore",
+            (SetOpKind::Diff, true) => "sdiffstore",
+        }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let mut keys = self.keys.clone();
+        keys.extend(self.destination.clone());
+        let mut locked = db.lock(&keys);
+
+        let members = match self.kind {
+            SetOpKind::Inter => intersect(&mut locked, &self.keys, 0),
+            SetOpKind::Union => union(&mut locked, &self.keys),
+            SetOpKind::Diff => diff(&mut locked, &self.keys),
+        };
+        let members = match members {
+            Err(e) => return e.into(),
+            Ok(members) => members,
+        };
+
+        match &self.destination {
+            None => RESPType::Set(members.into_iter().map(RESPType::Bulk).collect()),
+            Some(destination) => {
+                let set: Set = members.into_iter().collect();
+                let len = set.len();
+                locked.update(destination, |slot| {
+                    *slot = Some(Entry::new(Value::Set(set)));
+                });
+                RESPType::Integer(len as i64)
+            }
+        }
+    }
+}
+
+/// Members of the sets at `keys`, which must all be locked, that are in every one of them.
+/// Stops after `limit` members unless it is 0.
+pub(crate) fn intersect(
+    locked: &mut Locked<'_>,
+    keys: &[Bytes],
+    limit: usize,
+) -> crate::Result<Vec<Bytes>> {
+    // a missing key is an empty set, which empties the intersection
+    let lens: Option<Vec<_>> = lens(locked, keys)?.into_iter().collect();
+    let smallest = match lens.and_then(|lens| (0..lens.len()).min_by_key(|idx| lens[*idx])) {
+        None => return Ok(vec![]),
+        Some(idx) => idx,
+    };
+
+    let mut members = members(locked, &keys[smallest]);
+    for (idx, key) in keys.iter().enumerate() {
+        if idx == smallest {
+            continue;
+        }
+        locked.update(key, |slot| {
+            if let Some(Ok(set)) = slot.as_ref().map(Entry::set) {
+                members.retain(|member| set.contains(member));
+            }
+        });
+    }
+
+    if limit > 0 {
+        members.truncate(limit);
+    }
+    Ok(members)
+}
+
+fn union(locked: &mut Locked<'_>, keys: &[Bytes]) -> crate::Result<Vec<Bytes>> {
+    lens(locked, keys)?;
+
+    let mut union = Set::default();
+    for key in keys {
+        for member in members(locked, key) {
+            union.insert(member);
+        }
+    }
+    Ok(union.iter().collect())
+}
+
+fn diff(locked: &mut Locked<'_>, keys: &[Bytes]) -> crate::Result<Vec<Bytes>> {
+    lens(locked, keys)?;
+
+    let mut members = members(locked, &keys[0]);
+    for key in &keys[1..] {
+        locked.update(key, |slot| {
+            if let Some(Ok(set)) = slot.as_ref().map(Entry::set) {
+                members.retain(|member| !set.contains(member));
+            }
+        });
+    }
+    Ok(members)
+}
+
+/// Size of each set, `None` for missing keys. Fails if any key holds another type, even
+/// when the result wouldn't depend on it.
+fn lens(locked: &mut Locked<'_>, keys: &[Bytes]) -> crate::Result<Vec<Option<usize>>> {
+    keys.iter()
+        .map(|key| {
+            locked.update(key, |slot| {
+                slot.as_ref()
+                    .map(|entry| entry.set().map(Set::len))
+                    .transpose()
+            })
+        })
+        .collect()
+}
+
+fn members(locked: &mut Locked<'_>, key: &[u8]) -> Vec<Bytes> {
+    locked.update(key, |slot| match slot.as_ref().map(Entry::set) {
+        Some(Ok(set)) => set.iter().collect(),
+        _ => vec![],
+    })
+}
+
+impl From<SetOp> for RESPType {
+    fn from(setop: SetOp) -> RESPType {
+        let mut arr = vec![RESPType::Bulk(Bytes::from(setop.name()))];
+        if let Some(destination) = setop.destination {
+            arr.push(RESPType::Bulk(destination));
+        }
+        arr.extend(setop.keys.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,62 @@
+use bytes::{Bytes, BytesMut};
+
+use crate::db::{set_keep_ttl, Entry, Value, MAX_STRING_LEN};
+use crate::{Db, RESPType};
+
+use super::append::too_long;
+
+pub struct SetRange {
+    key: Bytes,
+    offset: usize,
+    value: Bytes,
+}
+
+impl SetRange {
+    pub fn new(key: Bytes, offset: usize, value: Bytes) -> Self {
+        SetRange { key, offset, value }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let current = match slot.as_ref().map(Entry::string) {
+                None => None,
+                Some(Err(e)) => return e.into(),
+                Some(Ok(value)) => Some(value.clone()),
+            };
+            let current_len = current.as_ref().map_or(0, |value| value.len());
+
+            // an empty write never creates or grows the string
+            if self.value.is_empty() {
+                return RESPType::Integer(current_len as i64);
+            }
+            let end = match self.offset.checked_add(self.value.len()) {
+                Some(end) if end <= MAX_STRING_LEN => end,
+                _ => return too_long(),
+            };
+
+            let mut value = BytesMut::with_capacity(end.max(current_len));
+            if let Some(current) = &current {
+                value.extend_from_slice(current);
+            }
+            if value.len() < end {
+                value.resize(end, 0);
+            }
+            value[self.offset..end].copy_from_slice(&self.value);
+            let len = value.len();
+
+            set_keep_ttl(slot, Value::String(value.freeze()));
+            RESPType::Integer(len as i64)
+        })
+    }
+}
+
+impl From<SetRange> for RESPType {
+    fn from(setrange: SetRange) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("setrange")),
+            RESPType::Bulk(setrange.key),
+            RESPType::Bulk(Bytes::from(setrange.offset.to_string())),
+            RESPType::Bulk(setrange.value),
+        ])
+    }
+}
@@ -0,0 +1,47 @@
+use bytes::Bytes;
+
+use crate::{Db, RESPType};
+
+use super::setop::intersect;
+
+/// SINTERCARD, counting the intersection without building a reply for it.
+pub struct SinterCard {
+    keys: Vec<Bytes>,
+    limit: usize,
+}
+
+impl SinterCard {
+    pub fn new(keys: Vec<Bytes>) -> Self {
+        SinterCard { keys, limit: 0 }
+    }
+
+    /// Stop counting at `limit`, 0 counts the whole intersection.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let mut locked = db.lock(&self.keys);
+        match intersect(&mut locked, &self.keys, self.limit) {
+            Err(e) => e.into(),
+            Ok(members) => RESPType::Integer(members.len() as i64),
+        }
+    }
+}
+
+impl From<SinterCard> for RESPType {
+    fn from(sintercard: SinterCard) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("sintercard")),
+            RESPType::Bulk(Bytes::from(sintercard.keys.len().to_string())),
+        ];
+        arr.extend(sintercard.keys.into_iter().map(RESPType::Bulk));
+        if sintercard.limit > 0 {
+            arr.push(RESPType::Bulk(Bytes::from("LIMIT")));
+            arr.push(RESPType::Bulk(Bytes::from(sintercard.limit.to_string())));
+        }
+
+        RESPType::Array(arr)
+    }
+}
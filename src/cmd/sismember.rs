@@ -0,0 +1,33 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Sismember {
+    key: Bytes,
+    member: Bytes,
+}
+
+impl Sismember {
+    pub fn new(key: Bytes, member: Bytes) -> Self {
+        Sismember { key, member }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| match slot.as_ref().map(Entry::set) {
+            None => RESPType::Integer(0),
+            Some(Err(e)) => e.into(),
+            Some(Ok(set)) => RESPType::Integer(set.contains(&self.member) as i64),
+        })
+    }
+}
+
+impl From<Sismember> for RESPType {
+    fn from(sismember: Sismember) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("sismember")),
+            RESPType::Bulk(sismember.key),
+            RESPType::Bulk(sismember.member),
+        ])
+    }
+}
@@ -0,0 +1,31 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Smembers {
+    key: Bytes,
+}
+
+impl Smembers {
+    pub fn new(key: Bytes) -> Self {
+        Smembers { key }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| match slot.as_ref().map(Entry::set) {
+            None => RESPType::Set(vec![]),
+            Some(Err(e)) => e.into(),
+            Some(Ok(set)) => RESPType::Set(set.iter().map(RESPType::Bulk).collect()),
+        })
+    }
+}
+
+impl From<Smembers> for RESPType {
+    fn from(smembers: Smembers) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("smembers")),
+            RESPType::Bulk(smembers.key),
+        ])
+    }
+}
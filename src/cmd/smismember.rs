@@ -0,0 +1,43 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Smismember {
+    key: Bytes,
+    members: Vec<Bytes>,
+}
+
+impl Smismember {
+    pub fn new(key: Bytes, members: Vec<Bytes>) -> Self {
+        Smismember { key, members }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let set = match slot.as_ref().map(Entry::set) {
+                None => None,
+                Some(Err(e)) => return e.into(),
+                Some(Ok(set)) => Some(set),
+            };
+
+            let found = self.members.iter().map(|member| {
+                let found = set.is_some_and(|set| set.contains(member));
+                RESPType::Integer(found as i64)
+            });
+            RESPType::Array(found.collect())
+        })
+    }
+}
+
+impl From<Smismember> for RESPType {
+    fn from(smismember: Smismember) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("smismember")),
+            RESPType::Bulk(smismember.key),
+        ];
+        arr.extend(smismember.members.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,68 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, Set, Value};
+use crate::{Db, RESPType};
+
+pub struct Smove {
+    source: Bytes,
+    destination: Bytes,
+    member: Bytes,
+}
+
+impl Smove {
+    pub fn new(source: Bytes, destination: Bytes, member: Bytes) -> Self {
+        Smove {
+            source,
+            destination,
+            member,
+        }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let mut locked = db.lock(&[&self.source, &self.destination]);
+
+        let found = locked.update(&self.source, |slot| {
+            slot.as_ref()
+                .map(|entry| entry.set().map(|set| set.contains(&self.member)))
+                .transpose()
+        });
+        let found = match found {
+            Err(e) => return e.into(),
+            Ok(found) => found == Some(true),
+        };
+        // the destination's type is checked even when there is nothing to move
+        let checked = locked.update(&self.destination, |slot| {
+            slot.as_ref().map(Entry::set).transpose().map(|_| ())
+        });
+        if let Err(e) = checked {
+            return e.into();
+        }
+        if !found || self.source == self.destination {
+            return RESPType::Integer(found as i64);
+        }
+
+        locked.update(&self.source, |slot| {
+            if let Some(Ok(set)) = slot.as_mut().map(Entry::set_mut) {
+                set.remove(&self.member);
+            }
+        });
+        locked.update(&self.destination, |slot| {
+            let entry = slot.get_or_insert_with(|| Entry::new(Value::Set(Set::default())));
+            if let Ok(set) = entry.set_mut() {
+                set.insert(self.member.clone());
+            }
+        });
+        RESPType::Integer(1)
+    }
+}
+
+impl From<Smove> for RESPType {
+    fn from(smove: Smove) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("smove")),
+            RESPType::Bulk(smove.source),
+            RESPType::Bulk(smove.destination),
+            RESPType::Bulk(smove.member),
+        ])
+    }
+}
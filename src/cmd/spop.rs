@@ -0,0 +1,59 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+/// SPOP, removing a single random member unless given a count.
+pub struct Spop {
+    key: Bytes,
+    count: Option<usize>,
+}
+
+impl Spop {
+    pub fn new(key: Bytes) -> Self {
+        Spop { key, count: None }
+    }
+
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let set = match slot.as_mut().map(Entry::set_mut) {
+                None if self.count.is_some() => return RESPType::Set(vec![]),
+                None => return RESPType::Null,
+                Some(Err(e)) => return e.into(),
+                Some(Ok(set)) => set,
+            };
+
+            match self.count {
+                None => match set.pop_random(1).pop() {
+                    None => RESPType::Null,
+                    Some(member) => RESPType::Bulk(member),
+                },
+                Some(count) => RESPType::Set(
+                    set.pop_random(count)
+                        .into_iter()
+                        .map(RESPType::Bulk)
+                        .collect(),
+                ),
+            }
+        })
+    }
+}
+
+impl From<Spop> for RESPType {
+    fn from(spop: Spop) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("spop")),
+            RESPType::Bulk(spop.key),
+        ];
+        if let Some(count) = spop.count {
+            arr.push(RESPType::Bulk(Bytes::from(count.to_string())));
+        }
+
+        RESPType::Array(arr)
+    }
+}
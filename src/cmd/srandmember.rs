@@ -0,0 +1,71 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::random;
+use crate::{Db, RESPType};
+
+/// SRANDMEMBER, replying with a single member unless given a count. A negative count allows
+/// the same member to be returned several times.
+pub struct SrandMember {
+    key: Bytes,
+    count: Option<i64>,
+}
+
+impl SrandMember {
+    pub fn new(key: Bytes) -> Self {
+        SrandMember { key, count: None }
+    }
+
+    pub fn count(mut self, count: i64) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let set = match slot.as_ref().map(Entry::set) {
+                None if self.count.is_some() => return RESPType::Array(vec![]),
+                None => return RESPType::Null,
+                Some(Err(e)) => return e.into(),
+                Some(Ok(set)) => set,
+            };
+
+            let count = match self.count {
+                None => return set.random().map_or(RESPType::Null, RESPType::Bulk),
+                Some(count) => count,
+            };
+            if count < 0 {
+                // members may repeat, so each pick is independent
+                let mut picked = vec![];
+                for _ in 0..count.unsigned_abs() {
+                    picked.extend(set.random().map(RESPType::Bulk));
+                }
+                return RESPType::Array(picked);
+            }
+
+            // partial shuffle, every member is picked at most once
+            let mut members: Vec<_> = set.iter().collect();
+            let count = members.len().min(count as usize);
+            for i in 0..count {
+                let j = i + random::below(members.len() - i);
+                members.swap(i, j);
+            }
+            members.truncate(count);
+            RESPType::Array(members.into_iter().map(RESPType::Bulk).collect())
+        })
+    }
+}
+
+impl From<SrandMember> for RESPType {
+    fn from(srandmember: SrandMember) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("srandmember")),
+            RESPType::Bulk(srandmember.key),
+        ];
+        if let Some(count) = srandmember.count {
+            arr.push(RESPType::Bulk(Bytes::from(count.to_string())));
+        }
+
+        RESPType::Array(arr)
+    }
+}
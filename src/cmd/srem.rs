@@ -0,0 +1,44 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Srem {
+    key: Bytes,
+    members: Vec<Bytes>,
+}
+
+impl Srem {
+    pub fn new(key: Bytes, members: Vec<Bytes>) -> Self {
+        Srem { key, members }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let set = match slot.as_mut().map(Entry::set_mut) {
+                None => return RESPType::Integer(0),
+                Some(Err(e)) => return e.into(),
+                Some(Ok(set)) => set,
+            };
+
+            let removed = self
+                .members
+                .iter()
+                .filter(|member| set.remove(member))
+                .count();
+            RESPType::Integer(removed as i64)
+        })
+    }
+}
+
+impl From<Srem> for RESPType {
+    fn from(srem: Srem) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("srem")),
+            RESPType::Bulk(srem.key),
+        ];
+        arr.extend(srem.members.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,76 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+use super::glob::glob_match;
+use super::hscan::scan_reply;
+
+/// SSCAN, iterating a set a page at a time with a cursor that survives modifications.
+pub struct Sscan {
+    key: Bytes,
+    cursor: u64,
+    pattern: Option<Bytes>,
+    count: usize,
+}
+
+impl Sscan {
+    pub fn new(key: Bytes, cursor: u64) -> Self {
+        Sscan {
+            key,
+            cursor,
+            pattern: None,
+            count: 10,
+        }
+    }
+
+    /// Only return members matching the glob style `pattern`.
+    pub fn pattern(mut self, pattern: Bytes) -> Self {
+        self.pattern = Some(pattern);
+        self
+    }
+
+    /// Hint of how many members to visit per call.
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let set = match slot.as_ref().map(Entry::set) {
+                None => return scan_reply(0, vec![]),
+                Some(Err(e)) => return e.into(),
+                Some(Ok(set)) => set,
+            };
+
+            let (cursor, members) = set.scan(self.cursor, self.count);
+            let page = members
+                .into_iter()
+                .filter(|member| match &self.pattern {
+                    None => true,
+                    Some(pattern) => glob_match(pattern, member, false),
+                })
+                .map(RESPType::Bulk);
+            scan_reply(cursor, page.collect())
+        })
+    }
+}
+
+impl From<Sscan> for RESPType {
+    fn from(sscan: Sscan) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("sscan")),
+            RESPType::Bulk(sscan.key),
+            RESPType::Bulk(Bytes::from(sscan.cursor.to_string())),
+        ];
+        if let Some(pattern) = sscan.pattern {
+            arr.push(RESPType::Bulk(Bytes::from("MATCH")));
+            arr.push(RESPType::Bulk(pattern));
+        }
+        arr.push(RESPType::Bulk(Bytes::from("COUNT")));
+        arr.push(RESPType::Bulk(Bytes::from(sscan.count.to_string())));
+
+        RESPType::Array(arr)
+    }
+}
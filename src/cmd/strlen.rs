@@ -0,0 +1,31 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Strlen {
+    key: Bytes,
+}
+
+impl Strlen {
+    pub fn new(key: Bytes) -> Self {
+        Strlen { key }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| match slot.as_ref().map(Entry::string) {
+            None => RESPType::Integer(0),
+            Some(Err(e)) => e.into(),
+            Some(Ok(value)) => RESPType::Integer(value.len() as i64),
+        })
+    }
+}
+
+impl From<Strlen> for RESPType {
+    fn from(strlen: Strlen) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("strlen")),
+            RESPType::Bulk(strlen.key),
+        ])
+    }
+}
@@ -0,0 +1,36 @@
+use bytes::Bytes;
+
+use crate::{Db, RESPType};
+
+use super::select::select_db;
+
+/// SWAPDB, exchanging the data of two databases for every connection at once.
+pub struct Swapdb {
+    first: i64,
+    second: i64,
+}
+
+impl Swapdb {
+    pub fn new(first: i64, second: i64) -> Self {
+        Swapdb { first, second }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let (first, second) = match (select_db(db, self.first), select_db(db, self.second)) {
+            (Err(e), _) | (_, Err(e)) => return e.into(),
+            (Ok(first), Ok(second)) => (first, second),
+        };
+        first.swap(&second);
+        RESPType::String("OK".into())
+    }
+}
+
+impl From<Swapdb> for RESPType {
+    fn from(swapdb: Swapdb) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("swapdb")),
+            RESPType::Bulk(Bytes::from(swapdb.first.to_string())),
+            RESPType::Bulk(Bytes::from(swapdb.second.to_string())),
+        ])
+    }
+}
@@ -0,0 +1,59 @@
+use bytes::Bytes;
+
+use crate::db::now_ms;
+use crate::{Db, RESPType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TtlKind {
+    Ttl,
+    Pttl,
+    ExpireTime,
+    PexpireTime,
+}
+
+/// TTL, PTTL, EXPIRETIME and PEXPIRETIME.
+pub struct Ttl {
+    key: Bytes,
+    kind: TtlKind,
+}
+
+impl Ttl {
+    pub fn new(key: Bytes, kind: TtlKind) -> Self {
+        Ttl { key, kind }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self.kind {
+            TtlKind::Ttl => "ttl",
+            TtlKind::Pttl => "pttl",
+            TtlKind::ExpireTime => "expiretime",
+            TtlKind::PexpireTime => "pexpiretime",
+        }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let when = match db.expires_at(&self.key) {
+            None => return RESPType::Integer(-2),
+            Some(None) => return RESPType::Integer(-1),
+            Some(Some(when)) => when,
+        };
+        let remaining = when.saturating_sub(now_ms());
+
+        let val = match self.kind {
+            TtlKind::Ttl => (remaining + 500) / 1000,
+            TtlKind::Pttl => remaining,
+            TtlKind::ExpireTime => when / 1000,
+            TtlKind::PexpireTime => when,
+        };
+        RESPType::Integer(val as i64)
+    }
+}
+
+impl From<Ttl> for RESPType {
+    fn from(ttl: Ttl) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from(ttl.name())),
+            RESPType::Bulk(ttl.key),
+        ])
+    }
+}
@@ -0,0 +1,47 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, StreamId};
+use crate::{Db, RESPType};
+
+use super::xrange::id_bulk;
+
+pub struct Xack {
+    key: Bytes,
+    group: Bytes,
+    ids: Vec<StreamId>,
+}
+
+impl Xack {
+    pub fn new(key: Bytes, group: Bytes, ids: Vec<StreamId>) -> Self {
+        Xack { key, group, ids }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let stream = match slot.as_mut().map(Entry::stream_mut) {
+                None => return RESPType::Integer(0),
+                Some(Err(e)) => return e.into(),
+                Some(Ok(stream)) => stream,
+            };
+
+            let acked = match stream.group_mut(&self.group) {
+                None => 0,
+                Some(group) => self.ids.iter().filter(|id| group.ack(id)).count(),
+            };
+            RESPType::Integer(acked as i64)
+        })
+    }
+}
+
+impl From<Xack> for RESPType {
+    fn from(xack: Xack) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("xack")),
+            RESPType::Bulk(xack.key),
+            RESPType::Bulk(xack.group),
+        ];
+        arr.extend(xack.ids.into_iter().map(id_bulk));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,128 @@
+use bytes::Bytes;
+
+use crate::db::{now_ms, Entry, Stream, StreamId, Value};
+use crate::{Db, Error, RESPType};
+
+use super::xrange::id_bulk;
+use super::StreamTrim;
+
+/// ID given to XADD, `*` generates it entirely and `ms-*` only its sequence number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XaddId {
+    Auto,
+    AutoSeq(u64),
+    Explicit(StreamId),
+}
+
+/// XADD, replying with the ID of the new entry.
+pub struct Xadd {
+    key: Bytes,
+    id: XaddId,
+    fields: Vec<(Bytes, Bytes)>,
+    nomkstream: bool,
+    trim: Option<StreamTrim>,
+}
+
+impl Xadd {
+    pub fn new(key: Bytes, id: XaddId, fields: Vec<(Bytes, Bytes)>) -> Self {
+        Xadd {
+            key,
+            id,
+            fields,
+            nomkstream: false,
+            trim: None,
+        }
+    }
+
+    /// Don't create the stream if it doesn't exist, replying with nil instead.
+    pub fn nomkstream(mut self, nomkstream: bool) -> Self {
+        self.nomkstream = nomkstream;
+        self
+    }
+
+    /// Trim the stream after adding the entry.
+    pub fn trim(mut self, trim: StreamTrim) -> Self {
+        self.trim = Some(trim);
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let added = db.update(&self.key, |slot| {
+            // the stream is only stored once the entry is in, a rejected ID creates nothing
+            let mut created = Stream::default();
+            let stream = match slot.as_mut().map(Entry::stream_mut) {
+                None if self.nomkstream => return Ok(None),
+                None => &mut created,
+                Some(Err(e)) => return Err(e),
+                Some(Ok(stream)) => stream,
+            };
+
+            let id = self.next_id(stream)?;
+            stream.add(id, self.fields.clone());
+            if let Some(trim) = &self.trim {
+                trim.apply(stream);
+            }
+            if slot.is_none() {
+                *slot = Some(Entry::new(Value::Stream(created)));
+            }
+            Ok(Some(id))
+        });
+
+        match added {
+            Err(e) => e.into(),
+            Ok(None) => RESPType::Null,
+            Ok(Some(id)) => {
+                // every blocked reader may want the new entry, not just the first in line
+                db.wake_all(&self.key);
+                id_bulk(id)
+            }
+        }
+    }
+
+    fn next_id(&self, stream: &Stream) -> crate::Result<StreamId> {
+        let too_small = || {
+            Error::command(
+                "The ID specified in XADD is equal or smaller than the target stream top item",
+            )
+        };
+        match self.id {
+            XaddId::Auto => stream.next_id(None, now_ms()).ok_or_else(|| {
+                Error::command(
+                    "The stream has exhausted the last possible ID, unable to add more items",
+                )
+            }),
+            XaddId::AutoSeq(ms) => stream.next_id(Some(ms), now_ms()).ok_or_else(too_small),
+            XaddId::Explicit(StreamId::MIN) => Err(Error::command(
+                "The ID specified in XADD must be greater than 0-0",
+            )),
+            XaddId::Explicit(id) if id <= stream.last_id() => Err(too_small()),
+            XaddId::Explicit(id) => Ok(id),
+        }
+    }
+}
+
+impl From<Xadd> for RESPType {
+    fn from(xadd: Xadd) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("xadd")),
+            RESPType::Bulk(xadd.key),
+        ];
+        if xadd.nomkstream {
+            arr.push(RESPType::Bulk(Bytes::from("NOMKSTREAM")));
+        }
+        if let Some(trim) = xadd.trim {
+            arr.extend(trim.args());
+        }
+        arr.push(RESPType::Bulk(Bytes::from(match xadd.id {
+            XaddId::Auto => "*".to_string(),
+            XaddId::AutoSeq(ms) => format!("{}-*", ms),
+            XaddId::Explicit(id) => id.to_string(),
+        })));
+        for (field, value) in xadd.fields {
+            arr.push(RESPType::Bulk(field));
+            arr.push(RESPType::Bulk(value));
+        }
+
+        RESPType::Array(arr)
+    }
+}
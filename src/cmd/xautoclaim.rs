@@ -0,0 +1,130 @@
+use bytes::Bytes;
+
+use crate::db::{now_ms, Entry, StreamId};
+use crate::{Db, RESPType};
+
+use super::xclaim::no_group;
+use super::xrange::{entry_reply, id_bulk};
+
+/// XAUTOCLAIM, claiming pending entries idle for long enough by scanning the pending list
+/// from a cursor instead of naming them.
+pub struct XautoClaim {
+    key: Bytes,
+    group: Bytes,
+    consumer: Bytes,
+    min_idle: u64,
+    start: StreamId,
+    count: usize,
+    just_id: bool,
+}
+
+impl XautoClaim {
+    pub fn new(key: Bytes, group: Bytes, consumer: Bytes, min_idle: u64, start: StreamId) -> Self {
+        XautoClaim {
+            key,
+            group,
+            consumer,
+            min_idle,
+            start,
+            count: 100,
+            just_id: false,
+        }
+    }
+
+    /// Claim up to `count` entries, scanning at most ten times as many.
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    pub fn just_id(mut self, just_id: bool) -> Self {
+        self.just_id = just_id;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let stream = match slot.as_mut().map(Entry::stream_mut) {
+                Some(Err(e)) => return e.into(),
+                Some(Ok(stream)) => stream,
+                None => return no_group(&self.key, &self.group),
+            };
+            let scanned: Vec<StreamId> = match stream.group(&self.group) {
+                None => return no_group(&self.key, &self.group),
+                Some(group) => group
+                    .pending
+                    .range(self.start..)
+                    .map(|(id, _)| *id)
+                    .take(self.count.saturating_mul(10))
+                    .collect(),
+            };
+
+            let now = now_ms();
+            let mut claimed = vec![];
+            let mut deleted = vec![];
+            let mut next = None;
+            for id in &scanned {
+                if claimed.len() == self.count {
+                    next = Some(*id);
+                    break;
+                }
+                if stream.get(id).is_none() {
+                    deleted.push(*id);
+                }
+                let pending =
+                    stream.claim(&self.group, &self.consumer, *id, self.min_idle, false, now);
+                if let Some(pending) = pending {
+                    pending.delivered_at = now;
+                    pending.deliveries += !self.just_id as u64;
+                    claimed.push(*id);
+                }
+            }
+            // when the scan ran out the cursor is whatever is pending after it, 0-0 at the end
+            let next = next.or_else(|| {
+                let after = scanned.last()?.next()?;
+                let group = stream.group(&self.group)?;
+                group.pending.range(after..).next().map(|(id, _)| *id)
+            });
+
+            if let Some(group) = stream.group_mut(&self.group) {
+                let consumer = group.touch(&self.consumer, now);
+                if !claimed.is_empty() {
+                    consumer.active_at = Some(now);
+                }
+            }
+
+            let claimed = claimed
+                .into_iter()
+                .map(|id| match self.just_id {
+                    true => id_bulk(id),
+                    false => entry_reply(id, stream.get(&id)),
+                })
+                .collect();
+            RESPType::Array(vec![
+                id_bulk(next.unwrap_or(StreamId::MIN)),
+                RESPType::Array(claimed),
+                RESPType::Array(deleted.into_iter().map(id_bulk).collect()),
+            ])
+        })
+    }
+}
+
+impl From<XautoClaim> for RESPType {
+    fn from(xautoclaim: XautoClaim) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("xautoclaim")),
+            RESPType::Bulk(xautoclaim.key),
+            RESPType::Bulk(xautoclaim.group),
+            RESPType::Bulk(xautoclaim.consumer),
+            RESPType::Bulk(Bytes::from(xautoclaim.min_idle.to_string())),
+            id_bulk(xautoclaim.start),
+            RESPType::Bulk(Bytes::from("COUNT")),
+            RESPType::Bulk(Bytes::from(xautoclaim.count.to_string())),
+        ];
+        if xautoclaim.just_id {
+            arr.push(RESPType::Bulk(Bytes::from("JUSTID")));
+        }
+
+        RESPType::Array(arr)
+    }
+}
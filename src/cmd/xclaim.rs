@@ -0,0 +1,183 @@
+use bytes::Bytes;
+
+use crate::db::{now_ms, Entry, StreamId};
+use crate::{Db, Error, RESPType};
+
+use super::xrange::{entry_reply, id_bulk};
+
+/// XCLAIM, handing pending entries idle for long enough over to another consumer.
+pub struct Xclaim {
+    key: Bytes,
+    group: Bytes,
+    consumer: Bytes,
+    min_idle: u64,
+    ids: Vec<StreamId>,
+    idle: Option<u64>,
+    time: Option<u64>,
+    retry_count: Option<u64>,
+    force: bool,
+    just_id: bool,
+    last_id: Option<StreamId>,
+}
+
+impl Xclaim {
+    pub fn new(
+        key: Bytes,
+        group: Bytes,
+        consumer: Bytes,
+        min_idle: u64,
+        ids: Vec<StreamId>,
+    ) -> Self {
+        Xclaim {
+            key,
+            group,
+            consumer,
+            min_idle,
+            ids,
+            idle: None,
+            time: None,
+            retry_count: None,
+            force: false,
+            just_id: false,
+            last_id: None,
+        }
+    }
+
+    /// Record the claimed entries as delivered `idle` milliseconds ago.
+    pub fn idle(mut self, idle: u64) -> Self {
+        self.idle = Some(idle);
+        self
+    }
+
+    /// Record the claimed entries as delivered at unix time `time` in milliseconds.
+    pub fn time(mut self, time: u64) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    pub fn retry_count(mut self, retry_count: u64) -> Self {
+        self.retry_count = Some(retry_count);
+        self
+    }
+
+    /// Claim entries of the stream that aren't pending in the group yet.
+    pub fn force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Reply with the claimed IDs only, without counting it as a delivery.
+    pub fn just_id(mut self, just_id: bool) -> Self {
+        self.just_id = just_id;
+        self
+    }
+
+    /// Move the group's last delivered ID up to `last_id` if it is behind.
+    pub fn last_id(mut self, last_id: StreamId) -> Self {
+        self.last_id = Some(last_id);
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let stream = match slot.as_mut().map(Entry::stream_mut) {
+                Some(Err(e)) => return e.into(),
+                Some(Ok(stream)) if stream.group(&self.group).is_some() => stream,
+                _ => return no_group(&self.key, &self.group),
+            };
+
+            let now = now_ms();
+            let delivered_at = match (self.time, self.idle) {
+                (Some(time), _) => time,
+                (None, Some(idle)) => now.saturating_sub(idle),
+                (None, None) => now,
+            };
+            let mut claimed = vec![];
+            for id in &self.ids {
+                let pending = stream.claim(
+                    &self.group,
+                    &self.consumer,
+                    *id,
+                    self.min_idle,
+                    self.force,
+                    now,
+                );
+                if let Some(pending) = pending {
+                    pending.delivered_at = delivered_at;
+                    pending.deliveries = match self.retry_count {
+                        Some(retry_count) => retry_count,
+                        None => pending.deliveries + !self.just_id as u64,
+                    };
+                    claimed.push(*id);
+                }
+            }
+
+            if let Some(group) = stream.group_mut(&self.group) {
+                if let Some(last_id) = self.last_id {
+                    group.last_delivered = group.last_delivered.max(last_id);
+                }
+                let consumer = group.touch(&self.consumer, now);
+                if !claimed.is_empty() {
+                    consumer.active_at = Some(now);
+                }
+            }
+
+            RESPType::Array(
+                claimed
+                    .into_iter()
+                    .map(|id| match self.just_id {
+                        true => id_bulk(id),
+                        false => entry_reply(id, stream.get(&id)),
+                    })
+                    .collect(),
+            )
+        })
+    }
+}
+
+pub(crate) fn no_group(key: &[u8], group: &[u8]) -> RESPType {
+    Error::server(
+        "NOGROUP",
+        format!(
+            "No such key '{}' or consumer group '{}'",
+            String::from_utf8_lossy(key),
+            String::from_utf8_lossy(group)
+        ),
+    )
+    .into()
+}
+
+impl From<Xclaim> for RESPType {
+    fn from(xclaim: Xclaim) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("xclaim")),
+            RESPType::Bulk(xclaim.key),
+            RESPType::Bulk(xclaim.group),
+            RESPType::Bulk(xclaim.consumer),
+            RESPType::Bulk(Bytes::from(xclaim.min_idle.to_string())),
+        ];
+        arr.extend(xclaim.ids.into_iter().map(id_bulk));
+        for (name, value) in [
+            ("IDLE", xclaim.idle),
+            ("TIME", xclaim.time),
+            ("RETRYCOUNT", xclaim.retry_count),
+        ] {
+            if let Some(value) = value {
+                arr.push(RESPType::Bulk(Bytes::from(name)));
+                arr.push(RESPType::Bulk(Bytes::from(value.to_string())));
+            }
+        }
+        if xclaim.force {
+            arr.push(RESPType::Bulk(Bytes::from("FORCE")));
+        }
+        if xclaim.just_id {
+            arr.push(RESPType::Bulk(Bytes::from("JUSTID")));
+        }
+        if let Some(last_id) = xclaim.last_id {
+            arr.push(RESPType::Bulk(Bytes::from("LASTID")));
+            arr.push(id_bulk(last_id));
+        }
+
+        RESPType::Array(arr)
+    }
+}
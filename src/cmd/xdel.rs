@@ -0,0 +1,42 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, StreamId};
+use crate::{Db, RESPType};
+
+use super::xrange::id_bulk;
+
+pub struct Xdel {
+    key: Bytes,
+    ids: Vec<StreamId>,
+}
+
+impl Xdel {
+    pub fn new(key: Bytes, ids: Vec<StreamId>) -> Self {
+        Xdel { key, ids }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let stream = match slot.as_mut().map(Entry::stream_mut) {
+                None => return RESPType::Integer(0),
+                Some(Err(e)) => return e.into(),
+                Some(Ok(stream)) => stream,
+            };
+
+            let deleted = self.ids.iter().filter(|id| stream.remove(id)).count();
+            RESPType::Integer(deleted as i64)
+        })
+    }
+}
+
+impl From<Xdel> for RESPType {
+    fn from(xdel: Xdel) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("xdel")),
+            RESPType::Bulk(xdel.key),
+        ];
+        arr.extend(xdel.ids.into_iter().map(id_bulk));
+
+        RESPType::Array(arr)
+    }
+}
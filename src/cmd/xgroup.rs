@@ -0,0 +1,142 @@
+use bytes::Bytes;
+
+use crate::db::{now_ms, Entry, Stream, Value};
+use crate::{Db, Error, RESPType};
+
+use super::xrange::id_bulk;
+use super::StartId;
+
+/// The XGROUP subcommands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XgroupOp {
+    /// Create the group delivering the entries after the ID, creating an empty stream
+    /// first with MKSTREAM.
+    Create {
+        id: StartId,
+        mkstream: bool,
+    },
+    /// Move the group's last delivered ID.
+    SetId(StartId),
+    Destroy,
+    CreateConsumer(Bytes),
+    DelConsumer(Bytes),
+}
+
+/// XGROUP, managing a consumer group of a stream and its consumers.
+pub struct Xgroup {
+    key: Bytes,
+    group: Bytes,
+    op: XgroupOp,
+}
+
+impl Xgroup {
+    pub fn new(key: Bytes, group: Bytes, op: XgroupOp) -> Self {
+        Xgroup { key, group, op }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            if slot.is_none() {
+                if let XgroupOp::Create { mkstream: true, .. } = self.op {
+                    *slot = Some(Entry::new(Value::Stream(Stream::default())));
+                }
+            }
+            let stream = match slot.as_mut().map(Entry::stream_mut) {
+                None => {
+                    return Error::command(
+                        "The XGROUP subcommand requires the key to exist. Note that for CREATE you may want to use the MKSTREAM option to create an empty stream automatically.",
+                    )
+                    .into()
+                }
+                Some(Err(e)) => return e.into(),
+                Some(Ok(stream)) => stream,
+            };
+            let last_id = stream.last_id();
+            let resolve = |id: StartId| match id {
+                StartId::Last => last_id,
+                StartId::After(id) => id,
+            };
+
+            match &self.op {
+                XgroupOp::Create { id, .. } => {
+                    let id = resolve(*id);
+                    if !stream.create_group(self.group.clone(), id) {
+                        return Error::server("BUSYGROUP", "Consumer Group name already exists")
+                            .into();
+                    }
+                    RESPType::String("OK".into())
+                }
+                XgroupOp::Destroy => RESPType::Integer(stream.destroy_group(&self.group) as i64),
+                XgroupOp::SetId(id) => {
+                    let id = resolve(*id);
+                    match stream.group_mut(&self.group) {
+                        None => self.no_group(),
+                        Some(group) => {
+                            group.last_delivered = id;
+                            RESPType::String("OK".into())
+                        }
+                    }
+                }
+                XgroupOp::CreateConsumer(consumer) => match stream.group_mut(&self.group) {
+                    None => self.no_group(),
+                    Some(group) => {
+                        RESPType::Integer(group.create_consumer(consumer.clone(), now_ms()) as i64)
+                    }
+                },
+                XgroupOp::DelConsumer(consumer) => match stream.group_mut(&self.group) {
+                    None => self.no_group(),
+                    Some(group) => RESPType::Integer(group.delete_consumer(consumer) as i64),
+                },
+            }
+        })
+    }
+
+    fn no_group(&self) -> RESPType {
+        Error::server(
+            "NOGROUP",
+            format!(
+                "No such consumer group '{}' for key name '{}'",
+                String::from_utf8_lossy(&self.group),
+                String::from_utf8_lossy(&self.key)
+            ),
+        )
+        .into()
+    }
+}
+
+impl From<Xgroup> for RESPType {
+    fn from(xgroup: Xgroup) -> RESPType {
+        let id_arg = |id: StartId| match id {
+            StartId::Last => RESPType::Bulk(Bytes::from("$")),
+            StartId::After(id) => id_bulk(id),
+        };
+        let subcommand = match &xgroup.op {
+            XgroupOp::Create { .. } => "CREATE",
+            XgroupOp::SetId(_) => "SETID",
+            XgroupOp::Destroy => "DESTROY",
+            XgroupOp::CreateConsumer(_) => "CREATECONSUMER",
+            XgroupOp::DelConsumer(_) => "DELCONSUMER",
+        };
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("xgroup")),
+            RESPType::Bulk(Bytes::from(subcommand)),
+            RESPType::Bulk(xgroup.key),
+            RESPType::Bulk(xgroup.group),
+        ];
+        match xgroup.op {
+            XgroupOp::Create { id, mkstream } => {
+                arr.push(id_arg(id));
+                if mkstream {
+                    arr.push(RESPType::Bulk(Bytes::from("MKSTREAM")));
+                }
+            }
+            XgroupOp::SetId(id) => arr.push(id_arg(id)),
+            XgroupOp::Destroy => {}
+            XgroupOp::CreateConsumer(consumer) | XgroupOp::DelConsumer(consumer) => {
+                arr.push(RESPType::Bulk(consumer))
+            }
+        }
+
+        RESPType::Array(arr)
+    }
+}
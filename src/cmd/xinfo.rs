@@ -0,0 +1,147 @@
+use bytes::Bytes;
+
+use crate::db::{now_ms, Entry, Stream, StreamId};
+use crate::{Db, Error, RESPType};
+
+use super::xclaim::no_group;
+use super::xrange::{entry_reply, id_bulk};
+
+pub enum XinfoKind {
+    Stream,
+    Groups,
+    Consumers(Bytes),
+}
+
+/// XINFO STREAM, GROUPS or CONSUMERS.
+pub struct Xinfo {
+    key: Bytes,
+    kind: XinfoKind,
+}
+
+impl Xinfo {
+    pub fn new(key: Bytes, kind: XinfoKind) -> Self {
+        Xinfo { key, kind }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let stream = match slot.as_ref().map(Entry::stream) {
+                None => return Error::command("no such key").into(),
+                Some(Err(e)) => return e.into(),
+                Some(Ok(stream)) => stream,
+            };
+            match &self.kind {
+                XinfoKind::Stream => stream_info(stream),
+                XinfoKind::Groups => groups_info(stream),
+                XinfoKind::Consumers(group) => match consumers_info(stream, group) {
+                    None => no_group(&self.key, group),
+                    Some(consumers) => consumers,
+                },
+            }
+        })
+    }
+}
+
+fn field(name: &'static str, value: RESPType) -> (RESPType, RESPType) {
+    (RESPType::Bulk(Bytes::from(name)), value)
+}
+
+fn stream_info(stream: &Stream) -> RESPType {
+    let first = stream.first();
+    RESPType::Map(vec![
+        field("length", RESPType::Integer(stream.len() as i64)),
+        field("last-generated-id", id_bulk(stream.last_id())),
+        field("max-deleted-entry-id", id_bulk(stream.max_deleted_id())),
+        field(
+            "entries-added",
+            RESPType::Integer(stream.entries_added() as i64),
+        ),
+        field(
+            "recorded-first-entry-id",
+            id_bulk(first.map_or(StreamId::MIN, |(id, _)| id)),
+        ),
+        field("groups", RESPType::Integer(stream.groups().count() as i64)),
+        field(
+            "first-entry",
+            first.map_or(RESPType::Null, |(id, fields)| entry_reply(id, Some(fields))),
+        ),
+        field(
+            "last-entry",
+            stream
+                .last()
+                .map_or(RESPType::Null, |(id, fields)| entry_reply(id, Some(fields))),
+        ),
+    ])
+}
+
+fn groups_info(stream: &Stream) -> RESPType {
+    let groups = stream
+        .groups()
+        .map(|(name, group)| {
+            let lag = stream.lag(group);
+            RESPType::Map(vec![
+                field("name", RESPType::Bulk(name.clone())),
+                field("consumers", RESPType::Integer(group.consumers.len() as i64)),
+                field("pending", RESPType::Integer(group.pending.len() as i64)),
+                field("last-delivered-id", id_bulk(group.last_delivered)),
+                field(
+                    "entries-read",
+                    RESPType::Integer(stream.entries_added().saturating_sub(lag as u64) as i64),
+                ),
+                field("lag", RESPType::Integer(lag as i64)),
+            ])
+        })
+        .collect();
+    RESPType::Array(groups)
+}
+
+fn consumers_info(stream: &Stream, group: &[u8]) -> Option<RESPType> {
+    let group = stream.group(group)?;
+    let now = now_ms();
+    let consumers = group
+        .consumers
+        .iter()
+        .map(|(name, consumer)| {
+            let inactive = match consumer.active_at {
+                None => -1,
+                Some(active_at) => now.saturating_sub(active_at) as i64,
+            };
+            RESPType::Map(vec![
+                field("name", RESPType::Bulk(name.clone())),
+                field(
+                    "pending",
+                    RESPType::Integer(group.pending_count(name) as i64),
+                ),
+                field(
+                    "idle",
+                    RESPType::Integer(now.saturating_sub(consumer.seen_at) as i64),
+                ),
+                field("inactive", RESPType::Integer(inactive)),
+            ])
+        })
+        .collect();
+    Some(RESPType::Array(consumers))
+}
+
+impl From<Xinfo> for RESPType {
+    fn from(xinfo: Xinfo) -> RESPType {
+        let mut arr = vec![RESPType::Bulk(Bytes::from("xinfo"))];
+        match xinfo.kind {
+            XinfoKind::Stream => {
+                arr.push(RESPType::Bulk(Bytes::from("STREAM")));
+                arr.push(RESPType::Bulk(xinfo.key));
+            }
+            XinfoKind::Groups => {
+                arr.push(RESPType::Bulk(Bytes::from("GROUPS")));
+                arr.push(RESPType::Bulk(xinfo.key));
+            }
+            XinfoKind::Consumers(group) => {
+                arr.push(RESPType::Bulk(Bytes::from("CONSUMERS")));
+                arr.push(RESPType::Bulk(xinfo.key));
+                arr.push(RESPType::Bulk(group));
+            }
+        }
+
+        RESPType::Array(arr)
+    }
+}
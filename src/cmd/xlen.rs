@@ -0,0 +1,31 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Xlen {
+    key: Bytes,
+}
+
+impl Xlen {
+    pub fn new(key: Bytes) -> Self {
+        Xlen { key }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| match slot.as_ref().map(Entry::stream) {
+            None => RESPType::Integer(0),
+            Some(Err(e)) => e.into(),
+            Some(Ok(stream)) => RESPType::Integer(stream.len() as i64),
+        })
+    }
+}
+
+impl From<Xlen> for RESPType {
+    fn from(xlen: Xlen) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("xlen")),
+            RESPType::Bulk(xlen.key),
+        ])
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::BTreeMap;
+use std::ops::Bound;
+
+use bytes::Bytes;
+
+use crate::db::{now_ms, Entry, StreamId};
+use crate::{Db, RESPType};
+
+use super::xclaim::no_group;
+use super::xrange::id_bulk;
+
+/// XPENDING, summarizing the pending entries of a group, or listing them when given a
+/// range.
+pub struct Xpending {
+    key: Bytes,
+    group: Bytes,
+    range: Option<(StreamId, StreamId, usize)>,
+    min_idle: Option<u64>,
+    consumer: Option<Bytes>,
+}
+
+impl Xpending {
+    pub fn new(key: Bytes, group: Bytes) -> Self {
+        Xpending {
+            key,
+            group,
+            range: None,
+            min_idle: None,
+            consumer: None,
+        }
+    }
+
+    /// List up to `count` pending entries with IDs from `start` to `end` inclusive.
+    pub fn range(mut self, start: StreamId, end: StreamId, count: usize) -> Self {
+        self.range = Some((start, end, count));
+        self
+    }
+
+    /// Only list entries idle for at least `min_idle` milliseconds.
+    pub fn min_idle(mut self, min_idle: u64) -> Self {
+        self.min_idle = Some(min_idle);
+        self
+    }
+
+    /// Only list entries pending for `consumer`.
+    pub fn consumer(mut self, consumer: Bytes) -> Self {
+        self.consumer = Some(consumer);
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let group = match slot.as_ref().map(Entry::stream) {
+                Some(Err(e)) => return e.into(),
+                Some(Ok(stream)) => stream.group(&self.group),
+                None => None,
+            };
+            let group = match group {
+                None => return no_group(&self.key, &self.group),
+                Some(group) => group,
+            };
+
+            let (start, end, count) = match self.range {
+                Some(range) => range,
+                None => {
+                    let (first, last) = match (
+                        group.pending.first_key_value(),
+                        group.pending.last_key_value(),
+                    ) {
+                        (Some((first, _)), Some((last, _))) => (*first, *last),
+                        _ => {
+                            return RESPType::Array(vec![
+                                RESPType::Integer(0),
+                                RESPType::Null,
+                                RESPType::Null,
+                                RESPType::NullArray,
+                            ])
+                        }
+                    };
+                    let mut consumers = BTreeMap::new();
+                    for pending in group.pending.values() {
+                        *consumers.entry(&pending.consumer).or_insert(0) += 1;
+                    }
+                    let consumers = consumers
+                        .into_iter()
+                        .map(|(consumer, count): (&Bytes, usize)| {
+                            RESPType::Array(vec![
+                                RESPType::Bulk(consumer.clone()),
+                                RESPType::Bulk(Bytes::from(count.to_string())),
+                            ])
+                        })
+                        .collect();
+                    return RESPType::Array(vec![
+                        RESPType::Integer(group.pending.len() as i64),
+                        id_bulk(first),
+                        id_bulk(last),
+                        RESPType::Array(consumers),
+                    ]);
+                }
+            };
+
+            let now = now_ms();
+            let entries = group
+                .pending_range(Bound::Included(start), Bound::Included(end))
+                .filter(|(_, pending)| match &self.consumer {
+                    None => true,
+                    Some(consumer) => pending.consumer == consumer,
+                })
+                .filter(|(_, pending)| match self.min_idle {
+                    None => true,
+                    Some(min_idle) => now.saturating_sub(pending.delivered_at) >= min_idle,
+                })
+                .take(count)
+                .map(|(id, pending)| {
+                    RESPType::Array(vec![
+                        id_bulk(*id),
+                        RESPType::Bulk(pending.consumer.clone()),
+                        RESPType::Integer(now.saturating_sub(pending.delivered_at) as i64),
+                        RESPType::Integer(pending.deliveries as i64),
+                    ])
+                })
+                .collect();
+            RESPType::Array(entries)
+        })
+    }
+}
+
+impl From<Xpending> for RESPType {
+    fn from(xpending: Xpending) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("xpending")),
+            RESPType::Bulk(xpending.key),
+            RESPType::Bulk(xpending.group),
+        ];
+        if let Some((start, end, count)) = xpending.range {
+            if let Some(min_idle) = xpending.min_idle {
+                arr.push(RESPType::Bulk(Bytes::from("IDLE")));
+                arr.push(RESPType::Bulk(Bytes::from(min_idle.to_string())));
+            }
+            arr.push(id_bulk(start));
+            arr.push(id_bulk(end));
+            arr.push(RESPType::Bulk(Bytes::from(count.to_string())));
+            arr.extend(xpending.consumer.map(RESPType::Bulk));
+        }
+
+        RESPType::Array(arr)
+    }
+}
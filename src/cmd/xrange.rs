@@ -0,0 +1,91 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, Fields, StreamId};
+use crate::{Db, RESPType};
+
+/// XRANGE, or XREVRANGE when walking from the end.
+pub struct Xrange {
+    key: Bytes,
+    start: StreamId,
+    end: StreamId,
+    rev: bool,
+    count: Option<usize>,
+}
+
+impl Xrange {
+    /// Entries from `start` to `end` inclusive, whatever the direction.
+    pub fn new(key: Bytes, start: StreamId, end: StreamId, rev: bool) -> Self {
+        Xrange {
+            key,
+            start,
+            end,
+            rev,
+            count: None,
+        }
+    }
+
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| match slot.as_ref().map(Entry::stream) {
+            None => RESPType::Array(vec![]),
+            Some(Err(e)) => e.into(),
+            Some(Ok(stream)) => {
+                entries_reply(stream.range(self.start, self.end, self.rev, self.count))
+            }
+        })
+    }
+}
+
+pub(crate) fn id_bulk(id: StreamId) -> RESPType {
+    RESPType::Bulk(Bytes::from(id.to_string()))
+}
+
+/// An entry as `[id, [field, value, ...]]`, with nil fields for an entry that was deleted.
+pub(crate) fn entry_reply(id: StreamId, fields: Option<&Fields>) -> RESPType {
+    let fields = match fields {
+        None => RESPType::Null,
+        Some(fields) => RESPType::Array(
+            fields
+                .iter()
+                .flat_map(|(field, value)| {
+                    [RESPType::Bulk(field.clone()), RESPType::Bulk(value.clone())]
+                })
+                .collect(),
+        ),
+    };
+    RESPType::Array(vec![id_bulk(id), fields])
+}
+
+pub(crate) fn entries_reply(entries: Vec<(StreamId, Fields)>) -> RESPType {
+    RESPType::Array(
+        entries
+            .iter()
+            .map(|(id, fields)| entry_reply(*id, Some(fields)))
+            .collect(),
+    )
+}
+
+impl From<Xrange> for RESPType {
+    fn from(xrange: Xrange) -> RESPType {
+        let (name, first, second) = match xrange.rev {
+            false => ("xrange", xrange.start, xrange.end),
+            true => ("xrevrange", xrange.end, xrange.start),
+        };
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from(name)),
+            RESPType::Bulk(xrange.key),
+            id_bulk(first),
+            id_bulk(second),
+        ];
+        if let Some(count) = xrange.count {
+            arr.push(RESPType::Bulk(Bytes::from("COUNT")));
+            arr.push(RESPType::Bulk(Bytes::from(count.to_string())));
+        }
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,131 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+
+use crate::db::StreamId;
+use crate::{Db, RESPType};
+
+use super::xrange::{entries_reply, id_bulk};
+
+/// Where reading a stream starts, `$` being whatever its last ID is when the command runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartId {
+    Last,
+    After(StreamId),
+}
+
+/// XREAD, replying with the entries after the given ID of each stream that has any, or
+/// waiting for new entries when blocking.
+pub struct Xread {
+    keys: Vec<Bytes>,
+    ids: Vec<StartId>,
+    count: Option<usize>,
+    block: Option<Duration>,
+}
+
+impl Xread {
+    /// `ids` pairs up with `keys`.
+    pub fn new(keys: Vec<Bytes>, ids: Vec<StartId>) -> Self {
+        Xread {
+            keys,
+            ids,
+            count: None,
+            block: None,
+        }
+    }
+
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Wait up to `timeout` for new entries, forever if zero.
+    pub fn block(mut self, timeout: Duration) -> Self {
+        self.block = Some(timeout);
+        self
+    }
+
+    pub async fn response(&self, db: &Db) -> RESPType {
+        let ids = match self.resolve(db) {
+            Err(e) => return e.into(),
+            Ok(ids) => ids,
+        };
+        let read = || self.read(db, &ids);
+
+        let read = match self.block {
+            None => read(),
+            Some(timeout) => {
+                let timeout = (!timeout.is_zero()).then_some(timeout);
+                db.block(&self.keys, timeout, read).await
+            }
+        };
+        match read {
+            Err(e) => e.into(),
+            Ok(None) => RESPType::NullArray,
+            Ok(Some(read)) => read,
+        }
+    }
+
+    /// Replaces `$` with the last ID of its stream, so only entries added later are read.
+    fn resolve(&self, db: &Db) -> crate::Result<Vec<StreamId>> {
+        let mut locked = db.lock(&self.keys);
+        self.keys
+            .iter()
+            .zip(&self.ids)
+            .map(|(key, id)| match id {
+                StartId::After(id) => Ok(*id),
+                StartId::Last => locked.update(key, |slot| {
+                    slot.as_ref()
+                        .map(|entry| entry.stream().map(|stream| stream.last_id()))
+                        .transpose()
+                        .map(Option::unwrap_or_default)
+                }),
+            })
+            .collect()
+    }
+
+    fn read(&self, db: &Db, ids: &[StreamId]) -> crate::Result<Option<RESPType>> {
+        let mut locked = db.lock(&self.keys);
+        let mut arr = vec![];
+        for (key, id) in self.keys.iter().zip(ids) {
+            let entries = locked.update(key, |slot| {
+                slot.as_ref()
+                    .map(|entry| entry.stream().map(|stream| stream.after(*id, self.count)))
+                    .transpose()
+            })?;
+            match entries {
+                Some(entries) if !entries.is_empty() => arr.push(RESPType::Array(vec![
+                    RESPType::Bulk(key.clone()),
+                    entries_reply(entries),
+                ])),
+                _ => {}
+            }
+        }
+        if arr.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(RESPType::Array(arr)))
+    }
+}
+
+impl From<Xread> for RESPType {
+    fn from(xread: Xread) -> RESPType {
+        let mut arr = vec![RESPType::Bulk(Bytes::from("xread"))];
+        if let Some(count) = xread.count {
+            arr.push(RESPType::Bulk(Bytes::from("COUNT")));
+            arr.push(RESPType::Bulk(Bytes::from(count.to_string())));
+        }
+        if let Some(block) = xread.block {
+            arr.push(RESPType::Bulk(Bytes::from("BLOCK")));
+            arr.push(RESPType::Bulk(Bytes::from(block.as_millis().to_string())));
+        }
+        arr.push(RESPType::Bulk(Bytes::from("STREAMS")));
+        arr.extend(xread.keys.into_iter().map(RESPType::Bulk));
+        arr.extend(xread.ids.into_iter().map(|id| match id {
+            StartId::Last => RESPType::Bulk(Bytes::from("$")),
+            StartId::After(id) => id_bulk(id),
+        }));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,164 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+
+use crate::db::{now_ms, Entry, StreamId};
+use crate::{Db, Error, RESPType};
+
+use super::xrange::{entry_reply, id_bulk};
+
+/// XREADGROUP, delivering entries to a consumer of a group. A `None` ID, given as `>`,
+/// reads entries new to the group, any other ID re-reads the consumer's pending entries
+/// after it.
+pub struct XreadGroup {
+    group: Bytes,
+    consumer: Bytes,
+    keys: Vec<Bytes>,
+    ids: Vec<Option<StreamId>>,
+    count: Option<usize>,
+    block: Option<Duration>,
+    noack: bool,
+}
+
+impl XreadGroup {
+    /// `ids` pairs up with `keys`.
+    pub fn new(
+        group: Bytes,
+        consumer: Bytes,
+        keys: Vec<Bytes>,
+        ids: Vec<Option<StreamId>>,
+    ) -> Self {
+        XreadGroup {
+            group,
+            consumer,
+            keys,
+            ids,
+            count: None,
+            block: None,
+            noack: false,
+        }
+    }
+
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Wait up to `timeout` for new entries, forever if zero. Reading pending entries
+    /// never blocks.
+    pub fn block(mut self, timeout: Duration) -> Self {
+        self.block = Some(timeout);
+        self
+    }
+
+    /// Don't add delivered entries to the pending list, as if acknowledged right away.
+    pub fn noack(mut self, noack: bool) -> Self {
+        self.noack = noack;
+        self
+    }
+
+    pub async fn response(&self, db: &Db) -> RESPType {
+        let history = self.ids.iter().any(Option::is_some);
+        let read = match self.block {
+            Some(timeout) if !history => {
+                let timeout = (!timeout.is_zero()).then_some(timeout);
+                db.block(&self.keys, timeout, || self.read(db)).await
+            }
+            _ => self.read(db),
+        };
+
+        match read {
+            Err(e) => e.into(),
+            Ok(None) => RESPType::NullArray,
+            Ok(Some(read)) => read,
+        }
+    }
+
+    fn read(&self, db: &Db) -> crate::Result<Option<RESPType>> {
+        let mut locked = db.lock(&self.keys);
+        let now = now_ms();
+
+        // every group has to exist before anything is delivered
+        for key in &self.keys {
+            let found = locked.update(key, |slot| match slot.as_ref().map(Entry::stream) {
+                Some(Ok(stream)) => Ok(stream.group(&self.group).is_some()),
+                Some(Err(e)) => Err(e),
+                None => Ok(false),
+            })?;
+            if !found {
+                return Err(Error::server(
+                    "NOGROUP",
+                    format!(
+                        "No such key '{}' or consumer group '{}' in XREADGROUP with GROUP option",
+                        String::from_utf8_lossy(key),
+                        String::from_utf8_lossy(&self.group)
+                    ),
+                ));
+            }
+        }
+
+        let mut arr = vec![];
+        for (key, id) in self.keys.iter().zip(&self.ids) {
+            let read = locked.update(key, |slot| match slot.as_mut().map(Entry::stream_mut) {
+                Some(Ok(stream)) => stream
+                    .read_group(
+                        &self.group,
+                        &self.consumer,
+                        *id,
+                        self.count,
+                        self.noack,
+                        now,
+                    )
+                    .unwrap_or_default(),
+                _ => vec![],
+            });
+            // a consumer's pending entries are listed even when there are none left
+            if read.is_empty() && id.is_none() {
+                continue;
+            }
+            let entries = read
+                .iter()
+                .map(|(id, fields)| entry_reply(*id, fields.as_ref()))
+                .collect();
+            arr.push(RESPType::Array(vec![
+                RESPType::Bulk(key.clone()),
+                RESPType::Array(entries),
+            ]));
+        }
+
+        if arr.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(RESPType::Array(arr)))
+    }
+}
+
+impl From<XreadGroup> for RESPType {
+    fn from(xreadgroup: XreadGroup) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("xreadgroup")),
+            RESPType::Bulk(Bytes::from("GROUP")),
+            RESPType::Bulk(xreadgroup.group),
+            RESPType::Bulk(xreadgroup.consumer),
+        ];
+        if let Some(count) = xreadgroup.count {
+            arr.push(RESPType::Bulk(Bytes::from("COUNT")));
+            arr.push(RESPType::Bulk(Bytes::from(count.to_string())));
+        }
+        if let Some(block) = xreadgroup.block {
+            arr.push(RESPType::Bulk(Bytes::from("BLOCK")));
+            arr.push(RESPType::Bulk(Bytes::from(block.as_millis().to_string())));
+        }
+        if xreadgroup.noack {
+            arr.push(RESPType::Bulk(Bytes::from("NOACK")));
+        }
+        arr.push(RESPType::Bulk(Bytes::from("STREAMS")));
+        arr.extend(xreadgroup.keys.into_iter().map(RESPType::Bulk));
+        arr.extend(xreadgroup.ids.into_iter().map(|id| match id {
+            None => RESPType::Bulk(Bytes::from(">")),
+            Some(id) => id_bulk(id),
+        }));
+
+        RESPType::Array(arr)
+    }
+}
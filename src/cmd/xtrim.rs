@@ -0,0 +1,91 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, Stream, TrimStrategy};
+use crate::{Db, RESPType};
+
+/// Trimming requested by XADD or XTRIM. Approximate trimming is exact here, which is
+/// always allowed, but it is the only mode that accepts a LIMIT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamTrim {
+    strategy: TrimStrategy,
+    approx: bool,
+    limit: Option<usize>,
+}
+
+impl StreamTrim {
+    pub fn new(strategy: TrimStrategy) -> Self {
+        StreamTrim {
+            strategy,
+            approx: false,
+            limit: None,
+        }
+    }
+
+    pub fn approx(mut self, approx: bool) -> Self {
+        self.approx = approx;
+        self
+    }
+
+    /// Remove at most `limit` entries, 0 meaning no limit.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Trims `stream`, returning the number of entries removed.
+    pub(crate) fn apply(&self, stream: &mut Stream) -> usize {
+        stream.trim(self.strategy, self.limit.filter(|limit| *limit > 0))
+    }
+
+    pub(crate) fn args(&self) -> Vec<RESPType> {
+        let mut arr = match self.strategy {
+            TrimStrategy::MaxLen(_) => vec![RESPType::Bulk(Bytes::from("MAXLEN"))],
+            TrimStrategy::MinId(_) => vec![RESPType::Bulk(Bytes::from("MINID"))],
+        };
+        if self.approx {
+            arr.push(RESPType::Bulk(Bytes::from("~")));
+        }
+        arr.push(RESPType::Bulk(Bytes::from(match self.strategy {
+            TrimStrategy::MaxLen(max) => max.to_string(),
+            TrimStrategy::MinId(min) => min.to_string(),
+        })));
+        if let Some(limit) = self.limit {
+            arr.push(RESPType::Bulk(Bytes::from("LIMIT")));
+            arr.push(RESPType::Bulk(Bytes::from(limit.to_string())));
+        }
+        arr
+    }
+}
+
+pub struct Xtrim {
+    key: Bytes,
+    trim: StreamTrim,
+}
+
+impl Xtrim {
+    pub fn new(key: Bytes, trim: StreamTrim) -> Self {
+        Xtrim { key, trim }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            match slot.as_mut().map(Entry::stream_mut) {
+                None => RESPType::Integer(0),
+                Some(Err(e)) => e.into(),
+                Some(Ok(stream)) => RESPType::Integer(self.trim.apply(stream) as i64),
+            }
+        })
+    }
+}
+
+impl From<Xtrim> for RESPType {
+    fn from(xtrim: Xtrim) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("xtrim")),
+            RESPType::Bulk(xtrim.key),
+        ];
+        arr.extend(xtrim.trim.args());
+
+        RESPType::Array(arr)
+    }
+}
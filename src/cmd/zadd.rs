@@ -0,0 +1,153 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, Value, ZSet};
+use crate::{Db, Error, RESPType};
+
+use super::incrbyfloat::format_float;
+use super::SetCondition;
+
+/// Restricts ZADD to updates that move a member's score up or down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreComparison {
+    Gt,
+    Lt,
+}
+
+/// ZADD, replying with the number of members added, or the new score with INCR.
+pub struct Zadd {
+    key: Bytes,
+    members: Vec<(f64, Bytes)>,
+    condition: Option<SetCondition>,
+    comparison: Option<ScoreComparison>,
+    changed: bool,
+    incr: bool,
+}
+
+impl Zadd {
+    pub fn new(key: Bytes, members: Vec<(f64, Bytes)>) -> Self {
+        Zadd {
+            key,
+            members,
+            condition: None,
+            comparison: None,
+            changed: false,
+            incr: false,
+        }
+    }
+
+    /// Only add new members with NX, or only update existing ones with XX.
+    pub fn condition(mut self, condition: SetCondition) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    pub fn comparison(mut self, comparison: ScoreComparison) -> Self {
+        self.comparison = Some(comparison);
+        self
+    }
+
+    /// Count updated members in the reply as well as added ones.
+    pub fn changed(mut self, changed: bool) -> Self {
+        self.changed = changed;
+        self
+    }
+
+    /// Increment the score of the single member instead of setting it, like ZINCRBY.
+    pub fn incr(mut self, incr: bool) -> Self {
+        self.incr = incr;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            // nothing can be added with XX, so the key isn't created
+            if slot.is_none() && self.condition == Some(SetCondition::Xx) {
+                return self.reply(0, None);
+            }
+            let entry = slot.get_or_insert_with(|| Entry::new(Value::ZSet(ZSet::default())));
+            let zset = match entry.zset_mut() {
+                Err(e) => return e.into(),
+                Ok(zset) => zset,
+            };
+
+            let mut added = 0;
+            let mut updated = 0;
+            let mut last = None;
+            for (score, member) in &self.members {
+                let current = zset.score(member);
+                let score = match current {
+                    Some(current) if self.incr => current + score,
+                    _ => *score,
+                };
+                if score.is_nan() {
+                    return Error::command("resulting score is not a number (NaN)").into();
+                }
+
+                match (current, self.condition) {
+                    (None, Some(SetCondition::Xx)) | (Some(_), Some(SetCondition::Nx)) => continue,
+                    (None, _) => {
+                        zset.insert(member.clone(), score);
+                        added += 1;
+                    }
+                    (Some(current), _) => {
+                        let allowed = match self.comparison {
+                            None => true,
+                            Some(ScoreComparison::Gt) => score > current,
+                            Some(ScoreComparison::Lt) => score < current,
+                        };
+                        if !allowed {
+                            continue;
+                        }
+                        if score != current {
+                            zset.insert(member.clone(), score);
+                            updated += 1;
+                        }
+                    }
+                }
+                last = Some(score);
+            }
+
+            let changed = if self.changed { updated } else { 0 };
+            self.reply(added + changed, last)
+        })
+    }
+
+    fn reply(&self, count: i64, score: Option<f64>) -> RESPType {
+        match (self.incr, score) {
+            (false, _) => RESPType::Integer(count),
+            (true, None) => RESPType::Null,
+            (true, Some(score)) => RESPType::Double(score),
+        }
+    }
+}
+
+impl From<Zadd> for RESPType {
+    fn from(zadd: Zadd) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("zadd")),
+            RESPType::Bulk(zadd.key),
+        ];
+        match zadd.condition {
+            None => {}
+            Some(SetCondition::Nx) => arr.push(RESPType::Bulk(Bytes::from("NX"))),
+            Some(SetCondition::Xx) => arr.push(RESPType::Bulk(Bytes::from("XX"))),
+        }
+        match zadd.comparison {
+            None => {}
+            Some(ScoreComparison::Gt) => arr.push(RESPType::Bulk(Bytes::from("GT"))),
+            Some(ScoreComparison::Lt) => arr.push(RESPType::Bulk(Bytes::from("LT"))),
+        }
+        if zadd.changed {
+            arr.push(RESPType::Bulk(Bytes::from("CH")));
+        }
+        if zadd.incr {
+            arr.push(RESPType::Bulk(Bytes::from("INCR")));
+        }
+        for (score, member) in zadd.members {
+            arr.push(RESPType::Bulk(Bytes::from(format_float(score))));
+            arr.push(RESPType::Bulk(member));
+        }
+
+        RESPType::Array(arr)
+    }
+}
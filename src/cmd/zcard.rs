@@ -0,0 +1,31 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Zcard {
+    key: Bytes,
+}
+
+impl Zcard {
+    pub fn new(key: Bytes) -> Self {
+        Zcard { key }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| match slot.as_ref().map(Entry::zset) {
+            None => RESPType::Integer(0),
+            Some(Err(e)) => e.into(),
+            Some(Ok(zset)) => RESPType::Integer(zset.len() as i64),
+        })
+    }
+}
+
+impl From<Zcard> for RESPType {
+    fn from(zcard: Zcard) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("zcard")),
+            RESPType::Bulk(zcard.key),
+        ])
+    }
+}
@@ -0,0 +1,76 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, LexBound, ScoreBound};
+use crate::{Db, RESPType};
+
+use super::zrange::{lex_bound_bulk, score_bound_bulk};
+
+/// ZCOUNT, counting the members within a score range.
+pub struct Zcount {
+    key: Bytes,
+    min: ScoreBound,
+    max: ScoreBound,
+}
+
+impl Zcount {
+    pub fn new(key: Bytes, min: ScoreBound, max: ScoreBound) -> Self {
+        Zcount { key, min, max }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| match slot.as_ref().map(Entry::zset) {
+            None => RESPType::Integer(0),
+            Some(Err(e)) => e.into(),
+            Some(Ok(zset)) => {
+                let (start, end) = zset.score_ranks(&self.min, &self.max);
+                RESPType::Integer((end - start) as i64)
+            }
+        })
+    }
+}
+
+impl From<Zcount> for RESPType {
+    fn from(zcount: Zcount) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("zcount")),
+            RESPType::Bulk(zcount.key),
+            score_bound_bulk(&zcount.min),
+            score_bound_bulk(&zcount.max),
+        ])
+    }
+}
+
+/// ZLEXCOUNT, counting the members within a lexicographical range.
+pub struct ZlexCount {
+    key: Bytes,
+    min: LexBound,
+    max: LexBound,
+}
+
+impl ZlexCount {
+    pub fn new(key: Bytes, min: LexBound, max: LexBound) -> Self {
+        ZlexCount { key, min, max }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| match slot.as_ref().map(Entry::zset) {
+            None => RESPType::Integer(0),
+            Some(Err(e)) => e.into(),
+            Some(Ok(zset)) => {
+                let (start, end) = zset.lex_ranks(&self.min, &self.max);
+                RESPType::Integer((end - start) as i64)
+            }
+        })
+    }
+}
+
+impl From<ZlexCount> for RESPType {
+    fn from(zlexcount: ZlexCount) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("zlexcount")),
+            RESPType::Bulk(zlexcount.key),
+            lex_bound_bulk(&zlexcount.min),
+            lex_bound_bulk(&zlexcount.max),
+        ])
+    }
+}
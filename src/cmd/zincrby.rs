@@ -0,0 +1,46 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, Value, ZSet};
+use crate::{Db, Error, RESPType};
+
+use super::incrbyfloat::format_float;
+
+pub struct Zincrby {
+    key: Bytes,
+    delta: f64,
+    member: Bytes,
+}
+
+impl Zincrby {
+    pub fn new(key: Bytes, delta: f64, member: Bytes) -> Self {
+        Zincrby { key, delta, member }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let entry = slot.get_or_insert_with(|| Entry::new(Value::ZSet(ZSet::default())));
+            let zset = match entry.zset_mut() {
+                Err(e) => return e.into(),
+                Ok(zset) => zset,
+            };
+
+            let score = zset.score(&self.member).unwrap_or(0.0) + self.delta;
+            if score.is_nan() {
+                return Error::command("resulting score is not a number (NaN)").into();
+            }
+            zset.insert(self.member.clone(), score);
+            RESPType::Double(score)
+        })
+    }
+}
+
+impl From<Zincrby> for RESPType {
+    fn from(zincrby: Zincrby) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("zincrby")),
+            RESPType::Bulk(zincrby.key),
+            RESPType::Bulk(Bytes::from(format_float(zincrby.delta))),
+            RESPType::Bulk(zincrby.member),
+        ])
+    }
+}
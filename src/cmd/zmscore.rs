@@ -0,0 +1,43 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Zmscore {
+    key: Bytes,
+    members: Vec<Bytes>,
+}
+
+impl Zmscore {
+    pub fn new(key: Bytes, members: Vec<Bytes>) -> Self {
+        Zmscore { key, members }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let zset = match slot.as_ref().map(Entry::zset) {
+                None => None,
+                Some(Err(e)) => return e.into(),
+                Some(Ok(zset)) => Some(zset),
+            };
+
+            let scores = self.members.iter().map(|member| {
+                zset.and_then(|zset| zset.score(member))
+                    .map_or(RESPType::Null, RESPType::Double)
+            });
+            RESPType::Array(scores.collect())
+        })
+    }
+}
+
+impl From<Zmscore> for RESPType {
+    fn from(zmscore: Zmscore) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("zmscore")),
+            RESPType::Bulk(zmscore.key),
+        ];
+        arr.extend(zmscore.members.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,72 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, Locked};
+use crate::{Db, RESPType};
+
+/// ZPOPMIN and ZPOPMAX, replying with each popped member followed by its score.
+pub struct Zpop {
+    key: Bytes,
+    max: bool,
+    count: usize,
+}
+
+impl Zpop {
+    pub fn new(key: Bytes, max: bool) -> Self {
+        Zpop { key, max, count: 1 }
+    }
+
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let keys = [self.key.clone()];
+        match pop_first(&mut db.lock(&keys), &keys, self.max, self.count) {
+            Err(e) => e.into(),
+            Ok(None) => RESPType::Array(vec![]),
+            Ok(Some((_, popped))) => RESPType::Array(
+                popped
+                    .into_iter()
+                    .flat_map(|(member, score)| [RESPType::Bulk(member), RESPType::Double(score)])
+                    .collect(),
+            ),
+        }
+    }
+}
+
+pub(crate) type ScoredMember = (Bytes, f64);
+
+/// Pops from the first of `keys` holding a non-empty sorted set, all of which must be locked.
+pub(crate) fn pop_first(
+    locked: &mut Locked<'_>,
+    keys: &[Bytes],
+    max: bool,
+    count: usize,
+) -> crate::Result<Option<(Bytes, Vec<ScoredMember>)>> {
+    for key in keys {
+        let popped = locked.update(key, |slot| {
+            let zset = match slot.as_mut().map(Entry::zset_mut).transpose()? {
+                None => return Ok(None),
+                Some(zset) => zset,
+            };
+            let popped = zset.pop(count, max);
+            Ok::<_, crate::Error>(Some(popped).filter(|popped| !popped.is_empty()))
+        })?;
+        if let Some(popped) = popped {
+            return Ok(Some((key.clone(), popped)));
+        }
+    }
+    Ok(None)
+}
+
+impl From<Zpop> for RESPType {
+    fn from(zpop: Zpop) -> RESPType {
+        let name = if zpop.max { "zpopmax" } else { "zpopmin" };
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from(name)),
+            RESPType::Bulk(zpop.key),
+            RESPType::Bulk(Bytes::from(zpop.count.to_string())),
+        ])
+    }
+}
@@ -0,0 +1,194 @@
+use bytes::Bytes;
+
+use crate::db::{Entry, LexBound, ScoreBound, Value, ZSet};
+use crate::{Db, RESPType};
+
+use super::incrbyfloat::format_float;
+use super::lrange::range;
+
+/// What the range of a ZRANGE selects members by, the lower end always comes first.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ZrangeBy {
+    Rank(i64, i64),
+    Score(ScoreBound, ScoreBound),
+    Lex(LexBound, LexBound),
+}
+
+/// ZRANGE and its legacy forms, or ZRANGESTORE when given a destination.
+pub struct Zrange {
+    key: Bytes,
+    by: ZrangeBy,
+    rev: bool,
+    limit: Option<(usize, Option<usize>)>,
+    with_scores: bool,
+    destination: Option<Bytes>,
+}
+
+impl Zrange {
+    pub fn new(key: Bytes, by: ZrangeBy) -> Self {
+        Zrange {
+            key,
+            by,
+            rev: false,
+            limit: None,
+            with_scores: false,
+            destination: None,
+        }
+    }
+
+    /// Order the members from the highest score, ranks then count from the end too.
+    pub fn rev(mut self, rev: bool) -> Self {
+        self.rev = rev;
+        self
+    }
+
+    /// Skip `offset` members and return at most `count`, only for score and lex ranges.
+    pub fn limit(mut self, offset: usize, count: Option<usize>) -> Self {
+        self.limit = Some((offset, count));
+        self
+    }
+
+    pub fn with_scores(mut self, with_scores: bool) -> Self {
+        self.with_scores = with_scores;
+        self
+    }
+
+    /// Store the range at `destination`, replacing whatever is there, instead of replying
+    /// with it.
+    pub fn store(mut self, destination: Bytes) -> Self {
+        self.destination = Some(destination);
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let mut keys = vec![&self.key];
+        keys.extend(&self.destination);
+        let mut locked = db.lock(&keys);
+
+        let members = locked.update(&self.key, |slot| {
+            slot.as_ref()
+                .map(|entry| entry.zset().map(|zset| self.members(zset)))
+                .transpose()
+        });
+        let members = match members {
+            Err(e) => return e.into(),
+            Ok(members) => members.unwrap_or_default(),
+        };
+
+        match &self.destination {
+            None => {
+                let mut arr = vec![];
+                for (member, score) in members {
+                    arr.push(RESPType::Bulk(member));
+                    if self.with_scores {
+                        arr.push(RESPType::Double(score));
+                    }
+                }
+                RESPType::Array(arr)
+            }
+            Some(destination) => {
+                let len = members.len();
+                let mut zset = ZSet::default();
+                for (member, score) in members {
+                    zset.insert(member, score);
+                }
+                locked.update(destination, |slot| {
+                    *slot = Some(Entry::new(Value::ZSet(zset)));
+                });
+                RESPType::Integer(len as i64)
+            }
+        }
+    }
+
+    fn members(&self, zset: &ZSet) -> Vec<(Bytes, f64)> {
+        let ranks = match &self.by {
+            ZrangeBy::Rank(start, stop) => match range(*start, *stop, zset.len()) {
+                None => return vec![],
+                // reversed ranks count from the highest score
+                Some((start, stop)) if self.rev => (zset.len() - 1 - stop, zset.len() - start),
+                Some((start, stop)) => (start, stop + 1),
+            },
+            ZrangeBy::Score(min, max) => zset.score_ranks(min, max),
+            ZrangeBy::Lex(min, max) => zset.lex_ranks(min, max),
+        };
+        let (offset, count) = self.limit.unwrap_or((0, None));
+        zset.range(ranks, self.rev, offset, count)
+    }
+}
+
+/// A score range bound in the syntax ZRANGE accepts.
+pub(crate) fn score_bound_bulk(bound: &ScoreBound) -> RESPType {
+    RESPType::Bulk(Bytes::from(match *bound {
+        ScoreBound::Inclusive(score) => format_score(score),
+        ScoreBound::Exclusive(score) => format!("({}", format_score(score)),
+    }))
+}
+
+/// A lexicographical range bound in the syntax ZRANGE accepts.
+pub(crate) fn lex_bound_bulk(bound: &LexBound) -> RESPType {
+    RESPType::Bulk(match bound {
+        LexBound::Min => Bytes::from("-"),
+        LexBound::Max => Bytes::from("+"),
+        LexBound::Inclusive(member) => [&b"["[..], member].concat().into(),
+        LexBound::Exclusive(member) => [&b"("[..], member].concat().into(),
+    })
+}
+
+fn format_score(score: f64) -> String {
+    match score {
+        f64::INFINITY => "+inf".into(),
+        f64::NEG_INFINITY => "-inf".into(),
+        score => format_float(score),
+    }
+}
+
+impl From<Zrange> for RESPType {
+    fn from(zrange: Zrange) -> RESPType {
+        let mut arr = vec![];
+        match zrange.destination {
+            None => arr.push(RESPType::Bulk(Bytes::from("zrange"))),
+            Some(destination) => {
+                arr.push(RESPType::Bulk(Bytes::from("zrangestore")));
+                arr.push(RESPType::Bulk(destination));
+            }
+        }
+        arr.push(RESPType::Bulk(zrange.key));
+
+        // reversed score and lex ranges are given from the upper end
+        let (mut start, mut stop, by) = match &zrange.by {
+            ZrangeBy::Rank(start, stop) => (
+                RESPType::Bulk(Bytes::from(start.to_string())),
+                RESPType::Bulk(Bytes::from(stop.to_string())),
+                None,
+            ),
+            ZrangeBy::Score(min, max) => (
+                score_bound_bulk(min),
+                score_bound_bulk(max),
+                Some("BYSCORE"),
+            ),
+            ZrangeBy::Lex(min, max) => (lex_bound_bulk(min), lex_bound_bulk(max), Some("BYLEX")),
+        };
+        if zrange.rev && by.is_some() {
+            std::mem::swap(&mut start, &mut stop);
+        }
+        arr.push(start);
+        arr.push(stop);
+        if let Some(by) = by {
+            arr.push(RESPType::Bulk(Bytes::from(by)));
+        }
+        if zrange.rev {
+            arr.push(RESPType::Bulk(Bytes::from("REV")));
+        }
+        if let Some((offset, count)) = zrange.limit {
+            let count = count.map_or(-1, |count| count as i64);
+            arr.push(RESPType::Bulk(Bytes::from("LIMIT")));
+            arr.push(RESPType::Bulk(Bytes::from(offset.to_string())));
+            arr.push(RESPType::Bulk(Bytes::from(count.to_string())));
+        }
+        if zrange.with_scores {
+            arr.push(RESPType::Bulk(Bytes::from("WITHSCORES")));
+        }
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,72 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+/// ZRANK, or ZREVRANK when ranking from the highest score.
+pub struct Zrank {
+    key: Bytes,
+    member: Bytes,
+    rev: bool,
+    with_score: bool,
+}
+
+impl Zrank {
+    pub fn new(key: Bytes, member: Bytes, rev: bool) -> Self {
+        Zrank {
+            key,
+            member,
+            rev,
+            with_score: false,
+        }
+    }
+
+    /// Reply with the member's score following its rank.
+    pub fn with_score(mut self, with_score: bool) -> Self {
+        self.with_score = with_score;
+        self
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let zset = match slot.as_ref().map(Entry::zset) {
+                None => return RESPType::Null,
+                Some(Err(e)) => return e.into(),
+                Some(Ok(zset)) => zset,
+            };
+
+            let (rank, score) = match (zset.rank(&self.member), zset.score(&self.member)) {
+                (Some(rank), Some(score)) => (rank, score),
+                _ => return RESPType::Null,
+            };
+            let rank = if self.rev {
+                zset.len() - 1 - rank
+            } else {
+                rank
+            };
+            match self.with_score {
+                false => RESPType::Integer(rank as i64),
+                true => RESPType::Array(vec![
+                    RESPType::Integer(rank as i64),
+                    RESPType::Double(score),
+                ]),
+            }
+        })
+    }
+}
+
+impl From<Zrank> for RESPType {
+    fn from(zrank: Zrank) -> RESPType {
+        let name = if zrank.rev { "zrevrank" } else { "zrank" };
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from(name)),
+            RESPType::Bulk(zrank.key),
+            RESPType::Bulk(zrank.member),
+        ];
+        if zrank.with_score {
+            arr.push(RESPType::Bulk(Bytes::from("WITHSCORE")));
+        }
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,44 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Zrem {
+    key: Bytes,
+    members: Vec<Bytes>,
+}
+
+impl Zrem {
+    pub fn new(key: Bytes, members: Vec<Bytes>) -> Self {
+        Zrem { key, members }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| {
+            let zset = match slot.as_mut().map(Entry::zset_mut) {
+                None => return RESPType::Integer(0),
+                Some(Err(e)) => return e.into(),
+                Some(Ok(zset)) => zset,
+            };
+
+            let removed = self
+                .members
+                .iter()
+                .filter(|member| zset.remove(member))
+                .count();
+            RESPType::Integer(removed as i64)
+        })
+    }
+}
+
+impl From<Zrem> for RESPType {
+    fn from(zrem: Zrem) -> RESPType {
+        let mut arr = vec![
+            RESPType::Bulk(Bytes::from("zrem")),
+            RESPType::Bulk(zrem.key),
+        ];
+        arr.extend(zrem.members.into_iter().map(RESPType::Bulk));
+
+        RESPType::Array(arr)
+    }
+}
@@ -0,0 +1,35 @@
+use bytes::Bytes;
+
+use crate::db::Entry;
+use crate::{Db, RESPType};
+
+pub struct Zscore {
+    key: Bytes,
+    member: Bytes,
+}
+
+impl Zscore {
+    pub fn new(key: Bytes, member: Bytes) -> Self {
+        Zscore { key, member }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        db.update(&self.key, |slot| match slot.as_ref().map(Entry::zset) {
+            None => RESPType::Null,
+            Some(Err(e)) => e.into(),
+            Some(Ok(zset)) => zset
+                .score(&self.member)
+                .map_or(RESPType::Null, RESPType::Double),
+        })
+    }
+}
+
+impl From<Zscore> for RESPType {
+    fn from(zscore: Zscore) -> RESPType {
+        RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("zscore")),
+            RESPType::Bulk(zscore.key),
+            RESPType::Bulk(zscore.member),
+        ])
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+
+use bytes::Bytes;
+
+use crate::db::{Entry, Locked, Value, ZSet};
+use crate::{Db, Error, RESPType};
+
+use super::incrbyfloat::format_float;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZsetOpKind {
+    Union,
+    Inter,
+    Diff,
+}
+
+/// How the scores of a member found in several inputs are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Sum,
+    Min,
+    Max,
+}
+
+impl Aggregate {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aggregate::Sum => "SUM",
+            Aggregate::Min => "MIN",
+            Aggregate::Max => "MAX",
+        }
+    }
+
+    fn apply(self, a: f64, b: f64) -> f64 {
+        let score = match self {
+            Aggregate::Sum => a + b,
+            Aggregate::Min => a.min(b),
+            Aggregate::Max => a.max(b),
+        };
+        // adding opposite infinities is the only way to get NaN here
+        if score.is_nan() {
+            0.0
+        } else {
+            score
+        }
+    }
+}
+
+/// ZUNION, ZINTER and ZDIFF, or their STORE variants when given a destination. Plain sets are
+/// accepted as inputs with every member scoring 1.
+pub struct ZsetOp {
+    kind: ZsetOpKind,
+    keys: Vec<Bytes>,
+    weights: Option<Vec<f64>>,
+    aggregate: Aggregate,
+    with_scores: bool,
+    destination: Option<Bytes>,
+}
+
+impl ZsetOp {
+    pub fn new(kind: ZsetOpKind, keys: Vec<Bytes>) -> Self {
+        ZsetOp {
+            kind,
+            keys,
+            weights: None,
+            aggregate: Aggregate::Sum,
+            with_scores: false,
+            destination: None,
+        }
+    }
+
+    /// Multiply the scores of each input by its weight, one per key.
+    pub fn weights(mut self, weights: Vec<f64>) -> Self {
+        self.weights = Some(weights);
+        self
+    }
+
+    pub fn aggregate(mut self, aggregate: Aggregate) -> Self {
+        self.aggregate = aggregate;
+        self
+    }
+
+    pub fn with_scores(mut self, with_scores: bool) -> Self {
+        self.with_scores = with_scores;
+        self
+    }
+
+    /// Store the result at `destination`, replacing whatever is there, instead of replying
+    /// with it.
+    pub fn store(mut self, destination: Bytes) -> Self {
+        self.destination = Some(destination);
+        self
+    }
+
+    pub fn name(&self) -> &'static str {
+        match (self.kind, self.destination.is_some()) {
+            (ZsetOpKind::Union, false) => "zunion",
+            (ZsetOpKind::Inter, false) => "zinter",
+            (ZsetOpKind::Diff, false) => "zdiff",
+            (ZsetOpKind::Union, true) => "zunionstore",
+            (ZsetOpKind::Inter, true) => "zinterstore",
+            (ZsetOpKind::Diff, true) => "zdiffstore",
+        }
+    }
+
+    pub fn response(&self, db: &Db) -> RESPType {
+        let mut keys = self.keys.clone();
+        keys.extend(self.destination.clone());
+        let mut locked = db.lock(&keys);
+
+        let inputs: crate::Result<Vec<_>> = self
+            .keys
+            .iter()
+            .map(|key| scores(&mut locked, key))
+            .collect();
+        let inputs = match inputs {
+            Err(e) => return e.into(),
+            Ok(inputs) => inputs,
+        };
+
+        let mut result = ZSet::default();
+        for (member, score) in self.combine(inputs) {
+            result.insert(member, score);
+        }
+
+        match &self.destination {
+            None => {
+                let mut arr = vec![];
+                for (member, score) in result.range((0, result.len()), false, 0, None) {
+                    arr.push(RESPType::Bulk(member));
+                    if self.with_scores {
+                        arr.push(RESPType::Double(score));
+                    }
+                }
+                RESPType::Array(arr)
+            }
+            Some(destination) => {
+                let len = result.len();
+                locked.update(destination, |slot| {
+                    *slot = Some(Entry::new(Value::ZSet(result)));
+                });
+                RESPType::Integer(len as i64)
+            }
+        }
+    }
+
+    fn combine(&self, inputs: Vec<HashMap<Bytes, f64>>) -> HashMap<Bytes, f64> {
+        let mut inputs = inputs.into_iter().enumerate().map(|(idx, mut input)| {
+            let weight = self.weights.as_ref().map_or(1.0, |weights| weights[idx]);
+            for score in input.values_mut() {
+                *score = weighted(*score, weight);
+            }
+            input
+        });
+        let mut result = inputs.next().unwrap_or_default();
+
+        for input in inputs {
+            match self.kind {
+                ZsetOpKind::Union => {
+                    for (member, score) in input {
+                        result
+                            .entry(member)
+                            .and_modify(|current| *current = self.aggregate.apply(*current, score))
+                            .or_insert(score);
+                    }
+                }
+                ZsetOpKind::Inter => result.retain(|member, current| match input.get(member) {
+                    None => false,
+                    Some(score) => {
+                        *current = self.aggregate.apply(*current, *score);
+                        true
+                    }
+                }),
+                ZsetOpKind::Diff => result.retain(|member, _| !input.contains_key(member)),
+            }
+        }
+        result
+    }
+}
+
+fn weighted(score: f64, weight: f64) -> f64 {
+    let score = score * weight;
+    // an infinite score weighted by 0 counts as 0
+    if score.is_nan() {
+        0.0
+    } else {
+        score
+    }
+}
+
+/// Members and scores of the sorted set or set at `key`, which must be locked.
+fn scores(locked: &mut Locked<'_>, key: &[u8]) -> crate::Result<HashMap<Bytes, f64>> {
+    locked.update(key, |slot| match slot.as_ref().map(|entry| &entry.value) {
+        None => Ok(HashMap::new()),
+        Some(Value::ZSet(zset)) => Ok(zset
+            .iter()
+            .map(|(member, score)| (member.clone(), score))
+            .collect()),
+        Some(Value::Set(set)) => Ok(set.iter().map(|member| (member, 1.0)).collect()),
+        Some(_) => Err(Error::WrongType),
+    })
+}
+
+impl From<ZsetOp> for RESPType {
+    fn from(zsetop: ZsetOp) -> RESPType {
+        let mut arr = vec![RESPType::Bulk(Bytes::from(zsetop.name()))];
+        if let Some(destination) = zsetop.destination {
+            arr.push(RESPType::Bulk(destination));
+        }
+        arr.push(RESPType::Bulk(Bytes::from(zsetop.keys.len().to_string())));
+        arr.extend(zsetop.keys.into_iter().map(RESPType::Bulk));
+        if let Some(weights) = zsetop.weights {
+            arr.push(RESPType::Bulk(Bytes::from("WEIGHTS")));
+            arr.extend(
+                weights
+                    .into_iter()
+                    .map(|weight| RESPType::Bulk(Bytes::from(format_float(weight)))),
+            );
+        }
+        if zsetop.aggregate != Aggregate::Sum {
+            arr.push(RESPType::Bulk(Bytes::from("AGGREGATE")));
+            arr.push(RESPType::Bulk(Bytes::from(zsetop.aggregate.name())));
+        }
+        if zsetop.with_scores {
+            arr.push(RESPType::Bulk(Bytes::from("WITHSCORES")));
+        }
+
+        RESPType::Array(arr)
+    }
+}
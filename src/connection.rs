@@ -1,50 +1,154 @@
-use bytes::{Buf, BytesMut};
-use std::io::Cursor;
+use bytes::{Bytes, BytesMut};
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::io::AsyncReadExt;
 use tokio::io::AsyncWriteExt;
 use tokio::net::TcpStream;
 
 use crate::resp::*;
+use crate::Error;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
 
 pub struct Connection {
     socket: TcpStream,
     buffer: BytesMut,
+    parser: RESPParser,
+    // reused between writes so replies are serialized without allocating
+    write_buffer: BytesMut,
+    id: u64,
+    name: Option<Bytes>,
+    protocol: Protocol,
 }
 
 impl Connection {
     pub fn new(socket: TcpStream) -> Self {
+        Self::with_limits(socket, ProtocolLimits::default())
+    }
+
+    /// Creates a connection that rejects frames from the peer exceeding `limits`.
+    pub fn with_limits(socket: TcpStream, limits: ProtocolLimits) -> Self {
         Connection {
-            socket: socket,
+            socket,
             buffer: BytesMut::with_capacity(4096),
+            parser: RESPParser::with_limits(limits),
+            write_buffer: BytesMut::with_capacity(4096),
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            name: None,
+            protocol: Protocol::default(),
         }
     }
 
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn name(&self) -> Option<&Bytes> {
+        self.name.as_ref()
+    }
+
+    pub fn set_name(&mut self, name: Option<Bytes>) {
+        self.name = name;
+    }
+
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    /// Changes how frames written from now on are serialized.
+    pub fn set_protocol(&mut self, protocol: Protocol) {
+        self.protocol = protocol;
+    }
+
     pub async fn read_frame(&mut self) -> crate::Result<Option<RESPType>> {
         loop {
-            let mut buf = Cursor::new(&self.buffer[..]);
-            if let Some(resp) = RESPParser::parse(&mut buf)? {
-                self.buffer.advance(buf.position() as usize);
+            if let Some(resp) = self.parser.parse(&mut self.buffer)? {
                 return Ok(Some(resp));
             }
 
+            if self.buffer.len() > self.parser.limits().max_query_buffer {
+                return Err(Error::protocol("query buffer limit exceeded"));
+            }
+
             if 0 == self.socket.read_buf(&mut self.buffer).await? {
                 if self.buffer.is_empty() {
                     // graceful shutdown
                     return Ok(None);
                 } else {
                     // connection shutdown in middle of sending frame
-                    return Err("Connection reset by peer".into());
+                    return Err(io::Error::from(io::ErrorKind::ConnectionReset).into());
                 }
             }
         }
     }
 
+    /// Resolves once the peer hangs up. Whatever it sends meanwhile is kept for the next
+    /// `read_frame`, so a blocked client can be watched for going away without losing the
+    /// commands it pipelined behind the blocking one.
+    pub async fn closed(&mut self) -> crate::Result<()> {
+        loop {
+            if self.buffer.len() > self.parser.limits().max_query_buffer {
+                return Err(Error::protocol("query buffer limit exceeded"));
+            }
+
+            if 0 == self.socket.read_buf(&mut self.buffer).await? {
+                return Ok(());
+            }
+        }
+    }
+
     pub async fn write_frame(&mut self, frame: &RESPType) -> crate::Result<()> {
-        let res = match RESPSerializer::serialize(frame) {
-            Ok(val) => val,
-            Err(_) => return Err("incorrect input".into()),
+        RESPSerializer::serialize_into(frame, self.protocol, &mut self.write_buffer)?;
+        let res = self.socket.write_all(&self.write_buffer).await;
+        self.write_buffer.clear();
+        Ok(res?)
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::Command;
+    use crate::db::{Entry, Value};
+    use crate::Db;
+    use std::time::Duration;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_closed_while_blocked() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let mut connection = Connection::new(listener.accept().await.unwrap().0);
+        let db = Db::new(2);
+
+        let blocked = {
+            let db = db.clone();
+            tokio::spawn(async move {
+                let frame = connection.read_frame().await.unwrap().unwrap();
+                let Ok(Command::Bpop(bpop)) = frame.try_into() else {
+                    panic!("not a blocking pop");
+                };
+                tokio::select! {
+                    response = bpop.response(&db) => Some(response),
+                    _ = connection.closed() => None,
+                }
+            })
         };
-        self.socket.write_all(&res).await?;
-        Ok(())
+        client
+            .write_all(b"*3\r\n$5\r\nBLPOP\r\n$4\r\nlist\r\n$1\r\n0\r\n")
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        // the client hanging up gives up its place in line instead of popping the next push
+        drop(client);
+        assert_eq!(blocked.await.unwrap(), None);
+        db.update(b"list", |slot| {
+            *slot = Some(Entry::new(Value::List([Bytes::from("a")].into())));
+        });
+        assert!(db.exists(b"list"));
     }
 }
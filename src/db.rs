@@ -0,0 +1,1226 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::hash::{Hash as _, Hasher};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bytes::Bytes;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+use crate::{random, Error};
+
+mod hash;
+pub(crate) use hash::Hash;
+
+mod set;
+pub(crate) use set::Set;
+
+mod zset;
+pub(crate) use zset::ZSet;
+pub use zset::{LexBound, ScoreBound};
+
+mod hll;
+pub(crate) use hll::{cache_count, Hll};
+
+mod stream;
+pub(crate) use stream::{Fields, Stream};
+pub use stream::{StreamId, TrimStrategy};
+
+mod evict;
+pub use evict::EvictionPolicy;
+use evict::{Memory, LFU_INIT_VAL};
+
+mod table;
+pub(crate) use table::{Table, POSITION_BITS};
+
+const DEFAULT_SHARDS: usize = 25;
+
+/// Databases SELECT can pick from unless configured otherwise, like redis.
+const DEFAULT_DATABASES: usize = 16;
+
+/// Values made of more allocations than this are dropped on a background thread when
+/// freed lazily, matching redis' lazyfree threshold.
+const LAZYFREE_THRESHOLD: usize = 64;
+
+/// Bytes an entry is accounted for on top of its key and value, about what redis spends on
+/// the dictionary entry and the object header.
+const ENTRY_OVERHEAD: usize = 56;
+
+/// Bytes accounted for every element of an aggregate on top of its data.
+const ELEMENT_OVERHEAD: usize = 16;
+
+/// Elements sampled to estimate the size of an aggregate, like MEMORY USAGE does.
+const SIZE_SAMPLES: usize = 5;
+
+/// Largest string value a command may create, matching redis' proto-max-bulk-len default.
+pub(crate) const MAX_STRING_LEN: usize = 512 * 1024 * 1024;
+
+/// A stored value together with its absolute expiry as unix time in milliseconds.
+#[derive(Debug, Clone)]
+pub(crate) struct Entry {
+    pub(crate) value: Value,
+    pub(crate) expires_at: Option<u64>,
+    // unix time in milliseconds of the last access and the logarithmic access counter,
+    // which rank keys for eviction
+    accessed_at: u64,
+    freq: u8,
+    // bytes the entry is accounted for in the used memory
+    size: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    String(Bytes),
+    List(VecDeque<Bytes>),
+    Hash(Hash),
+    Set(Set),
+    ZSet(ZSet),
+    Stream(Stream),
+}
+
+impl Value {
+    fn is_empty_aggregate(&self) -> bool {
+        match self {
+            Value::String(_) => false,
+            Value::List(list) => list.is_empty(),
+            Value::Hash(hash) => hash.is_empty(),
+            Value::Set(set) => set.is_empty(),
+            Value::ZSet(zset) => zset.is_empty(),
+            // streams outlive their entries, consumer groups may still be reading them
+            Value::Stream(_) => false,
+        }
+    }
+
+    /// The type name TYPE replies with.
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::List(_) => "list",
+            Value::Hash(_) => "hash",
+            Value::Set(_) => "set",
+            Value::ZSet(_) => "zset",
+            Value::Stream(_) => "stream",
+        }
+    }
+
+    /// Approximate bytes the value takes. Aggregates are estimated from a few sampled
+    /// elements, so accounting stays cheap however large they grow.
+    fn estimated_size(&self) -> usize {
+        fn sampled(len: usize, sizes: impl Iterator<Item = usize>) -> usize {
+            let (count, total) = sizes
+                .take(SIZE_SAMPLES)
+                .fold((0, 0), |(count, total), size| (count + 1, total + size));
+            match count {
+                0 => 0,
+                count => len * (total / count + ELEMENT_OVERHEAD),
+            }
+        }
+
+        match self {
+            Value::String(value) => value.len(),
+            Value::List(list) => sampled(list.len(), list.iter().map(Bytes::len)),
+            Value::Hash(hash) => sampled(
+                hash.len(),
+                hash.iter().map(|(field, value)| field.len() + value.len()),
+            ),
+            Value::Set(set) => sampled(set.len(), set.iter().map(|member| member.len())),
+            Value::ZSet(zset) => {
+                sampled(zset.len(), zset.iter().map(|(member, _)| member.len() + 8))
+            }
+            // consumer groups are left out, they are small next to the entries
+            Value::Stream(stream) => sampled(
+                stream.len(),
+                stream.iter().map(|(_, fields)| {
+                    16 + fields
+                        .iter()
+                        .map(|(field, value)| field.len() + value.len())
+                        .sum::<usize>()
+                }),
+            ),
+        }
+    }
+
+    /// Roughly how many allocations dropping the value frees.
+    fn free_effort(&self) -> usize {
+        match self {
+            Value::String(_) => 1,
+            Value::List(list) => list.len(),
+            Value::Hash(hash) => hash.len(),
+            Value::Set(set) => set.len(),
+            Value::ZSet(zset) => zset.len(),
+            Value::Stream(stream) => stream.len(),
+        }
+    }
+}
+
+impl Entry {
+    pub(crate) fn new(value: Value) -> Self {
+        Entry {
+            value,
+            expires_at: None,
+            accessed_at: now_ms(),
+            freq: LFU_INIT_VAL,
+            size: 0,
+        }
+    }
+
+    /// Expiry of the field that expires first, if this is a hash with a field ttl.
+    pub(crate) fn next_field_expiry(&self) -> Option<u64> {
+        match &self.value {
+            Value::Hash(hash) => hash.next_expiry(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn string(&self) -> crate::Result<&Bytes> {
+        match &self.value {
+            Value::String(value) => Ok(value),
+            _ => Err(Error::WrongType),
+        }
+    }
+
+    pub(crate) fn string_mut(&mut self) -> crate::Result<&mut Bytes> {
+        match &mut self.value {
+            Value::String(value) => Ok(value),
+            _ => Err(Error::WrongType),
+        }
+    }
+
+    pub(crate) fn list(&self) -> crate::Result<&VecDeque<Bytes>> {
+        match &self.value {
+            Value::List(list) => Ok(list),
+            _ => Err(Error::WrongType),
+        }
+    }
+
+    pub(crate) fn list_mut(&mut self) -> crate::Result<&mut VecDeque<Bytes>> {
+        match &mut self.value {
+            Value::List(list) => Ok(list),
+            _ => Err(Error::WrongType),
+        }
+    }
+
+    pub(crate) fn hash(&self) -> crate::Result<&Hash> {
+        match &self.value {
+            Value::Hash(hash) => Ok(hash),
+            _ => Err(Error::WrongType),
+        }
+    }
+
+    pub(crate) fn hash_mut(&mut self) -> crate::Result<&mut Hash> {
+        match &mut self.value {
+            Value::Hash(hash) => Ok(hash),
+            _ => Err(Error::WrongType),
+        }
+    }
+
+    pub(crate) fn set(&self) -> crate::Result<&Set> {
+        match &self.value {
+            Value::Set(set) => Ok(set),
+            _ => Err(Error::WrongType),
+        }
+    }
+
+    pub(crate) fn set_mut(&mut self) -> crate::Result<&mut Set> {
+        match &mut self.value {
+            Value::Set(set) => Ok(set),
+            _ => Err(Error::WrongType),
+        }
+    }
+
+    pub(crate) fn zset(&self) -> crate::Result<&ZSet> {
+        match &self.value {
+            Value::ZSet(zset) => Ok(zset),
+            _ => Err(Error::WrongType),
+        }
+    }
+
+    pub(crate) fn zset_mut(&mut self) -> crate::Result<&mut ZSet> {
+        match &mut self.value {
+            Value::ZSet(zset) => Ok(zset),
+            _ => Err(Error::WrongType),
+        }
+    }
+
+    pub(crate) fn stream(&self) -> crate::Result<&Stream> {
+        match &self.value {
+            Value::Stream(stream) => Ok(stream),
+            _ => Err(Error::WrongType),
+        }
+    }
+
+    pub(crate) fn stream_mut(&mut self) -> crate::Result<&mut Stream> {
+        match &mut self.value {
+            Value::Stream(stream) => Ok(stream),
+            _ => Err(Error::WrongType),
+        }
+    }
+}
+
+struct Shard {
+    entries: Table<Entry>,
+    // accounted bytes of the entries, part of the total in `memory`
+    used: usize,
+    memory: Arc<Memory>,
+    // keys ordered by expiry so the sweeper never scans persistent keys
+    expirations: BTreeSet<(u64, Bytes)>,
+    // the same keys again, for the volatile eviction policies to sample at random
+    volatile: Table<()>,
+    // hashes with a field ttl ordered by the earliest one, so the sweeper purges fields
+    // nobody reads again
+    field_expirations: BTreeSet<(u64, Bytes)>,
+    // clients blocked on a key, in the order they blocked
+    waiters: HashMap<Bytes, VecDeque<Arc<Notify>>>,
+}
+
+/// Handle on one of the numbered databases shared by every connection, each split into
+/// independently locked shards. Clones share the data, [`Db::select`] switches database.
+#[derive(Clone)]
+pub struct Db {
+    dbs: Arc<Vec<Vec<Mutex<Shard>>>>,
+    memory: Arc<Memory>,
+    index: usize,
+}
+
+// public functions
+impl Db {
+    /// The default 16 databases of `num_shards` shards each, with database 0 selected.
+    pub fn new(num_shards: usize) -> Self {
+        Db::with_databases(DEFAULT_DATABASES, num_shards)
+    }
+
+    pub fn with_databases(databases: usize, num_shards: usize) -> Self {
+        assert!(databases > 0, "there must be at least one database");
+        assert!(num_shards > 0, "db needs at least one shard");
+
+        let memory = Arc::new(Memory::default());
+        let mut dbs = Vec::with_capacity(databases);
+        for _ in 0..databases {
+            let mut shards = Vec::with_capacity(num_shards);
+            for _ in 0..num_shards {
+                shards.push(Mutex::new(Shard::new(memory.clone())));
+            }
+            dbs.push(shards);
+        }
+
+        Db {
+            dbs: Arc::new(dbs),
+            memory,
+            index: 0,
+        }
+    }
+
+    /// Index of the selected database.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn databases(&self) -> usize {
+        self.dbs.len()
+    }
+
+    /// A handle on database `index` sharing the same data, `None` if there is no such database.
+    pub fn select(&self, index: usize) -> Option<Db> {
+        (index < self.dbs.len()).then(|| Db {
+            dbs: self.dbs.clone(),
+            memory: self.memory.clone(),
+            index,
+        })
+    }
+
+    /// Returns the string stored at `key`, failing if the key holds another type.
+    pub fn get(&self, key: &[u8]) -> crate::Result<Option<Bytes>> {
+        self.update(key, |slot| {
+            slot.as_ref()
+                .map(|entry| entry.string().cloned())
+                .transpose()
+        })
+    }
+
+    /// Stores the string `value` under `key` without an expiry, replacing any value of any type.
+    pub fn set(&self, key: Bytes, value: Bytes) {
+        self.update(&key, |slot| *slot = Some(Entry::new(Value::String(value))));
+    }
+
+    pub fn delete(&self, key: &[u8]) -> bool {
+        self.update(key, |slot| slot.take().is_some())
+    }
+
+    /// Whether `key` exists, without counting as an access to it.
+    pub fn exists(&self, key: &[u8]) -> bool {
+        self.peek(key, |entry| entry.is_some())
+    }
+
+    /// Number of keys, including expired ones the sweeper hasn't removed yet.
+    pub fn len(&self) -> usize {
+        self.shards()
+            .iter()
+            .map(|shard| shard.lock().unwrap().entries.len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes every key. With `lazy` the memory is released on a background thread.
+    pub fn flush(&self, lazy: bool) {
+        let mut flushed = Vec::with_capacity(self.shards().len());
+        for shard in self.shards().iter() {
+            let mut shard = shard.lock().unwrap();
+            shard.expirations.clear();
+            shard.volatile = Table::default();
+            shard.field_expirations.clear();
+            shard.memory.account(shard.used, 0);
+            shard.used = 0;
+            flushed.push(std::mem::take(&mut shard.entries));
+        }
+        if lazy {
+            std::thread::spawn(move || drop(flushed));
+        }
+    }
+
+    /// A random live key, `None` if the db is empty. Only the shard the key is picked from is
+    /// locked, so keys sharing a shard with more keys are somewhat less likely to come up.
+    pub fn random_key(&self) -> Option<Bytes> {
+        let shards = self.shards();
+        let start = random::below(shards.len());
+        let mut offset = 0;
+        while offset < shards.len() {
+            let mut shard = shards[(start + offset) % shards.len()].lock().unwrap();
+            let key = match shard.entries.random() {
+                None => {
+                    offset += 1;
+                    continue;
+                }
+                Some((key, _)) => key.clone(),
+            };
+
+            // going through update removes the key instead if it has expired
+            if shard.update(&key, now_ms(), false, |slot| slot.is_some()) {
+                return Some(key);
+            }
+        }
+        None
+    }
+
+    /// Exchanges the data of this database with `other`. Clients blocked on a key stay with
+    /// their database, those whose key now exists there are woken.
+    pub fn swap(&self, other: &Db) {
+        if self.index == other.index {
+            return;
+        }
+        // databases are locked in ascending order, like the shards within one
+        let (first, second) = match self.index < other.index {
+            true => (self, other),
+            false => (other, self),
+        };
+        let mut firsts: Vec<_> = first.shards().iter().map(|s| s.lock().unwrap()).collect();
+        let mut seconds: Vec<_> = second.shards().iter().map(|s| s.lock().unwrap()).collect();
+
+        for (a, b) in firsts.iter_mut().zip(seconds.iter_mut()) {
+            std::mem::swap(&mut a.entries, &mut b.entries);
+            std::mem::swap(&mut a.expirations, &mut b.expirations);
+            std::mem::swap(&mut a.volatile, &mut b.volatile);
+            std::mem::swap(&mut a.used, &mut b.used);
+            std::mem::swap(&mut a.field_expirations, &mut b.field_expirations);
+            for shard in [a, b] {
+                for key in shard.waiters.keys() {
+                    if shard.entries.contains_key(key) {
+                        shard.wake(key);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Counts INFO reports for this database.
+    pub fn stats(&self) -> KeyspaceStats {
+        let now = now_ms();
+        let mut stats = KeyspaceStats::default();
+        let mut ttl_sum = 0;
+        for shard in self.shards() {
+            let shard = shard.lock().unwrap();
+            stats.keys += shard.entries.len();
+            stats.expires += shard.expirations.len();
+            ttl_sum += shard
+                .expirations
+                .iter()
+                .map(|(when, _)| when.saturating_sub(now))
+                .sum::<u64>();
+            stats.subexpiry += shard.field_expirations.len();
+        }
+        if stats.expires > 0 {
+            stats.avg_ttl = ttl_sum / stats.expires as u64;
+        }
+        stats
+    }
+
+    /// Sets the absolute expiry of `key` in unix milliseconds, `None` makes it persistent.
+    /// Returns false if the key does not exist.
+    pub fn expire_at(&self, key: &[u8], expires_at: Option<u64>) -> bool {
+        self.update(key, |slot| match slot {
+            None => false,
+            Some(entry) => {
+                entry.expires_at = expires_at;
+                true
+            }
+        })
+    }
+
+    /// Returns `None` if the key does not exist, otherwise its absolute expiry.
+    pub fn expires_at(&self, key: &[u8]) -> Option<Option<u64>> {
+        self.peek(key, |entry| entry.map(|entry| entry.expires_at))
+    }
+
+    /// Removes every key whose expiry has passed, and every hash field, returning how many
+    /// keys were removed. Hashes go with their last field.
+    pub fn purge_expired(&self) -> usize {
+        let now = now_ms();
+        let mut purged = 0;
+
+        for shard in self.shards().iter() {
+            let mut shard = shard.lock().unwrap();
+            while let Some((when, key)) = shard.expirations.first().cloned() {
+                if when > now {
+                    break;
+                }
+                shard.expirations.pop_first();
+                shard.volatile.remove(&key);
+                if let Some(entry) = shard.entries.remove(&key) {
+                    shard.memory.account(entry.size, 0);
+                    shard.used -= entry.size;
+                    if let Some(when) = entry.next_field_expiry() {
+                        shard.field_expirations.remove(&(when, key));
+                    }
+                }
+                purged += 1;
+            }
+
+            // the slot update purges the fields, and the hash with them once none is left
+            while let Some((when, key)) = shard.field_expirations.first().cloned() {
+                if when > now {
+                    break;
+                }
+                shard.field_expirations.pop_first();
+                if shard.update(&key, now, false, |slot| slot.is_none()) {
+                    purged += 1;
+                }
+            }
+        }
+
+        purged
+    }
+
+    /// Spawns the active expiry sweeper of every database, it stops once every handle to the
+    /// db is dropped.
+    pub fn start_active_expiry(&self, period: Duration) -> JoinHandle<()> {
+        let dbs = Arc::downgrade(&self.dbs);
+        let memory = self.memory.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                let dbs = match Weak::upgrade(&dbs) {
+                    None => return,
+                    Some(dbs) => dbs,
+                };
+                for index in 0..dbs.len() {
+                    let dbs = dbs.clone();
+                    let memory = memory.clone();
+                    Db { dbs, memory, index }.purge_expired();
+                }
+            }
+        })
+    }
+}
+
+impl Default for Db {
+    fn default() -> Self {
+        Db::new(DEFAULT_SHARDS)
+    }
+}
+
+/// Per database figures of INFO keyspace.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KeyspaceStats {
+    /// Keys, including expired ones the sweeper hasn't removed yet.
+    pub keys: usize,
+    /// Keys with a ttl.
+    pub expires: usize,
+    /// Average remaining ttl in milliseconds of the keys with one.
+    pub avg_ttl: u64,
+    /// Hashes with fields that have a ttl.
+    pub subexpiry: usize,
+}
+
+/// Registration of a blocked client in the waiter queues of its keys, removed on drop.
+struct Watch<'a> {
+    db: &'a Db,
+    keys: Vec<Bytes>,
+    notify: Arc<Notify>,
+}
+
+/// Shard locks held together so a command can touch several keys atomically.
+pub(crate) struct Locked<'a> {
+    db: &'a Db,
+    shards: Vec<(usize, MutexGuard<'a, Shard>)>,
+}
+
+// crate functions
+impl Db {
+    /// Runs `f` on the slot for `key` while holding its shard lock, expired entries are
+    /// removed beforehand so `f` only ever sees live data.
+    pub(crate) fn update<R, F>(&self, key: &[u8], f: F) -> R
+    where
+        F: FnOnce(&mut Option<Entry>) -> R,
+    {
+        let idx = self.shard_index(key);
+        self.shards()[idx]
+            .lock()
+            .unwrap()
+            .update(key, now_ms(), true, f)
+    }
+
+    /// Same as [`Db::update`] but read only, and without counting as an access for eviction.
+    pub(crate) fn peek<R, F>(&self, key: &[u8], f: F) -> R
+    where
+        F: FnOnce(Option<&Entry>) -> R,
+    {
+        let idx = self.shard_index(key);
+        self.shards()[idx]
+            .lock()
+            .unwrap()
+            .update(key, now_ms(), false, |slot| f(slot.as_ref()))
+    }
+
+    /// Locks every shard holding one of `keys`, so all of them can be read and written as a
+    /// single atomic step.
+    pub(crate) fn lock<K: AsRef<[u8]>>(&self, keys: &[K]) -> Locked<'_> {
+        let mut indexes: Vec<_> = keys
+            .iter()
+            .map(|key| self.shard_index(key.as_ref()))
+            .collect();
+        indexes.sort_unstable();
+        indexes.dedup();
+
+        // every multi key command locks in ascending shard order, so two of them can never
+        // wait on each other
+        let shards = indexes
+            .into_iter()
+            .map(|idx| (idx, self.shards()[idx].lock().unwrap()))
+            .collect();
+        Locked { db: self, shards }
+    }
+
+    /// Runs `f` on the slot for `key` here and the one for `other_key` in the `other`
+    /// database as a single atomic step. The databases must differ, keys of the same one are
+    /// locked together with [`Db::lock`].
+    pub(crate) fn update_across<R, F>(&self, key: &[u8], other: &Db, other_key: &[u8], f: F) -> R
+    where
+        F: FnOnce(&mut Option<Entry>, &mut Option<Entry>) -> R,
+    {
+        assert_ne!(
+            self.index, other.index,
+            "both keys are in the same database"
+        );
+        let here = &self.shards()[self.shard_index(key)];
+        let there = &other.shards()[other.shard_index(other_key)];
+        // databases are locked in ascending order, like the shards within one
+        let (mut here, mut there) = match self.index < other.index {
+            true => {
+                let here = here.lock().unwrap();
+                (here, there.lock().unwrap())
+            }
+            false => {
+                let there = there.lock().unwrap();
+                (here.lock().unwrap(), there)
+            }
+        };
+
+        let now = now_ms();
+        here.update(key, now, true, |slot| {
+            there.update(other_key, now, true, |other_slot| f(slot, other_slot))
+        })
+    }
+
+    /// Number of shards, which the keyspace commands walk one at a time.
+    pub(crate) fn shard_count(&self) -> usize {
+        self.shards().len()
+    }
+
+    /// Runs `f` on the unexpired keys of shard `idx` and their entries, holding only that
+    /// shard's lock so the rest of the keyspace stays available.
+    pub(crate) fn scan_shard<R, F>(&self, idx: usize, f: F) -> R
+    where
+        F: FnOnce(&mut dyn Iterator<Item = (&Bytes, &Entry)>) -> R,
+    {
+        let shard = self.shards()[idx].lock().unwrap();
+        let now = now_ms();
+        let mut entries = shard
+            .entries
+            .iter()
+            .filter(|(_, entry)| !matches!(entry.expires_at, Some(when) if when <= now));
+        f(&mut entries)
+    }
+
+    /// Runs `f` on about `count` keys of shard `idx` from `cursor` on in scan order, holding
+    /// only that shard's lock. Returns the cursor to continue the shard from, 0 once it is
+    /// done. Expired keys are left out but still count towards the page.
+    pub(crate) fn scan_shard_page<F>(&self, idx: usize, cursor: u64, count: usize, mut f: F) -> u64
+    where
+        F: FnMut(&Bytes, &Entry),
+    {
+        let shard = self.shards()[idx].lock().unwrap();
+        let now = now_ms();
+        let (next, page) = shard.entries.scan(cursor, count);
+        for (key, entry) in page {
+            if !matches!(entry.expires_at, Some(when) if when <= now) {
+                f(key, entry);
+            }
+        }
+        next
+    }
+
+    /// Runs `attempt` until it yields a value, parking between attempts until one of `keys`
+    /// is created. `None` once `timeout` elapses, no timeout blocks forever.
+    ///
+    /// Clients blocked on the same key are woken one at a time in the order they blocked.
+    /// Errors from the first attempt are reported as is. Later on only keys holding the awaited
+    /// type serve the client, so a WRONGTYPE keeps it waiting while other errors unblock it.
+    pub(crate) async fn block<T, F>(
+        &self,
+        keys: &[Bytes],
+        timeout: Option<Duration>,
+        mut attempt: F,
+    ) -> crate::Result<Option<T>>
+    where
+        F: FnMut() -> crate::Result<Option<T>>,
+    {
+        // registering before the first attempt means no push can slip in unnoticed
+        let watch = self.watch(keys);
+        if let Some(value) = attempt()? {
+            return Ok(Some(value));
+        }
+
+        let deadline = timeout.map(|timeout| tokio::time::Instant::now() + timeout);
+        loop {
+            let notified = watch.notify.notified();
+            match deadline {
+                None => notified.await,
+                Some(deadline) => {
+                    if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                        return Ok(None);
+                    }
+                }
+            }
+
+            match attempt() {
+                Ok(Some(value)) => return Ok(Some(value)),
+                Ok(None) | Err(Error::WrongType) => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Wakes every client blocked on `key`. Keys that grow without being created, like a
+    /// stream whose readers can all be served the same new entry, need this on top of the
+    /// wake up on creation.
+    pub(crate) fn wake_all(&self, key: &[u8]) {
+        let shard = self.shards()[self.shard_index(key)].lock().unwrap();
+        for notify in shard.waiters.get(key).into_iter().flatten() {
+            notify.notify_one();
+        }
+    }
+
+    fn watch(&self, keys: &[Bytes]) -> Watch<'_> {
+        let mut keys = keys.to_vec();
+        keys.sort_unstable();
+        keys.dedup();
+
+        let notify = Arc::new(Notify::new());
+        for key in &keys {
+            let mut shard = self.shards()[self.shard_index(key)].lock().unwrap();
+            shard
+                .waiters
+                .entry(key.clone())
+                .or_default()
+                .push_back(notify.clone());
+        }
+        Watch {
+            db: self,
+            keys,
+            notify,
+        }
+    }
+}
+
+impl Drop for Watch<'_> {
+    fn drop(&mut self) {
+        for key in &self.keys {
+            let mut shard = self.db.shards()[self.db.shard_index(key)].lock().unwrap();
+            let queue = match shard.waiters.get_mut(key) {
+                None => continue,
+                Some(queue) => queue,
+            };
+            let position = queue
+                .iter()
+                .position(|notify| Arc::ptr_eq(notify, &self.notify));
+            if let Some(position) = position {
+                queue.remove(position);
+            }
+            if queue.is_empty() {
+                shard.waiters.remove(key);
+                continue;
+            }
+
+            // the head may have been woken for data it left behind, or it may have timed out
+            // right after being woken, either way the next client gets its turn
+            if position == Some(0) && shard.entries.contains_key(key) {
+                shard.wake(key);
+            }
+        }
+    }
+}
+
+impl Locked<'_> {
+    /// Same as [`Db::update`], `key` must be one of the keys the shards were locked for.
+    pub(crate) fn update<R, F>(&mut self, key: &[u8], f: F) -> R
+    where
+        F: FnOnce(&mut Option<Entry>) -> R,
+    {
+        let idx = self.db.shard_index(key);
+        let shard = match self.shards.iter_mut().find(|(locked, _)| *locked == idx) {
+            Some((_, shard)) => shard,
+            None => panic!("shard of key was not locked"),
+        };
+        shard.update(key, now_ms(), true, f)
+    }
+}
+
+impl Shard {
+    fn new(memory: Arc<Memory>) -> Self {
+        Shard {
+            entries: Table::default(),
+            used: 0,
+            memory,
+            expirations: BTreeSet::new(),
+            volatile: Table::default(),
+            field_expirations: BTreeSet::new(),
+            waiters: HashMap::new(),
+        }
+    }
+
+    /// Runs `f` on the slot for `key`, which counts as an access to it if `touch` is set.
+    fn update<R, F>(&mut self, key: &[u8], now: u64, touch: bool, f: F) -> R
+    where
+        F: FnOnce(&mut Option<Entry>) -> R,
+    {
+        let Shard {
+            entries,
+            used,
+            memory,
+            expirations,
+            volatile,
+            field_expirations,
+            waiters,
+        } = self;
+        entries.update(key, |key, slot| {
+            let old_size = slot.as_ref().map_or(0, |entry| entry.size);
+            let old_field_expiry = slot.as_ref().and_then(Entry::next_field_expiry);
+            let old_expiry = slot.as_ref().and_then(|entry| entry.expires_at);
+            if matches!(old_expiry, Some(when) if when <= now) {
+                *slot = None;
+            }
+            // hash fields expire lazily too, taking the key with them once none is left
+            if let Some(Value::Hash(hash)) = slot.as_mut().map(|entry| &mut entry.value) {
+                hash.purge_expired(now);
+                if hash.is_empty() {
+                    *slot = None;
+                }
+            }
+            let created = slot.is_none();
+            if let (Some(entry), true) = (slot.as_mut(), touch) {
+                entry.touch(now);
+            }
+
+            let result = f(slot);
+
+            // aggregates are deleted together with their last element
+            if matches!(&slot, Some(entry) if entry.value.is_empty_aggregate()) {
+                *slot = None;
+            }
+
+            let new_expiry = slot.as_ref().and_then(|entry| entry.expires_at);
+            if old_expiry != new_expiry {
+                if let Some(when) = old_expiry {
+                    expirations.remove(&(when, key.clone()));
+                }
+                if let Some(when) = new_expiry {
+                    expirations.insert((when, key.clone()));
+                }
+                match new_expiry {
+                    None => volatile.remove(key),
+                    Some(_) => volatile.insert(key.clone(), ()),
+                };
+            }
+            // empty aggregates don't exist, so a blocked client can only be waiting for its
+            // key to be created
+            if created && slot.is_some() {
+                wake(waiters, key);
+            }
+
+            let new_size = match slot.as_mut() {
+                None => 0,
+                Some(entry) => {
+                    entry.size = ENTRY_OVERHEAD + key.len() + entry.value.estimated_size();
+                    entry.size
+                }
+            };
+            memory.account(old_size, new_size);
+            *used = *used + new_size - old_size;
+            let new_field_expiry = slot.as_ref().and_then(Entry::next_field_expiry);
+            if old_field_expiry != new_field_expiry {
+                if let Some(when) = old_field_expiry {
+                    field_expirations.remove(&(when, key.clone()));
+                }
+                if let Some(when) = new_field_expiry {
+                    field_expirations.insert((when, key.clone()));
+                }
+            }
+
+            result
+        })
+    }
+
+    /// Wakes the client that has been blocked on `key` the longest.
+    fn wake(&self, key: &[u8]) {
+        wake(&self.waiters, key);
+    }
+}
+
+/// [`Shard::wake`], for when the shard is borrowed field by field.
+fn wake(waiters: &HashMap<Bytes, VecDeque<Arc<Notify>>>, key: &[u8]) {
+    if let Some(notify) = waiters.get(key).and_then(VecDeque::front) {
+        notify.notify_one();
+    }
+}
+
+// private helper functions
+impl Db {
+    /// Shards of the selected database.
+    fn shards(&self) -> &[Mutex<Shard>] {
+        &self.dbs[self.index]
+    }
+
+    fn shard_index(&self, key: &[u8]) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        hasher.finish() as usize % self.shards().len()
+    }
+}
+
+/// Stores `value` in `slot`, an existing entry keeps its ttl.
+pub(crate) fn set_keep_ttl(slot: &mut Option<Entry>, value: Value) {
+    match slot {
+        Some(entry) => entry.value = value,
+        None => *slot = Some(Entry::new(value)),
+    }
+}
+
+/// Drops `values` on a background thread when freeing them would stall the caller,
+/// as UNLINK does.
+pub(crate) fn free_lazily(values: Vec<Value>) {
+    let effort: usize = values.iter().map(Value::free_effort).sum();
+    if effort > LAZYFREE_THRESHOLD {
+        std::thread::spawn(move || drop(values));
+    }
+}
+
+/// Current unix time in milliseconds.
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_get() {
+        let db = Db::new(4);
+        assert_eq!(db.get(b"key").unwrap(), None);
+        db.set(Bytes::from("key"), Bytes::from("value"));
+        assert_eq!(db.get(b"key").unwrap(), Some(Bytes::from("value")));
+        db.set(Bytes::from("key"), Bytes::from("other"));
+        assert_eq!(db.get(b"key").unwrap(), Some(Bytes::from("other")));
+    }
+
+    #[test]
+    fn test_delete_exists() {
+        let db = Db::default();
+        assert!(!db.exists(b"key"));
+        assert!(!db.delete(b"key"));
+        db.set(Bytes::from("key"), Bytes::from("value"));
+        assert!(db.exists(b"key"));
+        assert!(db.delete(b"key"));
+        assert!(!db.exists(b"key"));
+    }
+
+    #[test]
+    fn test_shared_between_clones() {
+        let db = Db::new(2);
+        let other = db.clone();
+        db.set(Bytes::from("key"), Bytes::from("value"));
+        assert_eq!(other.get(b"key").unwrap(), Some(Bytes::from("value")));
+    }
+
+    #[test]
+    fn test_lazy_expiry() {
+        let db = Db::new(2);
+        db.set(Bytes::from("key"), Bytes::from("value"));
+        assert!(db.expire_at(b"key", Some(now_ms() + 60_000)));
+        assert_eq!(db.get(b"key").unwrap(), Some(Bytes::from("value")));
+
+        assert!(db.expire_at(b"key", Some(now_ms() - 1)));
+        assert_eq!(db.get(b"key").unwrap(), None);
+        assert_eq!(db.expires_at(b"key"), None);
+        assert!(!db.expire_at(b"key", None));
+    }
+
+    #[test]
+    fn test_set_clears_expiry() {
+        let db = Db::new(2);
+        db.set(Bytes::from("key"), Bytes::from("value"));
+        let when = now_ms() + 60_000;
+        db.expire_at(b"key", Some(when));
+        assert_eq!(db.expires_at(b"key"), Some(Some(when)));
+        db.set(Bytes::from("key"), Bytes::from("other"));
+        assert_eq!(db.expires_at(b"key"), Some(None));
+    }
+
+    #[test]
+    fn test_types_and_empty_aggregates() {
+        let db = Db::new(2);
+        db.update(b"list", |slot| {
+            *slot = Some(Entry::new(Value::List(VecDeque::from([Bytes::from("a")]))))
+        });
+        assert!(matches!(db.get(b"list"), Err(Error::WrongType)));
+
+        db.update(b"list", |slot| {
+            slot.as_mut().unwrap().list_mut().unwrap().pop_front()
+        });
+        assert!(!db.exists(b"list"));
+    }
+
+    #[test]
+    fn test_lock_many() {
+        let db = Db::new(4);
+        let keys: Vec<_> = (0..16).map(|i| format!("key{}", i)).collect();
+        {
+            let mut locked = db.lock(&keys);
+            for key in &keys {
+                locked.update(key.as_bytes(), |slot| {
+                    *slot = Some(Entry::new(Value::String(Bytes::from("value"))))
+                });
+            }
+        }
+        assert!(keys.iter().all(|key| db.exists(key.as_bytes())));
+    }
+
+    #[test]
+    fn test_lock_order_does_not_deadlock() {
+        let db = Db::new(8);
+        let keys: Vec<_> = (0..32).map(|i| format!("key{}", i)).collect();
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let db = db.clone();
+                let mut keys = keys.clone();
+                if t % 2 == 1 {
+                    keys.reverse();
+                }
+                std::thread::spawn(move || {
+                    for _ in 0..200 {
+                        let mut locked = db.lock(&keys);
+                        locked.update(keys[0].as_bytes(), |slot| slot.take());
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_purge_expired() {
+        let db = Db::new(3);
+        for i in 0..10 {
+            db.set(Bytes::from(format!("key{}", i)), Bytes::from("value"));
+        }
+        for i in 0..4 {
+            db.expire_at(format!("key{}", i).as_bytes(), Some(now_ms() - 1));
+        }
+        db.expire_at(b"key9", Some(now_ms() + 60_000));
+
+        assert_eq!(db.purge_expired(), 4);
+        assert_eq!(db.purge_expired(), 0);
+        assert!(db.exists(b"key4"));
+        assert!(db.exists(b"key9"));
+
+        // hash fields expire even if nobody reads the hash again
+        for (key, fields) in [("gone", &["a"][..]), ("kept", &["a", "b"])] {
+            db.update(key.as_bytes(), |slot| {
+                let mut hash = Hash::default();
+                for field in fields {
+                    hash.insert(Bytes::from(*field), Bytes::from("value"));
+                }
+                hash.set_expiry(b"a", Some(now_ms() - 1));
+                *slot = Some(Entry::new(Value::Hash(hash)));
+            });
+        }
+        assert_eq!(db.stats().subexpiry, 2);
+        let used = db.used_memory();
+        assert_eq!(db.purge_expired(), 1);
+        assert_eq!(db.stats().subexpiry, 0);
+        assert_eq!(db.len(), 7);
+        assert!(db.used_memory() < used);
+        let shard = db.shards()[db.shard_index(b"kept")].lock().unwrap();
+        let kept = shard.entries.get(b"kept").unwrap().hash().unwrap();
+        assert_eq!(kept.len(), 1);
+    }
+
+    #[test]
+    fn test_random_key_skips_expired() {
+        let db = Db::new(3);
+        assert_eq!(db.random_key(), None);
+        for i in 0..10 {
+            db.set(Bytes::from(format!("key{}", i)), Bytes::from("value"));
+            db.expire_at(format!("key{}", i).as_bytes(), Some(now_ms() - 1));
+        }
+        db.set(Bytes::from("live"), Bytes::from("value"));
+
+        // every expired key sampled on the way is removed
+        for _ in 0..10 {
+            assert_eq!(db.random_key(), Some(Bytes::from("live")));
+        }
+        db.delete(b"live");
+        assert_eq!(db.random_key(), None);
+        assert_eq!(db.len(), 0);
+    }
+
+    fn pop(db: &Db, key: &[u8]) -> crate::Result<Option<Bytes>> {
+        db.update(key, |slot| match slot.as_mut() {
+            None => Ok(None),
+            Some(entry) => Ok(entry.list_mut()?.pop_front()),
+        })
+    }
+
+    fn push(db: &Db, key: &[u8], value: &'static str) {
+        db.update(key, |slot| {
+            let entry = slot.get_or_insert_with(|| Entry::new(Value::List(VecDeque::new())));
+            entry.list_mut().unwrap().push_back(Bytes::from(value));
+        });
+    }
+
+    #[tokio::test]
+    async fn test_block_times_out() {
+        let db = Db::new(2);
+        let keys = [Bytes::from("list")];
+        let timeout = Some(Duration::from_millis(20));
+        assert_eq!(
+            db.block(&keys, timeout, || pop(&db, b"list"))
+                .await
+                .unwrap(),
+            None
+        );
+        assert!(db
+            .shards()
+            .iter()
+            .all(|shard| shard.lock().unwrap().waiters.is_empty()));
+
+        db.set(Bytes::from("list"), Bytes::from("a"));
+        assert!(matches!(
+            db.block(&keys, timeout, || pop(&db, b"list")).await,
+            Err(Error::WrongType)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_block_wakes_in_order() {
+        let db = Db::new(2);
+        let mut waiters = vec![];
+        for _ in 0..3 {
+            let db = db.clone();
+            waiters.push(tokio::spawn(async move {
+                let keys = [Bytes::from("list")];
+                db.block(&keys, None, || pop(&db, b"list")).await.unwrap()
+            }));
+            // let each client block before the next one
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        push(&db, b"list", "a");
+        push(&db, b"list", "b");
+        push(&db, b"list", "c");
+        for (waiter, value) in waiters.into_iter().zip(["a", "b", "c"]) {
+            assert_eq!(waiter.await.unwrap(), Some(Bytes::from(value)));
+        }
+        assert!(db
+            .shards()
+            .iter()
+            .all(|shard| shard.lock().unwrap().waiters.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_swap_wakes_blocked() {
+        let db = Db::new(2);
+        let other = db.select(1).unwrap();
+        push(&other, b"list", "a");
+        assert!(db.select(16).is_none());
+
+        let waiter = {
+            let db = db.clone();
+            tokio::spawn(async move {
+                let keys = [Bytes::from("list")];
+                db.block(&keys, None, || pop(&db, b"list")).await.unwrap()
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        other.swap(&db);
+        assert_eq!(waiter.await.unwrap(), Some(Bytes::from("a")));
+        assert!(db.is_empty() && other.is_empty());
+    }
+
+    #[test]
+    fn test_memory_accounting() {
+        let db = Db::new(2);
+        assert_eq!(db.used_memory(), 0);
+        db.set(Bytes::from("key"), Bytes::from("value"));
+        let single = db.used_memory();
+        assert_eq!(single, ENTRY_OVERHEAD + 3 + 5);
+
+        // other databases count towards the same total
+        let other = db.select(1).unwrap();
+        for i in 0..100 {
+            push(&other, b"list", "item");
+            other.set(Bytes::from(format!("key:{i}")), Bytes::from("value"));
+        }
+        let list = ENTRY_OVERHEAD + 4 + 100 * (4 + ELEMENT_OVERHEAD);
+        assert!(db.used_memory() > single + list);
+        other.flush(false);
+        assert_eq!(db.used_memory(), single);
+
+        db.update(b"key", |slot| {
+            slot.as_mut().unwrap().expires_at = Some(now_ms() - 1);
+        });
+        assert_eq!(db.purge_expired(), 1);
+        assert_eq!(db.used_memory(), 0);
+    }
+}
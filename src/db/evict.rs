@@ -0,0 +1,264 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use bytes::Bytes;
+
+use super::{now_ms, Db, Entry, Shard};
+use crate::random;
+
+/// Shards sampled for an eviction candidate, and keys sampled within each, like redis'
+/// default maxmemory-samples.
+const EVICTION_SAMPLES: usize = 5;
+
+/// Counter new keys start with, so they get a chance to be used before being evicted.
+pub(super) const LFU_INIT_VAL: u8 = 5;
+
+/// How much harder each increment of the logarithmic access counter gets.
+const LFU_LOG_FACTOR: f64 = 10.0;
+
+/// Idle time that takes one off the access counter.
+const LFU_DECAY_MS: u64 = 60_000;
+
+/// What to do once the used memory exceeds maxmemory, named like redis' maxmemory-policy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Refuse commands that may grow the dataset.
+    #[default]
+    NoEviction,
+    AllKeysLru,
+    AllKeysLfu,
+    AllKeysRandom,
+    /// The `volatile` policies only evict keys with a ttl.
+    VolatileLru,
+    VolatileLfu,
+    VolatileRandom,
+    /// Evict the keys closest to expiring first.
+    VolatileTtl,
+}
+
+impl EvictionPolicy {
+    const ALL: [EvictionPolicy; 8] = [
+        EvictionPolicy::NoEviction,
+        EvictionPolicy::AllKeysLru,
+        EvictionPolicy::AllKeysLfu,
+        EvictionPolicy::AllKeysRandom,
+        EvictionPolicy::VolatileLru,
+        EvictionPolicy::VolatileLfu,
+        EvictionPolicy::VolatileRandom,
+        EvictionPolicy::VolatileTtl,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EvictionPolicy::NoEviction => "noeviction",
+            EvictionPolicy::AllKeysLru => "allkeys-lru",
+            EvictionPolicy::AllKeysLfu => "allkeys-lfu",
+            EvictionPolicy::AllKeysRandom => "allkeys-random",
+            EvictionPolicy::VolatileLru => "volatile-lru",
+            EvictionPolicy::VolatileLfu => "volatile-lfu",
+            EvictionPolicy::VolatileRandom => "volatile-random",
+            EvictionPolicy::VolatileTtl => "volatile-ttl",
+        }
+    }
+
+    /// The policy called `name`, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|policy| policy.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Whether access frequencies rather than idle times rank keys.
+    pub fn is_lfu(&self) -> bool {
+        matches!(
+            self,
+            EvictionPolicy::AllKeysLfu | EvictionPolicy::VolatileLfu
+        )
+    }
+
+    fn volatile(&self) -> bool {
+        matches!(
+            self,
+            EvictionPolicy::VolatileLru
+                | EvictionPolicy::VolatileLfu
+                | EvictionPolicy::VolatileRandom
+                | EvictionPolicy::VolatileTtl
+        )
+    }
+}
+
+/// Memory accounting and the eviction settings, shared by every database.
+#[derive(Debug, Default)]
+pub(super) struct Memory {
+    /// Accounted bytes of every entry of every database.
+    pub(super) used: AtomicUsize,
+    /// Limit on `used`, 0 for none.
+    maxmemory: AtomicUsize,
+    policy: Mutex<EvictionPolicy>,
+    evicted: AtomicUsize,
+}
+
+impl Memory {
+    /// Moves the accounting of an entry from `old` to `new` bytes.
+    pub(super) fn account(&self, old: usize, new: usize) {
+        // adding first means the counter never wraps below zero in between
+        self.used.fetch_add(new, Ordering::Relaxed);
+        self.used.fetch_sub(old, Ordering::Relaxed);
+    }
+}
+
+// public functions
+impl Db {
+    /// Accounted bytes of the values in every database, an estimate of the memory used.
+    pub fn used_memory(&self) -> usize {
+        self.memory.used.load(Ordering::Relaxed)
+    }
+
+    /// Limit [`Db::free_memory`] keeps the used memory under, 0 for none.
+    pub fn maxmemory(&self) -> usize {
+        self.memory.maxmemory.load(Ordering::Relaxed)
+    }
+
+    pub fn set_maxmemory(&self, maxmemory: usize) {
+        self.memory.maxmemory.store(maxmemory, Ordering::Relaxed);
+    }
+
+    pub fn eviction_policy(&self) -> EvictionPolicy {
+        *self.memory.policy.lock().unwrap()
+    }
+
+    pub fn set_eviction_policy(&self, policy: EvictionPolicy) {
+        *self.memory.policy.lock().unwrap() = policy;
+    }
+
+    /// Keys evicted since the db was created.
+    pub fn evicted_keys(&self) -> usize {
+        self.memory.evicted.load(Ordering::Relaxed)
+    }
+
+    /// Evicts keys of any database until the used memory is within maxmemory, returns false
+    /// if the policy ran out of keys it may evict before getting there.
+    ///
+    /// Like redis the eviction is approximated: the best candidate of a few sampled keys in
+    /// a few sampled shards goes first, rather than the best one in the whole keyspace.
+    pub fn free_memory(&self) -> bool {
+        loop {
+            let maxmemory = self.maxmemory();
+            if maxmemory == 0 || self.used_memory() <= maxmemory {
+                return true;
+            }
+            if !self.evict_one() {
+                return false;
+            }
+        }
+    }
+}
+
+// private helper functions
+impl Db {
+    fn evict_one(&self) -> bool {
+        let policy = self.eviction_policy();
+        if policy == EvictionPolicy::NoEviction {
+            return false;
+        }
+
+        // walks the shards of every database from a random one on, until enough of them
+        // offered a candidate
+        let per_db = self.shards().len();
+        let total = self.dbs.len() * per_db;
+        let start = random::below(total);
+        let now = now_ms();
+        let mut best: Option<(u64, usize, Bytes)> = None;
+        let mut sampled = 0;
+        for offset in 0..total {
+            let flat = (start + offset) % total;
+            let shard = self.dbs[flat / per_db][flat % per_db].lock().unwrap();
+            let candidate = match shard.eviction_candidate(policy, now) {
+                None => continue,
+                Some(candidate) => candidate,
+            };
+            if best
+                .as_ref()
+                .is_none_or(|(score, _, _)| candidate.0 > *score)
+            {
+                best = Some((candidate.0, flat, candidate.1));
+            }
+            sampled += 1;
+            if sampled == EVICTION_SAMPLES {
+                break;
+            }
+        }
+
+        let (_, flat, key) = match best {
+            None => return false,
+            Some(best) => best,
+        };
+        let mut shard = self.dbs[flat / per_db][flat % per_db].lock().unwrap();
+        // the key may be gone already, which frees memory just as well
+        let evicted = shard.update(&key, now_ms(), false, |slot| slot.take());
+        drop(shard);
+        if evicted.is_some() {
+            self.memory.evicted.fetch_add(1, Ordering::Relaxed);
+        }
+        true
+    }
+}
+
+impl Shard {
+    /// The key of this shard `policy` would evict first among a few sampled ones, with its
+    /// score. Higher scores are evicted first.
+    fn eviction_candidate(&self, policy: EvictionPolicy, now: u64) -> Option<(u64, Bytes)> {
+        if policy == EvictionPolicy::VolatileTtl {
+            let (when, key) = self.expirations.first()?;
+            return Some((u64::MAX - when, key.clone()));
+        }
+
+        // sampled with replacement, which hardly matters with so few samples
+        (0..EVICTION_SAMPLES)
+            .filter_map(|_| match policy.volatile() {
+                true => self.volatile.random().map(|(key, _)| key),
+                false => self.entries.random().map(|(key, _)| key),
+            })
+            .filter_map(|key| {
+                let entry = self.entries.get(key)?;
+                let score = match policy {
+                    EvictionPolicy::AllKeysLfu | EvictionPolicy::VolatileLfu => {
+                        u64::from(u8::MAX - entry.frequency(now))
+                    }
+                    EvictionPolicy::AllKeysRandom | EvictionPolicy::VolatileRandom => {
+                        random::next_u64()
+                    }
+                    _ => entry.idle_ms(now),
+                };
+                Some((score, key.clone()))
+            })
+            .max_by_key(|(score, _)| *score)
+    }
+}
+
+impl Entry {
+    /// Milliseconds since the entry was last accessed.
+    pub(crate) fn idle_ms(&self, now: u64) -> u64 {
+        now.saturating_sub(self.accessed_at)
+    }
+
+    /// The logarithmic access counter, less one for every minute the entry has been idle.
+    pub(crate) fn frequency(&self, now: u64) -> u8 {
+        let periods = self.idle_ms(now) / LFU_DECAY_MS;
+        self.freq.saturating_sub(periods.min(u8::MAX as u64) as u8)
+    }
+
+    /// Records an access, which makes the entry less likely to be evicted.
+    pub(super) fn touch(&mut self, now: u64) {
+        let freq = self.frequency(now);
+        // every increment takes more accesses than the previous one, so a byte is enough to
+        // count millions of them
+        let base = freq.saturating_sub(LFU_INIT_VAL) as f64;
+        if freq < u8::MAX && random::fraction() < 1.0 / (base * LFU_LOG_FACTOR + 1.0) {
+            self.freq = freq + 1;
+        } else {
+            self.freq = freq;
+        }
+        self.accessed_at = now;
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::BTreeSet;
+
+use bytes::Bytes;
+
+use super::Table;
+
+/// Field value map of a hash, where every field may carry its own expiry as unix time in
+/// milliseconds.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Hash {
+    fields: Table<Field>,
+    // fields with a ttl ordered by expiry, so purging never scans persistent fields
+    expirations: BTreeSet<(u64, Bytes)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Field {
+    value: Bytes,
+    expires_at: Option<u64>,
+}
+
+impl Hash {
+    pub(crate) fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub(crate) fn get(&self, field: &[u8]) -> Option<&Bytes> {
+        self.fields.get(field).map(|field| &field.value)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&Bytes, &Bytes)> {
+        self.fields.iter().map(|(name, field)| (name, &field.value))
+    }
+
+    /// A uniformly chosen field with its value, `None` if the hash is empty.
+    pub(crate) fn random(&self) -> Option<(&Bytes, &Bytes)> {
+        self.fields
+            .random()
+            .map(|(name, field)| (name, &field.value))
+    }
+
+    /// About `count` fields from `cursor` on in scan order, with the cursor to continue from.
+    pub(crate) fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<(&Bytes, &Bytes)>) {
+        let (next, page) = self.fields.scan(cursor, count);
+        let page = page
+            .into_iter()
+            .map(|(name, field)| (name, &field.value))
+            .collect();
+        (next, page)
+    }
+
+    /// Sets `field` to `value` and clears its ttl, returns true if the field is new.
+    pub(crate) fn insert(&mut self, field: Bytes, value: Bytes) -> bool {
+        self.set_expiry(&field, None);
+        self.fields
+            .insert(
+                field,
+                Field {
+                    value,
+                    expires_at: None,
+                },
+            )
+            .is_none()
+    }
+
+    /// Replaces the value of `field` but keeps its ttl, inserting the field if missing.
+    pub(crate) fn update(&mut self, field: Bytes, value: Bytes) {
+        match self.fields.get_mut(&field) {
+            Some(current) => current.value = value,
+            None => {
+                self.insert(field, value);
+            }
+        }
+    }
+
+    pub(crate) fn remove(&mut self, field: &[u8]) -> bool {
+        self.set_expiry(field, None);
+        self.fields.remove(field).is_some()
+    }
+
+    /// Expiry of the field that expires first, `None` if no field has a ttl.
+    pub(crate) fn next_expiry(&self) -> Option<u64> {
+        self.expirations.first().map(|(when, _)| *when)
+    }
+
+    /// `None` if the field doesn't exist, otherwise its expiry.
+    pub(crate) fn expires_at(&self, field: &[u8]) -> Option<Option<u64>> {
+        self.fields.get(field).map(|field| field.expires_at)
+    }
+
+    /// Sets the expiry of an existing field, `None` makes it persistent.
+    pub(crate) fn set_expiry(&mut self, field: &[u8], expires_at: Option<u64>) {
+        let (name, current) = match self.fields.get_key_value(field) {
+            None => return,
+            Some((name, current)) => (name.clone(), current.expires_at),
+        };
+        if let Some(when) = current {
+            self.expirations.remove(&(when, name.clone()));
+        }
+        if let Some(when) = expires_at {
+            self.expirations.insert((when, name.clone()));
+        }
+        if let Some(current) = self.fields.get_mut(field) {
+            current.expires_at = expires_at;
+        }
+    }
+
+    /// Removes every field whose expiry is at or before `now`.
+    pub(crate) fn purge_expired(&mut self, now: u64) {
+        while let Some((when, _)) = self.expirations.first() {
+            if *when > now {
+                break;
+            }
+            if let Some((_, name)) = self.expirations.pop_first() {
+                self.fields.remove(&name);
+            }
+        }
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_expiry() {
+        let mut hash = Hash::default();
+        assert!(hash.insert(Bytes::from("a"), Bytes::from("1")));
+        assert!(hash.insert(Bytes::from("b"), Bytes::from("2")));
+        assert!(!hash.insert(Bytes::from("b"), Bytes::from("3")));
+
+        hash.set_expiry(b"a", Some(100));
+        hash.set_expiry(b"b", Some(200));
+        hash.update(Bytes::from("b"), Bytes::from("4"));
+        assert_eq!(hash.expires_at(b"b"), Some(Some(200)));
+        assert_eq!(hash.expires_at(b"c"), None);
+
+        hash.purge_expired(150);
+        assert_eq!(hash.get(b"a"), None);
+        assert_eq!(hash.get(b"b"), Some(&Bytes::from("4")));
+
+        // overwriting a field makes it persistent again
+        hash.insert(Bytes::from("b"), Bytes::from("5"));
+        hash.purge_expired(300);
+        assert_eq!(hash.expires_at(b"b"), Some(None));
+        assert_eq!(hash.len(), 1);
+    }
+}
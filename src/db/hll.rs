@@ -0,0 +1,356 @@
+use bytes::{BufMut, Bytes, BytesMut};
+
+use crate::Error;
+
+/// Number of index bits, redis uses 2^14 registers.
+const HLL_P: u32 = 14;
+const HLL_Q: u32 = 64 - HLL_P;
+const HLL_REGISTERS: usize = 1 << HLL_P;
+const HLL_BITS: usize = 6;
+const HLL_REGISTER_MAX: u8 = (1 << HLL_BITS) - 1;
+const HLL_HEADER_LEN: usize = 16;
+const HLL_DENSE_LEN: usize = HLL_HEADER_LEN + HLL_REGISTERS * HLL_BITS / 8;
+const HLL_DENSE: u8 = 0;
+const HLL_SPARSE: u8 = 1;
+
+/// Sparse objects growing past this many bytes are promoted to the dense encoding,
+/// matching redis' hll-sparse-max-bytes default.
+const HLL_SPARSE_MAX_BYTES: usize = 3000;
+const HLL_SPARSE_VAL_MAX_VALUE: u8 = 32;
+const HLL_SPARSE_VAL_MAX_LEN: usize = 4;
+const HLL_SPARSE_ZERO_MAX_LEN: usize = 64;
+const HLL_SPARSE_XZERO_MAX_LEN: usize = 16384;
+
+const HLL_ALPHA_INF: f64 = 0.721_347_520_444_481_7;
+
+/// A HyperLogLog decoded from the string layout redis uses, so values can be moved between
+/// servers with GET and SET: a 16 byte header holding the `HYLL` magic, the encoding and the
+/// cached cardinality, followed by either packed 6 bit registers or the sparse run-length opcodes.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Hll {
+    registers: Vec<u8>,
+    dense: bool,
+    card: [u8; 8],
+}
+
+impl Default for Hll {
+    fn default() -> Self {
+        Hll {
+            registers: vec![0; HLL_REGISTERS],
+            dense: false,
+            card: [0; 8],
+        }
+    }
+}
+
+impl Hll {
+    /// Decodes a stored string, failing with redis' errors when it isn't an HLL at all
+    /// or when its sparse representation doesn't describe exactly every register.
+    pub(crate) fn parse(value: &[u8]) -> crate::Result<Self> {
+        if value.len() < HLL_HEADER_LEN || &value[..4] != b"HYLL" {
+            return Err(not_hll());
+        }
+        let card = value[8..HLL_HEADER_LEN]
+            .try_into()
+            .expect("header is 16 bytes");
+        let data = &value[HLL_HEADER_LEN..];
+
+        let (registers, dense) = match value[4] {
+            HLL_DENSE if value.len() == HLL_DENSE_LEN => (decode_dense(data), true),
+            HLL_SPARSE => (decode_sparse(data).ok_or_else(corrupted)?, false),
+            _ => return Err(not_hll()),
+        };
+        Ok(Hll {
+            registers,
+            dense,
+            card,
+        })
+    }
+
+    /// Hashes `element` into its register, returns true if the register grew.
+    pub(crate) fn add(&mut self, element: &[u8]) -> bool {
+        let (index, count) = pattern(element);
+        if self.registers[index] >= count {
+            return false;
+        }
+        self.registers[index] = count;
+        self.invalidate();
+        true
+    }
+
+    /// Folds `other` into this HLL by keeping the larger of every pair of registers.
+    pub(crate) fn merge(&mut self, other: &Hll) {
+        for (register, &value) in self.registers.iter_mut().zip(&other.registers) {
+            *register = (*register).max(value);
+        }
+        self.dense |= other.dense;
+        self.invalidate();
+    }
+
+    /// The cardinality stored in the header, unless a write invalidated it since.
+    pub(crate) fn cached(&self) -> Option<u64> {
+        (self.card[7] & 0x80 == 0).then(|| u64::from_le_bytes(self.card))
+    }
+
+    /// Estimates the cardinality with Ertl's improved estimator, as redis does.
+    pub(crate) fn estimate(&self) -> u64 {
+        let mut histogram = [0u32; 64];
+        for &register in &self.registers {
+            histogram[register as usize] += 1;
+        }
+
+        let m = HLL_REGISTERS as f64;
+        let mut z = m * tau((m - f64::from(histogram[HLL_Q as usize + 1])) / m);
+        for j in (1..=HLL_Q as usize).rev() {
+            z += f64::from(histogram[j]);
+            z *= 0.5;
+        }
+        z += m * sigma(f64::from(histogram[0]) / m);
+        (HLL_ALPHA_INF * m * m / z).round() as u64
+    }
+
+    /// Serializes the HLL, staying sparse while every register fits the sparse opcodes
+    /// and the result stays small enough.
+    pub(crate) fn encode(&self) -> Bytes {
+        let sparse = match self.dense {
+            true => None,
+            false => encode_sparse(&self.registers)
+                .filter(|data| HLL_HEADER_LEN + data.len() <= HLL_SPARSE_MAX_BYTES),
+        };
+        let (encoding, data) = match sparse {
+            Some(data) => (HLL_SPARSE, data),
+            None => (HLL_DENSE, encode_dense(&self.registers)),
+        };
+
+        let mut value = BytesMut::with_capacity(HLL_HEADER_LEN + data.len());
+        value.put_slice(b"HYLL");
+        value.put_slice(&[encoding, 0, 0, 0]);
+        value.put_slice(&self.card);
+        value.put_slice(&data);
+        value.freeze()
+    }
+
+    fn invalidate(&mut self) {
+        self.card[7] |= 0x80;
+    }
+}
+
+/// Rewrites the cached cardinality of an encoded HLL without touching its registers.
+pub(crate) fn cache_count(value: &[u8], count: u64) -> Bytes {
+    let mut value = BytesMut::from(value);
+    value[8..HLL_HEADER_LEN].copy_from_slice(&count.to_le_bytes());
+    value.freeze()
+}
+
+fn not_hll() -> Error {
+    Error::server("WRONGTYPE", "Key is not a valid HyperLogLog string value.")
+}
+
+fn corrupted() -> Error {
+    Error::server("INVALIDOBJ", "Corrupted HLL object detected")
+}
+
+fn decode_dense(data: &[u8]) -> Vec<u8> {
+    (0..HLL_REGISTERS)
+        .map(|register| {
+            let byte = register * HLL_BITS / 8;
+            let fb = (register * HLL_BITS) & 7;
+            let b0 = data[byte] as u16;
+            let b1 = data.get(byte + 1).copied().unwrap_or(0) as u16;
+            (((b0 >> fb) | (b1 << (8 - fb))) & HLL_REGISTER_MAX as u16) as u8
+        })
+        .collect()
+}
+
+fn encode_dense(registers: &[u8]) -> Vec<u8> {
+    let mut data = vec![0u8; HLL_REGISTERS * HLL_BITS / 8];
+    for (register, &value) in registers.iter().enumerate() {
+        let byte = register * HLL_BITS / 8;
+        let fb = (register * HLL_BITS) & 7;
+        let value = value as u16 & HLL_REGISTER_MAX as u16;
+        data[byte] |= (value << fb) as u8;
+        if let Some(next) = data.get_mut(byte + 1) {
+            *next |= (value >> (8 - fb)) as u8;
+        }
+    }
+    data
+}
+
+/// Expands the sparse opcodes: `00xxxxxx` is a run of up to 64 zero registers,
+/// `01xxxxxx yyyyyyyy` a run of up to 16384 zeros and `1vvvvvxx` a run of up to
+/// 4 registers set to a value of up to 32.
+fn decode_sparse(data: &[u8]) -> Option<Vec<u8>> {
+    let mut registers = Vec::with_capacity(HLL_REGISTERS);
+    let mut ops = data.iter();
+    while let Some(&op) = ops.next() {
+        let (value, len) = match op & 0xc0 {
+            0x00 => (0, (op & 0x3f) as usize + 1),
+            0x40 => (0, (((op & 0x3f) as usize) << 8 | *ops.next()? as usize) + 1),
+            _ => (((op >> 2) & 0x1f) + 1, (op & 0x3) as usize + 1),
+        };
+        if registers.len() + len > HLL_REGISTERS {
+            return None;
+        }
+        registers.resize(registers.len() + len, value);
+    }
+    (registers.len() == HLL_REGISTERS).then_some(registers)
+}
+
+/// The sparse opcodes for `registers`, `None` if a register is too large for them.
+fn encode_sparse(registers: &[u8]) -> Option<Vec<u8>> {
+    let mut data = vec![];
+    let mut idx = 0;
+    while idx < registers.len() {
+        let value = registers[idx];
+        let run = registers[idx..].iter().take_while(|&&r| r == value).count();
+        idx += run;
+
+        let mut left = run;
+        while left > 0 {
+            let len = match value {
+                0 if left > HLL_SPARSE_ZERO_MAX_LEN => {
+                    let len = left.min(HLL_SPARSE_XZERO_MAX_LEN);
+                    data.push(0x40 | ((len - 1) >> 8) as u8);
+                    data.push(((len - 1) & 0xff) as u8);
+                    len
+                }
+                0 => {
+                    data.push((left - 1) as u8);
+                    left
+                }
+                value if value <= HLL_SPARSE_VAL_MAX_VALUE => {
+                    let len = left.min(HLL_SPARSE_VAL_MAX_LEN);
+                    data.push(0x80 | ((value - 1) << 2) | (len - 1) as u8);
+                    len
+                }
+                _ => return None,
+            };
+            left -= len;
+        }
+    }
+    Some(data)
+}
+
+/// The register an element maps to and the length of the run of zero bits that follows,
+/// plus one, in its MurmurHash64A hash.
+fn pattern(element: &[u8]) -> (usize, u8) {
+    let mut hash = murmurhash64a(element, 0xadc8_3b19);
+    let index = (hash & (HLL_REGISTERS as u64 - 1)) as usize;
+    hash >>= HLL_P;
+    // guarantees the count stops at Q + 1
+    hash |= 1 << HLL_Q;
+    (index, hash.trailing_zeros() as u8 + 1)
+}
+
+fn murmurhash64a(key: &[u8], seed: u64) -> u64 {
+    const M: u64 = 0xc6a4_a793_5bd1_e995;
+    const R: u32 = 47;
+
+    let mut h = seed ^ (key.len() as u64).wrapping_mul(M);
+    let mut blocks = key.chunks_exact(8);
+    for block in &mut blocks {
+        let mut k = u64::from_le_bytes(block.try_into().expect("blocks are 8 bytes"));
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h ^= k;
+        h = h.wrapping_mul(M);
+    }
+
+    let tail = blocks.remainder();
+    if !tail.is_empty() {
+        for (i, &byte) in tail.iter().enumerate() {
+            h ^= (byte as u64) << (8 * i);
+        }
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> R;
+    h = h.wrapping_mul(M);
+    h ^= h >> R;
+    h
+}
+
+fn sigma(mut x: f64) -> f64 {
+    if x == 1.0 {
+        return f64::INFINITY;
+    }
+    let mut y = 1.0;
+    let mut z = x;
+    loop {
+        x *= x;
+        let prev = z;
+        z += x * y;
+        y += y;
+        if prev == z {
+            return z;
+        }
+    }
+}
+
+fn tau(mut x: f64) -> f64 {
+    if x == 0.0 || x == 1.0 {
+        return 0.0;
+    }
+    let mut y = 1.0;
+    let mut z = 1.0 - x;
+    loop {
+        x = x.sqrt();
+        let prev = z;
+        y *= 0.5;
+        z -= (1.0 - x).powi(2) * y;
+        if prev == z {
+            return z / 3.0;
+        }
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparse_encoding() {
+        // a single VAL opcode setting register 0 to 3 followed by an XZERO for the rest
+        let mut value = b"HYLL\x01\x00\x00\x00".to_vec();
+        value.extend_from_slice(&[0; 8]);
+        value.extend_from_slice(&[0x88, 0x7f, 0xfe]);
+        let hll = Hll::parse(&value).unwrap();
+        assert_eq!(hll.registers[0], 3);
+        assert!(hll.registers[1..].iter().all(|&r| r == 0));
+        assert_eq!(hll.encode(), value);
+        assert_eq!(hll.cached(), Some(0));
+
+        // opcodes must cover every register exactly once
+        let mut short = value.clone();
+        short.pop();
+        short.push(0xfd);
+        assert!(Hll::parse(&short).is_err());
+        value.push(0x00);
+        assert!(Hll::parse(&value).is_err());
+        assert!(Hll::parse(b"HYLL\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00").is_err());
+    }
+
+    #[test]
+    fn test_promotion_and_estimate() {
+        let mut hll = Hll::default();
+        assert_eq!(hll.estimate(), 0);
+        assert!(hll.add(b"a"));
+        assert!(!hll.add(b"a"));
+        assert_eq!(hll.cached(), None);
+        assert_eq!(hll.encode()[4], HLL_SPARSE);
+
+        for i in 0..10000 {
+            hll.add(i.to_string().as_bytes());
+        }
+        let encoded = hll.encode();
+        assert_eq!(encoded[4], HLL_DENSE);
+        assert_eq!(encoded.len(), HLL_DENSE_LEN);
+
+        let parsed = Hll::parse(&encoded).unwrap();
+        assert_eq!(parsed.registers, hll.registers);
+        let estimate = parsed.estimate() as f64;
+        assert!((estimate - 10001.0).abs() / 10001.0 < 0.02, "{estimate}");
+    }
+}
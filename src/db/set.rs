@@ -0,0 +1,208 @@
+use bytes::Bytes;
+
+use super::Table;
+use crate::random;
+
+/// Largest set kept in the integer encoding, matching redis' set-max-intset-entries default.
+const MAX_INTSET_ENTRIES: usize = 512;
+
+/// Unordered set of unique members. Sets holding only integers are stored as a sorted
+/// vector of them until they grow past [`MAX_INTSET_ENTRIES`], any other set is a hash table.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Set {
+    Ints(Vec<i64>),
+    Table(Table<()>),
+}
+
+impl Default for Set {
+    fn default() -> Self {
+        Set::Ints(vec![])
+    }
+}
+
+impl Set {
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Set::Ints(ints) => ints.len(),
+            Set::Table(table) => table.len(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn contains(&self, member: &[u8]) -> bool {
+        match self {
+            Set::Ints(ints) => match canonical_int(member) {
+                None => false,
+                Some(int) => ints.binary_search(&int).is_ok(),
+            },
+            Set::Table(table) => table.contains_key(member),
+        }
+    }
+
+    /// Adds `member`, returns true if it wasn't in the set yet.
+    pub(crate) fn insert(&mut self, member: Bytes) -> bool {
+        if let Set::Ints(ints) = self {
+            match canonical_int(&member) {
+                Some(int) => match ints.binary_search(&int) {
+                    Ok(_) => return false,
+                    Err(idx) if ints.len() < MAX_INTSET_ENTRIES => {
+                        ints.insert(idx, int);
+                        return true;
+                    }
+                    Err(_) => self.convert(),
+                },
+                None => self.convert(),
+            }
+        }
+
+        match self {
+            Set::Table(table) => table.insert(member, ()).is_none(),
+            Set::Ints(_) => unreachable!("set was converted to a table"),
+        }
+    }
+
+    pub(crate) fn remove(&mut self, member: &[u8]) -> bool {
+        match self {
+            Set::Ints(ints) => match canonical_int(member).map(|int| ints.binary_search(&int)) {
+                Some(Ok(idx)) => {
+                    ints.remove(idx);
+                    true
+                }
+                _ => false,
+            },
+            Set::Table(table) => table.remove(member).is_some(),
+        }
+    }
+
+    pub(crate) fn iter(&self) -> Box<dyn Iterator<Item = Bytes> + '_> {
+        match self {
+            Set::Ints(ints) => Box::new(ints.iter().map(|int| Bytes::from(int.to_string()))),
+            Set::Table(table) => Box::new(table.keys().cloned()),
+        }
+    }
+
+    /// About `count` members from `cursor` on in scan order, with the cursor to continue from.
+    /// Integer sets are small enough to be returned whole.
+    pub(crate) fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<Bytes>) {
+        match self {
+            Set::Ints(_) => (0, self.iter().collect()),
+            Set::Table(table) => {
+                let (next, page) = table.scan(cursor, count);
+                (
+                    next,
+                    page.into_iter().map(|(member, _)| member.clone()).collect(),
+                )
+            }
+        }
+    }
+
+    /// A uniformly chosen member, `None` if the set is empty.
+    pub(crate) fn random(&self) -> Option<Bytes> {
+        if self.is_empty() {
+            return None;
+        }
+        match self {
+            Set::Ints(ints) => Some(Bytes::from(ints[random::below(ints.len())].to_string())),
+            Set::Table(table) => table.random().map(|(member, _)| member.clone()),
+        }
+    }
+
+    /// Removes and returns up to `count` distinct random members.
+    pub(crate) fn pop_random(&mut self, count: usize) -> Vec<Bytes> {
+        // popping a single member is the common case and doesn't need a copy of the set
+        if count == 1 {
+            let member = self.random();
+            if let Some(member) = &member {
+                self.remove(member);
+            }
+            return member.into_iter().collect();
+        }
+
+        let mut members: Vec<_> = self.iter().collect();
+        let count = count.min(members.len());
+        for i in 0..count {
+            let j = i + random::below(members.len() - i);
+            members.swap(i, j);
+        }
+        members.truncate(count);
+
+        for member in &members {
+            self.remove(member);
+        }
+        members
+    }
+
+    fn convert(&mut self) {
+        if let Set::Ints(ints) = self {
+            let table = ints
+                .iter()
+                .map(|int| (Bytes::from(int.to_string()), ()))
+                .collect();
+            *self = Set::Table(table);
+        }
+    }
+}
+
+impl FromIterator<Bytes> for Set {
+    fn from_iter<I: IntoIterator<Item = Bytes>>(members: I) -> Self {
+        let mut set = Set::default();
+        for member in members {
+            set.insert(member);
+        }
+        set
+    }
+}
+
+/// Parses `member` as an integer only if that integer formats back to exactly the same bytes,
+/// so the integer encoding never changes what a member looks like.
+fn canonical_int(member: &[u8]) -> Option<i64> {
+    let int: i64 = std::str::from_utf8(member).ok()?.parse().ok()?;
+    (int.to_string().as_bytes() == member).then_some(int)
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoding_conversion() {
+        let mut set = Set::default();
+        assert!(set.insert(Bytes::from("3")));
+        assert!(set.insert(Bytes::from("-1")));
+        assert!(!set.insert(Bytes::from("3")));
+        assert!(matches!(set, Set::Ints(_)));
+        assert!(set.contains(b"-1"));
+
+        // members that only look like integers keep their exact spelling
+        assert!(set.insert(Bytes::from("007")));
+        assert!(matches!(set, Set::Table(_)));
+        assert!(set.contains(b"007"));
+        assert!(!set.contains(b"7"));
+        assert!(set.contains(b"3"));
+        assert!(set.remove(b"3"));
+        assert_eq!(set.len(), 2);
+
+        let mut set: Set = (0..MAX_INTSET_ENTRIES)
+            .map(|i| Bytes::from(i.to_string()))
+            .collect();
+        assert!(matches!(set, Set::Ints(_)));
+        set.insert(Bytes::from("-5"));
+        assert!(matches!(set, Set::Table(_)));
+        assert_eq!(set.len(), MAX_INTSET_ENTRIES + 1);
+    }
+
+    #[test]
+    fn test_pop_random() {
+        let mut set: Set = ["a", "b", "c"].into_iter().map(Bytes::from).collect();
+        let mut popped = set.pop_random(2);
+        assert_eq!(popped.len(), 2);
+        popped.extend(set.pop_random(5));
+        popped.sort();
+        assert_eq!(popped, vec!["a", "b", "c"]);
+        assert!(set.is_empty());
+    }
+}
@@ -0,0 +1,496 @@
+use std::collections::{btree_map, BTreeMap};
+use std::fmt;
+use std::ops::Bound;
+
+use bytes::Bytes;
+
+/// ID of a stream entry, the milliseconds part followed by a sequence number that orders
+/// entries added within the same millisecond.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StreamId {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+impl StreamId {
+    pub const MIN: StreamId = StreamId { ms: 0, seq: 0 };
+    pub const MAX: StreamId = StreamId {
+        ms: u64::MAX,
+        seq: u64::MAX,
+    };
+
+    pub fn new(ms: u64, seq: u64) -> Self {
+        StreamId { ms, seq }
+    }
+
+    /// Parses `ms-seq`, or `ms` alone with `default_seq` as the sequence number.
+    pub fn parse(id: &[u8], default_seq: u64) -> Option<StreamId> {
+        let id = std::str::from_utf8(id).ok()?;
+        match id.split_once('-') {
+            None => Some(StreamId::new(id.parse().ok()?, default_seq)),
+            Some((ms, seq)) => Some(StreamId::new(ms.parse().ok()?, seq.parse().ok()?)),
+        }
+    }
+
+    /// The smallest ID after this one, `None` for [`StreamId::MAX`].
+    pub fn next(self) -> Option<StreamId> {
+        match self.seq.checked_add(1) {
+            Some(seq) => Some(StreamId::new(self.ms, seq)),
+            None => Some(StreamId::new(self.ms.checked_add(1)?, 0)),
+        }
+    }
+
+    /// The largest ID before this one, `None` for [`StreamId::MIN`].
+    pub fn prev(self) -> Option<StreamId> {
+        match self.seq.checked_sub(1) {
+            Some(seq) => Some(StreamId::new(self.ms, seq)),
+            None => Some(StreamId::new(self.ms.checked_sub(1)?, u64::MAX)),
+        }
+    }
+}
+
+impl fmt::Display for StreamId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+/// How XADD and XTRIM shrink a stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrimStrategy {
+    /// Keep at most this many entries.
+    MaxLen(u64),
+    /// Drop the entries with a smaller ID.
+    MinId(StreamId),
+}
+
+/// Field value pairs of a stream entry, in the order they were added.
+pub(crate) type Fields = Vec<(Bytes, Bytes)>;
+
+/// Append only log of entries ordered by ID, with the consumer groups reading it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Stream {
+    entries: BTreeMap<StreamId, Fields>,
+    last_id: StreamId,
+    max_deleted_id: StreamId,
+    entries_added: u64,
+    groups: BTreeMap<Bytes, Group>,
+}
+
+/// A consumer group, tracking what was delivered to whom until it is acknowledged.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Group {
+    pub(crate) last_delivered: StreamId,
+    pub(crate) pending: BTreeMap<StreamId, Pending>,
+    pub(crate) consumers: BTreeMap<Bytes, Consumer>,
+}
+
+/// A delivered but not yet acknowledged entry.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Pending {
+    pub(crate) consumer: Bytes,
+    pub(crate) delivered_at: u64,
+    pub(crate) deliveries: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Consumer {
+    /// Last time the consumer tried to read or claim.
+    pub(crate) seen_at: u64,
+    /// Last time the consumer actually got entries, `None` if it never did.
+    pub(crate) active_at: Option<u64>,
+}
+
+impl Stream {
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub(crate) fn last_id(&self) -> StreamId {
+        self.last_id
+    }
+
+    pub(crate) fn max_deleted_id(&self) -> StreamId {
+        self.max_deleted_id
+    }
+
+    pub(crate) fn entries_added(&self) -> u64 {
+        self.entries_added
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&StreamId, &Fields)> {
+        self.entries.iter()
+    }
+
+    pub(crate) fn get(&self, id: &StreamId) -> Option<&Fields> {
+        self.entries.get(id)
+    }
+
+    pub(crate) fn first(&self) -> Option<(StreamId, &Fields)> {
+        self.entries
+            .first_key_value()
+            .map(|(id, fields)| (*id, fields))
+    }
+
+    pub(crate) fn last(&self) -> Option<(StreamId, &Fields)> {
+        self.entries
+            .last_key_value()
+            .map(|(id, fields)| (*id, fields))
+    }
+
+    /// ID for a new entry, in the millisecond `ms` if given, otherwise in the current one
+    /// unless the clock is behind the last entry. `None` if no larger ID is left.
+    pub(crate) fn next_id(&self, ms: Option<u64>, now: u64) -> Option<StreamId> {
+        match ms {
+            None if now > self.last_id.ms => Some(StreamId::new(now, 0)),
+            None => self.last_id.next(),
+            Some(ms) if ms > self.last_id.ms => Some(StreamId::new(ms, 0)),
+            Some(ms) if ms == self.last_id.ms => self.last_id.next().filter(|id| id.ms == ms),
+            Some(_) => None,
+        }
+    }
+
+    /// Appends an entry, `id` must be larger than any ID the stream ever had.
+    pub(crate) fn add(&mut self, id: StreamId, fields: Fields) {
+        debug_assert!(id > self.last_id);
+        self.entries.insert(id, fields);
+        self.last_id = id;
+        self.entries_added += 1;
+    }
+
+    pub(crate) fn remove(&mut self, id: &StreamId) -> bool {
+        if self.entries.remove(id).is_none() {
+            return false;
+        }
+        self.max_deleted_id = self.max_deleted_id.max(*id);
+        true
+    }
+
+    /// Entries with IDs from `start` to `end` inclusive, from the end if `rev` is set.
+    pub(crate) fn range(
+        &self,
+        start: StreamId,
+        end: StreamId,
+        rev: bool,
+        count: Option<usize>,
+    ) -> Vec<(StreamId, Fields)> {
+        if start > end {
+            return vec![];
+        }
+        let range = self.entries.range(start..=end);
+        let range: Box<dyn Iterator<Item = _>> = match rev {
+            false => Box::new(range),
+            true => Box::new(range.rev()),
+        };
+        range
+            .take(count.unwrap_or(usize::MAX))
+            .map(|(id, fields)| (*id, fields.clone()))
+            .collect()
+    }
+
+    /// Entries with IDs larger than `id`.
+    pub(crate) fn after(&self, id: StreamId, count: Option<usize>) -> Vec<(StreamId, Fields)> {
+        match id.next() {
+            None => vec![],
+            Some(start) => self.range(start, StreamId::MAX, false, count),
+        }
+    }
+
+    /// Drops the oldest entries according to `strategy`, at most `limit` of them if given.
+    /// Returns the number of entries removed.
+    pub(crate) fn trim(&mut self, strategy: TrimStrategy, limit: Option<usize>) -> usize {
+        let excess = match strategy {
+            TrimStrategy::MaxLen(max) => self.len().saturating_sub(max as usize),
+            TrimStrategy::MinId(min) => self.entries.range(..min).count(),
+        };
+        let excess = excess.min(limit.unwrap_or(usize::MAX));
+        for _ in 0..excess {
+            self.entries.pop_first();
+        }
+        excess
+    }
+
+    pub(crate) fn groups(&self) -> impl Iterator<Item = (&Bytes, &Group)> {
+        self.groups.iter()
+    }
+
+    pub(crate) fn group(&self, name: &[u8]) -> Option<&Group> {
+        self.groups.get(name)
+    }
+
+    pub(crate) fn group_mut(&mut self, name: &[u8]) -> Option<&mut Group> {
+        self.groups.get_mut(name)
+    }
+
+    /// Creates a group that delivers the entries after `last_delivered`, returns false if
+    /// the group exists already.
+    pub(crate) fn create_group(&mut self, name: Bytes, last_delivered: StreamId) -> bool {
+        if self.groups.contains_key(&name) {
+            return false;
+        }
+        let group = Group {
+            last_delivered,
+            pending: BTreeMap::new(),
+            consumers: BTreeMap::new(),
+        };
+        self.groups.insert(name, group);
+        true
+    }
+
+    pub(crate) fn destroy_group(&mut self, name: &[u8]) -> bool {
+        self.groups.remove(name).is_some()
+    }
+
+    /// Number of entries the group hasn't delivered yet.
+    pub(crate) fn lag(&self, group: &Group) -> usize {
+        match group.last_delivered.next() {
+            None => 0,
+            Some(start) => self.entries.range(start..).count(),
+        }
+    }
+
+    /// Delivers entries to `consumer` of `group`, `None` if there is no such group.
+    ///
+    /// Without a `start` ID these are entries never delivered to the group, which become
+    /// pending unless `noack` is set. With one they are the consumer's own pending entries
+    /// after `start`, with no fields for those deleted meanwhile.
+    pub(crate) fn read_group(
+        &mut self,
+        group: &[u8],
+        consumer: &Bytes,
+        start: Option<StreamId>,
+        count: Option<usize>,
+        noack: bool,
+        now: u64,
+    ) -> Option<Vec<(StreamId, Option<Fields>)>> {
+        let group = self.groups.get_mut(group)?;
+        let read: Vec<_> = match start {
+            None => {
+                let start = group.last_delivered.next();
+                let read: Vec<_> = start
+                    .map(|start| self.entries.range(start..))
+                    .into_iter()
+                    .flatten()
+                    .take(count.unwrap_or(usize::MAX))
+                    .map(|(id, fields)| (*id, Some(fields.clone())))
+                    .collect();
+                if let Some((id, _)) = read.last() {
+                    group.last_delivered = *id;
+                }
+                if !noack {
+                    for (id, _) in &read {
+                        group
+                            .pending
+                            .insert(*id, Pending::new(consumer.clone(), now));
+                    }
+                }
+                read
+            }
+            Some(start) => {
+                let start = match start.next() {
+                    None => return Some(vec![]),
+                    Some(start) => start,
+                };
+                let mut read = vec![];
+                let owned = group
+                    .pending
+                    .range_mut(start..)
+                    .filter(|(_, pending)| pending.consumer == consumer);
+                for (id, pending) in owned.take(count.unwrap_or(usize::MAX)) {
+                    pending.delivered_at = now;
+                    pending.deliveries += 1;
+                    read.push((*id, self.entries.get(id).cloned()));
+                }
+                read
+            }
+        };
+
+        let consumer = group.touch(consumer, now);
+        if !read.is_empty() {
+            consumer.active_at = Some(now);
+        }
+        Some(read)
+    }
+
+    /// Hands the pending entry `id` of `group` over to `consumer` if it has been idle for at
+    /// least `min_idle` milliseconds, returning it so the caller can update its delivery.
+    /// With `force` an entry that isn't pending yet becomes pending. Entries deleted from the
+    /// stream are dropped from the pending list instead of being claimed.
+    pub(crate) fn claim(
+        &mut self,
+        group: &[u8],
+        consumer: &Bytes,
+        id: StreamId,
+        min_idle: u64,
+        force: bool,
+        now: u64,
+    ) -> Option<&mut Pending> {
+        let exists = self.entries.contains_key(&id);
+        let group = self.groups.get_mut(group)?;
+        if !exists {
+            group.pending.remove(&id);
+            return None;
+        }
+
+        match group.pending.entry(id) {
+            btree_map::Entry::Vacant(vacant) if force => {
+                let mut pending = Pending::new(consumer.clone(), now);
+                pending.deliveries = 0;
+                Some(vacant.insert(pending))
+            }
+            btree_map::Entry::Vacant(_) => None,
+            btree_map::Entry::Occupied(occupied) => {
+                let pending = occupied.into_mut();
+                if now.saturating_sub(pending.delivered_at) < min_idle {
+                    return None;
+                }
+                pending.consumer = consumer.clone();
+                Some(pending)
+            }
+        }
+    }
+}
+
+impl Group {
+    /// Records that `consumer` was seen, creating it if needed.
+    pub(crate) fn touch(&mut self, consumer: &Bytes, now: u64) -> &mut Consumer {
+        let consumer = self
+            .consumers
+            .entry(consumer.clone())
+            .or_insert_with(|| Consumer::new(now));
+        consumer.seen_at = now;
+        consumer
+    }
+
+    /// Creates `consumer`, returns false if it exists already.
+    pub(crate) fn create_consumer(&mut self, consumer: Bytes, now: u64) -> bool {
+        if self.consumers.contains_key(&consumer) {
+            return false;
+        }
+        self.consumers.insert(consumer, Consumer::new(now));
+        true
+    }
+
+    /// Deletes `consumer` along with its pending entries, returns how many it had.
+    pub(crate) fn delete_consumer(&mut self, consumer: &[u8]) -> usize {
+        if self.consumers.remove(consumer).is_none() {
+            return 0;
+        }
+        let before = self.pending.len();
+        self.pending
+            .retain(|_, pending| pending.consumer != consumer);
+        before - self.pending.len()
+    }
+
+    /// Number of entries pending for `consumer`.
+    pub(crate) fn pending_count(&self, consumer: &[u8]) -> usize {
+        self.pending
+            .values()
+            .filter(|pending| pending.consumer == consumer)
+            .count()
+    }
+
+    pub(crate) fn ack(&mut self, id: &StreamId) -> bool {
+        self.pending.remove(id).is_some()
+    }
+
+    /// Pending entries with IDs between `start` and `end`.
+    pub(crate) fn pending_range(
+        &self,
+        start: Bound<StreamId>,
+        end: Bound<StreamId>,
+    ) -> impl Iterator<Item = (&StreamId, &Pending)> {
+        self.pending.range((start, end))
+    }
+}
+
+impl Pending {
+    pub(crate) fn new(consumer: Bytes, now: u64) -> Self {
+        Pending {
+            consumer,
+            delivered_at: now,
+            deliveries: 1,
+        }
+    }
+}
+
+impl Consumer {
+    fn new(now: u64) -> Self {
+        Consumer {
+            seen_at: now,
+            active_at: None,
+        }
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(value: &'static str) -> Fields {
+        vec![(Bytes::from("f"), Bytes::from(value))]
+    }
+
+    #[test]
+    fn test_ids() {
+        assert_eq!(StreamId::parse(b"5-3", 0), Some(StreamId::new(5, 3)));
+        assert_eq!(
+            StreamId::parse(b"5", u64::MAX),
+            Some(StreamId::new(5, u64::MAX))
+        );
+        assert_eq!(StreamId::parse(b"5-", 0), None);
+        assert_eq!(StreamId::parse(b"-1", 0), None);
+        assert_eq!(StreamId::new(1, u64::MAX).next(), Some(StreamId::new(2, 0)));
+        assert_eq!(StreamId::new(2, 0).prev(), Some(StreamId::new(1, u64::MAX)));
+        assert_eq!(StreamId::MAX.next(), None);
+        assert_eq!(StreamId::new(7, 1).to_string(), "7-1");
+
+        let mut stream = Stream::default();
+        assert_eq!(stream.next_id(None, 100), Some(StreamId::new(100, 0)));
+        assert_eq!(stream.next_id(Some(0), 100), Some(StreamId::new(0, 1)));
+        stream.add(StreamId::new(100, 0), fields("a"));
+        // a clock going backwards keeps using the last millisecond
+        assert_eq!(stream.next_id(None, 50), Some(StreamId::new(100, 1)));
+        assert_eq!(stream.next_id(Some(99), 200), None);
+        stream.add(StreamId::new(100, u64::MAX), fields("b"));
+        assert_eq!(stream.next_id(Some(100), 200), None);
+    }
+
+    #[test]
+    fn test_trim_and_groups() {
+        let mut stream = Stream::default();
+        for ms in 1..=5 {
+            stream.add(StreamId::new(ms, 0), fields("v"));
+        }
+        assert_eq!(stream.trim(TrimStrategy::MaxLen(3), Some(1)), 1);
+        assert_eq!(
+            stream.trim(TrimStrategy::MinId(StreamId::new(4, 0)), None),
+            2
+        );
+        assert_eq!(stream.len(), 2);
+
+        let consumer = Bytes::from("c");
+        assert!(stream.create_group(Bytes::from("g"), StreamId::MIN));
+        assert!(!stream.create_group(Bytes::from("g"), StreamId::MIN));
+        let read = stream.read_group(b"g", &consumer, None, Some(1), false, 10);
+        assert_eq!(read.unwrap()[0].0, StreamId::new(4, 0));
+        assert_eq!(stream.lag(stream.group(b"g").unwrap()), 1);
+
+        // claiming needs the entry to have been idle long enough
+        let other = Bytes::from("other");
+        let id = StreamId::new(4, 0);
+        assert!(stream.claim(b"g", &other, id, 100, false, 50).is_none());
+        assert!(stream.claim(b"g", &other, id, 100, false, 110).is_some());
+        assert_eq!(stream.group(b"g").unwrap().pending[&id].consumer, other);
+
+        // deleted entries stay pending and come back without fields, until claimed
+        stream.remove(&id);
+        let history = stream.read_group(b"g", &other, Some(StreamId::MIN), None, false, 120);
+        assert_eq!(history.unwrap(), vec![(id, None)]);
+        assert_eq!(stream.group(b"g").unwrap().pending[&id].deliveries, 2);
+        assert!(stream.claim(b"g", &consumer, id, 0, true, 130).is_none());
+        assert!(stream.group(b"g").unwrap().pending.is_empty());
+        assert_eq!(stream.max_deleted_id(), StreamId::new(4, 0));
+    }
+}
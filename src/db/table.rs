@@ -0,0 +1,233 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::{Hash as _, Hasher};
+
+use bytes::Bytes;
+
+use crate::random;
+
+/// Bits of a scan position, SCAN keeps the shard in the bits of its cursor above them.
+pub(crate) const POSITION_BITS: u32 = 48;
+
+/// Hash table of the keyspace and of the larger aggregates. On top of the lookups by key,
+/// the entries are packed in a vector so a random one is an index away, and their keys are
+/// ordered by scan position so a scan resumes from its cursor without visiting the entries
+/// before it.
+#[derive(Debug, Clone)]
+pub(crate) struct Table<V> {
+    slots: Vec<(Bytes, V)>,
+    // where in `slots` every key is
+    index: HashMap<Bytes, usize>,
+    order: BTreeSet<(u64, Bytes)>,
+}
+
+impl<V> Default for Table<V> {
+    fn default() -> Self {
+        Table {
+            slots: vec![],
+            index: HashMap::new(),
+            order: BTreeSet::new(),
+        }
+    }
+}
+
+impl<V> Table<V> {
+    pub(crate) fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    pub(crate) fn contains_key(&self, key: &[u8]) -> bool {
+        self.index.contains_key(key)
+    }
+
+    pub(crate) fn get(&self, key: &[u8]) -> Option<&V> {
+        self.get_key_value(key).map(|(_, value)| value)
+    }
+
+    pub(crate) fn get_mut(&mut self, key: &[u8]) -> Option<&mut V> {
+        let idx = *self.index.get(key)?;
+        Some(&mut self.slots[idx].1)
+    }
+
+    pub(crate) fn get_key_value(&self, key: &[u8]) -> Option<(&Bytes, &V)> {
+        let (key, value) = &self.slots[*self.index.get(key)?];
+        Some((key, value))
+    }
+
+    /// Stores `value` under `key`, returning the value it replaces.
+    pub(crate) fn insert(&mut self, key: Bytes, value: V) -> Option<V> {
+        if let Some(&idx) = self.index.get(&key) {
+            return Some(std::mem::replace(&mut self.slots[idx].1, value));
+        }
+        self.order.insert((scan_position(&key), key.clone()));
+        self.index.insert(key.clone(), self.slots.len());
+        self.slots.push((key, value));
+        None
+    }
+
+    pub(crate) fn remove(&mut self, key: &[u8]) -> Option<V> {
+        let idx = self.index.remove(key)?;
+        let (key, value) = self.take_slot(idx);
+        self.order.remove(&(scan_position(&key), key));
+        Some(value)
+    }
+
+    /// Runs `f` on the slot of `key`, the entry is created or removed as `f` leaves the slot.
+    /// Keys that stay in the table keep their place in the scan order untouched.
+    pub(crate) fn update<R, F>(&mut self, key: &[u8], f: F) -> R
+    where
+        F: FnOnce(&Bytes, &mut Option<V>) -> R,
+    {
+        let (key, mut slot) = match self.index.get(key) {
+            None => (Bytes::copy_from_slice(key), None),
+            Some(&idx) => {
+                let (key, value) = self.take_slot(idx);
+                (key, Some(value))
+            }
+        };
+        let existed = slot.is_some();
+
+        let result = f(&key, &mut slot);
+
+        match (slot, existed) {
+            (Some(value), true) => {
+                self.index.insert(key.clone(), self.slots.len());
+                self.slots.push((key, value));
+            }
+            (Some(value), false) => {
+                self.insert(key, value);
+            }
+            (None, true) => {
+                self.index.remove(&key);
+                self.order.remove(&(scan_position(&key), key));
+            }
+            (None, false) => {}
+        }
+        result
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&Bytes, &V)> {
+        self.slots.iter().map(|(key, value)| (key, value))
+    }
+
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &Bytes> {
+        self.slots.iter().map(|(key, _)| key)
+    }
+
+    /// A uniformly chosen entry, `None` if the table is empty.
+    pub(crate) fn random(&self) -> Option<(&Bytes, &V)> {
+        if self.is_empty() {
+            return None;
+        }
+        let (key, value) = &self.slots[random::below(self.slots.len())];
+        Some((key, value))
+    }
+
+    /// About `count` entries from `cursor` on in scan order, together with the cursor to
+    /// continue from, 0 once the walk is complete.
+    ///
+    /// Positions don't depend on the other keys, so entries present from the start to the end
+    /// of a walk are returned at least once however the table changes in between. Entries
+    /// with colliding positions are never split across pages.
+    pub(crate) fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<(&Bytes, &V)>) {
+        let mut page = vec![];
+        let mut last = None;
+        for (position, key) in self.order.range((cursor, Bytes::new())..) {
+            if page.len() >= count.max(1) && last != Some(*position) {
+                return (*position, page);
+            }
+            last = Some(*position);
+            page.extend(self.get_key_value(key));
+        }
+        (0, page)
+    }
+
+    /// Moves the entry at `idx` out of `slots`, leaving `index` and `order` to the caller.
+    fn take_slot(&mut self, idx: usize) -> (Bytes, V) {
+        let taken = self.slots.swap_remove(idx);
+        if let Some((moved, _)) = self.slots.get(idx) {
+            *self.index.get_mut(moved).expect("every slot is indexed") = idx;
+        }
+        taken
+    }
+}
+
+impl<V: PartialEq> PartialEq for Table<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key).is_some_and(|other| other == value))
+    }
+}
+
+impl<V> FromIterator<(Bytes, V)> for Table<V> {
+    fn from_iter<I: IntoIterator<Item = (Bytes, V)>>(entries: I) -> Self {
+        let mut table = Table::default();
+        for (key, value) in entries {
+            table.insert(key, value);
+        }
+        table
+    }
+}
+
+/// Position of `key` in the order every scan command walks its collection in, between 1 and
+/// 2^[`POSITION_BITS`] - 1. 0 is the cursor that starts and ends a walk, so no key sits there.
+pub(crate) fn scan_position(key: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    (hasher.finish() >> (64 - POSITION_BITS)).max(1)
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_remove() {
+        let mut table: Table<u32> = (0..10).map(|i| (Bytes::from(i.to_string()), i)).collect();
+        assert_eq!(table.insert(Bytes::from("3"), 30), Some(3));
+        assert_eq!(table.remove(b"0"), Some(0));
+        assert_eq!(table.remove(b"0"), None);
+        assert_eq!(table.len(), 9);
+        assert_eq!(table.get(b"3"), Some(&30));
+        assert!((1..10).all(|i| table.contains_key(i.to_string().as_bytes())));
+
+        // removing in the slot update keeps every other key reachable
+        table.update(b"9", |_, slot| *slot = None);
+        table.update(b"1", |_, slot| *slot = slot.map(|i| i + 100));
+        table.update(b"new", |_, slot| *slot = Some(7));
+        assert_eq!(table.get(b"9"), None);
+        assert_eq!(table.get(b"1"), Some(&101));
+        assert_eq!(table.get(b"new"), Some(&7));
+        assert!([2, 4, 5, 6, 7, 8]
+            .iter()
+            .all(|i| table.get(i.to_string().as_bytes()) == Some(i)));
+        assert_eq!(table.scan(0, usize::MAX).1.len(), table.len());
+    }
+
+    #[test]
+    fn test_scan() {
+        let mut table: Table<()> = (0..100).map(|i| (Bytes::from(i.to_string()), ())).collect();
+        let (mut cursor, mut seen, mut removed) = (0, vec![], 100);
+        loop {
+            let (next, page) = table.scan(cursor, 7);
+            assert!(page.len() >= 7 || next == 0);
+            seen.extend(page.into_iter().map(|(key, _)| key.clone()));
+            // keys come and go behind and ahead of the cursor
+            removed -= 1;
+            table.remove(removed.to_string().as_bytes());
+            table.insert(Bytes::from(format!("new:{removed}")), ());
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        assert!((0..removed).all(|i| seen.contains(&Bytes::from(i.to_string()))));
+    }
+}
@@ -0,0 +1,488 @@
+use std::collections::HashMap;
+
+use bytes::Bytes;
+
+use crate::random;
+
+/// Levels a skiplist node can have, enough for 4^32 members.
+const MAX_LEVEL: usize = 32;
+
+/// Index of the header node, which holds no member.
+const HEAD: usize = 0;
+
+/// End of a score range, `-inf` and `+inf` are inclusive infinities.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScoreBound {
+    Inclusive(f64),
+    Exclusive(f64),
+}
+
+/// End of a lexicographical range, `-` and `+` are [`LexBound::Min`] and [`LexBound::Max`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexBound {
+    Min,
+    Max,
+    Inclusive(Bytes),
+    Exclusive(Bytes),
+}
+
+impl ScoreBound {
+    /// Whether `score` sorts before the range this bound starts.
+    fn below(&self, score: f64) -> bool {
+        match *self {
+            ScoreBound::Inclusive(min) => score < min,
+            ScoreBound::Exclusive(min) => score <= min,
+        }
+    }
+
+    /// Whether `score` sorts at or before the end of the range this bound ends.
+    fn within(&self, score: f64) -> bool {
+        match *self {
+            ScoreBound::Inclusive(max) => score <= max,
+            ScoreBound::Exclusive(max) => score < max,
+        }
+    }
+}
+
+impl LexBound {
+    fn below(&self, member: &[u8]) -> bool {
+        match self {
+            LexBound::Min => false,
+            LexBound::Max => true,
+            LexBound::Inclusive(min) => member < &min[..],
+            LexBound::Exclusive(min) => member <= &min[..],
+        }
+    }
+
+    fn within(&self, member: &[u8]) -> bool {
+        match self {
+            LexBound::Min => false,
+            LexBound::Max => true,
+            LexBound::Inclusive(max) => member <= &max[..],
+            LexBound::Exclusive(max) => member < &max[..],
+        }
+    }
+}
+
+/// Members ordered by score, then by member for equal scores. A map gives the score of a
+/// member in O(1), a skiplist keeps the order and finds ranks and ranges in O(log n).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ZSet {
+    scores: HashMap<Bytes, f64>,
+    list: SkipList,
+}
+
+impl ZSet {
+    pub(crate) fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub(crate) fn score(&self, member: &[u8]) -> Option<f64> {
+        self.scores.get(member).copied()
+    }
+
+    /// Members with their scores in no particular order.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&Bytes, f64)> {
+        self.scores.iter().map(|(member, score)| (member, *score))
+    }
+
+    /// Adds `member` or moves it to `score`, returns true if the member is new.
+    pub(crate) fn insert(&mut self, member: Bytes, score: f64) -> bool {
+        match self.scores.insert(member.clone(), score) {
+            Some(current) if current == score => false,
+            Some(current) => {
+                self.list.remove(current, &member);
+                self.list.insert(score, member);
+                false
+            }
+            None => {
+                self.list.insert(score, member);
+                true
+            }
+        }
+    }
+
+    pub(crate) fn remove(&mut self, member: &[u8]) -> bool {
+        match self.scores.remove(member) {
+            None => false,
+            Some(score) => self.list.remove(score, member),
+        }
+    }
+
+    /// Zero based position of `member` in ascending order.
+    pub(crate) fn rank(&self, member: &[u8]) -> Option<usize> {
+        let score = self.score(member)?;
+        self.list.rank(score, member)
+    }
+
+    /// Ascending ranks `[start, end)` of the members whose score is within `min` and `max`.
+    pub(crate) fn score_ranks(&self, min: &ScoreBound, max: &ScoreBound) -> (usize, usize) {
+        let start = self.list.prefix_len(|node| min.below(node.score));
+        let end = self.list.prefix_len(|node| max.within(node.score));
+        (start, end.max(start))
+    }
+
+    /// Ascending ranks `[start, end)` of the members within `min` and `max`, only meaningful
+    /// when all scores are equal.
+    pub(crate) fn lex_ranks(&self, min: &LexBound, max: &LexBound) -> (usize, usize) {
+        let start = self.list.prefix_len(|node| min.below(&node.member));
+        let end = self.list.prefix_len(|node| max.within(&node.member));
+        (start, end.max(start))
+    }
+
+    /// Members at ascending ranks `[start, end)`, in descending order if `rev` is set.
+    /// `offset` members are skipped first and at most `count` are returned.
+    pub(crate) fn range(
+        &self,
+        (start, end): (usize, usize),
+        rev: bool,
+        offset: usize,
+        count: Option<usize>,
+    ) -> Vec<(Bytes, f64)> {
+        let len = end.saturating_sub(start);
+        if offset >= len {
+            return vec![];
+        }
+        let take = count.map_or(len - offset, |count| count.min(len - offset));
+
+        let mut range = Vec::with_capacity(take);
+        let first = if rev {
+            end - 1 - offset
+        } else {
+            start + offset
+        };
+        let mut idx = self.list.by_rank(first);
+        while let Some(node) = idx.map(|idx| &self.list.nodes[idx]) {
+            if range.len() == take {
+                break;
+            }
+            range.push((node.member.clone(), node.score));
+            idx = if rev {
+                node.backward
+            } else {
+                node.levels[0].forward
+            };
+        }
+        range
+    }
+
+    /// Removes and returns up to `count` members with the lowest scores, or the highest
+    /// if `max` is set.
+    pub(crate) fn pop(&mut self, count: usize, max: bool) -> Vec<(Bytes, f64)> {
+        let popped = self.range((0, self.len()), max, 0, Some(count));
+        for (member, _) in &popped {
+            self.remove(member);
+        }
+        popped
+    }
+}
+
+impl PartialEq for ZSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.scores == other.scores
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SkipList {
+    // nodes live in an arena and link to each other by index, freed slots are reused
+    nodes: Vec<Node>,
+    free: Vec<usize>,
+    level: usize,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    member: Bytes,
+    score: f64,
+    backward: Option<usize>,
+    levels: Vec<Level>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Level {
+    forward: Option<usize>,
+    // number of level 0 links the forward link skips
+    span: usize,
+}
+
+impl Default for SkipList {
+    fn default() -> Self {
+        let head = Node {
+            member: Bytes::new(),
+            score: 0.0,
+            backward: None,
+            levels: vec![Level::default(); MAX_LEVEL],
+        };
+        SkipList {
+            nodes: vec![head],
+            free: vec![],
+            level: 1,
+            len: 0,
+        }
+    }
+}
+
+impl SkipList {
+    /// Inserts a member that must not be in the list yet.
+    fn insert(&mut self, score: f64, member: Bytes) {
+        let mut update = [HEAD; MAX_LEVEL];
+        let mut rank = [0; MAX_LEVEL];
+        let mut x = HEAD;
+        for i in (0..self.level).rev() {
+            rank[i] = if i == self.level - 1 { 0 } else { rank[i + 1] };
+            while let Some(next) = self.nodes[x].levels[i].forward {
+                if !self.nodes[next].before(score, &member) {
+                    break;
+                }
+                rank[i] += self.nodes[x].levels[i].span;
+                x = next;
+            }
+            update[i] = x;
+        }
+
+        let level = random_level();
+        if level > self.level {
+            for i in self.level..level {
+                update[i] = HEAD;
+                rank[i] = 0;
+                self.nodes[HEAD].levels[i].span = self.len;
+            }
+            self.level = level;
+        }
+
+        let idx = self.alloc(Node {
+            member,
+            score,
+            backward: (update[0] != HEAD).then_some(update[0]),
+            levels: vec![Level::default(); level],
+        });
+        for i in 0..level {
+            let prev = self.nodes[update[i]].levels[i];
+            self.nodes[idx].levels[i] = Level {
+                forward: prev.forward,
+                span: prev.span - (rank[0] - rank[i]),
+            };
+            self.nodes[update[i]].levels[i] = Level {
+                forward: Some(idx),
+                span: rank[0] - rank[i] + 1,
+            };
+        }
+        for (i, &prev) in update.iter().enumerate().take(self.level).skip(level) {
+            self.nodes[prev].levels[i].span += 1;
+        }
+
+        if let Some(next) = self.nodes[idx].levels[0].forward {
+            self.nodes[next].backward = Some(idx);
+        }
+        self.len += 1;
+    }
+
+    fn remove(&mut self, score: f64, member: &[u8]) -> bool {
+        let mut update = [HEAD; MAX_LEVEL];
+        let mut x = HEAD;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.nodes[x].levels[i].forward {
+                if !self.nodes[next].before(score, member) {
+                    break;
+                }
+                x = next;
+            }
+            update[i] = x;
+        }
+
+        let idx = match self.nodes[x].levels[0].forward {
+            Some(idx) if self.nodes[idx].score == score && self.nodes[idx].member == member => idx,
+            _ => return false,
+        };
+        for (i, &x) in update.iter().enumerate().take(self.level) {
+            let prev = self.nodes[x].levels[i];
+            if prev.forward == Some(idx) {
+                let removed = self.nodes[idx].levels[i];
+                self.nodes[x].levels[i] = Level {
+                    forward: removed.forward,
+                    span: prev.span + removed.span - 1,
+                };
+            } else {
+                self.nodes[x].levels[i].span -= 1;
+            }
+        }
+
+        let backward = self.nodes[idx].backward;
+        if let Some(next) = self.nodes[idx].levels[0].forward {
+            self.nodes[next].backward = backward;
+        }
+        while self.level > 1 && self.nodes[HEAD].levels[self.level - 1].forward.is_none() {
+            self.level -= 1;
+        }
+        self.len -= 1;
+
+        self.nodes[idx] = Node {
+            member: Bytes::new(),
+            score: 0.0,
+            backward: None,
+            levels: vec![],
+        };
+        self.free.push(idx);
+        true
+    }
+
+    /// Zero based rank of a member in the list.
+    fn rank(&self, score: f64, member: &[u8]) -> Option<usize> {
+        let mut rank = 0;
+        let mut x = HEAD;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.nodes[x].levels[i].forward {
+                let node = &self.nodes[next];
+                if !(node.before(score, member) || node.score == score && node.member == member) {
+                    break;
+                }
+                rank += self.nodes[x].levels[i].span;
+                x = next;
+            }
+            if x != HEAD && self.nodes[x].member == member {
+                return Some(rank - 1);
+            }
+        }
+        None
+    }
+
+    /// Node at the zero based `rank`.
+    fn by_rank(&self, rank: usize) -> Option<usize> {
+        let target = rank + 1;
+        let mut traversed = 0;
+        let mut x = HEAD;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.nodes[x].levels[i].forward {
+                if traversed + self.nodes[x].levels[i].span > target {
+                    break;
+                }
+                traversed += self.nodes[x].levels[i].span;
+                x = next;
+            }
+            if traversed == target {
+                return Some(x);
+            }
+        }
+        None
+    }
+
+    /// Number of leading nodes satisfying `pred`, which must hold for a prefix of the list.
+    fn prefix_len(&self, pred: impl Fn(&Node) -> bool) -> usize {
+        let mut len = 0;
+        let mut x = HEAD;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.nodes[x].levels[i].forward {
+                if !pred(&self.nodes[next]) {
+                    break;
+                }
+                len += self.nodes[x].levels[i].span;
+                x = next;
+            }
+        }
+        len
+    }
+
+    fn alloc(&mut self, node: Node) -> usize {
+        match self.free.pop() {
+            Some(idx) => {
+                self.nodes[idx] = node;
+                idx
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+}
+
+impl Node {
+    /// Whether this node sorts strictly before `score` and `member`.
+    fn before(&self, score: f64, member: &[u8]) -> bool {
+        self.score < score || self.score == score && &self.member[..] < member
+    }
+}
+
+/// Level of a new node, each level being a quarter as likely as the one below.
+fn random_level() -> usize {
+    let mut level = 1;
+    while level < MAX_LEVEL && random::next_u64().is_multiple_of(4) {
+        level += 1;
+    }
+    level
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn members(range: Vec<(Bytes, f64)>) -> Vec<Bytes> {
+        range.into_iter().map(|(member, _)| member).collect()
+    }
+
+    #[test]
+    fn test_order_and_ranks() {
+        let mut zset = ZSet::default();
+        for i in 0..1000 {
+            // insert out of order so the list has to link nodes in the middle
+            let score = ((i * 7919) % 1000) as f64;
+            assert!(zset.insert(Bytes::from(format!("m{}", score)), score));
+        }
+        assert!(!zset.insert(Bytes::from("m5"), 5.0));
+        assert_eq!(zset.len(), 1000);
+
+        for rank in [0, 1, 500, 998, 999] {
+            let member = Bytes::from(format!("m{}", rank));
+            assert_eq!(zset.rank(&member), Some(rank));
+            assert_eq!(
+                members(zset.range((rank, rank + 1), false, 0, None)),
+                [member]
+            );
+        }
+
+        // moving a member reorders it, removing one shifts the ranks after it
+        zset.insert(Bytes::from("m0"), 2000.0);
+        assert_eq!(zset.rank(b"m0"), Some(999));
+        assert!(zset.remove(b"m500"));
+        assert!(!zset.remove(b"m500"));
+        assert_eq!(zset.rank(b"m501"), Some(499));
+        assert_eq!(zset.len(), 999);
+
+        let all = members(zset.range((0, zset.len()), false, 0, None));
+        let mut sorted = all.clone();
+        sorted.sort_by_key(|member| zset.score(member).unwrap() as i64);
+        assert_eq!(all, sorted);
+    }
+
+    #[test]
+    fn test_ranges() {
+        let mut zset = ZSet::default();
+        for (member, score) in [("a", 1.0), ("b", 2.0), ("c", 2.0), ("d", 3.0)] {
+            zset.insert(Bytes::from(member), score);
+        }
+
+        let ranks = zset.score_ranks(&ScoreBound::Exclusive(1.0), &ScoreBound::Inclusive(2.0));
+        assert_eq!(ranks, (1, 3));
+        assert_eq!(members(zset.range(ranks, false, 0, None)), ["b", "c"]);
+        assert_eq!(members(zset.range(ranks, true, 0, None)), ["c", "b"]);
+        assert_eq!(members(zset.range(ranks, true, 1, Some(5))), ["b"]);
+        let ranks = zset.score_ranks(&ScoreBound::Inclusive(5.0), &ScoreBound::Inclusive(1.0));
+        assert!(zset.range(ranks, false, 0, None).is_empty());
+
+        let ranks = zset.lex_ranks(&LexBound::Exclusive(Bytes::from("a")), &LexBound::Max);
+        assert_eq!(ranks, (1, 4));
+        let ranks = zset.lex_ranks(&LexBound::Min, &LexBound::Inclusive(Bytes::from("b")));
+        assert_eq!(members(zset.range(ranks, false, 0, None)), ["a", "b"]);
+
+        assert_eq!(members(zset.pop(2, true)), ["d", "c"]);
+        assert_eq!(members(zset.pop(5, false)), ["a", "b"]);
+        assert!(zset.is_empty());
+    }
+}
@@ -0,0 +1,181 @@
+use std::fmt;
+
+use crate::RESPType;
+
+/// Every failure surfaced by the connection, the command parsers and the client.
+///
+/// Command level variants render as the reply redis sends for them, prefixed with their
+/// error code, so the server can report them as-is.
+#[derive(Debug)]
+pub enum Error {
+    /// Malformed data on the wire, the stream can't be trusted afterwards.
+    Protocol(String),
+    Io(std::io::Error),
+    /// Wrong number of arguments for the named command.
+    WrongArity(String),
+    Syntax,
+    NotInteger,
+    WrongType,
+    /// Any other command failure, reported with the generic `ERR` code.
+    Command(String),
+    /// Error carrying its own code, e.g. one reported by the server as `NOAUTH ...`.
+    Server {
+        code: String,
+        message: String,
+    },
+    /// A reply whose type doesn't fit the command that was sent.
+    UnexpectedReply(RESPType),
+}
+
+impl Error {
+    pub fn protocol(msg: impl Into<String>) -> Self {
+        Error::Protocol(msg.into())
+    }
+
+    pub fn command(msg: impl Into<String>) -> Self {
+        Error::Command(msg.into())
+    }
+
+    pub fn server(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Error::Server {
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Splits an error reply into its code and message, the code being the leading
+    /// uppercase word as in `WRONGTYPE Operation against a key ...`.
+    pub fn from_reply(reply: &str) -> Self {
+        match reply.split_once(' ') {
+            Some((code, message))
+                if !code.is_empty() && code.bytes().all(|c| c.is_ascii_uppercase()) =>
+            {
+                Error::server(code, message)
+            }
+            _ => Error::server("ERR", reply),
+        }
+    }
+
+    /// The redis error code this error is reported with.
+    pub fn code(&self) -> &str {
+        match self {
+            Error::WrongType => "WRONGTYPE",
+            Error::Server { code, .. } => code,
+            _ => "ERR",
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Protocol(msg) => write!(f, "ERR Protocol error: {}", msg),
+            Error::Io(e) => write!(f, "{}", e),
+            Error::WrongArity(cmd) => write!(
+                f,
+                "ERR wrong number of arguments for '{}' command",
+                cmd.to_lowercase()
+            ),
+            Error::Syntax => write!(f, "ERR syntax error"),
+            Error::NotInteger => write!(f, "ERR value is not an integer or out of range"),
+            Error::WrongType => write!(
+                f,
+                "WRONGTYPE Operation against a key holding the wrong kind of value"
+            ),
+            Error::Command(msg) => write!(f, "ERR {}", msg),
+            Error::Server { code, message } => write!(f, "{} {}", code, message),
+            Error::UnexpectedReply(resp) => write!(f, "unexpected resp data type: {:?}", resp),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<Error> for RESPType {
+    fn from(err: Error) -> RESPType {
+        RESPType::Error(err.to_string())
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<std::str::Utf8Error> for Error {
+    fn from(e: std::str::Utf8Error) -> Self {
+        Error::Protocol(e.to_string())
+    }
+}
+
+impl From<std::string::FromUtf8Error> for Error {
+    fn from(e: std::string::FromUtf8Error) -> Self {
+        Error::Protocol(e.to_string())
+    }
+}
+
+impl From<std::num::ParseIntError> for Error {
+    fn from(e: std::num::ParseIntError) -> Self {
+        Error::Protocol(e.to_string())
+    }
+}
+
+impl From<std::num::ParseFloatError> for Error {
+    fn from(e: std::num::ParseFloatError) -> Self {
+        Error::Protocol(e.to_string())
+    }
+}
+
+impl From<fmt::Error> for Error {
+    fn from(e: fmt::Error) -> Self {
+        Error::Protocol(e.to_string())
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reply_format() {
+        assert_eq!(
+            Error::WrongArity("GET".into()).to_string(),
+            "ERR wrong number of arguments for 'get' command"
+        );
+        assert_eq!(
+            Error::protocol("invalid bulk length").to_string(),
+            "ERR Protocol error: invalid bulk length"
+        );
+        assert_eq!(
+            RESPType::from(Error::WrongType),
+            RESPType::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".into()
+            )
+        );
+    }
+
+    #[test]
+    fn test_from_reply() {
+        let err = Error::from_reply("WRONGTYPE Operation against a key");
+        assert_eq!(err.code(), "WRONGTYPE");
+        assert!(
+            matches!(err, Error::Server { ref message, .. } if message == "Operation against a key")
+        );
+
+        let err = Error::from_reply("NOAUTH Authentication required.");
+        assert_eq!(err.code(), "NOAUTH");
+
+        let err = Error::from_reply("something went wrong");
+        assert_eq!(err.code(), "ERR");
+        assert_eq!(err.to_string(), "ERR something went wrong");
+    }
+}
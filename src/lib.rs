@@ -6,8 +6,18 @@ pub use connection::Connection;
 pub mod client;
 pub use client::Client;
 
+pub mod error;
+pub use error::Error;
+
+pub mod db;
+pub use db::Db;
+
 pub mod resp;
 pub use resp::RESPType;
 
-pub type Error = Box<dyn std::error::Error + Send + Sync>;
+mod random;
+
+/// Redis version reported to clients, matching the command set implemented here.
+pub const REDIS_VERSION: &str = "7.4.0";
+
 pub type Result<T> = std::result::Result<T, Error>;
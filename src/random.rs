@@ -0,0 +1,30 @@
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+thread_local! {
+    static STATE: Cell<u64> = Cell::new(RandomState::new().build_hasher().finish() | 1);
+}
+
+/// Next number of a per thread xorshift generator, good enough to pick random elements but
+/// not for anything security related.
+pub(crate) fn next_u64() -> u64 {
+    STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        state.set(x);
+        x
+    })
+}
+
+/// Random index below `n`, which must not be zero.
+pub(crate) fn below(n: usize) -> usize {
+    (next_u64() % n as u64) as usize
+}
+
+/// Random float in `[0, 1)`.
+pub(crate) fn fraction() -> f64 {
+    (next_u64() >> 11) as f64 / (1u64 << 53) as f64
+}
@@ -1,15 +1,28 @@
-use bytes::{Buf, Bytes};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use std::fmt::Write;
 use std::io::Cursor;
 
+use crate::Error;
+
 const STRING: u8 = b'+';
 const ERROR: u8 = b'-';
 const INTEGER: u8 = b':';
 const BULK: u8 = b'$';
 const ARRAY: u8 = b'*';
+const NULL: u8 = b'_';
+const BOOLEAN: u8 = b'#';
+const DOUBLE: u8 = b',';
+const BIG_NUMBER: u8 = b'(';
+const BLOB_ERROR: u8 = b'!';
+const VERBATIM: u8 = b'=';
+const MAP: u8 = b'%';
+const SET: u8 = b'~';
+const ATTRIBUTE: u8 = b'|';
+const PUSH: u8 = b'>';
 
-type ResultOpt<T> = std::result::Result<Option<T>, Box<dyn std::error::Error + Send + Sync>>;
+type ResultOpt<T> = crate::Result<Option<T>>;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum RESPType {
     String(String),
     Error(String),
@@ -17,219 +30,565 @@ pub enum RESPType {
     Bulk(Bytes),
     Array(Vec<RESPType>),
     Null,
+    /// Null standing in for an array, which RESP2 spells differently from a null bulk string.
+    NullArray,
+    // RESP3 only types, downgraded to their RESP2 counterparts when serializing for RESP2
+    Map(Vec<(RESPType, RESPType)>),
+    Set(Vec<RESPType>),
+    Double(f64),
+    Boolean(bool),
+    BigNumber(String),
+    /// Verbatim string with its three character format, e.g. `txt` or `mkd`.
+    Verbatim(String, Bytes),
+    BlobError(Bytes),
+    /// Out of band metadata describing the reply that follows it.
+    Attribute(Vec<(RESPType, RESPType)>),
+    Push(Vec<RESPType>),
+}
+
+/// Protocol version spoken on a connection, selected with HELLO.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Protocol {
+    #[default]
+    Resp2,
+    Resp3,
+}
+
+/// Bounds on what a peer may send, exceeding any of them is a protocol error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolLimits {
+    pub max_bulk_len: usize,
+    pub max_array_len: usize,
+    pub max_depth: usize,
+    /// Longest inline command or header line.
+    pub max_inline_len: usize,
+    /// Most unparsed bytes a connection buffers while waiting for a frame to complete.
+    pub max_query_buffer: usize,
+}
+
+impl Default for ProtocolLimits {
+    fn default() -> Self {
+        ProtocolLimits {
+            max_bulk_len: 512 * 1024 * 1024,
+            max_array_len: 1024 * 1024,
+            max_depth: 32,
+            max_inline_len: 64 * 1024,
+            max_query_buffer: 1024 * 1024 * 1024,
+        }
+    }
+}
+
+impl ProtocolLimits {
+    /// Limits that never trigger, for trusted peers.
+    pub fn unlimited() -> Self {
+        ProtocolLimits {
+            max_bulk_len: usize::MAX,
+            max_array_len: usize::MAX,
+            max_depth: usize::MAX,
+            max_inline_len: usize::MAX,
+            max_query_buffer: usize::MAX,
+        }
+    }
+}
+
+enum Element {
+    Scalar,
+    Aggregate(usize),
 }
 
-pub struct RESPParser {}
+/// Incremental frame parser. Progress through a partially received frame is kept between
+/// calls so a large frame arriving in many reads is only scanned once.
+#[derive(Default)]
+pub struct RESPParser {
+    limits: ProtocolLimits,
+    // bytes of the pending frame already known to be well formed
+    checked: usize,
+    // elements still expected by every aggregate opened in the pending frame
+    pending: Vec<usize>,
+}
 
 // public functions
 impl RESPParser {
-    pub fn parse(src: &mut Cursor<&[u8]>) -> ResultOpt<RESPType> {
-        if let Some(_) = src.get_ref().windows(2).find(|window| window == b"\r\n") {
-            match Self::get_u8(src) {
-                None => Ok(None),
-                Some(char) => match char {
-                    STRING => {
-                        Self::to_result(Self::parse_simple(src)?, |x: String| RESPType::String(x))
-                    }
-                    ERROR => {
-                        Self::to_result(Self::parse_simple(src)?, |x: String| RESPType::Error(x))
-                    }
-                    INTEGER => {
-                        Self::to_result(Self::parse_integer(src)?, |x: i64| RESPType::Integer(x))
-                    }
-                    BULK => Self::to_result(Self::parse_bulk(src)?, |x: Option<Bytes>| match x {
-                        None => RESPType::Null,
-                        Some(val) => RESPType::Bulk(val),
-                    }),
-                    ARRAY => {
-                        Self::to_result(Self::parse_array(src)?, |x: Option<Vec<RESPType>>| match x
-                        {
-                            None => RESPType::Null,
-                            Some(val) => RESPType::Array(val),
-                        })
-                    }
-                    _ => todo!(),
-                },
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_limits(limits: ProtocolLimits) -> Self {
+        RESPParser {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    pub fn limits(&self) -> &ProtocolLimits {
+        &self.limits
+    }
+
+    /// Splits the next complete frame off the front of `buf`, leaving `buf` untouched if the
+    /// frame has not been fully received yet. Bulk payloads are slices of the split off
+    /// frame, so they are never copied.
+    ///
+    /// Anything not starting with a type marker is read as an inline command the way telnet
+    /// users type them, e.g. `SET key "hello world"`.
+    pub fn parse(&mut self, buf: &mut BytesMut) -> ResultOpt<RESPType> {
+        loop {
+            let len = match self.check(&buf[..]) {
+                Ok(None) => return Ok(None),
+                Ok(Some(len)) => len,
+                Err(e) => {
+                    self.reset();
+                    return Err(e);
+                }
+            };
+
+            let frame = buf.split_to(len).freeze();
+            match Self::decode(&frame)? {
+                // blank lines are ignored
+                None => continue,
+                Some(resp) => return Ok(Some(resp)),
             }
-        } else {
-            // no CRLF so no full message
-            Ok(None)
         }
     }
 }
 
-// private helper functions
+// private checking functions, these only validate framing and never allocate
 impl RESPParser {
-    fn get_u8(src: &mut Cursor<&[u8]>) -> Option<u8> {
-        if src.has_remaining() {
-            return Some(src.get_u8());
+    /// Returns the length of the frame at the start of `buf` once all of it is buffered.
+    fn check(&mut self, buf: &[u8]) -> ResultOpt<usize> {
+        let mut src = Cursor::new(buf);
+
+        if self.checked == 0 {
+            match buf.first() {
+                None => return Ok(None),
+                Some(marker) if !Self::is_marker(*marker) => return self.check_inline(&mut src),
+                Some(_) => {}
+            }
         }
+        src.set_position(self.checked as u64);
 
-        None
+        loop {
+            let start = src.position() as usize;
+            match self.check_element(&mut src)? {
+                None => {
+                    self.checked = start;
+                    return Ok(None);
+                }
+                Some(Element::Aggregate(len)) if len > 0 => {
+                    if self.pending.len() >= self.limits.max_depth {
+                        return Err(Error::protocol("nesting too deep"));
+                    }
+                    self.pending.push(len);
+                    continue;
+                }
+                Some(_) => {}
+            }
+
+            // an element is complete, close every aggregate it completes
+            loop {
+                match self.pending.last_mut() {
+                    None => {
+                        self.reset();
+                        return Ok(Some(src.position() as usize));
+                    }
+                    Some(remaining) => {
+                        *remaining -= 1;
+                        if *remaining > 0 {
+                            break;
+                        }
+                        self.pending.pop();
+                    }
+                }
+            }
+        }
     }
 
-    fn to_result<T, F>(option: Option<T>, constructor: F) -> ResultOpt<RESPType>
-    where
-        F: FnOnce(T) -> RESPType,
-    {
-        match option {
+    fn check_inline(&mut self, src: &mut Cursor<&[u8]>) -> ResultOpt<usize> {
+        // inline commands may be terminated by a bare LF
+        match Self::find_lf(src) {
+            None if src.get_ref().len() > self.limits.max_inline_len => {
+                Err(Error::protocol("too big inline request"))
+            }
             None => Ok(None),
-            Some(val) => Ok(Some(constructor(val))),
+            Some(end) if end > self.limits.max_inline_len => {
+                Err(Error::protocol("too big inline request"))
+            }
+            Some(end) => Ok(Some(end + 1)),
         }
     }
-}
 
-// private parsing functions
-impl RESPParser {
-    fn parse_simple(src: &mut Cursor<&[u8]>) -> ResultOpt<String> {
-        let start = src.position() as usize;
-        let size = match src.get_ref()[start..]
-            .windows(2)
-            .position(|window| window == b"\r\n")
-        {
-            // incomplete frame
+    fn check_element(&self, src: &mut Cursor<&[u8]>) -> ResultOpt<Element> {
+        let marker = match Self::get_u8(src) {
             None => return Ok(None),
-            Some(val) => val,
+            Some(marker) => marker,
         };
-        let mut result = String::new();
-
-        for _ in 0..size {
-            match Self::get_u8(src).unwrap() {
-                // start - 1 because of type identifier
-                b'\r' => return Err("CR not allowed in simples".into()),
-                b'\n' => return Err("LF not allowed in simples".into()),
-                c => result.push(c.into()),
+        let max_line = self.limits.max_inline_len;
+
+        match marker {
+            STRING | ERROR | INTEGER | NULL | BOOLEAN | DOUBLE | BIG_NUMBER => {
+                Ok(Self::line(src, max_line)?.map(|_| Element::Scalar))
+            }
+            BULK | BLOB_ERROR | VERBATIM => {
+                let len = match Self::length(src, max_line)? {
+                    None => return Ok(None),
+                    // null bulk
+                    Some(None) => return Ok(Some(Element::Scalar)),
+                    Some(Some(len)) if len > self.limits.max_bulk_len => {
+                        return Err(Error::protocol("invalid bulk length"))
+                    }
+                    Some(Some(len)) => len,
+                };
+                let start = src.position() as usize;
+                // the length is only bounded by the limits, which may be unlimited
+                let end = start
+                    .checked_add(len)
+                    .and_then(|end| end.checked_add(2))
+                    .ok_or_else(|| Error::protocol("invalid bulk length"))?;
+                if src.get_ref().len() < end {
+                    // incomplete frame
+                    return Ok(None);
+                }
+                if src.get_ref()[end - 2..end] != *b"\r\n" {
+                    return Err(Error::protocol(
+                        "Unexpected symbols at end of bulk, either wrong length or misformed string",
+                    ));
+                }
+                src.set_position(end as u64);
+                Ok(Some(Element::Scalar))
+            }
+            ARRAY | SET | PUSH | MAP | ATTRIBUTE => {
+                let len = match Self::length(src, max_line)? {
+                    None => return Ok(None),
+                    // null array
+                    Some(None) => return Ok(Some(Element::Scalar)),
+                    Some(Some(len)) if len > self.limits.max_array_len => {
+                        return Err(Error::protocol("invalid multibulk length"))
+                    }
+                    Some(Some(len)) => len,
+                };
+                match marker {
+                    MAP | ATTRIBUTE => match len.checked_mul(2) {
+                        Some(len) => Ok(Some(Element::Aggregate(len))),
+                        None => Err(Error::protocol("invalid multibulk length")),
+                    },
+                    _ => Ok(Some(Element::Aggregate(len))),
+                }
             }
+            c => Err(Error::protocol(format!(
+                "unknown type marker '{}'",
+                c.escape_ascii()
+            ))),
         }
-        src.advance(2);
-        // simple {string, error} type
-        Ok(Some(result))
     }
 
-    fn parse_integer(src: &mut Cursor<&[u8]>) -> ResultOpt<i64> {
-        let start = src.position() as usize;
-        let size = match src.get_ref()[start..]
-            .windows(2)
-            .position(|window| window == b"\r\n")
-        {
-            // incomplete frame
-            None => return Ok(None),
-            Some(val) => val,
-        };
-        let mut result: i64 = 0;
-
-        let sign: i64 = match Self::get_u8(src).unwrap() {
-            b'-' => -1,
-            b'+' => 1,
-            d @ (b'0'..=b'9') => {
-                result += (d - b'0') as i64;
-                1
+    fn reset(&mut self) {
+        self.checked = 0;
+        self.pending.clear();
+    }
+}
+
+// private decoding functions, the frame has already been checked to be complete
+impl RESPParser {
+    fn decode(frame: &Bytes) -> ResultOpt<RESPType> {
+        if !Self::is_marker(frame[0]) {
+            let mut line = frame.strip_suffix(b"\n").unwrap_or(&frame[..]);
+            if let Some(stripped) = line.strip_suffix(b"\r") {
+                line = stripped;
             }
-            _ => return Err("No integer found after integer type declaration".into()),
-        };
+            let args = Self::split_args(line)?;
+            if args.is_empty() {
+                return Ok(None);
+            }
+            return Ok(Some(RESPType::Array(
+                args.into_iter().map(RESPType::Bulk).collect(),
+            )));
+        }
 
-        for _ in 0..size - 1 {
-            match Self::get_u8(src).unwrap() {
-                b'\r' => return Err("CR not allowed in integers".into()),
-                b'\n' => return Err("LF not allowed in integers".into()),
-                d @ (b'0'..=b'9') => result = (result * 10) + (d - b'0') as i64,
-                _ => return Err("Digits are the only thing allowed in integers".into()),
+        let mut src = Cursor::new(&frame[..]);
+        Ok(Some(Self::decode_frame(&mut src, frame)?))
+    }
+
+    fn decode_frame(src: &mut Cursor<&[u8]>, frame: &Bytes) -> crate::Result<RESPType> {
+        let marker = src.get_u8();
+        match marker {
+            STRING => Ok(RESPType::String(Self::decode_simple(src)?)),
+            ERROR => Ok(RESPType::Error(Self::decode_simple(src)?)),
+            INTEGER => Ok(RESPType::Integer(Self::decode_integer(src)?)),
+            NULL => match Self::next_line(src)? {
+                b"" => Ok(RESPType::Null),
+                _ => Err(Error::protocol(
+                    "Unexpected data after null type declaration",
+                )),
+            },
+            BOOLEAN => match Self::next_line(src)? {
+                b"t" => Ok(RESPType::Boolean(true)),
+                b"f" => Ok(RESPType::Boolean(false)),
+                _ => Err(Error::protocol("Booleans can only be t or f")),
+            },
+            DOUBLE => Ok(RESPType::Double(Self::decode_double(src)?)),
+            BIG_NUMBER => Ok(RESPType::BigNumber(Self::decode_big_number(src)?)),
+            BULK | BLOB_ERROR | VERBATIM => {
+                let val = match Self::next_length(src)? {
+                    None => return Ok(RESPType::Null),
+                    Some(len) => Self::decode_blob(src, frame, len),
+                };
+                match marker {
+                    BULK => Ok(RESPType::Bulk(val)),
+                    BLOB_ERROR => Ok(RESPType::BlobError(val)),
+                    _ if val.len() >= 4 && val[3] == b':' => Ok(RESPType::Verbatim(
+                        String::from_utf8(val[..3].to_vec())?,
+                        val.slice(4..),
+                    )),
+                    _ => Err(Error::protocol(
+                        "Verbatim strings need a three character format",
+                    )),
+                }
+            }
+            ARRAY | SET | PUSH => {
+                let len = match Self::next_length(src)? {
+                    None if marker == ARRAY => return Ok(RESPType::NullArray),
+                    None => return Ok(RESPType::Null),
+                    Some(len) => len,
+                };
+                let mut result = Vec::with_capacity(len);
+                for _ in 0..len {
+                    result.push(Self::decode_frame(src, frame)?);
+                }
+                match marker {
+                    ARRAY => Ok(RESPType::Array(result)),
+                    SET => Ok(RESPType::Set(result)),
+                    _ => Ok(RESPType::Push(result)),
+                }
+            }
+            MAP | ATTRIBUTE => {
+                let len = Self::next_length(src)?.unwrap_or(0);
+                let mut result = Vec::with_capacity(len);
+                for _ in 0..len {
+                    let key = Self::decode_frame(src, frame)?;
+                    let val = Self::decode_frame(src, frame)?;
+                    result.push((key, val));
+                }
+                match marker {
+                    MAP => Ok(RESPType::Map(result)),
+                    _ => Ok(RESPType::Attribute(result)),
+                }
             }
+            c => Err(Error::protocol(format!(
+                "Unknown type marker '{}'",
+                c.escape_ascii()
+            ))),
         }
+    }
 
-        src.advance(2);
-        // integer type
-        Ok(Some(result * sign))
+    fn decode_simple(src: &mut Cursor<&[u8]>) -> crate::Result<String> {
+        Ok(String::from_utf8(Self::next_line(src)?.to_vec())?)
     }
 
-    fn parse_bulk(src: &mut Cursor<&[u8]>) -> ResultOpt<Option<Bytes>> {
-        let start = src.position() as usize;
-        // check if incomplete frame
-        let size_int = match src.get_ref()[start..]
-            .windows(2)
-            .position(|window| window == b"\r\n")
-        {
-            None => return Ok(None),
-            Some(val) => val,
-        };
+    fn decode_integer(src: &mut Cursor<&[u8]>) -> crate::Result<i64> {
+        match std::str::from_utf8(Self::next_line(src)?)?.parse::<i64>() {
+            Ok(val) => Ok(val),
+            Err(_) => Err(Error::protocol(
+                "Digits are the only thing allowed in integers",
+            )),
+        }
+    }
 
-        // check if null type
-        if src.get_ref()[start..start + 2] == *b"-1" {
-            src.advance(4);
-            return Ok(Some(None));
+    fn decode_double(src: &mut Cursor<&[u8]>) -> crate::Result<f64> {
+        match Self::next_line(src)? {
+            b"inf" => Ok(f64::INFINITY),
+            b"-inf" => Ok(f64::NEG_INFINITY),
+            b"nan" => Ok(f64::NAN),
+            val => Ok(std::str::from_utf8(val)?.parse::<f64>()?),
+        }
+    }
+
+    fn decode_big_number(src: &mut Cursor<&[u8]>) -> crate::Result<String> {
+        let val = Self::decode_simple(src)?;
+        let digits = val.strip_prefix(['-', '+']).unwrap_or(&val);
+        if digits.is_empty() || !digits.bytes().all(|d| d.is_ascii_digit()) {
+            return Err(Error::protocol(
+                "Digits are the only thing allowed in big numbers",
+            ));
         }
+        Ok(val)
+    }
 
-        // get len of bulk string
-        let len = str::parse::<usize>(&String::from_utf8(
-            src.get_ref()[start..(start + size_int)].to_vec(),
-        )?)?;
+    fn decode_blob(src: &mut Cursor<&[u8]>, frame: &Bytes, len: usize) -> Bytes {
+        let start = src.position() as usize;
+        src.advance(len + 2);
+        frame.slice(start..start + len)
+    }
 
-        src.advance(size_int + 2);
+    fn next_line<'a>(src: &mut Cursor<&'a [u8]>) -> crate::Result<&'a [u8]> {
+        Self::line(src, usize::MAX)?.ok_or_else(|| Error::protocol("incomplete frame"))
+    }
 
-        let mut result = String::new();
+    fn next_length(src: &mut Cursor<&[u8]>) -> crate::Result<Option<usize>> {
+        Self::length(src, usize::MAX)?.ok_or_else(|| Error::protocol("incomplete frame"))
+    }
+    /// Splits an inline command into arguments following the quoting rules of redis-cli:
+    /// double quotes support escapes such as `\n` and `\x41`, single quotes only `\'`.
+    fn split_args(line: &[u8]) -> crate::Result<Vec<Bytes>> {
+        let mut args = vec![];
+        let mut i = 0;
 
-        if len == 0 {
-            return Ok(Some(Some(Bytes::from(""))));
-        }
+        loop {
+            while i < line.len() && line[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            if i == line.len() {
+                return Ok(args);
+            }
 
-        for _ in 0..len {
-            if src.has_remaining() {
-                result.push(src.get_u8().into());
-            } else {
-                return Ok(None);
+            let mut arg = Vec::new();
+            let mut in_double = false;
+            let mut in_single = false;
+            loop {
+                let c = line.get(i).copied();
+                if in_double {
+                    match c {
+                        None => return Err(Error::protocol("unbalanced quotes in request")),
+                        Some(b'\\')
+                            if i + 3 < line.len()
+                                && line[i + 1] == b'x'
+                                && line[i + 2].is_ascii_hexdigit()
+                                && line[i + 3].is_ascii_hexdigit() =>
+                        {
+                            let hex = std::str::from_utf8(&line[i + 2..i + 4])?;
+                            arg.push(u8::from_str_radix(hex, 16)?);
+                            i += 3;
+                        }
+                        Some(b'\\') if i + 1 < line.len() => {
+                            i += 1;
+                            arg.push(match line[i] {
+                                b'n' => b'\n',
+                                b'r' => b'\r',
+                                b't' => b'\t',
+                                b'b' => 0x08,
+                                b'a' => 0x07,
+                                c => c,
+                            });
+                        }
+                        Some(b'"') => {
+                            // closing quote must be followed by a space or nothing
+                            if matches!(line.get(i + 1), Some(c) if !c.is_ascii_whitespace()) {
+                                return Err(Error::protocol("unbalanced quotes in request"));
+                            }
+                            i += 1;
+                            break;
+                        }
+                        Some(c) => arg.push(c),
+                    }
+                } else if in_single {
+                    match c {
+                        None => return Err(Error::protocol("unbalanced quotes in request")),
+                        Some(b'\\') if line.get(i + 1) == Some(&b'\'') => {
+                            i += 1;
+                            arg.push(b'\'');
+                        }
+                        Some(b'\'') => {
+                            if matches!(line.get(i + 1), Some(c) if !c.is_ascii_whitespace()) {
+                                return Err(Error::protocol("unbalanced quotes in request"));
+                            }
+                            i += 1;
+                            break;
+                        }
+                        Some(c) => arg.push(c),
+                    }
+                } else {
+                    match c {
+                        None => break,
+                        Some(c) if c.is_ascii_whitespace() => break,
+                        Some(b'"') => in_double = true,
+                        Some(b'\'') => in_single = true,
+                        Some(c) => arg.push(c),
+                    }
+                }
+                i += 1;
             }
+
+            args.push(Bytes::from(arg));
         }
+    }
+}
 
-        match (Self::get_u8(src), Self::get_u8(src)) {
-            (Some(b'\r'), Some(b'\n')) => {}
-            (None, _) => return Ok(None),
-            (_, None) => return Ok(None),
-            _ => {
-                return Err(
-                    "Unexpected symbols at end of bulk, either wrong length or misformed string"
-                        .into(),
-                )
-            }
+// private helper functions
+impl RESPParser {
+    fn is_marker(c: u8) -> bool {
+        matches!(
+            c,
+            STRING
+                | ERROR
+                | INTEGER
+                | BULK
+                | ARRAY
+                | NULL
+                | BOOLEAN
+                | DOUBLE
+                | BIG_NUMBER
+                | BLOB_ERROR
+                | VERBATIM
+                | MAP
+                | SET
+                | ATTRIBUTE
+                | PUSH
+        )
+    }
+
+    fn get_u8(src: &mut Cursor<&[u8]>) -> Option<u8> {
+        if src.has_remaining() {
+            return Some(src.get_u8());
         }
 
-        // bulk type
-        Ok(Some(Some(result.into())))
+        None
+    }
+
+    /// Position of the next LF, only looking at bytes after the cursor.
+    fn find_lf(src: &Cursor<&[u8]>) -> Option<usize> {
+        let start = src.position() as usize;
+        src.get_ref()[start..]
+            .iter()
+            .position(|c| *c == b'\n')
+            .map(|val| start + val)
     }
 
-    fn parse_array(src: &mut Cursor<&[u8]>) -> ResultOpt<Option<Vec<RESPType>>> {
+    /// Reads up to the next CRLF, which is consumed but not returned.
+    fn line<'a>(src: &mut Cursor<&'a [u8]>, max_len: usize) -> ResultOpt<&'a [u8]> {
+        let buf: &'a [u8] = src.get_ref();
         let start = src.position() as usize;
-        // check if incomplete frame
-        let size_int = match src.get_ref()[start..]
-            .windows(2)
-            .position(|window| window == b"\r\n")
-        {
+        let end = match Self::find_lf(src) {
+            None if buf.len() - start > max_len => return Err(Error::protocol("too big line")),
+            // incomplete frame
             None => return Ok(None),
+            Some(val) if val - start > max_len => return Err(Error::protocol("too big line")),
             Some(val) => val,
         };
 
-        // check if null type
-        if src.get_ref()[start..start + 2] == *b"-1" {
-            src.advance(4);
-            return Ok(Some(None));
+        if end == start || buf[end - 1] != b'\r' {
+            return Err(Error::protocol("LF not allowed in simples"));
+        }
+        let line = &buf[start..end - 1];
+        if line.contains(&b'\r') {
+            return Err(Error::protocol("CR not allowed in simples"));
         }
 
-        // get len of bulk string
-        let len = str::parse::<usize>(&String::from_utf8(
-            src.get_ref()[start..(start + size_int)].to_vec(),
-        )?)?;
+        src.set_position(end as u64 + 1);
+        Ok(Some(line))
+    }
 
-        src.advance(size_int + 2);
-        let mut result = vec![];
+    /// Reads the length header of a bulk or aggregate, `Some(None)` meaning a RESP2 null.
+    fn length(src: &mut Cursor<&[u8]>, max_len: usize) -> ResultOpt<Option<usize>> {
+        let line = match Self::line(src, max_len)? {
+            None => return Ok(None),
+            Some(line) => line,
+        };
 
-        for _ in 0..len {
-            match Self::parse(src)? {
-                None => return Ok(None),
-                Some(val) => result.push(val),
-            }
+        if line == b"-1" {
+            return Ok(Some(None));
+        }
+        match std::str::from_utf8(line)?.parse::<usize>() {
+            Ok(len) => Ok(Some(Some(len))),
+            Err(_) => Err(Error::protocol("invalid length")),
         }
-
-        // array type
-        Ok(Some(Some(result)))
     }
 }
 
@@ -237,63 +596,171 @@ pub struct RESPSerializer {}
 
 // public functions
 impl RESPSerializer {
+    /// Serializes `msg` for a RESP2 connection.
     pub fn serialize(msg: &RESPType) -> crate::Result<Bytes> {
-        match msg {
-            RESPType::String(str) => Ok(Bytes::from(format!("+{}", Self::serialize_simple(str)?))),
-            RESPType::Error(str) => Ok(Bytes::from(format!("-{}", Self::serialize_simple(str)?))),
-            RESPType::Integer(n) => Ok(Bytes::from(format!(":{}\r\n", n))),
-            RESPType::Bulk(str) => Ok(Self::serialize_bulk(str)),
-            RESPType::Array(arr) => Ok(Self::serialize_array(arr)?),
-            RESPType::Null => Ok(Bytes::from("$-1\r\n")),
+        Self::serialize_with(msg, Protocol::Resp2)
+    }
+
+    pub fn serialize_with(msg: &RESPType, protocol: Protocol) -> crate::Result<Bytes> {
+        let mut dst = BytesMut::new();
+        Self::serialize_into(msg, protocol, &mut dst)?;
+        Ok(dst.freeze())
+    }
+
+    /// Appends `msg` to `dst`. On error `dst` is left as it was before the call.
+    pub fn serialize_into(
+        msg: &RESPType,
+        protocol: Protocol,
+        dst: &mut BytesMut,
+    ) -> crate::Result<()> {
+        let start = dst.len();
+        let res = Self::write(msg, protocol, dst);
+        if res.is_err() {
+            dst.truncate(start);
         }
+        res
     }
 }
 
 // private serialization functions
 impl RESPSerializer {
-    fn serialize_simple(src: &String) -> crate::Result<String> {
-        if src
-            .bytes()
-            .find(|char| *char == b'\r' || *char == b'\n')
-            .is_some()
-        {
-            return Err("CR or LF is not allowed in strings and errors".into());
-        } else {
-            return Ok(String::from(src.to_owned() + "\r\n"));
+    fn write(msg: &RESPType, protocol: Protocol, dst: &mut BytesMut) -> crate::Result<()> {
+        match (msg, protocol) {
+            (RESPType::String(str), _) => Self::write_simple(STRING, str, dst),
+            (RESPType::Error(str), _) => Self::write_simple(ERROR, str, dst),
+            (RESPType::Integer(n), _) => Self::write_header(INTEGER, *n, dst),
+            (RESPType::Bulk(str), _) => Self::write_blob(BULK, str, dst),
+            (RESPType::Array(arr), _) => Self::write_array(ARRAY, arr, protocol, dst),
+            (RESPType::Null, Protocol::Resp2) => Self::write_raw(b"$-1\r\n", dst),
+            (RESPType::Null, Protocol::Resp3) => Self::write_raw(b"_\r\n", dst),
+            (RESPType::NullArray, Protocol::Resp2) => Self::write_raw(b"*-1\r\n", dst),
+            (RESPType::NullArray, Protocol::Resp3) => Self::write_raw(b"_\r\n", dst),
+            (RESPType::Map(map), Protocol::Resp2) => {
+                Self::write_header(ARRAY, map.len() as i64 * 2, dst)?;
+                for (key, val) in map {
+                    Self::write(key, protocol, dst)?;
+                    Self::write(val, protocol, dst)?;
+                }
+                Ok(())
+            }
+            (RESPType::Map(map), Protocol::Resp3) => Self::write_pairs(MAP, map, dst),
+            (RESPType::Set(arr), Protocol::Resp2) => Self::write_array(ARRAY, arr, protocol, dst),
+            (RESPType::Set(arr), Protocol::Resp3) => Self::write_array(SET, arr, protocol, dst),
+            (RESPType::Double(n), Protocol::Resp2) => {
+                Self::write_blob(BULK, Self::format_double(*n).as_bytes(), dst)
+            }
+            (RESPType::Double(n), Protocol::Resp3) => {
+                Self::write_simple(DOUBLE, &Self::format_double(*n), dst)
+            }
+            (RESPType::Boolean(b), Protocol::Resp2) => Self::write_header(INTEGER, *b as i64, dst),
+            (RESPType::Boolean(b), Protocol::Resp3) => {
+                Self::write_raw(if *b { b"#t\r\n" } else { b"#f\r\n" }, dst)
+            }
+            (RESPType::BigNumber(n), Protocol::Resp2) => Self::write_blob(BULK, n.as_bytes(), dst),
+            (RESPType::BigNumber(n), Protocol::Resp3) => Self::write_simple(BIG_NUMBER, n, dst),
+            (RESPType::Verbatim(_, str), Protocol::Resp2) => Self::write_blob(BULK, str, dst),
+            (RESPType::Verbatim(format, str), Protocol::Resp3) => {
+                if format.len() != 3 {
+                    return Err(Error::protocol(
+                        "Verbatim string format must be three characters",
+                    ));
+                }
+                Self::write_header(VERBATIM, str.len() as i64 + 4, dst)?;
+                dst.put_slice(format.as_bytes());
+                dst.put_u8(b':');
+                dst.put_slice(str);
+                Self::write_raw(b"\r\n", dst)
+            }
+            (RESPType::BlobError(err), Protocol::Resp2) => {
+                Self::write_simple(ERROR, &String::from_utf8_lossy(err), dst)
+            }
+            (RESPType::BlobError(err), Protocol::Resp3) => Self::write_blob(BLOB_ERROR, err, dst),
+            // RESP2 has no way to carry attributes so they are dropped
+            (RESPType::Attribute(_), Protocol::Resp2) => Ok(()),
+            (RESPType::Attribute(map), Protocol::Resp3) => Self::write_pairs(ATTRIBUTE, map, dst),
+            (RESPType::Push(arr), Protocol::Resp2) => Self::write_array(ARRAY, arr, protocol, dst),
+            (RESPType::Push(arr), Protocol::Resp3) => Self::write_array(PUSH, arr, protocol, dst),
         }
     }
 
-    fn serialize_bulk(src: &Bytes) -> Bytes {
-        let len = src.len();
-        match len {
-            0 => Bytes::from("$0\r\n"),
-            _ => Bytes::from(format!(
-                "${}\r\n{}\r\n",
-                len,
-                String::from_utf8(src[..].to_vec()).unwrap()
-            )),
+    fn write_raw(src: &[u8], dst: &mut BytesMut) -> crate::Result<()> {
+        dst.put_slice(src);
+        Ok(())
+    }
+
+    fn write_header(marker: u8, n: i64, dst: &mut BytesMut) -> crate::Result<()> {
+        dst.put_u8(marker);
+        write!(dst, "{}", n)?;
+        dst.put_slice(b"\r\n");
+        Ok(())
+    }
+
+    fn write_simple(marker: u8, src: &str, dst: &mut BytesMut) -> crate::Result<()> {
+        if src.bytes().any(|char| char == b'\r' || char == b'\n') {
+            return Err(Error::protocol(
+                "CR or LF is not allowed in strings and errors",
+            ));
         }
+        dst.put_u8(marker);
+        dst.put_slice(src.as_bytes());
+        dst.put_slice(b"\r\n");
+        Ok(())
+    }
+
+    fn write_blob(marker: u8, src: &[u8], dst: &mut BytesMut) -> crate::Result<()> {
+        Self::write_header(marker, src.len() as i64, dst)?;
+        dst.put_slice(src);
+        dst.put_slice(b"\r\n");
+        Ok(())
     }
 
-    fn serialize_array(src: &Vec<RESPType>) -> crate::Result<Bytes> {
-        let mut result = String::new();
-        result.push_str(&format!("*{}\r\n", src.len()));
+    fn write_array(
+        marker: u8,
+        src: &[RESPType],
+        protocol: Protocol,
+        dst: &mut BytesMut,
+    ) -> crate::Result<()> {
+        Self::write_header(marker, src.len() as i64, dst)?;
         for resp in src {
-            result.extend(String::from_utf8(Self::serialize(resp)?.to_vec()));
+            Self::write(resp, protocol, dst)?;
+        }
+        Ok(())
+    }
+
+    fn write_pairs(
+        marker: u8,
+        src: &[(RESPType, RESPType)],
+        dst: &mut BytesMut,
+    ) -> crate::Result<()> {
+        Self::write_header(marker, src.len() as i64, dst)?;
+        for (key, val) in src {
+            Self::write(key, Protocol::Resp3, dst)?;
+            Self::write(val, Protocol::Resp3, dst)?;
+        }
+        Ok(())
+    }
+
+    fn format_double(n: f64) -> String {
+        if n.is_nan() {
+            "nan".to_string()
+        } else if n.is_infinite() {
+            if n > 0.0 { "inf" } else { "-inf" }.to_string()
+        } else {
+            n.to_string()
         }
-        Ok(Bytes::from(result))
     }
 }
 
 // unit tests
 #[cfg(test)]
+#[allow(clippy::redundant_pattern_matching, clippy::cmp_owned)]
 mod tests {
     use super::*;
     use bytes::BytesMut;
 
     fn parse(src: &str) -> ResultOpt<RESPType> {
-        let buf = BytesMut::from(src);
-        RESPParser::parse(&mut Cursor::new(&buf[..]))
+        let mut buf = BytesMut::from(src);
+        RESPParser::new().parse(&mut buf)
     }
 
     #[test]
@@ -360,10 +827,9 @@ mod tests {
             parse("$12\r\nhello\r\nworld\r\n"),
             Ok(Some(RESPType::Bulk(ref s))) if **s == *b"hello\r\nworld"
         ));
-        assert!(matches!(
-            parse("$0\r\n"),
-            Ok(Some(RESPType::Bulk(ref s))) if **s == *b""
-        ));
+        // an empty bulk still carries its terminating CRLF
+        assert!(matches!(parse("$0\r\n"), Ok(None)));
+        assert!(parse("$3\r\ngetxx").is_err());
     }
 
     #[test]
@@ -380,7 +846,7 @@ mod tests {
            parse("*2\r\n$3\r\nget\r\n$3\r\nkey\r\n"),
            Ok(Some(RESPType::Array(ref vec))) if vec.eq(&vec![RESPType::Bulk(Bytes::from("get")), RESPType::Bulk(Bytes::from("key"))])
         ));
-        assert!(matches!(parse("*-1\r\n"), Ok(Some(RESPType::Null))));
+        assert!(matches!(parse("*-1\r\n"), Ok(Some(RESPType::NullArray))));
         assert!(matches!(parse("*2\r\n$3\r\nget\r\n$3\r\nkey"), Ok(None)));
         assert!(matches!(parse("*2\r\n$3\r\nget\r\n$3\r\nkey\r"), Ok(None)));
     }
@@ -496,5 +962,332 @@ mod tests {
             RESPSerializer::serialize(&RESPType::Null),
             Ok(ref b) if *b == Bytes::from("$-1\r\n")
         ));
+        assert!(matches!(
+            RESPSerializer::serialize(&RESPType::NullArray),
+            Ok(ref b) if *b == Bytes::from("*-1\r\n")
+        ));
+    }
+
+    #[test]
+    fn test_parse_resp3_simple() {
+        assert!(matches!(parse("_\r\n"), Ok(Some(RESPType::Null))));
+        assert!(matches!(parse("#t\r\n"), Ok(Some(RESPType::Boolean(true)))));
+        assert!(matches!(
+            parse("#f\r\n"),
+            Ok(Some(RESPType::Boolean(false)))
+        ));
+        assert!(parse("#x\r\n").is_err());
+        assert!(matches!(parse(",1.5\r\n"), Ok(Some(RESPType::Double(n))) if n == 1.5));
+        assert!(
+            matches!(parse(",-inf\r\n"), Ok(Some(RESPType::Double(n))) if n == f64::NEG_INFINITY)
+        );
+        assert!(matches!(parse(",nan\r\n"), Ok(Some(RESPType::Double(n))) if n.is_nan()));
+        assert!(parse(",abc\r\n").is_err());
+        assert!(matches!(
+            parse("(-3492890328409238509324850943850943825024385\r\n"),
+            Ok(Some(RESPType::BigNumber(ref n))) if n == "-3492890328409238509324850943850943825024385"
+        ));
+        assert!(parse("(12a\r\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_resp3_blobs() {
+        assert!(matches!(
+            parse("!21\r\nSYNTAX invalid syntax\r\n"),
+            Ok(Some(RESPType::BlobError(ref e))) if **e == *b"SYNTAX invalid syntax"
+        ));
+        assert!(matches!(
+            parse("=15\r\ntxt:Some string\r\n"),
+            Ok(Some(RESPType::Verbatim(ref f, ref s))) if f == "txt" && **s == *b"Some string"
+        ));
+        assert!(parse("=3\r\ntxt\r\n").is_err());
+        assert!(matches!(parse("=15\r\ntxt:Some"), Ok(None)));
+    }
+
+    #[test]
+    fn test_parse_resp3_aggregates() {
+        assert_eq!(
+            parse("%2\r\n+first\r\n:1\r\n+second\r\n:2\r\n").unwrap(),
+            Some(RESPType::Map(vec![
+                (RESPType::String("first".into()), RESPType::Integer(1)),
+                (RESPType::String("second".into()), RESPType::Integer(2)),
+            ]))
+        );
+        assert_eq!(
+            parse("~2\r\n$1\r\na\r\n#t\r\n").unwrap(),
+            Some(RESPType::Set(vec![
+                RESPType::Bulk(Bytes::from("a")),
+                RESPType::Boolean(true)
+            ]))
+        );
+        assert_eq!(
+            parse(">2\r\n+message\r\n$5\r\nhello\r\n").unwrap(),
+            Some(RESPType::Push(vec![
+                RESPType::String("message".into()),
+                RESPType::Bulk(Bytes::from("hello"))
+            ]))
+        );
+        assert_eq!(
+            parse("|1\r\n+ttl\r\n:3600\r\n").unwrap(),
+            Some(RESPType::Attribute(vec![(
+                RESPType::String("ttl".into()),
+                RESPType::Integer(3600)
+            )]))
+        );
+        assert!(matches!(parse("%1\r\n+first\r\n"), Ok(None)));
+    }
+
+    #[test]
+    fn test_serialize_resp3() {
+        let ser = |msg: &RESPType| RESPSerializer::serialize_with(msg, Protocol::Resp3).unwrap();
+        assert_eq!(ser(&RESPType::Null), "_\r\n");
+        assert_eq!(ser(&RESPType::NullArray), "_\r\n");
+        assert_eq!(ser(&RESPType::Boolean(true)), "#t\r\n");
+        assert_eq!(ser(&RESPType::Double(1.5)), ",1.5\r\n");
+        assert_eq!(ser(&RESPType::Double(f64::INFINITY)), ",inf\r\n");
+        assert_eq!(ser(&RESPType::BigNumber("123".into())), "(123\r\n");
+        assert_eq!(
+            ser(&RESPType::Verbatim(
+                "txt".into(),
+                Bytes::from("Some string")
+            )),
+            "=15\r\ntxt:Some string\r\n"
+        );
+        assert_eq!(
+            ser(&RESPType::BlobError(Bytes::from("SYNTAX invalid syntax"))),
+            "!21\r\nSYNTAX invalid syntax\r\n"
+        );
+        assert_eq!(
+            ser(&RESPType::Map(vec![(
+                RESPType::Bulk(Bytes::from("proto")),
+                RESPType::Integer(3)
+            )])),
+            "%1\r\n$5\r\nproto\r\n:3\r\n"
+        );
+        assert_eq!(
+            ser(&RESPType::Set(vec![RESPType::Integer(1)])),
+            "~1\r\n:1\r\n"
+        );
+        assert_eq!(
+            ser(&RESPType::Push(vec![RESPType::Integer(1)])),
+            ">1\r\n:1\r\n"
+        );
+        assert_eq!(
+            ser(&RESPType::Array(vec![
+                RESPType::Null,
+                RESPType::Boolean(false)
+            ])),
+            "*2\r\n_\r\n#f\r\n"
+        );
+    }
+
+    #[test]
+    fn test_serialize_resp3_downgrade() {
+        let ser = |msg: &RESPType| RESPSerializer::serialize(msg).unwrap();
+        assert_eq!(ser(&RESPType::Boolean(true)), ":1\r\n");
+        assert_eq!(ser(&RESPType::Double(1.5)), "$3\r\n1.5\r\n");
+        assert_eq!(
+            ser(&RESPType::Map(vec![(
+                RESPType::Bulk(Bytes::from("proto")),
+                RESPType::Double(2.0)
+            )])),
+            "*2\r\n$5\r\nproto\r\n$1\r\n2\r\n"
+        );
+        assert_eq!(ser(&RESPType::Set(vec![RESPType::Null])), "*1\r\n$-1\r\n");
+        assert_eq!(
+            ser(&RESPType::Attribute(vec![(RESPType::Null, RESPType::Null)])),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_parse_inline() {
+        let bulks = |args: &[&str]| {
+            RESPType::Array(
+                args.iter()
+                    .map(|arg| RESPType::Bulk(Bytes::from(arg.to_string())))
+                    .collect(),
+            )
+        };
+
+        assert_eq!(parse("PING\r\n").unwrap(), Some(bulks(&["PING"])));
+        assert_eq!(parse("PING\n").unwrap(), Some(bulks(&["PING"])));
+        assert_eq!(parse("PING").unwrap(), None);
+        assert_eq!(
+            parse("  set  key   value \r\n").unwrap(),
+            Some(bulks(&["set", "key", "value"]))
+        );
+        assert_eq!(
+            parse("\r\n\r\necho hi\r\n").unwrap(),
+            Some(bulks(&["echo", "hi"]))
+        );
+        assert_eq!(parse("\r\n").unwrap(), None);
+        assert_eq!(
+            parse("set \"hello world\" '\\'quoted\\''\r\n").unwrap(),
+            Some(bulks(&["set", "hello world", "'quoted'"]))
+        );
+        assert_eq!(
+            parse("echo \"a\\tb\\x41\\\"\" \"\"\r\n").unwrap(),
+            Some(bulks(&["echo", "a\tbA\"", ""]))
+        );
+        assert!(parse("echo \"unbalanced\r\n").is_err());
+        assert!(parse("echo \"closed\"trailing\r\n").is_err());
+        assert!(parse("echo 'unbalanced\r\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_unknown_marker_in_array() {
+        assert!(parse("*1\r\nPING\r\n").is_err());
+    }
+
+    #[test]
+    fn test_bulk_binary_roundtrip() {
+        let payloads = vec![
+            Bytes::from((0..=255u8).collect::<Vec<u8>>()),
+            Bytes::from_static(b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR"),
+            Bytes::from_static(b"\xff\xfe\r\n\r\n\x00"),
+            Bytes::new(),
+        ];
+
+        for payload in payloads {
+            let frame = RESPType::Array(vec![
+                RESPType::Bulk(payload.clone()),
+                RESPType::Bulk(payload.clone()),
+            ]);
+            let serialized = RESPSerializer::serialize(&frame).unwrap();
+            let mut prefix = format!("*2\r\n${}\r\n", payload.len()).into_bytes();
+            prefix.extend_from_slice(&payload);
+            assert!(serialized.starts_with(&prefix));
+
+            let mut buf = BytesMut::from(&serialized[..]);
+            assert_eq!(RESPParser::new().parse(&mut buf).unwrap(), Some(frame));
+            assert!(buf.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_parse_zero_copy() {
+        let mut buf = BytesMut::from("*2\r\n$3\r\nget\r\n$5\r\nhello\r\n+next\r\n");
+        let range = buf.as_ptr() as usize..buf.as_ptr() as usize + buf.len();
+
+        let mut parser = RESPParser::new();
+        match parser.parse(&mut buf).unwrap() {
+            Some(RESPType::Array(arr)) => {
+                for resp in arr {
+                    match resp {
+                        RESPType::Bulk(b) => assert!(range.contains(&(b.as_ptr() as usize))),
+                        _ => panic!("expected bulk"),
+                    }
+                }
+            }
+            _ => panic!("expected array"),
+        }
+        assert_eq!(&buf[..], b"+next\r\n");
+        assert_eq!(
+            parser.parse(&mut buf).unwrap(),
+            Some(RESPType::String("next".into()))
+        );
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_parse_incomplete_leaves_buffer() {
+        let mut parser = RESPParser::new();
+        let mut buf = BytesMut::from("*2\r\n$3\r\nget\r\n$5\r\nhel");
+        assert_eq!(parser.parse(&mut buf).unwrap(), None);
+        assert_eq!(buf.len(), 20);
+        buf.extend_from_slice(b"lo\r\n");
+        assert!(parser.parse(&mut buf).unwrap().is_some());
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_serialize_into() {
+        let mut dst = BytesMut::from("+prefix\r\n");
+        RESPSerializer::serialize_into(&RESPType::Integer(1), Protocol::Resp2, &mut dst).unwrap();
+        assert_eq!(&dst[..], b"+prefix\r\n:1\r\n");
+
+        let bad = RESPType::Array(vec![
+            RESPType::Bulk(Bytes::from("ok")),
+            RESPType::String("bad\r\n".into()),
+        ]);
+        assert!(RESPSerializer::serialize_into(&bad, Protocol::Resp2, &mut dst).is_err());
+        assert_eq!(&dst[..], b"+prefix\r\n:1\r\n");
+    }
+
+    #[test]
+    fn test_parse_incremental() {
+        let mut parser = RESPParser::new();
+        let frame = b"*2\r\n*2\r\n:1\r\n$3\r\nabc\r\n%1\r\n+k\r\n#t\r\n+after\r\n";
+        let mut buf = BytesMut::new();
+
+        // feed the frame one byte at a time, it must only complete once fully received
+        for (i, c) in frame[..frame.len() - 8].iter().enumerate() {
+            buf.extend_from_slice(&[*c]);
+            let res = parser.parse(&mut buf).unwrap();
+            assert_eq!(res.is_some(), i == frame.len() - 9);
+            if let Some(resp) = res {
+                assert_eq!(
+                    resp,
+                    RESPType::Array(vec![
+                        RESPType::Array(vec![
+                            RESPType::Integer(1),
+                            RESPType::Bulk(Bytes::from("abc"))
+                        ]),
+                        RESPType::Map(vec![(
+                            RESPType::String("k".into()),
+                            RESPType::Boolean(true)
+                        )]),
+                    ])
+                );
+            }
+        }
+        buf.extend_from_slice(&frame[frame.len() - 8..]);
+        assert_eq!(
+            parser.parse(&mut buf).unwrap(),
+            Some(RESPType::String("after".into()))
+        );
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        let limits = ProtocolLimits {
+            max_bulk_len: 8,
+            max_array_len: 4,
+            max_depth: 2,
+            max_inline_len: 16,
+            max_query_buffer: 1024,
+        };
+        let parse = |src: &str| RESPParser::with_limits(limits).parse(&mut BytesMut::from(src));
+
+        assert!(parse("$8\r\n12345678\r\n").unwrap().is_some());
+        assert!(parse("$9\r\n123456789\r\n").is_err());
+        assert!(parse("$4000000000\r\n").is_err());
+        assert!(parse("*4\r\n:1\r\n:2\r\n:3\r\n:4\r\n").unwrap().is_some());
+        assert!(parse("*9999999999\r\n").is_err());
+        assert!(parse("%3\r\n").unwrap().is_none());
+        assert!(parse("%5\r\n").is_err());
+        assert!(parse("*1\r\n*1\r\n:1\r\n").unwrap().is_some());
+        assert!(parse("*1\r\n*1\r\n*1\r\n:1\r\n").is_err());
+        assert!(parse("ping 0123456789\r\n").unwrap().is_some());
+        assert!(parse("ping 0123456789abcdef").is_err());
+        assert!(parse("+0123456789abcdefgh").is_err());
+        assert!(parse("$-12\r\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_unlimited_lengths() {
+        let parse = |src: &str| {
+            RESPParser::with_limits(ProtocolLimits::unlimited()).parse(&mut BytesMut::from(src))
+        };
+
+        assert!(parse("$18446744073709551615\r\n").is_err());
+        assert!(parse("$18446744073709551613\r\nab").is_err());
+        assert!(parse("%9223372036854775808\r\n").is_err());
+        assert!(parse("|18446744073709551615\r\n").is_err());
+        // lengths that fit are still just waiting for more data
+        assert!(parse("$9223372036854775807\r\nab").unwrap().is_none());
+        assert!(parse("%9223372036854775807\r\n").unwrap().is_none());
+        assert!(parse("*18446744073709551615\r\n:1\r\n").unwrap().is_none());
     }
 }